@@ -3,7 +3,7 @@ use lact_client::DaemonClient;
 use lact_schema::args::{CliArgs, CliCommand};
 
 pub fn run(args: CliArgs) -> Result<()> {
-    let client = DaemonClient::connect()?;
+    let client = DaemonClient::connect_no_retry()?;
 
     let f = match args.subcommand {
         CliCommand::ListGpus => list_gpus,
@@ -16,10 +16,11 @@ fn list_gpus(_: &CliArgs, client: &DaemonClient) -> Result<()> {
     let buffer = client.list_devices()?;
     for entry in buffer.inner()? {
         let id = entry.id;
-        if let Some(name) = entry.name {
-            println!("{id} ({name})");
-        } else {
-            println!("{id}");
+        match (entry.label, entry.name) {
+            (Some(label), Some(name)) => println!("{id} ({label}, {name})"),
+            (Some(label), None) => println!("{id} ({label})"),
+            (None, Some(name)) => println!("{id} ({name})"),
+            (None, None) => println!("{id}"),
         }
     }
     Ok(())
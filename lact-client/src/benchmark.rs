@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// Round-trip latencies of a series of pings sent over the client's connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchmarkReport {
+    pub requests: u32,
+    pub min_latency: Duration,
+    pub avg_latency: Duration,
+    pub max_latency: Duration,
+    /// Time taken by all of the requests, including the client-side processing in between
+    pub total: Duration,
+}
+
+impl BenchmarkReport {
+    pub(crate) fn from_latencies(latencies: &[Duration], total: Duration) -> Option<Self> {
+        let requests = u32::try_from(latencies.len()).ok()?;
+        Some(Self {
+            requests,
+            min_latency: *latencies.iter().min()?,
+            avg_latency: latencies.iter().sum::<Duration>() / requests,
+            max_latency: *latencies.iter().max()?,
+            total,
+        })
+    }
+
+    /// Requests completed per second
+    pub fn throughput(&self) -> f64 {
+        f64::from(self.requests) / self.total.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BenchmarkReport;
+    use std::time::Duration;
+
+    #[test]
+    fn report_from_latencies() {
+        let latencies = [2, 1, 6].map(Duration::from_millis);
+        let report =
+            BenchmarkReport::from_latencies(&latencies, Duration::from_millis(10)).unwrap();
+        assert_eq!(report.requests, 3);
+        assert_eq!(report.min_latency, Duration::from_millis(1));
+        assert_eq!(report.avg_latency, Duration::from_millis(3));
+        assert_eq!(report.max_latency, Duration::from_millis(6));
+        assert!((report.throughput() - 300.0).abs() < f64::EPSILON);
+
+        assert!(BenchmarkReport::from_latencies(&[], Duration::ZERO).is_none());
+    }
+}
@@ -0,0 +1,133 @@
+//! MSI Afterburner profiles (`Profiles/VEN_*.cfg`), which are INI files with a `[Startup]` section and one `[ProfileN]` section per slot.
+//! The format differs between vendors and versions, so this is a best-effort mapping of the common keys:
+//! clocks are stored in kHz, either as absolute values or as offsets, and unset values are left empty.
+
+use super::ImportedSettings;
+use anyhow::anyhow;
+
+/// Power limits below this are offsets from the default (as with AMD cards), others are a percentage of the default
+const POWER_LIMIT_OFFSET_MAX: f64 = 50.0;
+
+/// Reads the settings from the given section, such as `Startup` or `Profile1`
+pub fn parse(cfg: &str, section: &str) -> anyhow::Result<ImportedSettings> {
+    let header = format!("[{section}]");
+    let mut lines = cfg.lines().map(str::trim);
+    if !lines.any(|line| line.eq_ignore_ascii_case(&header)) {
+        return Err(anyhow!("The profile has no `{header}` section"));
+    }
+
+    let mut imported = ImportedSettings::default();
+    let mut fan_speed = None;
+    let mut manual_fan = false;
+
+    for line in lines.take_while(|line| !line.starts_with('[')) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let number = value.parse::<f64>().ok();
+        #[allow(clippy::cast_possible_truncation)]
+        let mhz = number.map(|khz| (khz / 1000.0).round() as i32);
+
+        match (key.trim(), mhz) {
+            ("Format", _) => (),
+            ("CoreClk", Some(clock)) => imported.set_clock("max_core_clock", clock),
+            ("MemClk", Some(clock)) => imported.set_clock("max_memory_clock", clock),
+            ("CoreClkBoost", Some(offset)) => imported.relative.core_clock_offset = Some(offset),
+            ("MemClkBoost", Some(offset)) => imported.relative.memory_clock_offset = Some(offset),
+            ("PowerLimit", _) => {
+                imported.relative.power_limit_percent = number.map(|limit| {
+                    if limit < POWER_LIMIT_OFFSET_MAX {
+                        100.0 + limit
+                    } else {
+                        limit
+                    }
+                });
+            }
+            ("FanMode", _) => manual_fan = value == "0",
+            ("FanSpeed", _) => fan_speed = number.map(|percent| percent / 100.0),
+            (key, _) => imported.ignored.push(key.to_owned()),
+        }
+    }
+
+    if manual_fan {
+        if let Some(speed) = fan_speed {
+            imported.set_fan_control(Some(speed), None);
+        }
+    }
+
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use serde_json::json;
+
+    const PROFILE: &str = "\
+[Startup]
+Format=2
+PowerLimit=
+CoreClk=
+[Profile1]
+Format=2
+CoreVoltageBoost=
+PowerLimit=15
+ThermalLimit=
+CoreClk=2350000
+MemClk=1000000
+CoreClkBoost=
+FanMode=0
+FanSpeed=65
+[Profile2]
+Format=2
+PowerLimit=110
+CoreClkBoost=150000
+MemClkBoost=500000
+ThermalLimit=83
+FanMode=1
+FanSpeed=40
+";
+
+    #[test]
+    fn afterburner_profile() {
+        let amd = parse(PROFILE, "Profile1").unwrap();
+        assert_eq!(
+            amd.resolve(None, None, Some(200.0)),
+            json!({
+                "clocks_configuration": {
+                    "max_core_clock": 2350,
+                    "max_memory_clock": 1000,
+                },
+                "fan_control_enabled": true,
+                "fan_control_settings": {
+                    "mode": "static",
+                    "static_speed": 0.65,
+                    "temperature_key": "edge",
+                    "interval_ms": 500,
+                    "curve": amd.settings["fan_control_settings"]["curve"],
+                },
+                "power_cap_microwatts": 230_000_000,
+            })
+        );
+        assert!(amd.ignored.is_empty());
+
+        let relative = parse(PROFILE, "Profile2").unwrap();
+        assert_eq!(
+            relative.resolve(Some(1900), Some(7000), Some(250.0)),
+            json!({
+                "clocks_configuration": {
+                    "max_core_clock": 2050,
+                    "max_memory_clock": 7500,
+                },
+                "power_cap_microwatts": 275_000_000,
+            })
+        );
+        assert_eq!(relative.ignored, ["ThermalLimit"]);
+
+        assert!(parse(PROFILE, "Profile5").is_err());
+    }
+}
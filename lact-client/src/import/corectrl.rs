@@ -0,0 +1,262 @@
+//! CoreCtrl profiles, from the `profile.xml` file inside of a `.ccpro` archive.
+//! Only the active settings are imported: CoreCtrl keeps the values of every mode, marking the unused ones with `active="false"`.
+
+use super::ImportedSettings;
+use crate::schema::FanCurveMap;
+use anyhow::Context;
+use std::collections::BTreeMap;
+use xml::{attribute::OwnedAttribute, reader::XmlEvent, EventReader};
+
+/// Elements which only group other settings
+const CONTAINERS: &[&str] = &[
+    "CoreCtrl_Profile",
+    "AMD_PM_PERFMODE",
+    "AMD_PM_ADVANCED",
+    "AMD_PM_OVERDRIVE",
+    "AMD_FAN_MODE",
+    "CURVE",
+    "STATE",
+    "POINT",
+];
+
+/// Returns the settings of each GPU in the profile
+pub fn parse(xml: &str) -> anyhow::Result<Vec<ImportedSettings>> {
+    let mut gpus = Vec::new();
+    let mut current: Option<ImportedSettings> = None;
+    // Depth of the inactive element being skipped
+    let mut skip_depth: Option<usize> = None;
+    let mut depth = 0;
+    let mut freq_range: Option<(String, BTreeMap<u32, i32>)> = None;
+    let mut fan_curve: Option<FanCurveMap> = None;
+
+    for event in EventReader::from_str(xml) {
+        match event.context("Invalid CoreCtrl profile")? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                depth += 1;
+                if skip_depth.is_some() {
+                    continue;
+                }
+                let attr = |key: &str| attribute(&attributes, key);
+                if attr("active") == Some("false") || name.local_name == "CPU" {
+                    skip_depth = Some(depth);
+                    continue;
+                }
+
+                if name.local_name == "GPU" {
+                    current = Some(ImportedSettings {
+                        device_id: attr("deviceid").map(str::to_owned),
+                        ..Default::default()
+                    });
+                    continue;
+                }
+                let Some(gpu) = current.as_mut() else {
+                    continue;
+                };
+
+                match name.local_name.as_str() {
+                    "AMD_PM_POWERCAP" => {
+                        if let Some(watts) = attr("value").and_then(|value| value.parse().ok()) {
+                            gpu.set_power_cap_watts(watts);
+                        }
+                    }
+                    "AMD_PM_VOLT_OFFSET" => {
+                        if let Some(offset) = attr("value").and_then(|value| value.parse().ok()) {
+                            gpu.set_clock("voltage_offset", offset);
+                        }
+                    }
+                    "AMD_PM_FREQ_RANGE" => {
+                        let control = attr("controlName").unwrap_or_default().to_owned();
+                        freq_range = Some((control, BTreeMap::new()));
+                    }
+                    "STATE" => {
+                        let state = attr("index")
+                            .and_then(|index| index.parse().ok())
+                            .zip(attr("freq").and_then(|freq| freq.parse().ok()));
+                        if let Some(((_, states), (index, freq))) = freq_range.as_mut().zip(state) {
+                            states.insert(index, freq);
+                        }
+                    }
+                    "AMD_PM_FIXED" => match attr("mode") {
+                        Some(level @ ("low" | "high")) => {
+                            gpu.settings
+                                .insert("performance_level".to_owned(), level.into());
+                        }
+                        _ => gpu.ignored.push("fixed performance mode".to_owned()),
+                    },
+                    "AMD_PM_AUTO" => {
+                        gpu.settings
+                            .insert("performance_level".to_owned(), "auto".into());
+                    }
+                    "AMD_PM_POWER_PROFILE" => gpu.ignored.push(format!(
+                        "power profile mode {}",
+                        attr("mode").unwrap_or_default()
+                    )),
+                    "AMD_FAN_AUTO" => {
+                        gpu.settings
+                            .insert("fan_control_enabled".to_owned(), false.into());
+                    }
+                    "AMD_FAN_FIXED" => {
+                        let speed = attr("value")
+                            .and_then(|value| value.parse::<f64>().ok())
+                            .map(|percent| percent / 100.0);
+                        gpu.set_fan_control(speed, None);
+                        ignore_fan_stop(gpu, &attributes);
+                    }
+                    "AMD_FAN_CURVE" => {
+                        fan_curve = Some(FanCurveMap::new());
+                        ignore_fan_stop(gpu, &attributes);
+                    }
+                    "POINT" => {
+                        let point = attr("temp")
+                            .and_then(|temp| temp.parse().ok())
+                            .zip(attr("pwm").and_then(|pwm| pwm.parse::<f32>().ok()));
+                        if let Some((curve, (temp, percent))) = fan_curve.as_mut().zip(point) {
+                            curve.insert(temp, percent / 100.0);
+                        }
+                    }
+                    other if CONTAINERS.contains(&other) => (),
+                    other => gpu.ignored.push(other.to_owned()),
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                if skip_depth == Some(depth) {
+                    skip_depth = None;
+                } else if skip_depth.is_none() {
+                    if let Some(gpu) = current.as_mut() {
+                        match name.local_name.as_str() {
+                            "AMD_PM_FREQ_RANGE" => apply_freq_range(gpu, freq_range.take()),
+                            "AMD_FAN_CURVE" => {
+                                if let Some(curve) = fan_curve.take() {
+                                    gpu.set_fan_control(None, Some(curve));
+                                }
+                            }
+                            "GPU" => gpus.extend(current.take()),
+                            _ => (),
+                        }
+                    }
+                }
+                depth -= 1;
+            }
+            _ => (),
+        }
+    }
+
+    Ok(gpus)
+}
+
+fn attribute<'a>(attributes: &'a [OwnedAttribute], key: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == key)
+        .map(|attribute| attribute.value.as_str())
+}
+
+/// The lowest state is the minimum clock, the highest one is the maximum clock
+fn apply_freq_range(gpu: &mut ImportedSettings, range: Option<(String, BTreeMap<u32, i32>)>) {
+    let Some((control, states)) = range else {
+        return;
+    };
+    let (min_key, max_key) = match control.as_str() {
+        "SCLK" => ("min_core_clock", "max_core_clock"),
+        "MCLK" => ("min_memory_clock", "max_memory_clock"),
+        _ => {
+            gpu.ignored.push(format!("{control} frequency range"));
+            return;
+        }
+    };
+
+    if states.len() > 1 {
+        if let Some(min) = states.values().next() {
+            gpu.set_clock(min_key, *min);
+        }
+    }
+    if let Some(max) = states.values().next_back() {
+        gpu.set_clock(max_key, *max);
+    }
+}
+
+fn ignore_fan_stop(gpu: &mut ImportedSettings, attributes: &[OwnedAttribute]) {
+    if attribute(attributes, "fanStop") == Some("true") {
+        gpu.ignored.push("fan stop".to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use serde_json::json;
+
+    const PROFILE: &str = r#"<?xml version="1.0"?>
+<CoreCtrl_Profile active="true" name="_global_" exe="_global_">
+ <GPU index="0" deviceid="73bf" revision="c1" uniqueid="3a5a3c5f6d1d52a1">
+  <AMD_PM_PERFMODE active="true" mode="AMD_PM_ADVANCED">
+   <AMD_PM_AUTO active="false"/>
+   <AMD_PM_FIXED active="false" mode="low"/>
+   <AMD_PM_ADVANCED active="true">
+    <AMD_PM_POWERCAP active="true" value="255"/>
+    <AMD_PM_POWER_PROFILE active="true" mode="3D_FULL_SCREEN"/>
+    <AMD_PM_OVERDRIVE active="true">
+     <AMD_PM_FREQ_RANGE active="true" controlName="SCLK">
+      <STATE index="0" freq="500"/>
+      <STATE index="1" freq="2450"/>
+     </AMD_PM_FREQ_RANGE>
+     <AMD_PM_FREQ_RANGE active="true" controlName="MCLK">
+      <STATE index="1" freq="1075"/>
+     </AMD_PM_FREQ_RANGE>
+     <AMD_PM_VOLT_OFFSET active="true" value="-50"/>
+    </AMD_PM_OVERDRIVE>
+   </AMD_PM_ADVANCED>
+  </AMD_PM_PERFMODE>
+  <AMD_FAN_MODE active="true" mode="AMD_FAN_CURVE">
+   <AMD_FAN_AUTO active="false"/>
+   <AMD_FAN_FIXED active="false" value="64" fanStop="false" fanStartValue="54"/>
+   <AMD_FAN_CURVE active="true" fanStop="true" fanStartValue="54">
+    <CURVE>
+     <POINT temp="35" pwm="25"/>
+     <POINT temp="60" pwm="50"/>
+     <POINT temp="90" pwm="100"/>
+    </CURVE>
+   </AMD_FAN_CURVE>
+  </AMD_FAN_MODE>
+ </GPU>
+ <CPU physicalId="0">
+  <CPU_CPUFREQ active="true" scalingGovernor="schedutil"/>
+ </CPU>
+</CoreCtrl_Profile>
+"#;
+
+    #[test]
+    fn corectrl_profile() {
+        let gpus = parse(PROFILE).unwrap();
+        assert_eq!(gpus.len(), 1);
+        let gpu = &gpus[0];
+
+        assert_eq!(gpu.device_id.as_deref(), Some("73bf"));
+        assert_eq!(
+            serde_json::Value::Object(gpu.settings.clone()),
+            json!({
+                "power_cap_microwatts": 255_000_000,
+                "clocks_configuration": {
+                    "min_core_clock": 500,
+                    "max_core_clock": 2450,
+                    "max_memory_clock": 1075,
+                    "voltage_offset": -50,
+                },
+                "fan_control_enabled": true,
+                "fan_control_settings": {
+                    "mode": "curve",
+                    "static_speed": 0.5,
+                    "temperature_key": "edge",
+                    "interval_ms": 500,
+                    "curve": { "35": 0.25, "60": 0.5, "90": 1.0 },
+                },
+            })
+        );
+        assert_eq!(
+            gpu.ignored,
+            ["power profile mode 3D_FULL_SCREEN", "fan stop"]
+        );
+    }
+}
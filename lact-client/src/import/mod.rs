@@ -0,0 +1,86 @@
+//! Importers for the profiles of other tuning tools, for migrating their settings to LACT.
+//! Settings which have no equivalent are listed in `ImportedSettings::ignored` instead of failing the import.
+
+pub mod afterburner;
+pub mod corectrl;
+
+use crate::schema::{default_fan_curve, FanCurveMap};
+use serde_json::{json, Map, Value};
+
+/// Settings of one GPU from an imported profile
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedSettings {
+    /// PCI device id of the card the profile was made for, if the profile records it
+    pub device_id: Option<String>,
+    /// GPU settings in the format of a `gpus` entry of the config
+    pub settings: Map<String, Value>,
+    /// Settings relative to the current values of the GPU, which get resolved by `DaemonClient::apply_imported`
+    pub relative: RelativeSettings,
+    /// Settings from the profile which could not be mapped
+    pub ignored: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RelativeSettings {
+    /// In MHz, relative to the current maximum core clock
+    pub core_clock_offset: Option<i32>,
+    /// In MHz, relative to the current maximum memory clock
+    pub memory_clock_offset: Option<i32>,
+    /// Percentage of the default power cap
+    pub power_limit_percent: Option<f64>,
+}
+
+impl ImportedSettings {
+    fn set_clock(&mut self, name: &str, value: i32) {
+        let clocks = self
+            .settings
+            .entry("clocks_configuration")
+            .or_insert_with(|| json!({}));
+        clocks[name] = json!(value);
+    }
+
+    fn set_power_cap_watts(&mut self, watts: f64) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let microwatts = (watts * 1_000_000.0).round() as u64;
+        self.settings
+            .insert("power_cap_microwatts".to_owned(), json!(microwatts));
+    }
+
+    fn set_fan_control(&mut self, static_speed: Option<f64>, curve: Option<FanCurveMap>) {
+        let mode = if curve.is_some() { "curve" } else { "static" };
+        self.settings
+            .insert("fan_control_enabled".to_owned(), json!(true));
+        self.settings.insert(
+            "fan_control_settings".to_owned(),
+            json!({
+                "mode": mode,
+                "static_speed": static_speed.unwrap_or(0.5),
+                "temperature_key": "edge",
+                "interval_ms": 500,
+                "curve": curve.unwrap_or_else(default_fan_curve),
+            }),
+        );
+    }
+
+    /// The settings with the relative values resolved against the current values of the GPU.
+    /// Relative values whose base is not known are left out.
+    pub fn resolve(
+        &self,
+        max_core_clock: Option<i32>,
+        max_memory_clock: Option<i32>,
+        default_power_cap: Option<f64>,
+    ) -> Value {
+        let mut resolved = self.clone();
+        let relative = self.relative;
+        if let Some(clock) = relative.core_clock_offset.zip(max_core_clock) {
+            resolved.set_clock("max_core_clock", clock.0 + clock.1);
+        }
+        if let Some(clock) = relative.memory_clock_offset.zip(max_memory_clock) {
+            resolved.set_clock("max_memory_clock", clock.0 + clock.1);
+        }
+        if let Some((percent, cap)) = relative.power_limit_percent.zip(default_power_cap) {
+            resolved.set_power_cap_watts(cap * percent / 100.0);
+        }
+        Value::Object(resolved.settings)
+    }
+}
@@ -1,38 +1,151 @@
+#[cfg(feature = "full")]
+mod benchmark;
+#[cfg(feature = "full")]
+pub mod import;
+#[cfg(feature = "full")]
 #[macro_use]
 mod macros;
+pub mod minimal;
+#[cfg(feature = "full")]
+mod stabilize;
+#[cfg(feature = "full")]
+mod stats_stream;
+#[cfg(feature = "full")]
+mod subscription;
+#[cfg(feature = "full")]
+mod watchdog;
 
+#[cfg(feature = "full")]
+pub use benchmark::BenchmarkReport;
+#[cfg(feature = "full")]
 pub use lact_schema as schema;
+#[cfg(feature = "full")]
+pub use stabilize::StabilizedStats;
+#[cfg(feature = "full")]
+pub use watchdog::WatchdogEvent;
 
-use anyhow::{anyhow, Context};
 use nix::unistd::getuid;
-use schema::{
-    amdgpu_sysfs::gpu_handle::{
-        power_profile_mode::PowerProfileModesTable, PerformanceLevel, PowerLevelKind,
-    },
-    request::{ConfirmCommand, SetClocksCommand},
-    ClocksInfo, DeviceInfo, DeviceListEntry, DeviceStats, FanControlMode, FanCurveMap, PowerStates,
-    Request, Response, SystemInfo,
-};
-use serde::Deserialize;
 use std::{
-    cell::RefCell,
-    io::{BufRead, BufReader, Write},
-    marker::PhantomData,
-    os::unix::net::UnixStream,
+    env,
+    ffi::OsStr,
     path::{Path, PathBuf},
-    rc::Rc,
-    time::Duration,
 };
-use tracing::{error, info};
+#[cfg(feature = "full")]
+use {
+    anyhow::{anyhow, Context},
+    miniz_oxide::inflate::decompress_to_vec_zlib,
+    schema::{
+        amdgpu_sysfs::gpu_handle::{
+            power_profile_mode::PowerProfileModesTable, PerformanceLevel, PowerLevelKind,
+        },
+        request::{ConfirmCommand, SetClocksCommand},
+        validate_fan_curve, AdaptiveClocks, AppliedChange, AspmInfo, AspmPolicy, AudioDeviceInfo,
+        ClockDomain, ClocksInfo, ConfigReloadSummary, Control, ControlStatus, ControllableNode,
+        DaemonEvent, DefaultFanCurve, DeviceInfo, DeviceListEntry, DeviceMatch, DeviceSnapshot,
+        DeviceStats, EffectiveConfig, EventMask, FanControlMode, FanCurveMap, FanDecision,
+        FleetSummary, GpuProcess, InterconnectInfo, LogLevel, LogRecord, PendingConfigStatus, Pong,
+        PowerProfileModesDetails, PowerStates, PowerTier, PowerTiers, PpFeature, PresetInfo,
+        ProfileLoadResult, QuickStats, ReadFailureCount, Request, ResetMethod, ResetMethods,
+        Response, ResponseTiming, ResponseWarnings, RetiredPage, Schedule, SelfTestReport,
+        SettingChange, SettingsDiff, SmuMetrics, SystemInfo, SystemPower, TemperatureUnit,
+        TimeRange, TotalPower, TuningSuggestions, VoltageCurve,
+    },
+    serde::Deserialize,
+    std::{
+        cell::{Cell, RefCell},
+        collections::{BTreeMap, HashMap},
+        fmt,
+        io::{BufRead, BufReader, Read, Write},
+        marker::PhantomData,
+        net::Shutdown,
+        os::unix::net::UnixStream,
+        rc::Rc,
+        sync::mpsc::{self, Receiver, Sender},
+        time::{Duration, Instant},
+    },
+    tracing::{debug, error, info, warn},
+};
 
+#[cfg(feature = "full")]
 const RECONNECT_INTERVAL_MS: u64 = 250;
+/// How many times an idempotent request is sent again after reconnecting, before giving up
+#[cfg(feature = "full")]
+const MAX_REQUEST_RETRIES: u32 = 3;
+/// How long to wait for the rest of a stale response before giving up on the connection
+#[cfg(feature = "full")]
+const STALE_RESPONSE_TIMEOUT_MS: u64 = 1000;
+/// Only the latest warnings are kept when they are not taken, so the buffer can't grow without bounds
+#[cfg(feature = "full")]
+const MAX_BUFFERED_WARNINGS: usize = 32;
+/// Interval between the stats samples of `apply_and_wait`
+#[cfg(feature = "full")]
+const STABILIZE_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// How many samples in a row have to be within the tolerances for the stats to count as stable
+#[cfg(feature = "full")]
+const STABLE_SAMPLES: u32 = 3;
+
+#[cfg(feature = "full")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientError {
+    /// The connection was lost while sending a request that modifies state.
+    /// The connection has been re-established, but the request was not sent again,
+    /// as it is unknown whether it was applied by the daemon.
+    ConnectionLostDuringWrite,
+    /// The connection kept getting lost, and the request was not sent again after the retry limit was reached.
+    RetriesExceeded,
+}
 
+#[cfg(feature = "full")]
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::ConnectionLostDuringWrite => write!(
+                f,
+                "Connection to the daemon was lost while making a request that modifies state"
+            ),
+            // The first attempt is not a retry
+            ClientError::RetriesExceeded => write!(
+                f,
+                "Connection to the daemon was lost {} times in a row, giving up on the request",
+                MAX_REQUEST_RETRIES + 1
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "full")]
+impl std::error::Error for ClientError {}
+
+/// Changes of the connection state of a client, for informing the user about the daemon being unavailable
+#[cfg(feature = "full")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The connection was lost, the client is reconnecting
+    Lost { error: String },
+    /// The connection was re-established after being lost for `downtime`
+    Reconnected { downtime: Duration },
+}
+
+#[cfg(feature = "full")]
 #[derive(Clone)]
 pub struct DaemonClient {
     stream: Rc<RefCell<(BufReader<UnixStream>, UnixStream)>>,
     pub embedded: bool,
+    /// Whether to keep trying to reconnect when a request fails
+    reconnect: bool,
+    /// Raw `DeviceInfo` responses by GPU id, when caching is enabled
+    device_info_cache: Option<Rc<RefCell<HashMap<String, String>>>>,
+    connection_events: Rc<RefCell<Option<Sender<ConnectionEvent>>>>,
+    /// Whether the daemon was asked to report the processing time of requests on the connection
+    response_timing: Rc<Cell<bool>>,
+    /// Whether the responses on the connection are compressed, see `set_response_compression`
+    response_compression: Rc<Cell<bool>>,
+    last_processing_time: Rc<Cell<Option<Duration>>>,
+    /// Warnings of the successful responses, until they are taken (up to `MAX_BUFFERED_WARNINGS`)
+    warnings: Rc<RefCell<Vec<String>>>,
 }
 
+#[cfg(feature = "full")]
 impl DaemonClient {
     pub fn connect() -> anyhow::Result<Self> {
         let path =
@@ -43,17 +156,144 @@ impl DaemonClient {
         Ok(Self {
             stream: Rc::new(RefCell::new(stream_pair)),
             embedded: false,
+            reconnect: true,
+            device_info_cache: None,
+            connection_events: Rc::default(),
+            response_timing: Rc::default(),
+            response_compression: Rc::default(),
+            last_processing_time: Rc::default(),
+            warnings: Rc::default(),
         })
     }
 
+    /// Connect to the daemon, waiting up to `timeout` for it to start if the socket is not available yet.
+    pub fn connect_blocking(timeout: Duration) -> anyhow::Result<Self> {
+        let start = Instant::now();
+        loop {
+            match Self::connect() {
+                Ok(client) => return Ok(client),
+                Err(err) if start.elapsed() >= timeout => {
+                    return Err(err.context(format!(
+                        "Daemon did not become available within {}s",
+                        timeout.as_secs_f32()
+                    )))
+                }
+                Err(err) => {
+                    debug!("daemon is not available yet: {err:#}");
+                    std::thread::sleep(Duration::from_millis(RECONNECT_INTERVAL_MS));
+                }
+            }
+        }
+    }
+
+    /// Connect to the daemon without reconnecting when the connection is lost.
+    /// Requests will return the connection error immediately instead, which is useful for short-lived scripts.
+    pub fn connect_no_retry() -> anyhow::Result<Self> {
+        let mut client = Self::connect()?;
+        client.reconnect = false;
+        Ok(client)
+    }
+
     pub fn from_stream(stream: UnixStream, embedded: bool) -> anyhow::Result<Self> {
         let reader = BufReader::new(stream.try_clone()?);
         Ok(Self {
             stream: Rc::new(RefCell::new((reader, stream))),
             embedded,
+            reconnect: true,
+            device_info_cache: None,
+            connection_events: Rc::default(),
+            response_timing: Rc::default(),
+            response_compression: Rc::default(),
+            last_processing_time: Rc::default(),
+            warnings: Rc::default(),
         })
     }
 
+    /// Keep the device info of each GPU after it was fetched once, as it doesn't change while the daemon is running.
+    /// The cache is shared with clients cloned afterwards, and cleared when reconnecting to the daemon
+    /// (which only detects GPUs when it starts) or with `invalidate_device_info`.
+    pub fn enable_device_info_cache(&mut self) {
+        self.device_info_cache = Some(Rc::default());
+    }
+
+    /// Fetch the device info from the daemon again on the next `get_device_info`
+    pub fn invalidate_device_info(&self) {
+        if let Some(cache) = &self.device_info_cache {
+            cache.borrow_mut().clear();
+        }
+    }
+
+    /// Get notified when the connection gets lost and re-established while making requests.
+    /// Only the last returned receiver gets the events, and it is shared with clones of the client.
+    pub fn connection_events(&self) -> Receiver<ConnectionEvent> {
+        let (tx, rx) = mpsc::channel();
+        *self.connection_events.borrow_mut() = Some(tx);
+        rx
+    }
+
+    fn send_connection_event(&self, event: ConnectionEvent) {
+        let mut sender = self.connection_events.borrow_mut();
+        if let Some(tx) = sender.as_ref() {
+            if tx.send(event).is_err() {
+                *sender = None;
+            }
+        }
+    }
+
+    /// Reconnects after the connection was lost, keeping the new connection for the following requests
+    fn recover_connection(
+        &self,
+        stream: &UnixStream,
+        err: &anyhow::Error,
+    ) -> anyhow::Result<(BufReader<UnixStream>, UnixStream)> {
+        error!("{err:#}, reconnecting to socket");
+        self.send_connection_event(ConnectionEvent::Lost {
+            error: format!("{err:#}"),
+        });
+
+        let start = Instant::now();
+        let mut new_connection = reconnect(stream)?;
+        self.invalidate_device_info();
+        // The setting only applies to the connection it was sent on
+        if self.response_timing.get() {
+            let (reader, writer) = &mut new_connection;
+            process_request(
+                &Request::SetResponseTiming { enabled: true },
+                reader,
+                writer,
+            )?;
+        }
+        if self.response_compression.get() {
+            let (reader, writer) = &mut new_connection;
+            process_request(
+                &Request::SetResponseCompression { enabled: true },
+                reader,
+                writer,
+            )?;
+        }
+        self.send_connection_event(ConnectionEvent::Reconnected {
+            downtime: start.elapsed(),
+        });
+        Ok(new_connection)
+    }
+
+    /// Gracefully close the connection by shutting down the write half of the socket.
+    /// The daemon sees the end of the stream and drops the connection state right away.
+    ///
+    /// Cloned clients share the same connection, so the socket is only shut down when the last handle is closed.
+    /// Closing any other handle just drops it.
+    pub fn close(self) -> anyhow::Result<()> {
+        if Rc::strong_count(&self.stream) > 1 {
+            return Ok(());
+        }
+
+        let stream_guard = self.stream.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        let (_, writer) = &*stream_guard;
+        writer
+            .shutdown(Shutdown::Write)
+            .context("Could not shut down the connection")
+    }
+
     fn make_request<'a, T: Deserialize<'a>>(
         &self,
         request: Request,
@@ -62,42 +302,169 @@ impl DaemonClient {
             .stream
             .try_borrow_mut()
             .map_err(|err| anyhow!("{err}"))?;
-        let (reader, writer) = &mut *stream_guard;
 
-        if !reader.buffer().is_empty() {
-            return Err(anyhow!("Another request was not processed properly"));
-        }
+        for _ in 0..=MAX_REQUEST_RETRIES {
+            let (reader, writer) = &mut *stream_guard;
 
-        let response_payload = match process_request(&request, reader, writer) {
-            Ok(payload) => payload,
-            Err(err) => {
-                error!("Could not make request: {err}, reconnecting to socket");
-                let peer_addr = writer.peer_addr().context("Could not read peer address")?;
-                let path = peer_addr
-                    .as_pathname()
-                    .context("Connected socket addr is not a path")?;
-
-                loop {
-                    match connect_pair(path) {
-                        Ok(new_connection) => {
-                            info!("Established new socket connection");
-                            *stream_guard = new_connection;
-                            drop(stream_guard);
-                            return self.make_request(request);
-                        }
-                        Err(err) => {
-                            error!("Could not reconnect: {err:#}, retrying in {RECONNECT_INTERVAL_MS}ms");
-                            std::thread::sleep(Duration::from_millis(RECONNECT_INTERVAL_MS));
-                        }
+            // A previous request may have failed while reading its response, leaving the rest of it in the stream
+            if !reader.buffer().is_empty() {
+                warn!("discarding a stale response left by a previous request");
+                if let Err(err) = discard_stale_response(reader) {
+                    if !self.reconnect {
+                        return Err(err);
                     }
+                    *stream_guard = self.recover_connection(writer, &err)?;
+                    continue;
                 }
             }
-        };
 
-        Ok(ResponseBuffer {
-            buf: response_payload,
-            _phantom: PhantomData,
-        })
+            // The daemon always answers the toggles themselves uncompressed
+            let uncompressed_reply = matches!(
+                request,
+                Request::SetResponseCompression { .. } | Request::SetResponseTiming { .. }
+            );
+            let result = if self.response_compression.get() && !uncompressed_reply {
+                process_compressed_request(&request, reader, writer)
+            } else {
+                process_request(&request, reader, writer)
+            };
+            match result {
+                Ok(payload) => {
+                    if self.response_timing.get() {
+                        let timing = serde_json::from_str::<ResponseTiming>(&payload)
+                            .ok()
+                            .and_then(|timing| timing.processing_time_us);
+                        self.last_processing_time
+                            .set(timing.map(Duration::from_micros));
+                    }
+                    let response = ResponseBuffer {
+                        buf: payload,
+                        _phantom: PhantomData,
+                    };
+                    let mut warnings = self.warnings.borrow_mut();
+                    warnings.extend(response.warnings());
+                    let excess = warnings.len().saturating_sub(MAX_BUFFERED_WARNINGS);
+                    warnings.drain(..excess);
+                    return Ok(response);
+                }
+                Err(err) if !self.reconnect => return Err(err.context("Could not make request")),
+                Err(err) => {
+                    let err = err.context("Could not make request");
+                    *stream_guard = self.recover_connection(writer, &err)?;
+
+                    if !request.is_idempotent() {
+                        return Err(ClientError::ConnectionLostDuringWrite.into());
+                    }
+                }
+            }
+        }
+
+        Err(ClientError::RetriesExceeded.into())
+    }
+
+    /// Periodically ping the daemon from a background thread, reporting the round-trip latency and failures.
+    /// The pings use a separate connection, so they don't interfere with requests made through this client.
+    /// The background thread stops when the returned receiver is dropped.
+    pub fn watchdog(&self, interval: Duration) -> anyhow::Result<Receiver<WatchdogEvent>> {
+        Ok(watchdog::spawn(self.socket_path()?, interval))
+    }
+
+    /// Send the given number of pings over this client's connection one after another,
+    /// for measuring the latency and throughput of the transport.
+    pub fn benchmark(&self, n_requests: u32) -> anyhow::Result<BenchmarkReport> {
+        let mut latencies = Vec::with_capacity(n_requests as usize);
+        let start = Instant::now();
+        for _ in 0..n_requests {
+            let request_start = Instant::now();
+            self.make_request::<Pong>(Request::Ping)?.inner()?;
+            latencies.push(request_start.elapsed());
+        }
+        BenchmarkReport::from_latencies(&latencies, start.elapsed())
+            .context("At least one request is needed for a benchmark")
+    }
+
+    /// Ask the daemon to report how long it takes to handle each request made through this client, see `last_processing_time`
+    pub fn set_response_timing(&self, enabled: bool) -> anyhow::Result<()> {
+        self.make_request::<()>(Request::SetResponseTiming { enabled })?
+            .inner()?;
+        self.response_timing.set(enabled);
+        self.last_processing_time.set(None);
+        Ok(())
+    }
+
+    /// Ask the daemon to compress the responses on this connection, which reduces the size of large responses
+    /// (such as snapshots) at the cost of some processing time. This is not useful on the local socket.
+    pub fn set_response_compression(&self, enabled: bool) -> anyhow::Result<()> {
+        self.make_request::<()>(Request::SetResponseCompression { enabled })?
+            .inner()?;
+        self.response_compression.set(enabled);
+        Ok(())
+    }
+
+    /// How long the daemon took to handle the last request, when response timing is enabled.
+    /// Comparing it with the total latency shows whether a slow request is caused by the socket or by the daemon (such as slow sysfs reads).
+    pub fn last_processing_time(&self) -> Option<Duration> {
+        self.last_processing_time.get()
+    }
+
+    /// Take the warnings of the requests which succeeded with caveats (such as when a reboot is needed) since the last call,
+    /// for showing them to the user without treating them as errors.
+    /// Only the last `MAX_BUFFERED_WARNINGS` are kept when they are not taken.
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.take()
+    }
+
+    /// Poll the stats of a GPU every `interval` from a background thread, yielding them as they arrive.
+    /// The polling uses a separate connection, which is re-established after yielding the error if it gets lost.
+    /// The background thread stops when the returned iterator is dropped.
+    pub fn stats_stream(
+        &self,
+        id: &str,
+        interval: Duration,
+    ) -> anyhow::Result<impl Iterator<Item = anyhow::Result<DeviceStats>>> {
+        let rx = stats_stream::spawn(self.socket_path()?, id.to_owned(), interval);
+        Ok(rx.into_iter())
+    }
+
+    /// Receive the log events of the daemon up to the given level, for showing them without access to the system journal.
+    /// The logs are read over a separate connection, which is closed when the returned receiver is dropped.
+    pub fn subscribe_logs(&self, level: LogLevel) -> anyhow::Result<Receiver<LogRecord>> {
+        subscription::subscribe(&self.socket_path()?, &Request::SubscribeLogs { level })
+    }
+
+    /// Receive the stats, logs and device events selected in the mask over a single connection,
+    /// which is closed when the returned receiver is dropped.
+    pub fn subscribe(&self, events: EventMask) -> anyhow::Result<Receiver<DaemonEvent>> {
+        subscription::subscribe(&self.socket_path()?, &Request::Subscribe { events })
+    }
+
+    fn socket_path(&self) -> anyhow::Result<PathBuf> {
+        let stream_guard = self.stream.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        let peer_addr = stream_guard
+            .1
+            .peer_addr()
+            .context("Could not read peer address")?;
+        let path = peer_addr
+            .as_pathname()
+            .context("Connected socket addr is not a path")?;
+        Ok(path.to_owned())
+    }
+
+    /// Runs the given request function, sending it again if the connection was lost while it was being made.
+    ///
+    /// Requests that modify state are not replayed automatically after a reconnect, use this to opt into it
+    /// when sending the same request twice is known to be safe.
+    pub fn with_replay<T>(&self, f: impl Fn(&Self) -> anyhow::Result<T>) -> anyhow::Result<T> {
+        match f(self) {
+            Err(err)
+                if err.downcast_ref::<ClientError>()
+                    == Some(&ClientError::ConnectionLostDuringWrite) =>
+            {
+                info!("replaying request after reconnect");
+                f(self)
+            }
+            result => result,
+        }
     }
 
     pub fn list_devices<'a>(&self) -> anyhow::Result<ResponseBuffer<Vec<DeviceListEntry<'a>>>> {
@@ -111,7 +478,10 @@ impl DaemonClient {
         mode: Option<FanControlMode>,
         static_speed: Option<f64>,
         curve: Option<FanCurveMap>,
-    ) -> anyhow::Result<u64> {
+    ) -> anyhow::Result<AppliedChange> {
+        if let Some(curve) = &curve {
+            validate_fan_curve(curve).map_err(|err| anyhow!("Invalid fan curve: {err}"))?;
+        }
         self.make_request(Request::SetFanControl {
             id,
             enabled,
@@ -122,27 +492,234 @@ impl DaemonClient {
         .inner()
     }
 
-    pub fn set_power_cap(&self, id: &str, cap: Option<f64>) -> anyhow::Result<u64> {
+    /// Set the temperature sensor used by the fan curve. If `gpu_id` is specified, the sensor of that GPU is used.
+    pub fn set_fan_temperature_source(
+        &self,
+        id: &str,
+        gpu_id: Option<&str>,
+        sensor: &str,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetFanTemperatureSource { id, gpu_id, sensor })?
+            .inner()
+    }
+
+    /// Shift the trigger temperatures of the fan curve by the given amount of degrees
+    pub fn set_fan_curve_offset(
+        &self,
+        id: &str,
+        temp_offset: i32,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetFanCurveOffset { id, temp_offset })?
+            .inner()
+    }
+
+    /// `None` removes the spindown curve, so the fan follows the main curve in both directions
+    pub fn set_fan_spindown_curve(
+        &self,
+        id: &str,
+        curve: Option<FanCurveMap>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetFanSpindownCurve { id, curve })?
+            .inner()
+    }
+
+    /// Make the fan curve see the given temperature for a while, `None` goes back to the real temperature
+    pub fn set_simulated_temperature(
+        &self,
+        id: &str,
+        temp: Option<f32>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetSimulatedTemperature { id, temp })?
+            .inner()
+    }
+
+    /// Let the firmware control the fan for `duration`, then restore the configured fan control.
+    /// This blocks until the fan control was restored, and returns the time when it happened.
+    pub fn suspend_fan_control(&self, id: &str, duration: Duration) -> anyhow::Result<u64> {
+        self.make_request(Request::SuspendFanControl {
+            id,
+            duration: duration.as_secs(),
+        })?
+        .inner()
+    }
+
+    /// Set the power cap in watts. Use `set_power_cap_microwatts` to set the exact value used by the driver.
+    pub fn set_power_cap(&self, id: &str, cap: Option<f64>) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::SetPowerCap { id, cap })?.inner()
     }
 
+    pub fn set_power_tier(&self, id: &str, tier: PowerTier) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetPowerTier { id, tier })?
+            .inner()
+    }
+
+    pub fn set_power_cap_microwatts(
+        &self,
+        id: &str,
+        cap: Option<u64>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetPowerCapMicrowatts { id, cap })?
+            .inner()
+    }
+
     request_plain!(get_system_info, SystemInfo, SystemInfo);
-    request_plain!(enable_overdrive, EnableOverdrive, ());
-    request_with_id!(get_device_info, DeviceInfo, DeviceInfo);
+    request_plain!(get_total_power, GetTotalPower, TotalPower);
+    request_plain!(get_system_power, GetSystemPower, SystemPower);
+    request_plain!(get_fleet_summary, GetFleetSummary, FleetSummary);
+    request_plain!(self_test, SelfTest, SelfTestReport);
+
+    /// Why a control can't be used on the GPU, for explaining disabled controls to the user
+    pub fn explain_control(&self, id: &str, control: Control) -> anyhow::Result<ControlStatus> {
+        self.make_request(Request::ExplainControl { id, control })?
+            .inner()
+    }
+
+    /// Enable overdrive in the kernel module options. The change only takes effect after a reboot.
+    pub fn enable_overdrive(&self) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::EnableOverdrive)?.inner()
+    }
+
+    /// Enable or disable memory error correction. The change only takes effect after a reboot.
+    /// The mode is set for the amdgpu driver, so it also changes for the other AMD GPUs in the system.
+    pub fn set_ecc_mode(&self, id: &str, enabled: bool) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetEccMode { id, enabled })?
+            .inner()
+    }
+
+    request_with_id!(get_retired_pages, GetRetiredPages, Vec<RetiredPage>);
+    request_with_id!(get_reset_methods, GetResetMethods, ResetMethods);
+    request_with_id!(get_audio_device, GetAudioDevice, AudioDeviceInfo);
+    request_with_id!(get_effective_config, GetEffectiveConfig, EffectiveConfig);
+    request_with_id!(
+        get_tuning_suggestions,
+        GetTuningSuggestions,
+        TuningSuggestions
+    );
+    request_with_id!(get_interconnect, GetInterconnect, InterconnectInfo);
+
+    pub fn set_audio_pinned_active(&self, id: &str, pinned: bool) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetAudioPinnedActive { id, pinned })?
+            .inner()
+    }
+    request_with_id!(get_aspm_policy, GetAspmPolicy, AspmInfo);
+
+    /// Set the system-wide PCIe ASPM policy until the next reboot.
+    /// The policy applies to every PCIe device, `id` has to be a GPU managed by LACT.
+    pub fn set_aspm_policy(&self, id: &str, policy: AspmPolicy) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetAspmPolicy { id, policy })?
+            .inner()
+    }
+
+    /// Reset the GPU with the given method from `get_reset_methods`.
+    /// Resetting stops all applications using the GPU, which is only done when `confirm` is set.
+    /// The GPU which was used for the display on boot can't be reset.
+    pub fn reset_gpu(
+        &self,
+        id: &str,
+        method: ResetMethod,
+        confirm: bool,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::ResetGpu {
+            id,
+            method,
+            confirm,
+        })?
+        .inner()
+    }
+
+    /// Clear the retired pages table of the GPU. `count` has to be the number of pages from `get_retired_pages`.
+    pub fn clear_retired_pages(&self, id: &str, count: usize) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::ClearRetiredPages { id, count })?
+            .inner()
+    }
+
+    /// Served from the cache when it is enabled with `enable_device_info_cache`
+    pub fn get_device_info(&self, id: &str) -> anyhow::Result<ResponseBuffer<DeviceInfo<'_>>> {
+        let Some(cache) = &self.device_info_cache else {
+            return self.make_request(Request::DeviceInfo { id });
+        };
+
+        if let Some(buf) = cache.borrow().get(id) {
+            return Ok(ResponseBuffer {
+                buf: buf.clone(),
+                _phantom: PhantomData,
+            });
+        }
+
+        let response = self.make_request(Request::DeviceInfo { id })?;
+        // Errors are not cached, the GPU may become available later
+        if response.inner().is_ok() {
+            cache
+                .borrow_mut()
+                .insert(id.to_owned(), response.buf.clone());
+        }
+        Ok(response)
+    }
+    request_with_id!(get_device_snapshot, GetDeviceSnapshot, DeviceSnapshot);
+    request_with_id!(get_smu_metrics, GetSmuMetrics, SmuMetrics);
+    request_with_id!(
+        get_controllable_nodes,
+        GetControllableNodes,
+        Vec<ControllableNode>
+    );
     request_with_id!(get_device_stats, DeviceStats, DeviceStats);
+    request_with_id!(get_power_tiers, GetPowerTiers, PowerTiers);
+    request_with_id!(get_default_fan_curve, GetDefaultFanCurve, DefaultFanCurve);
+    request_with_id!(get_fan_control_log, GetFanControlLog, Vec<FanDecision>);
+    request_with_id!(get_quick_stats, GetQuickStats, QuickStats);
+    request_with_id!(
+        get_read_failures,
+        GetReadFailures,
+        BTreeMap<String, ReadFailureCount>
+    );
+    request_with_id!(get_stats_line_protocol, GetStatsLineProtocol, String);
     request_with_id!(get_device_clocks_info, DeviceClocksInfo, ClocksInfo);
+    request_with_id!(get_voltage_curve, GetVoltageCurve, VoltageCurve);
     request_with_id!(
         get_device_power_profile_modes,
         DevicePowerProfileModes,
         PowerProfileModesTable
     );
+    request_with_id!(
+        get_power_profile_mode_details,
+        GetPowerProfileModeDetails,
+        PowerProfileModesDetails
+    );
     request_with_id!(get_power_states, GetPowerStates, PowerStates);
+    request_with_id!(get_performance_level, GetPerformanceLevel, PerformanceLevel);
+
+    /// Get the value that would actually be applied when setting the given clocks value
+    pub fn snap_clock(&self, id: &str, domain: ClockDomain, value: i32) -> anyhow::Result<i32> {
+        self.make_request(Request::SnapClock { id, domain, value })?
+            .inner()
+    }
+    request_with_id!(get_pp_features, GetPpFeatures, Vec<PpFeature>);
+
+    /// Enable or disable a powerplay feature. The change is not saved and does not survive a reboot.
+    pub fn set_pp_feature(
+        &self,
+        id: &str,
+        feature: &str,
+        enabled: bool,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetPpFeature {
+            id,
+            feature,
+            enabled,
+        })?
+        .inner()
+    }
+
+    /// Enable or disable GFXOFF through its powerplay feature. Like other powerplay features, the change is not saved.
+    pub fn set_gfxoff(&self, id: &str, enabled: bool) -> anyhow::Result<AppliedChange> {
+        self.set_pp_feature(id, "GFXOFF", enabled)
+    }
 
     pub fn set_performance_level(
         &self,
         id: &str,
         performance_level: PerformanceLevel,
-    ) -> anyhow::Result<u64> {
+    ) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::SetPerformanceLevel {
             id,
             performance_level,
@@ -150,7 +727,11 @@ impl DaemonClient {
         .inner()
     }
 
-    pub fn set_clocks_value(&self, id: &str, command: SetClocksCommand) -> anyhow::Result<u64> {
+    pub fn set_clocks_value(
+        &self,
+        id: &str,
+        command: SetClocksCommand,
+    ) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::SetClocksValue { id, command })?
             .inner()
     }
@@ -159,54 +740,433 @@ impl DaemonClient {
         &self,
         id: &str,
         commands: Vec<SetClocksCommand>,
-    ) -> anyhow::Result<u64> {
+    ) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::BatchSetClocksValue { id, commands })?
             .inner()
     }
 
+    /// Move a maximum clock to the target value in steps of `step` MHz, waiting `interval` on each step.
+    /// This blocks until the target is reached, after which it has to be confirmed like with `set_clocks_value`.
+    /// The progress can be followed in the `clocks_ramp` stats from another connection.
+    pub fn set_clocks_ramped(
+        &self,
+        id: &str,
+        target: SetClocksCommand,
+        step: i32,
+        interval: Duration,
+        abort_on_throttling: bool,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetClocksRamped {
+            id,
+            target,
+            step,
+            interval: interval.as_secs(),
+            abort_on_throttling,
+        })?
+        .inner()
+    }
+
+    pub fn set_clocks_value_confirmed(
+        &self,
+        id: &str,
+        command: SetClocksCommand,
+    ) -> anyhow::Result<AppliedChange> {
+        self.apply_confirmed(Request::SetClocksValue { id, command })
+    }
+
+    pub fn batch_set_clocks_value_confirmed(
+        &self,
+        id: &str,
+        commands: Vec<SetClocksCommand>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.apply_confirmed(Request::BatchSetClocksValue { id, commands })
+    }
+
+    pub fn set_power_cap_confirmed(
+        &self,
+        id: &str,
+        cap: Option<f64>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.apply_confirmed(Request::SetPowerCap { id, cap })
+    }
+
+    pub fn set_performance_level_confirmed(
+        &self,
+        id: &str,
+        performance_level: PerformanceLevel,
+    ) -> anyhow::Result<AppliedChange> {
+        self.apply_confirmed(Request::SetPerformanceLevel {
+            id,
+            performance_level,
+        })
+    }
+
+    pub fn set_power_profile_mode_confirmed(
+        &self,
+        id: &str,
+        index: Option<u16>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.apply_confirmed(Request::SetPowerProfileMode { id, index })
+    }
+
+    /// Apply a settings request and confirm it in the same round trip.
+    ///
+    /// This skips the window in which the daemon reverts the change when it is not confirmed,
+    /// so settings which make the system unstable (such as an unstable overclock) stay applied and get saved.
+    /// Only use it for values that are known to work, interactive clients should confirm changes separately.
+    pub fn apply_confirmed(&self, request: Request) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::ApplyConfirmed(Box::new(request)))?
+            .inner()
+    }
+
     pub fn set_enabled_power_states(
         &self,
         id: &str,
         kind: PowerLevelKind,
         states: Vec<u8>,
-    ) -> anyhow::Result<u64> {
+    ) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::SetEnabledPowerStates { id, kind, states })?
             .inner()
     }
 
-    pub fn set_power_profile_mode(&self, id: &str, index: Option<u16>) -> anyhow::Result<u64> {
+    pub fn set_power_profile_mode(
+        &self,
+        id: &str,
+        index: Option<u16>,
+    ) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::SetPowerProfileMode { id, index })?
             .inner()
     }
 
-    pub fn confirm_pending_config(&self, command: ConfirmCommand) -> anyhow::Result<()> {
+    /// Enable or disable LACT management of the GPU.
+    /// When disabled, the settings of the GPU are reset and it is left alone for other tools to control.
+    pub fn set_managed(&self, id: &str, managed: bool) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetManaged { id, managed })?
+            .inner()
+    }
+
+    /// Give the GPU a nickname, which is returned in the device list. An empty label removes it.
+    pub fn set_device_label(&self, id: &str, label: &str) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetDeviceLabel { id, label })?
+            .inner()
+    }
+
+    /// Define a group of GPUs, whose name can then be used in place of a GPU id when changing settings.
+    /// Changes made to a group are confirmed right away. An empty list of ids deletes the group.
+    pub fn define_group(&self, name: &str, ids: Vec<String>) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::DefineGroup { name, ids })?
+            .inner()
+    }
+
+    /// Set the power profile mode by its name (such as "COMPUTE" or "VR"), which is resolved by the daemon
+    pub fn set_power_profile_mode_by_name(
+        &self,
+        id: &str,
+        name: &str,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetPowerProfileModeByName { id, name })?
+            .inner()
+    }
+
+    pub fn set_adaptive_clocks(
+        &self,
+        id: &str,
+        adaptive_clocks: Option<AdaptiveClocks>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetAdaptiveClocks {
+            id,
+            adaptive_clocks,
+        })?
+        .inner()
+    }
+
+    pub fn set_memory_clock_boost(&self, id: &str, enabled: bool) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetMemoryClockBoost { id, enabled })?
+            .inner()
+    }
+
+    /// Replace all settings of a GPU, which then have to be confirmed once like a single change
+    pub fn apply_config(
+        &self,
+        id: &str,
+        config: serde_json::Value,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::ApplyDeviceConfig {
+            id,
+            config,
+            expected_device: None,
+        })?
+        .inner()
+    }
+
+    /// Apply settings imported from another tool with `import`, like `apply_config`.
+    /// Relative settings are resolved against the current clocks and the default power cap of the GPU.
+    pub fn apply_imported(
+        &self,
+        id: &str,
+        imported: &import::ImportedSettings,
+    ) -> anyhow::Result<AppliedChange> {
+        let relative = imported.relative;
+        let clocks_info =
+            if relative.core_clock_offset.is_some() || relative.memory_clock_offset.is_some() {
+                Some(self.get_device_clocks_info(id)?.inner()?)
+            } else {
+                None
+            };
+        let default_power_cap = if relative.power_limit_percent.is_some() {
+            self.get_device_stats(id)?.inner()?.power.cap_default
+        } else {
+            None
+        };
+
+        let config = imported.resolve(
+            clocks_info.as_ref().and_then(|info| info.max_sclk),
+            clocks_info.as_ref().and_then(|info| info.max_mclk),
+            default_power_cap,
+        );
+        self.apply_config(id, config)
+    }
+
+    /// Same as `apply_config`, but the daemon refuses the settings when the GPU is not the expected card.
+    /// This should be used for settings shared between users, which can be unsafe on other cards.
+    pub fn apply_config_for_device(
+        &self,
+        id: &str,
+        config: serde_json::Value,
+        expected_device: DeviceMatch,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::ApplyDeviceConfig {
+            id,
+            config,
+            expected_device: Some(expected_device),
+        })?
+        .inner()
+    }
+
+    /// Replace all settings of a GPU like `apply_config`, then sample the stats until the temperature, clock and power draw
+    /// stop changing or `stabilize_secs` elapse, for judging the stability of an overclock.
+    /// The change is confirmed right away, as waiting for the stats would outlast the confirmation timeout.
+    pub fn apply_and_wait(
+        &self,
+        id: &str,
+        config: serde_json::Value,
+        stabilize_secs: u64,
+    ) -> anyhow::Result<StabilizedStats> {
+        let change = self.apply_config(id, config)?;
+        if change.confirm_timeout.is_some() {
+            self.confirm()?;
+        }
+
+        let start = Instant::now();
+        let timeout = Duration::from_secs(stabilize_secs);
+        let mut previous = self.get_device_stats(id)?.inner()?;
+        let mut stable_samples = 0;
+
+        loop {
+            let elapsed = start.elapsed();
+            if stable_samples >= STABLE_SAMPLES || elapsed >= timeout {
+                return Ok(StabilizedStats {
+                    stats: previous,
+                    stable: stable_samples >= STABLE_SAMPLES,
+                    elapsed,
+                });
+            }
+
+            std::thread::sleep(STABILIZE_SAMPLE_INTERVAL);
+            let current = self.get_device_stats(id)?.inner()?;
+            if stabilize::is_stable(&previous, &current) {
+                stable_samples += 1;
+            } else {
+                stable_samples = 0;
+            }
+            previous = current;
+        }
+    }
+
+    /// List the shared presets that match the card model of the GPU
+    pub fn list_presets_for(&self, id: &str) -> anyhow::Result<Vec<PresetInfo>> {
+        self.make_request(Request::ListPresets { id })?.inner()
+    }
+
+    /// Apply one of the presets from `list_presets_for`, which then has to be confirmed like `apply_config`
+    pub fn apply_preset(&self, id: &str, name: &str) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::ApplyPreset { id, name })?
+            .inner()
+    }
+
+    /// Apply the hardware state from before LACT first managed the GPU, which then has to be confirmed like `apply_config`
+    pub fn restore_factory_baseline(&self, id: &str) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::RestoreFactoryBaseline { id })?
+            .inner()
+    }
+
+    pub fn confirm_pending_config(&self, command: ConfirmCommand) -> anyhow::Result<AppliedChange> {
         self.make_request(Request::ConfirmPendingConfig(command))?
             .inner()
     }
+
+    /// Confirm the pending settings change, which saves it
+    pub fn confirm(&self) -> anyhow::Result<AppliedChange> {
+        self.confirm_pending_config(ConfirmCommand::Confirm)
+    }
+
+    /// Revert the pending settings change right away, instead of waiting for it to time out
+    pub fn revert(&self) -> anyhow::Result<AppliedChange> {
+        self.confirm_pending_config(ConfirmCommand::Revert)
+    }
+
+    /// Revert the GPU to its last confirmed settings if it has a pending change, doing nothing otherwise
+    pub fn discard_all_pending(&self, id: &str) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::DiscardAllPending { id })?
+            .inner()
+    }
+
+    /// Get the settings change which is waiting for a confirmation, `None` if nothing is pending
+    pub fn pending_config_status(&self) -> anyhow::Result<Option<PendingConfigStatus>> {
+        self.make_request(Request::GetPendingConfig)?.inner()
+    }
+
+    request_plain!(list_schedules, ListSchedules, Vec<Schedule>);
+    request_plain!(list_profiles, ListProfiles, Vec<String>);
+
+    /// Save the current settings of all GPUs as a profile, replacing the GPU settings of the profile if it already exists
+    pub fn save_profile(&self, name: &str) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SaveProfile { name })?.inner()
+    }
+
+    pub fn diff_profile(&self, id: &str, name: &str) -> anyhow::Result<Vec<SettingsDiff>> {
+        self.make_request(Request::DiffProfile { id, name })?
+            .inner()
+    }
+
+    /// Apply settings between the two profiles by `factor` (0.0 to 1.0), which then have to be confirmed like `apply_config`
+    pub fn blend_profiles(
+        &self,
+        id: &str,
+        from: &str,
+        to: &str,
+        factor: f64,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::BlendProfiles {
+            id,
+            from,
+            to,
+            factor,
+        })?
+        .inner()
+    }
+
+    /// Load the model sections of a profile on all managed GPUs, with the result for each GPU
+    pub fn load_profile_all(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<BTreeMap<String, ProfileLoadResult>> {
+        self.make_request(Request::LoadProfileAll { name })?.inner()
+    }
+
+    request_with_id!(get_gpu_processes, GetGpuProcesses, Vec<GpuProcess>);
+    request_with_id!(
+        get_settings_history,
+        GetSettingsHistory,
+        BTreeMap<String, SettingChange>
+    );
+
+    /// Terminate a process that is using one of the GPUs
+    pub fn kill_gpu_process(&self, pid: i32) -> anyhow::Result<()> {
+        self.make_request(Request::KillGpuProcess { pid })?.inner()
+    }
+
+    /// Get the full daemon configuration (settings of all GPUs, profiles and schedules), for backing it up
+    pub fn export_config(&self) -> anyhow::Result<serde_json::Value> {
+        self.make_request(Request::GetConfig)?.inner()
+    }
+
+    /// Replace the full daemon configuration with one previously returned by `export_config`.
+    /// The settings are applied immediately, without waiting for a confirmation.
+    pub fn import_config(&self, config: serde_json::Value) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetConfig { config })?.inner()
+    }
+
+    /// Re-read the config file after it was edited by hand, applying the settings from it
+    pub fn reload_config(&self) -> anyhow::Result<ConfigReloadSummary> {
+        self.make_request(Request::ReloadConfig)?.inner()
+    }
+
+    pub fn set_schedule(
+        &self,
+        profile: &str,
+        time_range: TimeRange,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetSchedule {
+            profile,
+            time_range,
+        })?
+        .inner()
+    }
+
+    pub fn delete_schedule(&self, profile: &str) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::DeleteSchedule { profile })?
+            .inner()
+    }
+
+    /// Set the unit in which temperatures should be shown, which is shared between all clients
+    pub fn set_temperature_unit(&self, unit: TemperatureUnit) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetTemperatureUnit { unit })?
+            .inner()
+    }
+
+    pub fn set_power_source_profiles(
+        &self,
+        ac_profile: Option<&str>,
+        battery_profile: Option<&str>,
+    ) -> anyhow::Result<AppliedChange> {
+        self.make_request(Request::SetPowerSourceProfiles {
+            ac_profile,
+            battery_profile,
+        })?
+        .inner()
+    }
 }
 
+/// Finds the socket of the daemon, see `socket_candidates` for the order
 fn get_socket_path() -> Option<PathBuf> {
-    let root_path = PathBuf::from("/var/run/lactd.sock");
+    let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(
+            || PathBuf::from(format!("/var/run/user/{}", getuid())),
+            PathBuf::from,
+        );
 
-    if root_path.exists() {
-        return Some(root_path);
-    }
+    let flatpak_id = env::var_os("FLATPAK_ID").filter(|id| !id.is_empty());
 
-    let uid = getuid();
-    let user_path = PathBuf::from(format!("/var/run/user/{}/lactd.sock", uid));
+    socket_candidates(&runtime_dir, flatpak_id.as_deref())
+        .into_iter()
+        .find(|path| path.exists())
+}
 
-    if user_path.exists() {
-        Some(user_path)
-    } else {
-        None
+/// The system socket comes first, then the socket of a daemon running as the user.
+///
+/// Inside of a Flatpak sandbox the system paths are not available,
+/// so the app's runtime directory (which is shared with the host) is checked as well.
+/// See the sandboxed clients section in `API.md` for how to make the socket available there.
+fn socket_candidates(runtime_dir: &Path, flatpak_id: Option<&OsStr>) -> Vec<PathBuf> {
+    let mut candidates = vec![
+        PathBuf::from("/var/run/lactd.sock"),
+        runtime_dir.join("lactd.sock"),
+    ];
+    if let Some(app_id) = flatpak_id {
+        candidates.push(runtime_dir.join("app").join(app_id).join("lactd.sock"));
     }
+    candidates
 }
 
+#[cfg(feature = "full")]
 pub struct ResponseBuffer<T> {
     buf: String,
     _phantom: PhantomData<T>,
 }
 
+#[cfg(feature = "full")]
 impl<'a, T: Deserialize<'a>> ResponseBuffer<T> {
     pub fn inner(&'a self) -> anyhow::Result<T> {
         let response: Response<T> = serde_json::from_str(&self.buf)
@@ -216,25 +1176,300 @@ impl<'a, T: Deserialize<'a>> ResponseBuffer<T> {
             Response::Error(err) => Err(anyhow!("Got error from daemon: {err}")),
         }
     }
+
+    /// Advisories of a successful response, empty for errors
+    pub fn warnings(&self) -> Vec<String> {
+        // Avoids parsing the response twice when there are no warnings, which is the usual case
+        if !self.buf.contains("\"warnings\"") {
+            return vec![];
+        }
+        serde_json::from_str::<ResponseWarnings>(&self.buf)
+            .map(|warnings| warnings.warnings)
+            .unwrap_or_default()
+    }
 }
 
+#[cfg(feature = "full")]
 fn connect_pair(path: &Path) -> anyhow::Result<(BufReader<UnixStream>, UnixStream)> {
     let stream = UnixStream::connect(path).context("Could not connect to daemon")?;
     let reader = BufReader::new(stream.try_clone()?);
     Ok((reader, stream))
 }
 
+/// Connects to the same socket again, retrying until the daemon is available
+#[cfg(feature = "full")]
+fn reconnect(stream: &UnixStream) -> anyhow::Result<(BufReader<UnixStream>, UnixStream)> {
+    let peer_addr = stream.peer_addr().context("Could not read peer address")?;
+    let path = peer_addr
+        .as_pathname()
+        .context("Connected socket addr is not a path")?;
+
+    let new_connection = loop {
+        match connect_pair(path) {
+            Ok(new_connection) => break new_connection,
+            Err(err) => {
+                error!("Could not reconnect: {err:#}, retrying in {RECONNECT_INTERVAL_MS}ms");
+                std::thread::sleep(Duration::from_millis(RECONNECT_INTERVAL_MS));
+            }
+        }
+    };
+    info!("Established new socket connection");
+    Ok(new_connection)
+}
+
+/// Reads the responses left in the buffer up to the end of the line, so that the next response is read from its start
+#[cfg(feature = "full")]
+fn discard_stale_response(reader: &mut BufReader<UnixStream>) -> anyhow::Result<()> {
+    let previous_timeout = reader.get_ref().read_timeout()?;
+    reader
+        .get_ref()
+        .set_read_timeout(Some(Duration::from_millis(STALE_RESPONSE_TIMEOUT_MS)))?;
+
+    let mut discarded = Vec::new();
+    let mut result = Ok(());
+    while !reader.buffer().is_empty() {
+        discarded.clear();
+        if let Err(err) = reader.read_until(b'\n', &mut discarded) {
+            result = Err(err);
+            break;
+        }
+    }
+
+    reader.get_ref().set_read_timeout(previous_timeout)?;
+    result.context("Could not discard stale response")
+}
+
+#[cfg(feature = "full")]
 fn process_request(
     request: &Request,
     reader: &mut BufReader<UnixStream>,
     writer: &mut UnixStream,
 ) -> anyhow::Result<String> {
+    write_request(request, writer)?;
+
+    let mut response_payload = String::new();
+    if reader.read_line(&mut response_payload)? == 0 {
+        return Err(anyhow!("Connection closed by the daemon"));
+    }
+
+    Ok(response_payload)
+}
+
+/// Same as `process_request`, for connections with compressed responses
+#[cfg(feature = "full")]
+fn process_compressed_request(
+    request: &Request,
+    reader: &mut BufReader<UnixStream>,
+    writer: &mut UnixStream,
+) -> anyhow::Result<String> {
+    write_request(request, writer)?;
+
+    let mut header = String::new();
+    if reader.read_line(&mut header)? == 0 {
+        return Err(anyhow!("Connection closed by the daemon"));
+    }
+    let len = header
+        .trim()
+        .parse()
+        .context("Invalid compressed response header")?;
+    let mut compressed = vec![0; len];
+    reader.read_exact(&mut compressed)?;
+
+    let payload = decompress_to_vec_zlib(&compressed)
+        .map_err(|err| anyhow!("Could not decompress the response: {err}"))?;
+    String::from_utf8(payload).context("Invalid response")
+}
+
+#[cfg(feature = "full")]
+fn write_request(request: &Request, writer: &mut UnixStream) -> anyhow::Result<()> {
     let request_payload = serde_json::to_string(request)?;
     writer.write_all(request_payload.as_bytes())?;
     writer.write_all(b"\n")?;
+    Ok(())
+}
 
-    let mut response_payload = String::new();
-    reader.read_line(&mut response_payload)?;
+#[cfg(all(test, feature = "full"))]
+mod tests {
+    use super::{socket_candidates, ConnectionEvent, DaemonClient, MAX_REQUEST_RETRIES};
+    use miniz_oxide::deflate::compress_to_vec_zlib;
+    use std::{
+        env,
+        ffi::OsStr,
+        fs,
+        io::{BufRead, BufReader, Write},
+        os::unix::net::{UnixListener, UnixStream},
+        path::{Path, PathBuf},
+        thread,
+    };
 
-    Ok(response_payload)
+    #[test]
+    fn compressed_responses() {
+        let path = env::temp_dir().join(format!(
+            "lact-test-client-compression-{}.sock",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let devices: Vec<String> = (0..200)
+            .map(|i| format!(r#"{{"id":"1002:73BF-1002:0E3A-0000:{i:02x}:00.0","name":"Navi 21","runtime_pm_status":null,"temperature":45.0,"managed":true,"configured":false,"driver":"amdgpu","controllable":true}}"#))
+            .collect();
+        let response = format!(r#"{{"status":"ok","data":[{}]}}"#, devices.join(","));
+        let server_response = response.clone();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut next_request = || {
+                request.clear();
+                reader.read_line(&mut request).unwrap();
+                request.clone()
+            };
+
+            next_request();
+            (&stream)
+                .write_all(format!("{server_response}\n").as_bytes())
+                .unwrap();
+
+            assert!(next_request().contains("set_response_compression"));
+            (&stream)
+                .write_all(b"{\"status\":\"ok\",\"data\":null}\n")
+                .unwrap();
+
+            next_request();
+            let compressed = compress_to_vec_zlib(server_response.as_bytes(), 6);
+            assert!(compressed.len() < server_response.len() / 4);
+            (&stream)
+                .write_all(format!("{}\n", compressed.len()).as_bytes())
+                .unwrap();
+            (&stream).write_all(&compressed).unwrap();
+
+            assert!(next_request().contains("set_response_compression"));
+            (&stream)
+                .write_all(b"{\"status\":\"ok\",\"data\":null}\n")
+                .unwrap();
+
+            next_request();
+            (&stream)
+                .write_all(format!("{server_response}\n").as_bytes())
+                .unwrap();
+        });
+
+        let client = DaemonClient::from_stream(UnixStream::connect(&path).unwrap(), false).unwrap();
+        let uncompressed = client.list_devices().unwrap();
+        client.set_response_compression(true).unwrap();
+        let compressed = client.list_devices().unwrap();
+        client.set_response_compression(false).unwrap();
+        let plain = client.list_devices().unwrap();
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let uncompressed = uncompressed.inner().unwrap();
+        assert_eq!(uncompressed.len(), 200);
+        assert_eq!(
+            serde_json::to_value(uncompressed).unwrap(),
+            serde_json::to_value(compressed.inner().unwrap()).unwrap()
+        );
+        assert_eq!(plain.inner().unwrap().len(), 200);
+    }
+
+    #[test]
+    fn reconnect_events() {
+        let path = env::temp_dir().join(format!("lact-test-client-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            // The first connection gets closed right away, like when the daemon restarts
+            drop(listener.accept().unwrap());
+
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            reader.read_line(&mut request).unwrap();
+            (&stream)
+                .write_all(b"{\"status\":\"ok\",\"data\":[]}\n")
+                .unwrap();
+        });
+
+        let client = DaemonClient::from_stream(UnixStream::connect(&path).unwrap(), false).unwrap();
+        let events = client.connection_events();
+        let devices = client.list_devices().unwrap();
+        server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(devices.inner().unwrap().is_empty());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ConnectionEvent::Lost { .. })
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Ok(ConnectionEvent::Reconnected { .. })
+        ));
+    }
+
+    #[test]
+    fn retries_exceeded() {
+        let path = env::temp_dir().join(format!(
+            "lact-test-client-retries-{}.sock",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            // Every connection gets closed after receiving the request, until the test stops the server
+            let mut requests = 0;
+            loop {
+                let (stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                BufReader::new(stream).read_line(&mut request).unwrap();
+                match request.as_str() {
+                    "stop\n" => return requests,
+                    // The client reconnects after the last attempt too, without sending anything
+                    "" => (),
+                    _ => requests += 1,
+                }
+            }
+        });
+
+        let client = DaemonClient::from_stream(UnixStream::connect(&path).unwrap(), false).unwrap();
+        let Err(err) = client.list_devices() else {
+            panic!("request succeeded without a daemon");
+        };
+        drop(client);
+        (&UnixStream::connect(&path).unwrap())
+            .write_all(b"stop\n")
+            .unwrap();
+        let requests = server.join().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // The request is sent once, and again after reconnecting for each retry
+        assert_eq!(requests, 1 + MAX_REQUEST_RETRIES);
+        assert!(err
+            .to_string()
+            .contains(&format!("lost {} times", MAX_REQUEST_RETRIES + 1)));
+    }
+
+    #[test]
+    fn socket_candidate_order() {
+        let runtime_dir = Path::new("/run/user/1000");
+        assert_eq!(
+            socket_candidates(runtime_dir, None),
+            [
+                PathBuf::from("/var/run/lactd.sock"),
+                PathBuf::from("/run/user/1000/lactd.sock"),
+            ]
+        );
+        assert_eq!(
+            socket_candidates(runtime_dir, Some(OsStr::new("io.github.lact-linux"))),
+            [
+                PathBuf::from("/var/run/lactd.sock"),
+                PathBuf::from("/run/user/1000/lactd.sock"),
+                PathBuf::from("/run/user/1000/app/io.github.lact-linux/lactd.sock"),
+            ]
+        );
+    }
 }
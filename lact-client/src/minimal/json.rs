@@ -0,0 +1,292 @@
+//! Small JSON parser for the responses of the daemon, used instead of `serde_json` by the minimal client.
+
+use anyhow::{anyhow, bail, Context};
+use std::{fmt::Write, iter::Peekable, str::Chars};
+
+/// Objects and arrays can't be nested deeper than this, to avoid overflowing the stack on malformed input
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Object entries in the order they were received
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Gets the value of an object entry. Returns `None` if this is not an object or the key is missing.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+pub fn parse(input: &str) -> anyhow::Result<Value> {
+    let mut chars = input.chars().peekable();
+    let value = parse_value(&mut chars, 0)?;
+    skip_whitespace(&mut chars);
+    if let Some(c) = chars.next() {
+        bail!("Unexpected trailing character '{c}'");
+    }
+    Ok(value)
+}
+
+/// Formats a string as a JSON string literal
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn parse_value(chars: &mut Peekable<Chars>, depth: usize) -> anyhow::Result<Value> {
+    if depth > MAX_DEPTH {
+        bail!("Value is nested too deeply");
+    }
+
+    skip_whitespace(chars);
+    match chars.peek().copied() {
+        Some('{') => {
+            chars.next();
+            let mut entries = Vec::new();
+            if consume(chars, '}') {
+                return Ok(Value::Object(entries));
+            }
+            loop {
+                skip_whitespace(chars);
+                expect(chars, '"')?;
+                let key = parse_string(chars)?;
+                expect(chars, ':')?;
+                entries.push((key, parse_value(chars, depth + 1)?));
+                if !consume(chars, ',') {
+                    expect(chars, '}')?;
+                    return Ok(Value::Object(entries));
+                }
+            }
+        }
+        Some('[') => {
+            chars.next();
+            let mut values = Vec::new();
+            if consume(chars, ']') {
+                return Ok(Value::Array(values));
+            }
+            loop {
+                values.push(parse_value(chars, depth + 1)?);
+                if !consume(chars, ',') {
+                    expect(chars, ']')?;
+                    return Ok(Value::Array(values));
+                }
+            }
+        }
+        Some('"') => {
+            chars.next();
+            parse_string(chars).map(Value::String)
+        }
+        Some('t') => parse_literal(chars, "true", Value::Bool(true)),
+        Some('f') => parse_literal(chars, "false", Value::Bool(false)),
+        Some('n') => parse_literal(chars, "null", Value::Null),
+        Some(c) if c == '-' || c.is_ascii_digit() => parse_number(chars),
+        Some(c) => Err(anyhow!("Unexpected character '{c}'")),
+        None => Err(anyhow!("Unexpected end of input")),
+    }
+}
+
+/// Parses the rest of a string, after the opening quote
+fn parse_string(chars: &mut Peekable<Chars>) -> anyhow::Result<String> {
+    let mut value = String::new();
+    loop {
+        match chars.next().context("Unterminated string")? {
+            '"' => return Ok(value),
+            '\\' => match chars.next().context("Unterminated escape sequence")? {
+                '"' => value.push('"'),
+                '\\' => value.push('\\'),
+                '/' => value.push('/'),
+                'b' => value.push('\u{8}'),
+                'f' => value.push('\u{c}'),
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                'u' => {
+                    let mut code = parse_hex_escape(chars)?;
+                    // Characters outside of the basic plane are escaped as a surrogate pair
+                    if (0xD800..0xDC00).contains(&code) {
+                        expect(chars, '\\')?;
+                        expect(chars, 'u')?;
+                        let low = parse_hex_escape(chars)?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            bail!("Invalid surrogate pair");
+                        }
+                        code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    }
+                    value.push(char::from_u32(code).context("Invalid unicode escape")?);
+                }
+                c => bail!("Invalid escape sequence '\\{c}'"),
+            },
+            c => value.push(c),
+        }
+    }
+}
+
+fn parse_hex_escape(chars: &mut Peekable<Chars>) -> anyhow::Result<u32> {
+    let digits: String = chars.take(4).collect();
+    if digits.len() != 4 {
+        bail!("Unterminated unicode escape");
+    }
+    u32::from_str_radix(&digits, 16).context("Invalid unicode escape")
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> anyhow::Result<Value> {
+    let mut number = String::new();
+    while let Some(c) = chars.next_if(|c| matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E')) {
+        number.push(c);
+    }
+    number
+        .parse()
+        .map(Value::Number)
+        .with_context(|| format!("Invalid number '{number}'"))
+}
+
+fn parse_literal(
+    chars: &mut Peekable<Chars>,
+    literal: &str,
+    value: Value,
+) -> anyhow::Result<Value> {
+    for expected in literal.chars() {
+        if chars.next() != Some(expected) {
+            bail!("Invalid literal, expected '{literal}'");
+        }
+    }
+    Ok(value)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_ascii_whitespace()).is_some() {}
+}
+
+/// Consumes the given character if it's the next one after any whitespace
+fn consume(chars: &mut Peekable<Chars>, expected: char) -> bool {
+    skip_whitespace(chars);
+    chars.next_if_eq(&expected).is_some()
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> anyhow::Result<()> {
+    if consume(chars, expected) {
+        Ok(())
+    } else {
+        match chars.peek() {
+            Some(c) => Err(anyhow!("Expected '{expected}', found '{c}'")),
+            None => Err(anyhow!("Expected '{expected}', found end of input")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, quote, Value};
+
+    #[test]
+    fn parse_response() {
+        let value = parse(
+            r#"{"status": "ok", "data": [{"id": "1002:687F", "name": null, "temperature": 45.5, "active": true}]}"#,
+        )
+        .unwrap();
+        assert_eq!(value.get("status").and_then(Value::as_str), Some("ok"));
+
+        let devices = value.get("data").and_then(Value::as_array).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(
+            devices[0].get("id").and_then(Value::as_str),
+            Some("1002:687F")
+        );
+        assert!(devices[0].get("name").unwrap().is_null());
+        assert_eq!(
+            devices[0].get("temperature").and_then(Value::as_f64),
+            Some(45.5)
+        );
+        assert_eq!(
+            devices[0].get("active").and_then(Value::as_bool),
+            Some(true)
+        );
+        assert_eq!(devices[0].get("missing"), None);
+    }
+
+    #[test]
+    fn parse_escapes() {
+        let value = parse(r#""a\"b\\c\n°C 😀""#).unwrap();
+        assert_eq!(value.as_str(), Some("a\"b\\c\n°C 😀"));
+        assert_eq!(parse(&quote("a\"b\\c\n°C 😀")).unwrap(), value);
+        assert_eq!(
+            parse(r#""\u00b0C \ud83d\ude00""#).unwrap().as_str(),
+            Some("°C 😀")
+        );
+    }
+
+    #[test]
+    fn parse_numbers() {
+        assert_eq!(parse("-12").unwrap(), Value::Number(-12.0));
+        assert_eq!(parse("1.5e3").unwrap(), Value::Number(1500.0));
+        assert_eq!(parse("[]").unwrap(), Value::Array(vec![]));
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(parse("{\"status\": }").is_err());
+        assert!(parse("[1, 2").is_err());
+        assert!(parse("\"unterminated").is_err());
+        assert!(parse("nul").is_err());
+        assert!(parse("{} {}").is_err());
+        assert!(parse(&"[".repeat(100)).is_err());
+    }
+}
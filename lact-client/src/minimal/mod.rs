@@ -0,0 +1,98 @@
+//! Client with a minimal set of requests, which doesn't depend on `serde` or the schema crate.
+//!
+//! It is always available, and is the only client when the crate is built without the default `full` feature.
+//! Responses are parsed with a small built-in JSON parser, and the stats are returned as a generic JSON value
+//! using the field names of `DeviceStats` in the schema.
+
+pub mod json;
+
+use crate::get_socket_path;
+use anyhow::{anyhow, Context};
+use json::{quote, Value};
+use std::{
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+};
+
+pub struct MinimalClient {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceEntry {
+    pub id: String,
+    pub name: Option<String>,
+}
+
+impl MinimalClient {
+    pub fn connect() -> anyhow::Result<Self> {
+        let path = get_socket_path().context("Could not connect to daemon: socket not found")?;
+        let stream = UnixStream::connect(path).context("Could not connect to daemon")?;
+        Self::from_stream(stream)
+    }
+
+    pub fn from_stream(stream: UnixStream) -> anyhow::Result<Self> {
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(Self {
+            reader,
+            writer: stream,
+        })
+    }
+
+    pub fn ping(&mut self) -> anyhow::Result<()> {
+        self.request(r#"{"command":"ping"}"#)?;
+        Ok(())
+    }
+
+    pub fn list_devices(&mut self) -> anyhow::Result<Vec<DeviceEntry>> {
+        let data = self.request(r#"{"command":"list_devices"}"#)?;
+        let entries = data
+            .as_array()
+            .context("Unexpected device list in the response")?;
+
+        entries
+            .iter()
+            .map(|entry| {
+                let id = entry
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .context("Device entry has no id")?;
+                let name = entry.get("name").and_then(Value::as_str);
+                Ok(DeviceEntry {
+                    id: id.to_owned(),
+                    name: name.map(str::to_owned),
+                })
+            })
+            .collect()
+    }
+
+    /// Get the stats of a GPU, such as `temps`, `power` or `busy_percent`
+    pub fn get_device_stats(&mut self, id: &str) -> anyhow::Result<Value> {
+        let payload = format!(
+            r#"{{"command":"device_stats","args":{{"id":{}}}}}"#,
+            quote(id)
+        );
+        self.request(&payload)
+    }
+
+    fn request(&mut self, payload: &str) -> anyhow::Result<Value> {
+        self.writer.write_all(payload.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+
+        let mut response_payload = String::new();
+        self.reader.read_line(&mut response_payload)?;
+
+        let response =
+            json::parse(&response_payload).context("Could not deserialize response from daemon")?;
+        let data = response.get("data").cloned().unwrap_or(Value::Null);
+        match response.get("status").and_then(Value::as_str) {
+            Some("ok") => Ok(data),
+            Some("error") => Err(anyhow!(
+                "Got error from daemon: {}",
+                data.as_str().unwrap_or_default()
+            )),
+            _ => Err(anyhow!("Invalid response from daemon")),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::schema::DeviceStats;
+use std::time::Duration;
+
+/// Largest change of the hottest temperature between samples, in degrees celsius
+const TEMPERATURE_TOLERANCE: f32 = 1.0;
+/// Largest relative change of the core clock and the power draw between samples
+const RELATIVE_TOLERANCE: f64 = 0.03;
+
+/// Stats of a GPU after waiting for them to settle with `apply_and_wait`
+#[derive(Debug, Clone)]
+pub struct StabilizedStats {
+    pub stats: DeviceStats,
+    /// `false` when the stats were still changing once the timeout elapsed
+    pub stable: bool,
+    /// Time spent waiting after the settings were applied
+    pub elapsed: Duration,
+}
+
+/// Whether the temperature, core clock and power draw stayed within the tolerances between two samples.
+/// Values which the GPU doesn't report are ignored.
+pub(crate) fn is_stable(previous: &DeviceStats, current: &DeviceStats) -> bool {
+    let temperature_stable = match (hottest(previous), hottest(current)) {
+        (Some(previous), Some(current)) => (current - previous).abs() <= TEMPERATURE_TOLERANCE,
+        _ => true,
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    let clock = |stats: &DeviceStats| stats.clockspeed.gpu_clockspeed.map(|clock| clock as f64);
+    let power = |stats: &DeviceStats| stats.power.average.or(stats.power.current);
+
+    temperature_stable
+        && within_tolerance(clock(previous), clock(current))
+        && within_tolerance(power(previous), power(current))
+}
+
+fn hottest(stats: &DeviceStats) -> Option<f32> {
+    stats
+        .temps
+        .values()
+        .filter_map(|temp| temp.current)
+        .reduce(f32::max)
+}
+
+fn within_tolerance(previous: Option<f64>, current: Option<f64>) -> bool {
+    match (previous, current) {
+        (Some(previous), Some(current)) => {
+            (current - previous).abs() <= previous.abs() * RELATIVE_TOLERANCE
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_stable;
+    use crate::schema::{amdgpu_sysfs::hw_mon::Temperature, DeviceStats};
+
+    fn stats(temperature: f32, clock: u64, power: f64) -> DeviceStats {
+        let mut stats = DeviceStats::default();
+        stats.temps.insert(
+            "edge".to_owned(),
+            Temperature {
+                current: Some(temperature),
+                crit: None,
+                crit_hyst: None,
+            },
+        );
+        stats.clockspeed.gpu_clockspeed = Some(clock);
+        stats.power.average = Some(power);
+        stats
+    }
+
+    #[test]
+    fn stability() {
+        let previous = stats(70.0, 2500, 250.0);
+        assert!(is_stable(&previous, &stats(70.5, 2480, 255.0)));
+        assert!(!is_stable(&previous, &stats(72.0, 2500, 250.0)));
+        assert!(!is_stable(&previous, &stats(70.0, 2300, 250.0)));
+        assert!(!is_stable(&previous, &stats(70.0, 2500, 200.0)));
+        assert!(is_stable(&DeviceStats::default(), &previous));
+    }
+}
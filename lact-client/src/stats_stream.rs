@@ -0,0 +1,54 @@
+use crate::{
+    connect_pair, process_request,
+    schema::{DeviceStats, Request, Response},
+};
+use anyhow::anyhow;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Polls the stats of a GPU over a separate connection from a background thread.
+/// A failed poll yields an error, and the connection is re-established on the next one.
+/// The thread stops after the receiver is dropped.
+pub(crate) fn spawn(
+    path: PathBuf,
+    id: String,
+    interval: Duration,
+) -> Receiver<anyhow::Result<DeviceStats>> {
+    // Only one frame is buffered, so a slow consumer always gets recent stats instead of a backlog
+    let (tx, rx) = mpsc::sync_channel(1);
+
+    thread::spawn(move || {
+        let mut connection = None;
+
+        loop {
+            let start = Instant::now();
+            let result = connection
+                .take()
+                .map_or_else(|| connect_pair(&path), Ok)
+                .and_then(|mut stream_pair| {
+                    let (reader, writer) = &mut stream_pair;
+                    let payload =
+                        process_request(&Request::DeviceStats { id: &id }, reader, writer)?;
+                    match serde_json::from_str::<Response<DeviceStats>>(&payload)? {
+                        Response::Ok(stats) => Ok((stream_pair, stats)),
+                        Response::Error(err) => Err(anyhow!("Got error from daemon: {err}")),
+                    }
+                })
+                .map(|(stream_pair, stats)| {
+                    connection = Some(stream_pair);
+                    stats
+                });
+
+            if tx.send(result).is_err() {
+                break;
+            }
+            thread::sleep(interval.saturating_sub(start.elapsed()));
+        }
+    });
+
+    rx
+}
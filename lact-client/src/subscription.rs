@@ -0,0 +1,49 @@
+use crate::{
+    connect_pair, process_request,
+    schema::{Request, Response},
+};
+use anyhow::{anyhow, Context};
+use serde::de::DeserializeOwned;
+use std::{
+    io::BufRead,
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+/// Sends a subscription request over a separate connection, reading the items from a background thread.
+/// The thread stops when the connection is closed or after the receiver is dropped.
+pub(crate) fn subscribe<T>(path: &Path, request: &Request) -> anyhow::Result<Receiver<T>>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    let (mut reader, mut writer) = connect_pair(path)?;
+    // Nothing else is sent on the connection, it is closed once the reader is dropped
+    let payload = process_request(request, &mut reader, &mut writer)?;
+    match serde_json::from_str::<Response<()>>(&payload)? {
+        Response::Ok(()) => (),
+        Response::Error(err) => return Err(anyhow!("Got error from daemon: {err}")),
+    }
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let item = line
+                .context("Could not read subscription item")
+                .and_then(|line| Ok(serde_json::from_str::<T>(&line)?));
+            match item {
+                Ok(item) => {
+                    if tx.send(item).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    tracing::error!("stopped receiving from the daemon: {err:#}");
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(rx)
+}
@@ -0,0 +1,63 @@
+use crate::{
+    connect_pair, process_request,
+    schema::{Pong, Request, Response},
+};
+use anyhow::anyhow;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchdogEvent {
+    /// The daemon responded to a ping
+    Pong { latency: Duration },
+    /// The daemon could not be reached or returned an invalid response
+    Failed { error: String },
+}
+
+/// Pings the daemon over a separate connection from a background thread.
+/// The thread stops after the receiver is dropped.
+pub(crate) fn spawn(path: PathBuf, interval: Duration) -> Receiver<WatchdogEvent> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut connection = None;
+
+        loop {
+            let start = Instant::now();
+            let result = connection
+                .take()
+                .map_or_else(|| connect_pair(&path), Ok)
+                .and_then(|mut stream_pair| {
+                    let (reader, writer) = &mut stream_pair;
+                    let payload = process_request(&Request::Ping, reader, writer)?;
+                    match serde_json::from_str::<Response<Pong>>(&payload)? {
+                        Response::Ok(Pong) => Ok(stream_pair),
+                        Response::Error(err) => Err(anyhow!("Got error from daemon: {err}")),
+                    }
+                });
+
+            let event = match result {
+                Ok(stream_pair) => {
+                    connection = Some(stream_pair);
+                    WatchdogEvent::Pong {
+                        latency: start.elapsed(),
+                    }
+                }
+                Err(err) => WatchdogEvent::Failed {
+                    error: format!("{err:#}"),
+                },
+            };
+
+            if tx.send(event).is_err() {
+                break;
+            }
+            thread::sleep(interval);
+        }
+    });
+
+    rx
+}
@@ -1,44 +1,135 @@
 use crate::server::gpu_controller::fan_control::FanCurve;
-use anyhow::Context;
+use anyhow::{anyhow, Context};
 use lact_schema::{
     amdgpu_sysfs::gpu_handle::{PerformanceLevel, PowerLevelKind},
     default_fan_curve,
+    diff::diff_json,
     request::SetClocksCommand,
-    FanControlMode,
+    AdaptiveClocks, ConfigReloadSummary, FanControlMode, PowerSourceProfiles, Schedule,
+    SettingsDiff, TemperatureUnit,
 };
 use nix::unistd::getuid;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 use serde_with::skip_serializing_none;
-use std::{collections::HashMap, env, fs, path::PathBuf};
-use tracing::debug;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env, fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, info};
 
 const FILE_NAME: &str = "config.yaml";
+/// Version of the config format, which is increased whenever existing settings change their layout.
+/// Configs without a version are from before versioning was introduced, and are considered version 1.
+pub const CONFIG_VERSION: u64 = 3;
 const DEFAULT_ADMIN_GROUPS: [&str; 2] = ["wheel", "sudo"];
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
+    pub version: u64,
     pub daemon: Daemon,
     #[serde(default = "default_apply_settings_timer")]
     pub apply_settings_timer: u64,
     pub gpus: HashMap<String, Gpu>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default)]
+    pub schedules: Vec<Schedule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub power_source_profiles: Option<PowerSourceProfiles>,
+    /// GPUs which are left alone by LACT, so they can be controlled by other tools
+    #[serde(default)]
+    pub unmanaged_gpus: BTreeSet<String>,
+    /// Named groups of GPU ids, which can be used to change the settings of multiple GPUs at once
+    #[serde(default)]
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Nicknames of GPUs, by GPU id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             daemon: Daemon::default(),
             apply_settings_timer: default_apply_settings_timer(),
             gpus: HashMap::new(),
+            profiles: BTreeMap::new(),
+            schedules: Vec::new(),
+            power_source_profiles: None,
+            unmanaged_gpus: BTreeSet::new(),
+            groups: BTreeMap::new(),
+            labels: BTreeMap::new(),
         }
     }
 }
 
+/// A named set of GPU settings, which take priority over the base `gpus` settings when the profile is active
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Profile {
+    #[serde(default)]
+    pub gpus: HashMap<String, Gpu>,
+    /// Settings for every GPU of a model, keyed by `vendor:device` or `vendor:device-subsystem_vendor:subsystem_device`.
+    /// These are only used when loading the profile on all GPUs at once.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, Gpu>,
+}
+
+impl Profile {
+    /// The model section matching the GPU id along with its key, preferring a section for the exact board over one for the chip
+    pub fn model_settings(&self, gpu_id: &str) -> Option<(&str, &Gpu)> {
+        self.models
+            .iter()
+            .filter(|(model, _)| {
+                gpu_id
+                    .get(..model.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(model))
+                    && gpu_id[model.len()..].starts_with('-')
+            })
+            .max_by_key(|(model, _)| model.len())
+            .map(|(model, settings)| (model.as_str(), settings))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Daemon {
     pub log_level: String,
     pub admin_groups: Vec<String>,
     #[serde(default)]
     pub disable_clocks_cleanup: bool,
+    /// Do not re-apply the settings after the driver resets a GPU
+    #[serde(default)]
+    pub disable_reset_recovery: bool,
+    /// Serve the device list and stats as JSON over HTTP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_stats: Option<HttpStats>,
+    /// Serve the device list and stats on the system D-Bus
+    #[serde(default)]
+    pub dbus_stats: bool,
+    /// Only used for showing temperatures in the clients, the daemon always uses °C
+    #[serde(default)]
+    pub temperature_unit: TemperatureUnit,
+    /// Allow overriding the temperature seen by the fan curve with `SetSimulatedTemperature`, for testing curves
+    #[serde(default)]
+    pub allow_simulated_temperature: bool,
+    /// Stats requests which arrive sooner than this after the last read are served from a cache,
+    /// so that clients polling aggressively don't overload the driver
+    #[serde(default)]
+    pub min_stats_interval_ms: u64,
+    /// Switch to the manual performance level when setting a power profile mode or power states,
+    /// instead of rejecting them while the level is not manual
+    #[serde(default)]
+    pub auto_manual_performance_level: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpStats {
+    /// Address to listen on, only local connections are accepted by default
+    #[serde(default = "default_http_stats_address")]
+    pub address: String,
 }
 
 impl Default for Daemon {
@@ -47,6 +138,13 @@ impl Default for Daemon {
             log_level: "info".to_owned(),
             admin_groups: DEFAULT_ADMIN_GROUPS.map(str::to_owned).to_vec(),
             disable_clocks_cleanup: false,
+            disable_reset_recovery: false,
+            http_stats: None,
+            dbus_stats: false,
+            temperature_unit: TemperatureUnit::default(),
+            allow_simulated_temperature: false,
+            min_stats_interval_ms: 0,
+            auto_manual_performance_level: false,
         }
     }
 }
@@ -56,13 +154,24 @@ impl Default for Daemon {
 pub struct Gpu {
     pub fan_control_enabled: bool,
     pub fan_control_settings: Option<FanControlSettings>,
-    pub power_cap: Option<f64>,
+    /// Power cap in microwatts, the unit used by the driver
+    pub power_cap_microwatts: Option<u64>,
     pub performance_level: Option<PerformanceLevel>,
-    #[serde(default, flatten)]
+    #[serde(default)]
     pub clocks_configuration: ClocksConfiguration,
     pub power_profile_mode_index: Option<u16>,
     #[serde(default)]
     pub power_states: HashMap<PowerLevelKind, Vec<u8>>,
+    pub adaptive_clocks: Option<AdaptiveClocks>,
+    pub memory_clock_boost: Option<MemoryClockBoost>,
+}
+
+/// Forces the highest memory clock while a game is using the GPU
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct MemoryClockBoost {
+    /// Process names which count as games, in addition to Windows games running through Wine or Proton
+    #[serde(default)]
+    pub processes: Vec<String>,
 }
 
 #[skip_serializing_none]
@@ -82,6 +191,28 @@ impl Gpu {
         self.clocks_configuration != ClocksConfiguration::default()
     }
 
+    /// Power profile modes and power states only have an effect with the manual performance level
+    pub fn requires_manual_performance_level(&self) -> bool {
+        self.power_profile_mode_index.is_some() || !self.power_states.is_empty()
+    }
+
+    /// Switches to the manual performance level when it is required by the other settings,
+    /// returns whether the level was changed
+    pub fn ensure_manual_performance_level(&mut self) -> bool {
+        let switch = self.requires_manual_performance_level()
+            && self.performance_level != Some(PerformanceLevel::Manual);
+        if switch {
+            self.performance_level = Some(PerformanceLevel::Manual);
+        }
+        switch
+    }
+
+    /// Power states can only be configured with the manual performance level
+    fn select_power_state(&mut self, kind: PowerLevelKind, state: u8) {
+        self.performance_level = Some(PerformanceLevel::Manual);
+        self.power_states.insert(kind, vec![state]);
+    }
+
     pub fn apply_clocks_command(&mut self, command: &SetClocksCommand) {
         let clocks = &mut self.clocks_configuration;
         match command {
@@ -92,6 +223,12 @@ impl Gpu {
             SetClocksCommand::MinMemoryClock(clock) => clocks.min_memory_clock = Some(*clock),
             SetClocksCommand::MinVoltage(voltage) => clocks.min_voltage = Some(*voltage),
             SetClocksCommand::VoltageOffset(offset) => clocks.voltage_offset = Some(*offset),
+            SetClocksCommand::CoreState(state) => {
+                self.select_power_state(PowerLevelKind::CoreClock, *state);
+            }
+            SetClocksCommand::MemoryState(state) => {
+                self.select_power_state(PowerLevelKind::MemoryClock, *state);
+            }
             SetClocksCommand::Reset => {
                 *clocks = ClocksConfiguration::default();
                 assert!(!self.is_core_clocks_used());
@@ -107,8 +244,18 @@ pub struct FanControlSettings {
     #[serde(default = "default_fan_static_speed")]
     pub static_speed: f64,
     pub temperature_key: String,
+    /// Id of another GPU to read the temperature sensor from, for when its heat affects this GPU
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_source: Option<String>,
     pub interval_ms: u64,
     pub curve: FanCurve,
+    /// Shifts the temperatures of the curve points by this many degrees.
+    /// A negative offset makes the fan ramp up earlier.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub temperature_offset: i32,
+    /// Curve used while the fan slows down, see `fan_control::spindown_pwm`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spindown_curve: Option<FanCurve>,
 }
 
 impl Default for FanControlSettings {
@@ -117,8 +264,11 @@ impl Default for FanControlSettings {
             mode: FanControlMode::default(),
             static_speed: default_fan_static_speed(),
             temperature_key: "edge".to_owned(),
+            temperature_source: None,
             interval_ms: 500,
             curve: FanCurve(default_fan_curve()),
+            temperature_offset: 0,
+            spindown_curve: None,
         }
     }
 }
@@ -131,9 +281,21 @@ impl Config {
     pub fn load() -> anyhow::Result<Option<Self>> {
         let path = get_path();
         if path.exists() {
-            let raw_config = fs::read_to_string(path).context("Could not open config file")?;
-            let config =
-                serde_yaml::from_str(&raw_config).context("Could not deserialize config")?;
+            let raw_config = fs::read_to_string(&path).context("Could not open config file")?;
+            let raw_value: serde_yaml::Value =
+                serde_yaml::from_str(&raw_config).context("Could not parse config")?;
+            let mut value = serde_json::to_value(raw_value).context("Could not parse config")?;
+
+            let original_version = migrate(&mut value)?;
+            let config: Self =
+                serde_json::from_value(value).context("Could not deserialize config")?;
+
+            if original_version != CONFIG_VERSION {
+                let backup_path = path.with_extension(format!("yaml.v{original_version}"));
+                info!("migrated config from version {original_version}, saving the previous one to {backup_path:?}");
+                fs::write(backup_path, raw_config).context("Could not back up config")?;
+                config.save()?;
+            }
             Ok(Some(config))
         } else {
             let parent = path.parent().unwrap();
@@ -149,6 +311,72 @@ impl Config {
         fs::write(path, raw_config).context("Could not write config")
     }
 
+    /// Settings of all GPUs, with the settings from the given profile taking priority over the base ones
+    pub fn gpus_with_profile(&self, profile: Option<&str>) -> HashMap<String, Gpu> {
+        let mut gpus = self.gpus.clone();
+        if let Some(profile) = profile.and_then(|name| self.profiles.get(name)) {
+            gpus.extend(profile.gpus.clone());
+        }
+        gpus
+    }
+
+    /// Checks the consistency of values which can't be expressed in the config types
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.apply_settings_timer == 0 {
+            return Err(anyhow!(
+                "The apply settings timer has to be at least 1 second"
+            ));
+        }
+
+        let all_gpus = self.gpus.iter().chain(
+            self.profiles
+                .values()
+                .flat_map(|profile| profile.gpus.iter().chain(&profile.models)),
+        );
+        for (id, gpu) in all_gpus {
+            if let Some(settings) = &gpu.fan_control_settings {
+                settings
+                    .curve
+                    .validate()
+                    .with_context(|| format!("Invalid settings for GPU {id}"))?;
+            }
+        }
+
+        let mut scheduled_profiles = BTreeSet::new();
+        for schedule in &self.schedules {
+            if !self.profiles.contains_key(&schedule.profile) {
+                return Err(anyhow!(
+                    "Schedule references profile `{}` which does not exist",
+                    schedule.profile
+                ));
+            }
+            if !scheduled_profiles.insert(schedule.profile.as_str()) {
+                return Err(anyhow!(
+                    "Profile `{}` has more than one schedule",
+                    schedule.profile
+                ));
+            }
+            if schedule.time_range.start == schedule.time_range.end {
+                return Err(anyhow!("Schedule start and end times must be different"));
+            }
+        }
+
+        if let Some(profiles) = &self.power_source_profiles {
+            let power_source_profiles = profiles.ac_profile.iter().chain(&profiles.battery_profile);
+            for profile in power_source_profiles {
+                if !self.profiles.contains_key(profile) {
+                    return Err(anyhow!("Power source profile `{profile}` does not exist"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_managed(&self, id: &str) -> bool {
+        !self.unmanaged_gpus.contains(id)
+    }
+
     pub fn load_or_create() -> anyhow::Result<Self> {
         if let Some(config) = Config::load()? {
             Ok(config)
@@ -160,6 +388,222 @@ impl Config {
     }
 }
 
+/// Upgrades a config in place to the current format, returning the version it had before
+pub fn migrate(config: &mut Value) -> anyhow::Result<u64> {
+    let original_version = match config.get("version") {
+        Some(version) => version.as_u64().context("Invalid config version")?,
+        None => 1,
+    };
+    if original_version > CONFIG_VERSION {
+        return Err(anyhow!(
+            "Config version {original_version} is newer than the supported version {CONFIG_VERSION}"
+        ));
+    }
+
+    if original_version < 2 {
+        for_each_gpu(config, nest_clocks_settings);
+    }
+    if original_version < 3 {
+        for_each_gpu(config, convert_power_cap);
+    }
+
+    if let Some(map) = config.as_object_mut() {
+        map.insert("version".to_owned(), CONFIG_VERSION.into());
+    }
+    Ok(original_version)
+}
+
+/// Upgrades the settings of a single GPU written for any older config version.
+/// Used for presets, which have no version of their own; every step leaves already migrated settings untouched.
+pub fn migrate_gpu_settings(gpu: &mut serde_json::Map<String, Value>) {
+    nest_clocks_settings(gpu);
+    convert_power_cap(gpu);
+}
+
+/// Runs a migration on the settings of every GPU, both in the main section and in the profiles
+fn for_each_gpu(config: &mut Value, f: fn(&mut serde_json::Map<String, Value>)) {
+    let migrate_gpus = |gpus: &mut Value| {
+        if let Some(gpus) = gpus.as_object_mut() {
+            for gpu in gpus.values_mut().filter_map(Value::as_object_mut) {
+                f(gpu);
+            }
+        }
+    };
+
+    if let Some(gpus) = config.get_mut("gpus") {
+        migrate_gpus(gpus);
+    }
+    if let Some(Value::Object(profiles)) = config.get_mut("profiles") {
+        for profile in profiles.values_mut() {
+            if let Some(gpus) = profile.get_mut("gpus") {
+                migrate_gpus(gpus);
+            }
+        }
+    }
+}
+
+/// Version 1 had the clocks settings directly in the GPU section, they are now grouped in `clocks_configuration`
+fn nest_clocks_settings(gpu: &mut serde_json::Map<String, Value>) {
+    const CLOCKS_KEYS: [&str; 7] = [
+        "min_core_clock",
+        "min_memory_clock",
+        "min_voltage",
+        "max_core_clock",
+        "max_memory_clock",
+        "max_voltage",
+        "voltage_offset",
+    ];
+
+    let clocks: serde_json::Map<String, Value> = CLOCKS_KEYS
+        .iter()
+        .filter_map(|key| gpu.remove(*key).map(|value| ((*key).to_owned(), value)))
+        .collect();
+    if !clocks.is_empty() {
+        gpu.insert("clocks_configuration".to_owned(), Value::Object(clocks));
+    }
+}
+
+/// Version 2 stored the power cap in watts as a float, it is now stored in microwatts to avoid rounding errors
+fn convert_power_cap(gpu: &mut serde_json::Map<String, Value>) {
+    if let Some(watts) = gpu.remove("power_cap").as_ref().and_then(Value::as_f64) {
+        gpu.insert(
+            "power_cap_microwatts".to_owned(),
+            watts_to_microwatts(watts).into(),
+        );
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn watts_to_microwatts(watts: f64) -> u64 {
+    (watts.max(0.0) * 1_000_000.0).round() as u64
+}
+
+/// Summarizes the differences between two configs, comparing the GPU settings with the given active profiles
+pub fn summarize_changes(
+    previous: &Config,
+    previous_profile: Option<&str>,
+    new: &Config,
+    new_profile: Option<&str>,
+) -> anyhow::Result<ConfigReloadSummary> {
+    let previous_gpus = previous.gpus_with_profile(previous_profile);
+    let new_gpus = new.gpus_with_profile(new_profile);
+
+    let ids: BTreeSet<&String> = previous_gpus.keys().chain(new_gpus.keys()).collect();
+    let mut gpus = BTreeMap::new();
+    for id in ids {
+        let diffs = diff_gpu_settings(
+            &previous_gpus.get(id).cloned().unwrap_or_default(),
+            &new_gpus.get(id).cloned().unwrap_or_default(),
+        )?;
+        if !diffs.is_empty() {
+            gpus.insert(
+                id.clone(),
+                diffs.into_iter().map(|diff| diff.field).collect(),
+            );
+        }
+    }
+
+    Ok(ConfigReloadSummary {
+        gpus,
+        profiles_changed: previous.profiles != new.profiles,
+        schedules_changed: previous.schedules != new.schedules
+            || previous.power_source_profiles != new.power_source_profiles,
+        other_changed: previous.daemon != new.daemon
+            || previous.apply_settings_timer != new.apply_settings_timer
+            || previous.unmanaged_gpus != new.unmanaged_gpus
+            || previous.groups != new.groups
+            || previous.labels != new.labels,
+    })
+}
+
+/// Lists the settings which differ between two GPU configurations
+pub fn diff_gpu_settings(current: &Gpu, other: &Gpu) -> anyhow::Result<Vec<SettingsDiff>> {
+    let current = serde_json::to_value(current)?;
+    let other = serde_json::to_value(other)?;
+    let diffs = diff_json(&current, &other)
+        .into_iter()
+        .map(|diff| SettingsDiff {
+            field: diff.field,
+            current: diff.before,
+            profile: diff.after,
+        })
+        .collect();
+    Ok(diffs)
+}
+
+/// Interpolates between two GPU configurations by `factor`, where 0.0 is `from` and 1.0 is `to`.
+/// Numbers are interpolated (integers are rounded), while other values and values set on only one side switch at 0.5.
+pub fn blend_gpu_settings(from: &Gpu, to: &Gpu, factor: f64) -> anyhow::Result<Value> {
+    if !(0.0..=1.0).contains(&factor) {
+        return Err(anyhow!("The blend factor has to be between 0.0 and 1.0"));
+    }
+    let from = serde_json::to_value(from)?;
+    let to = serde_json::to_value(to)?;
+    Ok(blend_json(&from, &to, factor))
+}
+
+fn blend_json(from: &Value, to: &Value, factor: f64) -> Value {
+    match (from, to) {
+        (Value::Number(from), Value::Number(to)) => blend_number(from, to, factor),
+        (Value::Object(from), Value::Object(to)) => {
+            let keys: BTreeSet<&String> = from.keys().chain(to.keys()).collect();
+            let blended: Map<String, Value> = keys
+                .into_iter()
+                .filter_map(|key| {
+                    let value = match (from.get(key), to.get(key)) {
+                        (Some(from), Some(to)) => blend_json(from, to, factor),
+                        (from, to) => snap(from, to, factor)?.clone(),
+                    };
+                    Some((key.clone(), value))
+                })
+                .collect();
+            Value::Object(blended)
+        }
+        _ => snap(from, to, factor).clone(),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn blend_number(from: &Number, to: &Number, factor: f64) -> Value {
+    let (Some(start), Some(end)) = (from.as_f64(), to.as_f64()) else {
+        return Value::Number(snap(from, to, factor).clone());
+    };
+    let blended = start + (end - start) * factor;
+
+    if from.is_u64() && to.is_u64() {
+        Value::from(blended.round() as u64)
+    } else if from.is_i64() && to.is_i64() {
+        Value::from(blended.round() as i64)
+    } else {
+        Number::from_f64(blended).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn snap<T>(from: T, to: T, factor: f64) -> T {
+    if factor < 0.5 {
+        from
+    } else {
+        to
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    get_path()
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// See `server::baseline`
+pub fn baselines_path() -> PathBuf {
+    get_path().with_file_name("baselines.yaml")
+}
+
+/// Shared settings presets are stored next to the config file
+pub fn presets_dir() -> PathBuf {
+    get_path().with_file_name("presets")
+}
+
 fn get_path() -> PathBuf {
     let uid = getuid();
     if uid.is_root() {
@@ -177,13 +621,29 @@ fn default_apply_settings_timer() -> u64 {
     5
 }
 
+fn default_http_stats_address() -> String {
+    "127.0.0.1:12853".to_owned()
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_zero(value: &i32) -> bool {
+    *value == 0
+}
+
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{BTreeMap, HashMap};
 
-    use lact_schema::FanControlMode;
+    use lact_schema::{
+        amdgpu_sysfs::gpu_handle::{PerformanceLevel, PowerLevelKind},
+        request::SetClocksCommand,
+        DayTime, FanControlMode, PowerSourceProfiles, Schedule, TimeRange,
+    };
 
-    use super::{ClocksConfiguration, Config, Daemon, FanControlSettings, Gpu};
+    use super::{
+        blend_gpu_settings, diff_gpu_settings, migrate, summarize_changes, watts_to_microwatts,
+        ClocksConfiguration, Config, Daemon, FanControlSettings, Gpu, Profile, CONFIG_VERSION,
+    };
     use crate::server::gpu_controller::fan_control::FanCurve;
 
     #[test]
@@ -196,7 +656,10 @@ mod tests {
                     fan_control_enabled: true,
                     fan_control_settings: Some(FanControlSettings {
                         curve: FanCurve::default(),
+                        temperature_offset: 0,
+                        spindown_curve: None,
                         temperature_key: "edge".to_owned(),
+                        temperature_source: None,
                         interval_ms: 500,
                         mode: FanControlMode::Curve,
                         static_speed: 0.5,
@@ -217,15 +680,312 @@ mod tests {
         let mut gpu = Gpu {
             fan_control_enabled: false,
             fan_control_settings: None,
-            power_cap: None,
+            power_cap_microwatts: None,
             performance_level: None,
             clocks_configuration: ClocksConfiguration::default(),
             power_profile_mode_index: None,
             power_states: HashMap::new(),
+            adaptive_clocks: None,
+            memory_clock_boost: None,
         };
 
         assert!(!gpu.is_core_clocks_used());
         gpu.clocks_configuration.voltage_offset = Some(10);
         assert!(gpu.is_core_clocks_used());
     }
+
+    #[test]
+    fn validate_schedules() {
+        let mut config = Config {
+            schedules: vec![Schedule {
+                profile: "night".to_owned(),
+                time_range: TimeRange {
+                    start: DayTime {
+                        hour: 22,
+                        minute: 0,
+                    },
+                    end: DayTime { hour: 6, minute: 0 },
+                },
+            }],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        config
+            .profiles
+            .insert("night".to_owned(), Profile::default());
+        config.validate().unwrap();
+
+        config.power_source_profiles = Some(PowerSourceProfiles {
+            ac_profile: None,
+            battery_profile: Some("battery".to_owned()),
+        });
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn blend_settings() {
+        let settings = |power_cap, level, static_speed, voltage_offset| Gpu {
+            power_cap_microwatts: Some(power_cap),
+            performance_level: Some(level),
+            fan_control_settings: Some(FanControlSettings {
+                static_speed,
+                ..Default::default()
+            }),
+            clocks_configuration: ClocksConfiguration {
+                voltage_offset,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let quiet = settings(200_000_000, PerformanceLevel::Auto, 0.25, Some(-50));
+        let performance = settings(250_000_000, PerformanceLevel::High, 0.75, None);
+
+        let blended: Gpu =
+            serde_json::from_value(blend_gpu_settings(&quiet, &performance, 0.25).unwrap())
+                .unwrap();
+        assert_eq!(blended.power_cap_microwatts, Some(212_500_000));
+        assert_eq!(blended.performance_level, Some(PerformanceLevel::Auto));
+        let static_speed = blended.fan_control_settings.unwrap().static_speed;
+        assert!((static_speed - 0.375).abs() < f64::EPSILON);
+        assert_eq!(blended.clocks_configuration.voltage_offset, Some(-50));
+
+        let blended: Gpu =
+            serde_json::from_value(blend_gpu_settings(&quiet, &performance, 0.5).unwrap()).unwrap();
+        assert_eq!(blended.power_cap_microwatts, Some(225_000_000));
+        assert_eq!(blended.performance_level, Some(PerformanceLevel::High));
+        assert_eq!(blended.clocks_configuration.voltage_offset, None);
+
+        assert_eq!(
+            serde_json::from_value::<Gpu>(blend_gpu_settings(&quiet, &performance, 1.0).unwrap())
+                .unwrap(),
+            performance
+        );
+        assert!(blend_gpu_settings(&quiet, &performance, 1.5).is_err());
+    }
+
+    #[test]
+    fn profile_model_settings() {
+        let settings = |power_cap| Gpu {
+            power_cap_microwatts: Some(power_cap),
+            ..Default::default()
+        };
+        let profile = Profile {
+            models: BTreeMap::from([
+                ("1002:73BF".to_owned(), settings(200_000_000)),
+                ("1002:73bf-1002:0E3A".to_owned(), settings(250_000_000)),
+                ("1002:73B".to_owned(), settings(100_000_000)),
+            ]),
+            ..Default::default()
+        };
+
+        let (model, gpu) = profile
+            .model_settings("1002:73BF-1002:0E3A-0000:0c:00.0")
+            .unwrap();
+        assert_eq!(model, "1002:73bf-1002:0E3A");
+        assert_eq!(gpu.power_cap_microwatts, Some(250_000_000));
+
+        let (model, _) = profile
+            .model_settings("1002:73BF-1849:5217-0000:0d:00.0")
+            .unwrap();
+        assert_eq!(model, "1002:73BF");
+        assert!(profile
+            .model_settings("1002:744C-1002:0E3B-0000:03:00.0")
+            .is_none());
+    }
+
+    #[test]
+    fn diff_settings() {
+        let current = Gpu {
+            power_cap_microwatts: Some(200_000_000),
+            fan_control_settings: Some(FanControlSettings::default()),
+            ..Default::default()
+        };
+        let mut other = current.clone();
+        other.power_cap_microwatts = None;
+        other.clocks_configuration.max_core_clock = Some(2000);
+        other.fan_control_settings.as_mut().unwrap().interval_ms = 1000;
+
+        let diffs = diff_gpu_settings(&current, &other).unwrap();
+        let fields: Vec<&str> = diffs.iter().map(|diff| diff.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "clocks_configuration.max_core_clock",
+                "fan_control_settings.interval_ms",
+                "power_cap_microwatts"
+            ]
+        );
+        assert_eq!(diffs[0].current, None);
+        assert_eq!(diffs[0].profile, Some(2000.into()));
+        assert!(diff_gpu_settings(&current, &current).unwrap().is_empty());
+    }
+
+    #[test]
+    fn migrate_v1() {
+        let raw = r"
+daemon:
+  log_level: info
+  admin_groups:
+  - wheel
+  disable_clocks_cleanup: false
+apply_settings_timer: 5
+gpus:
+  1002:73BF-1002:0E3A-0000:0c:00.0:
+    fan_control_enabled: true
+    fan_control_settings:
+      mode: curve
+      static_speed: 0.5
+      temperature_key: edge
+      interval_ms: 500
+      curve:
+        40: 0.2
+        80: 1.0
+    power_cap: 255.0
+    max_core_clock: 2600
+    voltage_offset: -50
+profiles:
+  quiet:
+    gpus:
+      1002:73BF-1002:0E3A-0000:0c:00.0:
+        fan_control_enabled: false
+        max_memory_clock: 1000
+";
+        let raw_value: serde_yaml::Value = serde_yaml::from_str(raw).unwrap();
+        let mut value = serde_json::to_value(raw_value).unwrap();
+        assert_eq!(migrate(&mut value).unwrap(), 1);
+        let config: Config = serde_json::from_value(value).unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        let gpu = &config.gpus["1002:73BF-1002:0E3A-0000:0c:00.0"];
+        assert_eq!(gpu.power_cap_microwatts, Some(255_000_000));
+        assert_eq!(
+            gpu.clocks_configuration,
+            ClocksConfiguration {
+                max_core_clock: Some(2600),
+                voltage_offset: Some(-50),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            gpu.fan_control_settings.as_ref().unwrap().curve.0,
+            [(40, 0.2), (80, 1.0)].into()
+        );
+
+        let profile_gpu = &config.profiles["quiet"].gpus["1002:73BF-1002:0E3A-0000:0c:00.0"];
+        assert_eq!(
+            profile_gpu.clocks_configuration.max_memory_clock,
+            Some(1000)
+        );
+    }
+
+    #[test]
+    fn migrate_v2_power_cap() {
+        let mut value = serde_json::json!({
+            "version": 2,
+            "gpus": {
+                "my-gpu": { "fan_control_enabled": false, "power_cap": 212.3 }
+            },
+            "profiles": {
+                "quiet": {
+                    "gpus": {
+                        "my-gpu": { "fan_control_enabled": false, "power_cap": 150.000_001 }
+                    }
+                }
+            }
+        });
+        assert_eq!(migrate(&mut value).unwrap(), 2);
+        assert_eq!(value["gpus"]["my-gpu"]["power_cap_microwatts"], 212_300_000);
+        assert_eq!(
+            value["profiles"]["quiet"]["gpus"]["my-gpu"]["power_cap_microwatts"],
+            150_000_001
+        );
+        assert!(value["gpus"]["my-gpu"].get("power_cap").is_none());
+    }
+
+    #[test]
+    fn power_cap_round_trip() {
+        // Values read from the driver in watts convert back to the same microwatts
+        for microwatts in (0..400_000_000).step_by(12_345_679) {
+            #[allow(clippy::cast_precision_loss)]
+            let watts = microwatts as f64 / 1_000_000.0;
+            assert_eq!(watts_to_microwatts(watts), microwatts);
+        }
+        assert_eq!(watts_to_microwatts(212.3), 212_300_000);
+        assert_eq!(watts_to_microwatts(-5.0), 0);
+    }
+
+    #[test]
+    fn power_state_selected() {
+        let mut gpu = Gpu::default();
+        gpu.apply_clocks_command(&SetClocksCommand::CoreState(2));
+        assert_eq!(gpu.performance_level, Some(PerformanceLevel::Manual));
+        assert_eq!(gpu.power_states[&PowerLevelKind::CoreClock], [2]);
+        assert!(!gpu.is_core_clocks_used());
+    }
+
+    #[test]
+    fn manual_performance_level_ensured() {
+        let mut gpu = Gpu {
+            performance_level: Some(PerformanceLevel::Auto),
+            ..Default::default()
+        };
+        assert!(!gpu.ensure_manual_performance_level());
+        assert_eq!(gpu.performance_level, Some(PerformanceLevel::Auto));
+
+        gpu.power_profile_mode_index = Some(1);
+        assert!(gpu.requires_manual_performance_level());
+        assert!(gpu.ensure_manual_performance_level());
+        assert_eq!(gpu.performance_level, Some(PerformanceLevel::Manual));
+        assert!(!gpu.ensure_manual_performance_level());
+    }
+
+    #[test]
+    fn migrate_current_and_future() {
+        let mut value = serde_json::to_value(Config::default()).unwrap();
+        let expected = value.clone();
+        assert_eq!(migrate(&mut value).unwrap(), CONFIG_VERSION);
+        assert_eq!(value, expected);
+
+        let mut value = serde_json::json!({ "version": CONFIG_VERSION + 1 });
+        assert!(migrate(&mut value).is_err());
+    }
+
+    #[test]
+    fn reload_summary() {
+        let previous = Config {
+            gpus: HashMap::from([(
+                "my-gpu".to_owned(),
+                Gpu {
+                    power_cap_microwatts: Some(200_000_000),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        };
+        let mut new = previous.clone();
+        new.profiles.insert(
+            "quiet".to_owned(),
+            Profile {
+                gpus: HashMap::from([(
+                    "my-gpu".to_owned(),
+                    Gpu {
+                        power_cap_microwatts: Some(150_000_000),
+                        ..Default::default()
+                    },
+                )]),
+                ..Default::default()
+            },
+        );
+        new.apply_settings_timer += 1;
+
+        let summary = summarize_changes(&previous, None, &new, None).unwrap();
+        assert!(summary.gpus.is_empty());
+        assert!(summary.profiles_changed);
+        assert!(!summary.schedules_changed);
+        assert!(summary.other_changed);
+
+        let summary = summarize_changes(&previous, None, &new, Some("quiet")).unwrap();
+        assert_eq!(summary.gpus["my-gpu"], ["power_cap_microwatts"]);
+    }
 }
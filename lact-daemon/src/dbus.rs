@@ -0,0 +1,84 @@
+use crate::server::handler::Handler;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+use zbus::{dbus_interface, fdo, ConnectionBuilder};
+
+const BUS_NAME: &str = "io.github.lact_linux";
+const OBJECT_PATH: &str = "/io/github/lact_linux";
+
+enum Query {
+    ListDevices,
+    Stats(String),
+}
+
+type QueryMessage = (Query, oneshot::Sender<fdo::Result<String>>);
+
+/// The D-Bus object server runs its interfaces on other tasks, which can't access the handler directly.
+/// The queries are instead forwarded over a channel to a local task that answers them.
+struct StatsInterface {
+    tx: mpsc::Sender<QueryMessage>,
+}
+
+impl StatsInterface {
+    async fn query(&self, query: Query) -> fdo::Result<String> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.tx
+            .send((query, response_tx))
+            .await
+            .map_err(|_| fdo::Error::Failed("Daemon is shutting down".to_owned()))?;
+        response_rx
+            .await
+            .map_err(|_| fdo::Error::Failed("Query was dropped".to_owned()))?
+    }
+}
+
+/// Read-only access to the devices and their stats. The values are returned as JSON in the same format as the socket API.
+#[dbus_interface(name = "io.github.lact_linux.Stats")]
+impl StatsInterface {
+    async fn list_devices(&self) -> fdo::Result<String> {
+        self.query(Query::ListDevices).await
+    }
+
+    async fn get_stats(&self, id: String) -> fdo::Result<String> {
+        self.query(Query::Stats(id)).await
+    }
+}
+
+/// Serves the device list and stats on the system bus
+pub async fn run(handler: Handler) {
+    let (tx, mut rx) = mpsc::channel::<QueryMessage>(16);
+
+    let connection = match Box::pin(async {
+        ConnectionBuilder::system()?
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, StatsInterface { tx })?
+            .build()
+            .await
+    })
+    .await
+    {
+        Ok(connection) => connection,
+        Err(err) => {
+            error!("could not register dbus service: {err}");
+            return;
+        }
+    };
+    info!("serving stats on dbus as {BUS_NAME}");
+
+    while let Some((query, response_tx)) = rx.recv().await {
+        let response = match query {
+            Query::ListDevices => to_json(&handler.list_devices()),
+            Query::Stats(id) => handler
+                .get_gpu_stats(&id)
+                .map_err(|err| fdo::Error::Failed(format!("{err:#}")))
+                .and_then(|stats| to_json(&stats)),
+        };
+        let _ = response_tx.send(response);
+    }
+
+    drop(connection);
+}
+
+fn to_json<T: serde::Serialize>(data: &T) -> fdo::Result<String> {
+    serde_json::to_string(data).map_err(|err| fdo::Error::Failed(err.to_string()))
+}
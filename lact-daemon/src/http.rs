@@ -0,0 +1,175 @@
+use crate::server::handler::Handler;
+use anyhow::{anyhow, Context};
+use std::{collections::BTreeMap, time::Duration};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+use tracing::{debug, error, info};
+
+/// Clients which don't send the whole request head in this time are disconnected
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest accepted request or header line, in bytes
+const MAX_LINE_LENGTH: u64 = 8192;
+const MAX_HEADERS: usize = 100;
+
+/// Serves a read-only JSON view of the devices and their stats over plain HTTP
+pub async fn run(handler: Handler, address: String) {
+    let listener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("could not bind http stats endpoint to {address}: {err}");
+            return;
+        }
+    };
+    info!("serving http stats on {address}");
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                debug!("http connection from {peer}");
+                let handler = handler.clone();
+                tokio::task::spawn_local(async move {
+                    if let Err(err) = handle_connection(stream, &handler).await {
+                        debug!("http connection error: {err}");
+                    }
+                });
+            }
+            Err(err) => error!("failed to accept http connection: {err}"),
+        }
+    }
+}
+
+async fn handle_connection(stream: TcpStream, handler: &Handler) -> anyhow::Result<()> {
+    let mut stream = BufReader::new(stream);
+
+    let request_line = timeout(READ_TIMEOUT, read_head(&mut stream))
+        .await
+        .context("Timed out reading the request")??;
+
+    let (status, body) = match parse_request_line(&request_line) {
+        Some(("GET", "/stats")) => json_body(&all_stats(handler)),
+        Some(("GET", "/devices")) => json_body(&handler.list_devices()),
+        Some(("GET", _)) => ("404 Not Found", b"{\"error\":\"not found\"}".to_vec()),
+        Some(_) => (
+            "405 Method Not Allowed",
+            b"{\"error\":\"method not allowed\"}".to_vec(),
+        ),
+        None => ("400 Bad Request", b"{\"error\":\"bad request\"}".to_vec()),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await?;
+
+    Ok(())
+}
+
+/// Reads the request line and the headers, returning only the request line
+async fn read_head(stream: &mut (impl AsyncBufRead + Unpin)) -> anyhow::Result<String> {
+    let mut request_line = String::new();
+    read_line(stream, &mut request_line).await?;
+
+    // The headers are not used, but have to be read before responding
+    let mut header = String::new();
+    for _ in 0..MAX_HEADERS {
+        header.clear();
+        if read_line(stream, &mut header).await? == 0 || header.trim_end().is_empty() {
+            return Ok(request_line);
+        }
+    }
+    Err(anyhow!("Too many headers"))
+}
+
+async fn read_line(
+    stream: &mut (impl AsyncBufRead + Unpin),
+    buf: &mut String,
+) -> anyhow::Result<usize> {
+    let read = stream.take(MAX_LINE_LENGTH).read_line(buf).await?;
+    if read as u64 == MAX_LINE_LENGTH && !buf.ends_with('\n') {
+        return Err(anyhow!("Request line is too long"));
+    }
+    Ok(read)
+}
+
+fn all_stats(handler: &Handler) -> BTreeMap<&str, lact_schema::DeviceStats> {
+    handler
+        .gpu_controllers
+        .keys()
+        .filter_map(|id| match handler.get_gpu_stats(id) {
+            Ok(stats) => Some((id.as_str(), stats)),
+            Err(err) => {
+                error!("could not get stats of gpu {id}: {err:#}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn json_body<T: serde::Serialize>(data: &T) -> (&'static str, Vec<u8>) {
+    match serde_json::to_vec(data) {
+        Ok(body) => ("200 OK", body),
+        Err(err) => {
+            error!("could not serialize http response: {err}");
+            (
+                "500 Internal Server Error",
+                b"{\"error\":\"internal error\"}".to_vec(),
+            )
+        }
+    }
+}
+
+/// Returns the method and the path without the query string
+fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+    Some((method, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_request_line, read_head, MAX_LINE_LENGTH};
+
+    #[test]
+    fn request_line() {
+        assert_eq!(
+            parse_request_line("GET /stats HTTP/1.1\r\n"),
+            Some(("GET", "/stats"))
+        );
+        assert_eq!(
+            parse_request_line("GET /devices?pretty=1 HTTP/1.0\r\n"),
+            Some(("GET", "/devices"))
+        );
+        assert_eq!(parse_request_line("GET /stats\r\n"), None);
+        assert_eq!(parse_request_line(""), None);
+    }
+
+    #[tokio::test]
+    async fn request_head_limits() {
+        let request = b"GET /stats HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(
+            read_head(&mut &request[..]).await.unwrap(),
+            "GET /stats HTTP/1.1\r\n"
+        );
+
+        let long_header = format!(
+            "GET /stats HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+            "a".repeat(usize::try_from(MAX_LINE_LENGTH).unwrap())
+        );
+        assert!(read_head(&mut long_header.as_bytes()).await.is_err());
+
+        let many_headers = format!("GET /stats HTTP/1.1\r\n{}\r\n", "X-A: b\r\n".repeat(200));
+        assert!(read_head(&mut many_headers.as_bytes()).await.is_err());
+    }
+}
@@ -2,10 +2,18 @@
 #![allow(clippy::missing_panics_doc)]
 
 mod config;
+mod dbus;
 mod fork;
+mod http;
+mod log_stream;
+mod power_source;
+mod reset;
+mod schedule;
 mod server;
 mod socket;
 mod suspend;
+#[cfg(test)]
+mod test_util;
 
 use anyhow::Context;
 use config::Config;
@@ -19,6 +27,7 @@ use tokio::{
     task::LocalSet,
 };
 use tracing::{debug_span, info, Instrument, Level};
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 pub use server::system::MODULE_CONF_PATH;
 
@@ -42,14 +51,30 @@ pub fn run() -> anyhow::Result<()> {
         let config = Config::load_or_create()?;
 
         let max_level = Level::from_str(&config.daemon.log_level).context("Invalid log level")?;
-        tracing_subscriber::fmt().with_max_level(max_level).init();
+        tracing_subscriber::registry()
+            .with(LevelFilter::from_level(max_level))
+            .with(tracing_subscriber::fmt::layer())
+            .with(log_stream::layer())
+            .init();
 
         LocalSet::new()
             .run_until(async move {
+                let http_stats = config.daemon.http_stats.clone();
+                let dbus_stats = config.daemon.dbus_stats;
                 let server = Server::new(config).await?;
                 let handler = server.handler.clone();
 
+                if let Some(http_stats) = http_stats {
+                    tokio::task::spawn_local(http::run(handler.clone(), http_stats.address));
+                }
+                if dbus_stats {
+                    tokio::task::spawn_local(dbus::run(handler.clone()));
+                }
+
                 tokio::task::spawn_local(listen_exit_signals(handler.clone()));
+                tokio::task::spawn_local(schedule::run(handler.clone()));
+                tokio::task::spawn_local(power_source::run(handler.clone()));
+                tokio::task::spawn_local(reset::listen_events(handler.clone()));
                 tokio::task::spawn_local(suspend::listen_events(handler));
                 server.run().await;
                 Ok(())
@@ -0,0 +1,149 @@
+//! Forwarding of the daemon's log events to clients, for troubleshooting without access to the system journal.
+
+use lact_schema::{LogLevel, LogRecord};
+use std::{
+    fmt::{self, Write},
+    sync::OnceLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream,
+    select,
+    sync::broadcast::{self, error::RecvError},
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+/// Records are dropped for subscribers which fall further behind than this
+const CHANNEL_CAPACITY: usize = 256;
+
+static LOG_SENDER: OnceLock<broadcast::Sender<LogRecord>> = OnceLock::new();
+
+/// Tracing layer which sends the log events to the subscribed connections
+pub struct LogLayer {
+    sender: broadcast::Sender<LogRecord>,
+}
+
+pub fn layer() -> LogLayer {
+    let sender = LOG_SENDER
+        .get_or_init(|| broadcast::channel(CHANNEL_CAPACITY).0)
+        .clone();
+    LogLayer { sender }
+}
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if self.sender.receiver_count() == 0 {
+            return;
+        }
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| {
+                u64::try_from(time.as_millis()).unwrap_or(u64::MAX)
+            });
+        let record = LogRecord {
+            timestamp,
+            level: convert_level(*metadata.level()),
+            target: metadata.target().to_owned(),
+            message: visitor.message,
+        };
+        // Sending only fails when all subscribers disconnected in the meantime
+        let _ = self.sender.send(record);
+    }
+}
+
+/// Formats the message of an event, followed by its other fields as `key=value`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if !self.message.is_empty() {
+            self.message.push(' ');
+        }
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.message, "{}={value:?}", field.name());
+        }
+    }
+}
+
+fn convert_level(level: Level) -> LogLevel {
+    match level {
+        Level::ERROR => LogLevel::Error,
+        Level::WARN => LogLevel::Warn,
+        Level::INFO => LogLevel::Info,
+        Level::DEBUG => LogLevel::Debug,
+        Level::TRACE => LogLevel::Trace,
+    }
+}
+
+pub fn subscribe() -> anyhow::Result<broadcast::Receiver<LogRecord>> {
+    Ok(LOG_SENDER
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Log streaming is not available"))?
+        .subscribe())
+}
+
+/// Writes the log records up to the given level to the stream, until the client disconnects.
+/// Only the events enabled by the daemon's `log_level` are available.
+pub async fn forward(stream: &mut BufReader<UnixStream>, level: LogLevel) -> anyhow::Result<()> {
+    let mut receiver = subscribe()?;
+
+    // Clients don't send anything after subscribing, reading is only used to notice when they disconnect
+    let mut buf = String::new();
+    loop {
+        select! {
+            result = receiver.recv() => match result {
+                Ok(record) if record.level <= level => {
+                    let mut line = serde_json::to_vec(&record)?;
+                    line.push(b'\n');
+                    stream.write_all(&line).await?;
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => (),
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            result = stream.read_line(&mut buf) => {
+                if result? == 0 {
+                    return Ok(());
+                }
+                buf.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layer, LOG_SENDER};
+    use lact_schema::LogLevel;
+    use tracing::subscriber::with_default;
+    use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+    #[test]
+    fn record_events() {
+        let subscriber = Registry::default().with(layer());
+        let mut receiver = LOG_SENDER.get().unwrap().subscribe();
+
+        with_default(subscriber, || {
+            tracing::warn!(gpu = "1002:687F", "could not set fan speed");
+        });
+
+        let record = receiver.try_recv().unwrap();
+        assert_eq!(record.level, LogLevel::Warn);
+        assert_eq!(record.message, "could not set fan speed gpu=\"1002:687F\"");
+        assert!(record.target.starts_with("lact_daemon"));
+    }
+}
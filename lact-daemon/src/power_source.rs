@@ -0,0 +1,98 @@
+use crate::server::handler::Handler;
+use lact_schema::PowerSource;
+use std::{fs, time::Duration};
+use tokio::time::sleep;
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+const POWER_SOURCE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+struct PowerSupply {
+    kind: String,
+    scope: Option<String>,
+    online: bool,
+}
+
+pub async fn run(handler: Handler) {
+    loop {
+        handler.set_power_source(current()).await;
+        sleep(POWER_SOURCE_CHECK_INTERVAL).await;
+    }
+}
+
+/// Reads the power source from the power supplies in sysfs. `None` when the system has no battery.
+pub fn current() -> Option<PowerSource> {
+    let supplies: Vec<PowerSupply> = fs::read_dir(POWER_SUPPLY_PATH)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let read = |name: &str| {
+                fs::read_to_string(path.join(name))
+                    .ok()
+                    .map(|value| value.trim().to_owned())
+            };
+            Some(PowerSupply {
+                kind: read("type")?,
+                scope: read("scope"),
+                online: read("online").as_deref() == Some("1"),
+            })
+        })
+        .collect();
+    detect(&supplies)
+}
+
+fn detect(supplies: &[PowerSupply]) -> Option<PowerSource> {
+    // Batteries of peripherals such as wireless mice are reported with the `Device` scope
+    let system_supplies = supplies
+        .iter()
+        .filter(|supply| supply.scope.as_deref() != Some("Device"));
+
+    let mut has_battery = false;
+    let mut on_ac = false;
+    for supply in system_supplies {
+        if supply.kind == "Battery" {
+            has_battery = true;
+        } else if supply.online {
+            on_ac = true;
+        }
+    }
+
+    match (has_battery, on_ac) {
+        (false, _) => None,
+        (true, true) => Some(PowerSource::Ac),
+        (true, false) => Some(PowerSource::Battery),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect, PowerSupply};
+    use lact_schema::PowerSource;
+
+    fn supply(kind: &str, scope: Option<&str>, online: bool) -> PowerSupply {
+        PowerSupply {
+            kind: kind.to_owned(),
+            scope: scope.map(str::to_owned),
+            online,
+        }
+    }
+
+    #[test]
+    fn detect_power_source() {
+        let battery = supply("Battery", Some("System"), false);
+        assert_eq!(
+            detect(&[battery, supply("Mains", None, true)]),
+            Some(PowerSource::Ac)
+        );
+
+        let battery = supply("Battery", None, false);
+        assert_eq!(
+            detect(&[battery, supply("USB", None, false)]),
+            Some(PowerSource::Battery)
+        );
+
+        let mouse = supply("Battery", Some("Device"), false);
+        assert_eq!(detect(&[mouse, supply("Mains", None, true)]), None);
+        assert_eq!(detect(&[]), None);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::server::handler::Handler;
+use nix::{
+    errno::Errno,
+    time::{clock_gettime, ClockId},
+};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+const KMSG_PATH: &str = "/dev/kmsg";
+
+#[derive(Debug, PartialEq, Eq)]
+struct ResetEvent {
+    slot_name: String,
+    /// Kernel log timestamp, in microseconds since boot
+    timestamp_us: u64,
+}
+
+/// Watches the kernel log for amdgpu reset messages.
+/// The log contains records from before the daemon was started, these are counted but don't trigger re-applying the settings.
+pub async fn listen_events(handler: Handler) {
+    let started_at = monotonic_micros().unwrap_or_default();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        if let Err(err) = read_kmsg(&tx) {
+            error!("could not read kernel log: {err}");
+        }
+    });
+
+    while let Some(event) = rx.recv().await {
+        info!("detected reset of GPU {}", event.slot_name);
+        let reapply = event.timestamp_us >= started_at;
+        handler
+            .handle_gpu_reset(
+                &event.slot_name,
+                to_unix_millis(event.timestamp_us),
+                reapply,
+            )
+            .await;
+    }
+    error!("GPU resets will not be detected.");
+}
+
+fn read_kmsg(tx: &mpsc::UnboundedSender<ResetEvent>) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(KMSG_PATH)?);
+    let mut record = String::new();
+
+    loop {
+        record.clear();
+        match reader.read_line(&mut record) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {
+                if let Some(event) = parse_reset_record(&record) {
+                    if tx.send(event).is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            // The records got overwritten in the ring buffer before they could be read
+            Err(err) if err.raw_os_error() == Some(Errno::EPIPE as i32) => (),
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => (),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Parses records such as `6,1234,5678901,-;amdgpu 0000:0b:00.0: amdgpu: GPU reset(2) succeeded!`
+fn parse_reset_record(record: &str) -> Option<ResetEvent> {
+    let (prefix, message) = record.split_once(';')?;
+    let timestamp_us = prefix.split(',').nth(2)?.parse().ok()?;
+
+    let (slot_name, message) = message.strip_prefix("amdgpu ")?.split_once(": ")?;
+    let (_, reset_result) = message.split_once("GPU reset(")?;
+    if !reset_result.contains(") succeeded") {
+        return None;
+    }
+
+    Some(ResetEvent {
+        slot_name: slot_name.to_owned(),
+        timestamp_us,
+    })
+}
+
+fn monotonic_micros() -> Option<u64> {
+    let time = clock_gettime(ClockId::CLOCK_MONOTONIC).ok()?;
+    let secs = u64::try_from(time.tv_sec()).ok()?;
+    let nanos = u64::try_from(time.tv_nsec()).ok()?;
+    Some(secs * 1_000_000 + nanos / 1000)
+}
+
+/// Converts a kernel log timestamp to an approximate unix timestamp in milliseconds
+fn to_unix_millis(timestamp_us: u64) -> Option<u64> {
+    let elapsed_ms = monotonic_micros()?.saturating_sub(timestamp_us) / 1000;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .and_then(|time| u64::try_from(time.as_millis()).ok())?;
+    now_ms.checked_sub(elapsed_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_reset_record, ResetEvent};
+
+    #[test]
+    fn parse_reset_succeeded() {
+        let record = "6,1785,3796452816,-;amdgpu 0000:0b:00.0: amdgpu: GPU reset(2) succeeded!\n";
+        assert_eq!(
+            parse_reset_record(record),
+            Some(ResetEvent {
+                slot_name: "0000:0b:00.0".to_owned(),
+                timestamp_us: 3_796_452_816,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_unrelated_records() {
+        for record in [
+            "3,1780,3796450000,-;amdgpu 0000:0b:00.0: amdgpu: GPU reset begin!",
+            "3,1790,3796460000,-;amdgpu 0000:0b:00.0: amdgpu: GPU reset(3) failed",
+            "6,1791,3796460001,-;usb 1-4: new high-speed USB device number 5 using xhci_hcd",
+            "malformed record",
+        ] {
+            assert_eq!(parse_reset_record(record), None);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::server::handler::Handler;
+use chrono::{Local, Timelike};
+use lact_schema::{DayTime, Schedule};
+use std::time::Duration;
+use tokio::time::sleep;
+
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+const MINUTES_IN_DAY: u16 = 24 * 60;
+
+pub async fn run(handler: Handler) {
+    loop {
+        handler.apply_schedule(current_time()).await;
+        sleep(SCHEDULE_CHECK_INTERVAL).await;
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub fn current_time() -> DayTime {
+    let now = Local::now();
+    DayTime {
+        hour: now.hour() as u8,
+        minute: now.minute() as u8,
+    }
+}
+
+/// Unix timestamp of the next time any of the schedules starts or ends
+pub fn next_transition(schedules: &[Schedule]) -> Option<i64> {
+    let now = Local::now();
+    let minutes = minutes_until_transition(schedules, current_time())?;
+    Some(now.timestamp() - i64::from(now.second()) + i64::from(minutes) * 60)
+}
+
+fn minutes_until_transition(schedules: &[Schedule], time: DayTime) -> Option<u16> {
+    let current_minute = time.minute_of_day();
+
+    schedules
+        .iter()
+        .flat_map(|schedule| [schedule.time_range.start, schedule.time_range.end])
+        .map(|boundary| {
+            match (boundary.minute_of_day() + MINUTES_IN_DAY - current_minute) % MINUTES_IN_DAY {
+                0 => MINUTES_IN_DAY,
+                minutes => minutes,
+            }
+        })
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::minutes_until_transition;
+    use lact_schema::{DayTime, Schedule, TimeRange};
+
+    #[test]
+    fn next_transition() {
+        let time = |s: &str| s.parse::<DayTime>().unwrap();
+        let schedules = [
+            Schedule {
+                profile: "quiet".to_owned(),
+                time_range: TimeRange {
+                    start: time("23:00"),
+                    end: time("07:30"),
+                },
+            },
+            Schedule {
+                profile: "performance".to_owned(),
+                time_range: TimeRange {
+                    start: time("18:00"),
+                    end: time("21:00"),
+                },
+            },
+        ];
+
+        assert_eq!(
+            minutes_until_transition(&schedules, time("12:00")),
+            Some(360)
+        );
+        assert_eq!(
+            minutes_until_transition(&schedules, time("22:00")),
+            Some(60)
+        );
+        assert_eq!(
+            minutes_until_transition(&schedules, time("23:00")),
+            Some(510)
+        );
+        assert_eq!(minutes_until_transition(&[], time("23:00")), None);
+    }
+}
@@ -0,0 +1,78 @@
+//! The hardware state of each GPU from before LACT first managed it, stored in `baselines.yaml` next to the config.
+//! A baseline is only recorded once per GPU, so restarting the daemon doesn't replace it with settings that were applied by LACT.
+//! GPUs which already have settings only get a baseline while their state matches the driver defaults.
+
+use crate::config;
+use anyhow::Context;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fs, io,
+    path::Path,
+};
+use tracing::info;
+
+pub fn load(path: &Path) -> anyhow::Result<BTreeMap<String, config::Gpu>> {
+    match fs::read_to_string(path) {
+        Ok(raw) => serde_yaml::from_str(&raw).context("Could not parse the hardware baselines"),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err).context("Could not read the hardware baselines"),
+    }
+}
+
+/// Stores the baselines of the GPUs which don't have one yet
+pub fn record_missing(
+    path: &Path,
+    current: impl IntoIterator<Item = (String, config::Gpu)>,
+) -> anyhow::Result<()> {
+    let mut baselines = load(path)?;
+    let mut changed = false;
+    for (id, settings) in current {
+        if let Entry::Vacant(entry) = baselines.entry(id) {
+            info!("recorded the hardware baseline of gpu {}", entry.key());
+            entry.insert(settings);
+            changed = true;
+        }
+    }
+
+    if changed {
+        let raw = serde_yaml::to_string(&baselines)?;
+        fs::write(path, raw).context("Could not write the hardware baselines")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load, record_missing};
+    use crate::{config, test_util::TempDir};
+
+    #[test]
+    fn baselines_recorded_once() {
+        let dir = TempDir::new("baselines");
+        let path = dir.join("baselines.yaml");
+        let original = config::Gpu {
+            power_cap_microwatts: Some(250_000_000),
+            ..Default::default()
+        };
+        let changed = config::Gpu {
+            power_cap_microwatts: Some(200_000_000),
+            ..Default::default()
+        };
+
+        let empty = load(&path).unwrap();
+        record_missing(&path, [("gpu-1".to_owned(), original.clone())]).unwrap();
+        record_missing(
+            &path,
+            [
+                ("gpu-1".to_owned(), changed.clone()),
+                ("gpu-2".to_owned(), changed.clone()),
+            ],
+        )
+        .unwrap();
+        let baselines = load(&path).unwrap();
+
+        assert!(empty.is_empty());
+        assert_eq!(baselines["gpu-1"], original);
+        assert_eq!(baselines["gpu-2"], changed);
+    }
+}
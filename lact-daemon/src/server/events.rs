@@ -0,0 +1,97 @@
+//! Event subscriptions, which send the stats, logs and device events over a single connection.
+
+use super::handler::Handler;
+use crate::log_stream;
+use lact_schema::{DaemonEvent, EventMask};
+use std::{future::pending, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream,
+    select,
+    sync::broadcast::{self, error::RecvError},
+    time::{interval, Interval, MissedTickBehavior},
+};
+
+/// Shorter stats intervals are raised to this, as reading the stats more often only adds load
+const MIN_STATS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Writes the requested events to the stream, until the client disconnects
+pub async fn forward(
+    stream: &mut BufReader<UnixStream>,
+    handler: &Handler,
+    mask: EventMask,
+) -> anyhow::Result<()> {
+    let mut logs = mask.logs.map(|_| log_stream::subscribe()).transpose()?;
+    let mut device_events = mask.device_events.then(|| handler.subscribe_events());
+    let mut stats_interval = mask.stats_interval_ms.map(|interval_ms| {
+        let mut stats_interval =
+            interval(Duration::from_millis(interval_ms).max(MIN_STATS_INTERVAL));
+        stats_interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        stats_interval
+    });
+
+    // Clients don't send anything after subscribing, reading is only used to notice when they disconnect
+    let mut buf = String::new();
+    loop {
+        let events = select! {
+            result = recv(&mut logs) => match result {
+                Ok(record) if mask.logs.is_some_and(|level| record.level <= level) => {
+                    vec![DaemonEvent::Log(record)]
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            result = recv(&mut device_events) => match result {
+                Ok(event) => vec![event],
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return Ok(()),
+            },
+            () = tick(&mut stats_interval) => stats_events(handler),
+            result = stream.read_line(&mut buf) => {
+                if result? == 0 {
+                    return Ok(());
+                }
+                buf.clear();
+                continue;
+            }
+        };
+
+        for event in events {
+            let mut line = serde_json::to_vec(&event)?;
+            line.push(b'\n');
+            stream.write_all(&line).await?;
+        }
+    }
+}
+
+/// Never completes for kinds of events which were not subscribed to
+async fn recv<T: Clone>(receiver: &mut Option<broadcast::Receiver<T>>) -> Result<T, RecvError> {
+    match receiver {
+        Some(receiver) => receiver.recv().await,
+        None => pending().await,
+    }
+}
+
+async fn tick(stats_interval: &mut Option<Interval>) {
+    match stats_interval {
+        Some(stats_interval) => {
+            stats_interval.tick().await;
+        }
+        None => pending().await,
+    }
+}
+
+/// GPUs whose stats can't be read are left out
+fn stats_events(handler: &Handler) -> Vec<DaemonEvent> {
+    handler
+        .gpu_controllers
+        .keys()
+        .filter_map(|id| {
+            let stats = Box::new(handler.get_gpu_stats(id).ok()?);
+            Some(DaemonEvent::Stats {
+                id: id.clone(),
+                stats,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,127 @@
+//! Aggregates of the stats of all GPUs, for monitoring many GPUs with a single request.
+
+use lact_schema::{DeviceStats, FleetSummary, HottestGpu};
+use std::collections::BTreeMap;
+
+pub struct GpuSample {
+    pub stats: DeviceStats,
+    pub retired_pages: usize,
+}
+
+/// GPUs whose stats could not be read are `None`
+pub fn summarize(gpus: BTreeMap<String, Option<GpuSample>>) -> FleetSummary {
+    let mut summary = FleetSummary {
+        gpu_count: gpus.len(),
+        ..Default::default()
+    };
+
+    for (id, sample) in gpus {
+        let Some(GpuSample {
+            stats,
+            retired_pages,
+        }) = sample
+        else {
+            summary.unavailable.push(id);
+            continue;
+        };
+
+        if stats.is_power_capped || stats.throttle_residency.values().any(|value| *value > 0.0) {
+            summary.throttling.push(id.clone());
+        }
+
+        summary.total_power += stats
+            .power
+            .current
+            .filter(|power| *power != 0.0)
+            .or(stats.power.average)
+            .unwrap_or_default();
+
+        for (sensor, temp) in &stats.temps {
+            let Some(current) = temp.current else {
+                continue;
+            };
+            if summary
+                .hottest
+                .as_ref()
+                .is_none_or(|hottest| current > hottest.temperature)
+            {
+                summary.hottest = Some(HottestGpu {
+                    id: id.clone(),
+                    sensor: sensor.clone(),
+                    temperature: current,
+                });
+            }
+        }
+
+        if stats.reset_count > 0 {
+            summary.resets.insert(id.clone(), stats.reset_count);
+        }
+        if retired_pages > 0 {
+            summary.retired_pages.insert(id, retired_pages);
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{summarize, GpuSample};
+    use lact_schema::{amdgpu_sysfs::hw_mon::Temperature, DeviceStats, HottestGpu, ThrottleReason};
+    use std::collections::BTreeMap;
+
+    fn sample(power: f64, edge: f32, reset_count: u32, retired_pages: usize) -> GpuSample {
+        let mut stats = DeviceStats {
+            reset_count,
+            ..Default::default()
+        };
+        stats.power.current = Some(power);
+        stats.temps.insert(
+            "edge".to_owned(),
+            Temperature {
+                current: Some(edge),
+                crit: None,
+                crit_hyst: None,
+            },
+        );
+        GpuSample {
+            stats,
+            retired_pages,
+        }
+    }
+
+    #[test]
+    fn fleet_summary() {
+        let mut throttled = sample(300.0, 85.0, 0, 0);
+        throttled
+            .stats
+            .throttle_residency
+            .insert(ThrottleReason::Temperature, 0.25);
+
+        let gpus = BTreeMap::from([
+            ("gpu-a".to_owned(), Some(sample(100.0, 50.0, 1, 0))),
+            ("gpu-b".to_owned(), Some(throttled)),
+            ("gpu-c".to_owned(), Some(sample(50.0, 40.0, 0, 3))),
+            ("gpu-d".to_owned(), None),
+        ]);
+        let summary = summarize(gpus);
+
+        assert_eq!(summary.gpu_count, 4);
+        assert_eq!(summary.unavailable, ["gpu-d"]);
+        assert_eq!(summary.throttling, ["gpu-b"]);
+        assert!((summary.total_power - 450.0).abs() < f64::EPSILON);
+        assert_eq!(
+            summary.hottest,
+            Some(HottestGpu {
+                id: "gpu-b".to_owned(),
+                sensor: "edge".to_owned(),
+                temperature: 85.0,
+            })
+        );
+        assert_eq!(summary.resets, BTreeMap::from([("gpu-a".to_owned(), 1)]));
+        assert_eq!(
+            summary.retired_pages,
+            BTreeMap::from([("gpu-c".to_owned(), 3)])
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use super::TaskHandle;
+use lact_schema::{
+    amdgpu_sysfs::gpu_handle::{
+        overdrive::{ClocksTable, ClocksTableGen},
+        GpuHandle,
+    },
+    AdaptiveClocks, AdaptiveClocksState, AdaptiveClocksStatus,
+};
+use std::{cell::Cell, rc::Rc, time::Duration};
+use tokio::{select, sync::Notify, time::sleep};
+use tracing::{debug, error};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+/// How far below the load threshold the utilization has to drop before switching back to the idle clock
+const HYSTERESIS_PCT: u8 = 10;
+
+pub fn next_state(
+    settings: &AdaptiveClocks,
+    current: AdaptiveClocksState,
+    busy_percent: u8,
+) -> AdaptiveClocksState {
+    match current {
+        AdaptiveClocksState::Idle if busy_percent >= settings.load_threshold_pct => {
+            AdaptiveClocksState::Load
+        }
+        AdaptiveClocksState::Load
+            if busy_percent < settings.load_threshold_pct.saturating_sub(HYSTERESIS_PCT) =>
+        {
+            AdaptiveClocksState::Idle
+        }
+        state => state,
+    }
+}
+
+pub fn set_max_core_clock(handle: &GpuHandle, clockspeed: i32) -> anyhow::Result<()> {
+    let mut table = handle.get_clocks_table()?;
+    if let ClocksTableGen::Vega20(ref mut table) = table {
+        // Only write the max clock, the rest of the previously committed values stay the same
+        table.clear();
+    }
+    table.set_max_sclk(clockspeed)?;
+    handle.set_clocks_table(&table)?;
+    Ok(())
+}
+
+/// Spawns a task which periodically checks the GPU utilization and switches the maximum core clock.
+/// The idle clock is expected to already be applied.
+pub fn spawn(
+    handle: GpuHandle,
+    settings: AdaptiveClocks,
+    status: Rc<Cell<Option<AdaptiveClocksStatus>>>,
+) -> TaskHandle {
+    let notify = Rc::new(Notify::new());
+    let task_notify = notify.clone();
+
+    let task = tokio::task::spawn_local(async move {
+        let mut state = AdaptiveClocksState::Idle;
+
+        loop {
+            select! {
+                () = sleep(INTERVAL) => (),
+                () = task_notify.notified() => break,
+            }
+
+            let busy_percent = match handle.get_busy_percent() {
+                Ok(busy_percent) => busy_percent,
+                Err(err) => {
+                    error!("could not read GPU utilization: {err}, disabling adaptive clocks");
+                    status.set(None);
+                    break;
+                }
+            };
+
+            let new_state = next_state(&settings, state, busy_percent);
+            if new_state == state {
+                continue;
+            }
+
+            let max_core_clock = match new_state {
+                AdaptiveClocksState::Idle => settings.idle_max,
+                AdaptiveClocksState::Load => settings.load_max,
+            };
+            debug!("switching adaptive clocks to {new_state:?} with max clock {max_core_clock}");
+
+            if let Err(err) = set_max_core_clock(&handle, max_core_clock) {
+                error!("could not set max core clock: {err:#}, disabling adaptive clocks");
+                status.set(None);
+                break;
+            }
+            state = new_state;
+            status.set(Some(AdaptiveClocksStatus {
+                state,
+                max_core_clock,
+            }));
+        }
+        debug!("exited adaptive clocks task");
+    });
+
+    (notify, task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_state;
+    use lact_schema::{AdaptiveClocks, AdaptiveClocksState};
+
+    const SETTINGS: AdaptiveClocks = AdaptiveClocks {
+        idle_max: 800,
+        load_max: 2400,
+        load_threshold_pct: 50,
+    };
+
+    #[test]
+    fn switch_to_load() {
+        assert_eq!(
+            next_state(&SETTINGS, AdaptiveClocksState::Idle, 49),
+            AdaptiveClocksState::Idle
+        );
+        assert_eq!(
+            next_state(&SETTINGS, AdaptiveClocksState::Idle, 50),
+            AdaptiveClocksState::Load
+        );
+    }
+
+    #[test]
+    fn hysteresis() {
+        assert_eq!(
+            next_state(&SETTINGS, AdaptiveClocksState::Load, 45),
+            AdaptiveClocksState::Load
+        );
+        assert_eq!(
+            next_state(&SETTINGS, AdaptiveClocksState::Load, 40),
+            AdaptiveClocksState::Load
+        );
+        assert_eq!(
+            next_state(&SETTINGS, AdaptiveClocksState::Load, 39),
+            AdaptiveClocksState::Idle
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use anyhow::Context;
+use lact_schema::AudioDeviceInfo;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// PCI class of HD Audio controllers
+const AUDIO_CLASS_PREFIX: &str = "0x0403";
+
+/// Finds the HDMI/DisplayPort audio function of the GPU, which is another function of the same PCI device
+pub fn find_function(device_path: &Path) -> Option<PathBuf> {
+    let device_path = fs::canonicalize(device_path).ok()?;
+    let slot_name = device_path.file_name()?.to_str()?;
+    // The slot has the format `domain:bus:device.function`
+    let (device, _) = slot_name.rsplit_once('.')?;
+
+    fs::read_dir(device_path.parent()?)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                return false;
+            };
+            name != slot_name
+                && name
+                    .rsplit_once('.')
+                    .is_some_and(|(other_device, _)| other_device == device)
+                && fs::read_to_string(path.join("class"))
+                    .is_ok_and(|class| class.starts_with(AUDIO_CLASS_PREFIX))
+        })
+}
+
+pub fn info(function_path: &Path) -> AudioDeviceInfo {
+    let read = |file: &str| {
+        fs::read_to_string(function_path.join(file))
+            .ok()
+            .map(|value| value.trim().to_owned())
+    };
+
+    AudioDeviceInfo {
+        pci_slot: function_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        driver: fs::read_link(function_path.join("driver"))
+            .ok()
+            .and_then(|driver| Some(driver.file_name()?.to_string_lossy().into_owned())),
+        runtime_pm_status: read("power/runtime_status").and_then(|status| status.parse().ok()),
+        pinned_active: read("power/control").as_deref() == Some("on"),
+    }
+}
+
+/// Keeping the device active stops the audio pops some receivers make when it wakes up from runtime suspend
+pub fn set_pinned_active(function_path: &Path, pinned: bool) -> anyhow::Result<()> {
+    let control = if pinned { "on" } else { "auto" };
+    fs::write(function_path.join("power/control"), control)
+        .context("Could not set the runtime power management of the audio device")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_function, info};
+    use crate::test_util::fake_sysfs;
+    use lact_schema::RuntimePmStatus;
+
+    #[test]
+    fn audio_function() {
+        let root = fake_sysfs(
+            "audio",
+            &[
+                ("0000:0b:00.0/class", "0x030000\n"),
+                ("0000:0b:00.1/class", "0x040300\n"),
+                ("0000:0b:00.1/power/runtime_status", "suspended\n"),
+                ("0000:0b:00.1/power/control", "auto\n"),
+                ("0000:0c:00.1/class", "0x040300\n"),
+            ],
+        );
+        let audio_path = root.join("0000:0b:00.1");
+
+        let found = find_function(&root.join("0000:0b:00.0"));
+        let audio_info = info(&audio_path);
+        let missing = find_function(&root.join("0000:0c:00.1"));
+
+        assert_eq!(found, Some(audio_path));
+        assert_eq!(audio_info.pci_slot, "0000:0b:00.1");
+        assert_eq!(
+            audio_info.runtime_pm_status,
+            Some(RuntimePmStatus::Suspended)
+        );
+        assert!(!audio_info.pinned_active);
+        assert_eq!(missing, None);
+    }
+}
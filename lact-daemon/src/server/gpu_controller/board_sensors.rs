@@ -0,0 +1,128 @@
+//! Temperature sensors on the board of the GPU (such as the VRM or the backplate), which are separate chips on the I2C buses of the GPU.
+//! Their sensors are keyed as `<chip>_<label>`, to keep them apart from the sensors of the GPU itself.
+
+use super::get_temps;
+use lact_schema::amdgpu_sysfs::{
+    hw_mon::{HwMon, Temperature},
+    sysfs::SysFS,
+};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Finds the monitors at `<device>/i2c-N/N-XXXX/hwmon/hwmonM`
+pub fn find(device_path: &Path) -> Vec<HwMon> {
+    let mut hw_mons = Vec::new();
+
+    for adapter in read_dir_sorted(device_path) {
+        let is_adapter = adapter
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("i2c-"));
+        if !is_adapter {
+            continue;
+        }
+
+        for client in read_dir_sorted(&adapter) {
+            for hw_mon_path in read_dir_sorted(&client.join("hwmon")) {
+                if let Ok(hw_mon) = HwMon::new_from_path(hw_mon_path) {
+                    hw_mons.push(hw_mon);
+                }
+            }
+        }
+    }
+
+    hw_mons
+}
+
+fn read_dir_sorted(path: &Path) -> Vec<std::path::PathBuf> {
+    let mut entries: Vec<_> = fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    entries
+}
+
+fn chip_name(hw_mon: &HwMon) -> String {
+    hw_mon
+        .read_file("name")
+        .unwrap_or_else(|_| "board".to_owned())
+}
+
+/// Reads all sensors of the monitors, where the first one belongs to the GPU itself and the rest are board sensors
+pub fn temps(hw_mons: &[HwMon]) -> HashMap<String, Temperature> {
+    let Some((gpu_hw_mon, board_hw_mons)) = hw_mons.split_first() else {
+        return HashMap::new();
+    };
+
+    let mut temps = get_temps(gpu_hw_mon);
+    for hw_mon in board_hw_mons {
+        let chip = chip_name(hw_mon);
+        for (label, temperature) in get_temps(hw_mon) {
+            temps
+                .entry(format!("{chip}_{label}"))
+                .or_insert(temperature);
+        }
+    }
+    temps
+}
+
+/// Finds the monitor with the given sensor from `temps`, along with the key of the sensor within that monitor
+pub fn resolve(hw_mons: &[HwMon], key: &str) -> Option<(HwMon, String)> {
+    let (gpu_hw_mon, board_hw_mons) = hw_mons.split_first()?;
+    if get_temps(gpu_hw_mon).contains_key(key) {
+        return Some((gpu_hw_mon.clone(), key.to_owned()));
+    }
+
+    board_hw_mons.iter().find_map(|hw_mon| {
+        let label = key.strip_prefix(&chip_name(hw_mon))?.strip_prefix('_')?;
+        get_temps(hw_mon)
+            .contains_key(label)
+            .then(|| (hw_mon.clone(), label.to_owned()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find, resolve, temps};
+    use crate::test_util::fake_sysfs;
+    use lact_schema::amdgpu_sysfs::{hw_mon::HwMon, sysfs::SysFS};
+    use std::fs;
+
+    #[test]
+    fn board_temps() {
+        let device_path = fake_sysfs(
+            "board",
+            &[
+                ("hwmon/hwmon3/name", "amdgpu"),
+                ("hwmon/hwmon3/temp1_input", "45000"),
+                ("hwmon/hwmon3/temp1_label", "edge"),
+                ("i2c-5/5-0020/hwmon/hwmon7/name", "ir35217"),
+                ("i2c-5/5-0020/hwmon/hwmon7/temp1_input", "58000"),
+                ("i2c-5/5-0020/hwmon/hwmon7/temp1_label", "VR_GFX"),
+                ("i2c-5/5-0020/hwmon/hwmon7/temp2_input", "52000"),
+                ("i2c-5/5-0020/hwmon/hwmon7/temp2_label", "VR_MEM"),
+            ],
+        );
+        let gpu_path = device_path.join("hwmon/hwmon3");
+        let vrm_path = device_path.join("i2c-5/5-0020/hwmon/hwmon7");
+        // Adapters without a sensor chip are skipped
+        fs::create_dir_all(device_path.join("i2c-6/6-0050")).unwrap();
+
+        let mut hw_mons = vec![HwMon::new_from_path(gpu_path).unwrap()];
+        hw_mons.extend(find(&device_path));
+        let temps = temps(&hw_mons);
+        let resolved = resolve(&hw_mons, "ir35217_VR_MEM");
+        let missing = resolve(&hw_mons, "ir35217_VR_SOC");
+
+        assert_eq!(hw_mons.len(), 2);
+        assert_eq!(temps.len(), 3);
+        assert_eq!(temps["edge"].current, Some(45.0));
+        assert_eq!(temps["ir35217_VR_GFX"].current, Some(58.0));
+
+        let (hw_mon, label) = resolved.unwrap();
+        assert_eq!(hw_mon.get_path(), vrm_path);
+        assert_eq!(label, "VR_MEM");
+        assert!(missing.is_none());
+    }
+}
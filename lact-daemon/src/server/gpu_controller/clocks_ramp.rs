@@ -0,0 +1,76 @@
+use super::GpuController;
+use lact_schema::ThrottleReason;
+
+/// Clockspeeds to apply one after another when moving from `start` to `target`.
+/// The last step is always the target, which can be closer to the previous step than `step`.
+pub fn steps(start: i32, target: i32, step: i32) -> Vec<i32> {
+    let step = step.max(1);
+    let mut values = Vec::new();
+    let mut current = start;
+
+    while current != target {
+        current = if current < target {
+            current.saturating_add(step).min(target)
+        } else {
+            current.saturating_sub(step).max(target)
+        };
+        values.push(current);
+    }
+    values
+}
+
+/// Checks whether the GPU got reset or started hitting its limits since the ramp started,
+/// returning the reason for aborting the ramp.
+pub fn check_stability(
+    controller: &GpuController,
+    reset_count: u32,
+    abort_on_throttling: bool,
+) -> Option<String> {
+    if controller.reset_count.get() != reset_count {
+        return Some("made the GPU reset".to_owned());
+    }
+
+    if abort_on_throttling {
+        let limits: Vec<_> = controller
+            .get_throttle_reasons()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|reason| is_limiting(*reason))
+            .collect();
+        if !limits.is_empty() {
+            return Some(format!("made the GPU throttle because of {limits:?}"));
+        }
+    }
+
+    None
+}
+
+/// Throttle reasons which indicate that the clockspeed is higher than what the GPU can sustain.
+/// Power throttling is not included, as it is expected when the power cap is reached.
+fn is_limiting(reason: ThrottleReason) -> bool {
+    matches!(
+        reason,
+        ThrottleReason::Temperature | ThrottleReason::Current
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::steps;
+
+    #[test]
+    fn ramp_up() {
+        assert_eq!(steps(2000, 2250, 100), [2100, 2200, 2250]);
+        assert_eq!(steps(2000, 2200, 100), [2100, 2200]);
+    }
+
+    #[test]
+    fn ramp_down() {
+        assert_eq!(steps(1000, 750, 100), [900, 800, 750]);
+    }
+
+    #[test]
+    fn already_at_target() {
+        assert!(steps(2000, 2000, 100).is_empty());
+    }
+}
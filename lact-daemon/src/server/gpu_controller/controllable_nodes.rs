@@ -0,0 +1,147 @@
+use lact_schema::{ControllableNode, NodeRange, NodeStatus};
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+/// Files in the device directory which the daemon writes to
+const DEVICE_NODES: &[&str] = &[
+    "power_dpm_force_performance_level",
+    "pp_od_clk_voltage",
+    "pp_power_profile_mode",
+    "pp_dpm_sclk",
+    "pp_dpm_mclk",
+    "pp_dpm_fclk",
+    "pp_dpm_socclk",
+    "pp_dpm_pcie",
+    "pp_features",
+];
+
+/// Device files which the driver only uses with the manual performance level
+const MANUAL_PERFORMANCE_LEVEL_NODES: &[&str] = &[
+    "pp_power_profile_mode",
+    "pp_dpm_sclk",
+    "pp_dpm_mclk",
+    "pp_dpm_fclk",
+    "pp_dpm_socclk",
+    "pp_dpm_pcie",
+];
+
+/// Files in the hwmon directory which the daemon writes to, with the files defining their range
+const HW_MON_NODES: &[(&str, Option<(&str, &str)>)] = &[
+    ("power1_cap", Some(("power1_cap_min", "power1_cap_max"))),
+    ("pwm1", Some(("pwm1_min", "pwm1_max"))),
+    ("pwm1_enable", None),
+    ("fan1_target", Some(("fan1_min", "fan1_max"))),
+];
+
+/// Files in the `gpu_od/fan_ctrl` directory, which is used for fan control on RDNA3 and newer
+const FAN_CTRL_NODES: &[&str] = &[
+    "fan_curve",
+    "acoustic_limit_rpm_threshold",
+    "acoustic_target_rpm_threshold",
+    "fan_minimum_pwm",
+    "fan_target_temperature",
+    "fan_zero_rpm_enable",
+    "fan_zero_rpm_stop_temperature",
+];
+
+pub fn list(device_path: &Path, hw_mon_path: Option<&Path>) -> Vec<ControllableNode> {
+    let mut nodes: Vec<_> = DEVICE_NODES
+        .iter()
+        .map(|name| read_node(device_path, name, None))
+        .collect();
+
+    if let Some(hw_mon_path) = hw_mon_path {
+        nodes.extend(
+            HW_MON_NODES
+                .iter()
+                .map(|(name, range)| read_node(hw_mon_path, name, *range)),
+        );
+    }
+
+    let fan_ctrl_path = device_path.join("gpu_od/fan_ctrl");
+    nodes.extend(
+        FAN_CTRL_NODES
+            .iter()
+            .map(|name| read_node(&fan_ctrl_path, name, None)),
+    );
+
+    nodes
+}
+
+fn read_node(dir: &Path, name: &str, range_files: Option<(&str, &str)>) -> ControllableNode {
+    let path = dir.join(name);
+
+    let status = match fs::metadata(&path) {
+        Ok(metadata) if metadata.permissions().mode() & 0o222 != 0 => NodeStatus::Writable,
+        Ok(_) => NodeStatus::ReadOnly,
+        Err(_) => NodeStatus::Missing,
+    };
+    let value = fs::read_to_string(&path)
+        .ok()
+        .map(|value| value.trim_end().to_owned());
+    let range = range_files.and_then(|(min_file, max_file)| {
+        let read = |file| {
+            fs::read_to_string(dir.join(file))
+                .ok()
+                .and_then(|value| value.trim().parse().ok())
+        };
+        Some(NodeRange {
+            min: read(min_file)?,
+            max: read(max_file)?,
+        })
+    });
+
+    ControllableNode {
+        path: path.to_string_lossy().into_owned(),
+        status,
+        value,
+        range,
+        requires_manual_performance_level: MANUAL_PERFORMANCE_LEVEL_NODES.contains(&name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_node;
+    use crate::test_util::fake_sysfs;
+    use lact_schema::{NodeRange, NodeStatus};
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    #[test]
+    fn node_status_and_range() {
+        let path = fake_sysfs(
+            "nodes",
+            &[
+                ("power1_cap", "200000000\n"),
+                ("power1_cap_min", "0\n"),
+                ("power1_cap_max", "250000000\n"),
+                ("pwm1", "80\n"),
+            ],
+        );
+        fs::set_permissions(path.join("pwm1"), fs::Permissions::from_mode(0o444)).unwrap();
+
+        let power_cap = read_node(
+            &path,
+            "power1_cap",
+            Some(("power1_cap_min", "power1_cap_max")),
+        );
+        let pwm = read_node(&path, "pwm1", Some(("pwm1_min", "pwm1_max")));
+        let missing = read_node(&path, "fan1_target", None);
+
+        assert_eq!(power_cap.status, NodeStatus::Writable);
+        assert_eq!(power_cap.value.as_deref(), Some("200000000"));
+        assert_eq!(
+            power_cap.range,
+            Some(NodeRange {
+                min: 0,
+                max: 250_000_000
+            })
+        );
+
+        assert_eq!(pwm.status, NodeStatus::ReadOnly);
+        assert_eq!(pwm.range, None);
+
+        assert_eq!(missing.status, NodeStatus::Missing);
+        assert_eq!(missing.value, None);
+        assert!(!power_cap.requires_manual_performance_level);
+    }
+}
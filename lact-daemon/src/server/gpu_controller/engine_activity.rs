@@ -0,0 +1,109 @@
+//! Activity of each engine (such as `gfx`, `compute`, `dma`, `dec` and `enc`), from the engine time of the DRM clients in fdinfo.
+//! The driver only reports the busy percentage of the graphics engine directly.
+
+use lact_schema::GpuProcess;
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+/// Reads closer together than this keep the previous activity, as the engine times would be too noisy
+const MIN_WINDOW: Duration = Duration::from_secs(1);
+
+/// Total engine time of each process, by pid and engine name
+pub struct EngineUsageSample {
+    at: Instant,
+    usage: BTreeMap<(i32, String), u64>,
+}
+
+impl EngineUsageSample {
+    pub fn new(processes: &[GpuProcess], at: Instant) -> Self {
+        let usage = processes
+            .iter()
+            .flat_map(|process| {
+                process
+                    .engine_usage
+                    .iter()
+                    .map(|(engine, ns)| ((process.pid, engine.clone()), *ns))
+            })
+            .collect();
+        Self { at, usage }
+    }
+
+    pub fn is_recent(&self, now: Instant) -> bool {
+        now.checked_duration_since(self.at)
+            .is_some_and(|elapsed| elapsed < MIN_WINDOW)
+    }
+}
+
+/// Share of time (in percent) each engine was busy between the samples.
+/// Processes which started in between are left out, as their engine time is not known from the start of the window.
+/// Engines with multiple rings are capped at 100%.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+pub fn activity(previous: &EngineUsageSample, current: &EngineUsageSample) -> HashMap<String, f32> {
+    let elapsed = current.at.saturating_duration_since(previous.at).as_nanos() as f64;
+    if elapsed == 0.0 {
+        return HashMap::new();
+    }
+
+    let mut busy: HashMap<String, u64> = HashMap::new();
+    for ((pid, engine), ns) in &current.usage {
+        let entry = busy.entry(engine.clone()).or_default();
+        if let Some(previous_ns) = previous.usage.get(&(*pid, engine.clone())) {
+            *entry += ns.saturating_sub(*previous_ns);
+        }
+    }
+
+    busy.into_iter()
+        .map(|(engine, ns)| {
+            let percent = (ns as f64 / elapsed * 100.0).min(100.0);
+            (engine, percent as f32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{activity, EngineUsageSample};
+    use lact_schema::GpuProcess;
+    use std::{
+        collections::BTreeMap,
+        time::{Duration, Instant},
+    };
+
+    fn process(pid: i32, gfx_ns: u64, compute_ns: u64) -> GpuProcess {
+        GpuProcess {
+            pid,
+            name: String::new(),
+            vram_usage: None,
+            gtt_usage: None,
+            engine_usage: BTreeMap::from([
+                ("gfx".to_owned(), gfx_ns),
+                ("compute".to_owned(), compute_ns),
+            ]),
+        }
+    }
+
+    #[test]
+    fn engine_activity() {
+        let start = Instant::now();
+        let end = start + Duration::from_secs(2);
+        let previous = EngineUsageSample::new(&[process(1, 1_000_000_000, 0)], start);
+        let current = EngineUsageSample::new(
+            &[
+                process(1, 2_000_000_000, 500_000_000),
+                // Started during the window
+                process(2, 5_000_000_000, 5_000_000_000),
+            ],
+            end,
+        );
+
+        let activity = activity(&previous, &current);
+        assert_eq!(activity.len(), 2);
+        assert!((activity["gfx"] - 50.0).abs() < f32::EPSILON);
+        assert!((activity["compute"] - 25.0).abs() < f32::EPSILON);
+
+        assert!(previous.is_recent(start + Duration::from_millis(500)));
+        assert!(!previous.is_recent(end));
+    }
+}
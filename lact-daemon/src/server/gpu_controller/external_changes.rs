@@ -0,0 +1,315 @@
+use crate::config;
+use lact_schema::amdgpu_sysfs::gpu_handle::{
+    overdrive::{ClocksTable, ClocksTableGen},
+    PerformanceLevel,
+};
+
+const MICROWATTS_PER_WATT: u64 = 1_000_000;
+
+/// Current values of the settings which can be compared with the ones applied by the daemon.
+/// `None` means that the value could not be read.
+#[derive(Debug, Default)]
+pub struct LiveSettings {
+    pub power_cap_microwatts: Option<u64>,
+    pub power_cap_default_microwatts: Option<u64>,
+    pub performance_level: Option<PerformanceLevel>,
+    pub power_profile_mode_index: Option<u16>,
+    /// Whether the fan is in manual mode, only read while the daemon is controlling the fan
+    pub fan_manual_control: Option<bool>,
+}
+
+/// Lists the settings which no longer have the value that was applied, as they were changed outside of the daemon
+pub fn diverging_fields(applied: &config::Gpu, live: &LiveSettings) -> Vec<String> {
+    let mut fields = Vec::new();
+
+    // The driver stores the cap in whole watts, so the value read back can be rounded down
+    let expected_cap = applied
+        .power_cap_microwatts
+        .or(live.power_cap_default_microwatts);
+    if let (Some(expected), Some(current)) = (expected_cap, live.power_cap_microwatts) {
+        if expected / MICROWATTS_PER_WATT != current / MICROWATTS_PER_WATT {
+            fields.push("power_cap_microwatts");
+        }
+    }
+
+    if let Some(current) = live.performance_level {
+        if current != applied.performance_level.unwrap_or(PerformanceLevel::Auto) {
+            fields.push("performance_level");
+        }
+    }
+
+    if let (Some(expected), Some(current)) = (
+        applied.power_profile_mode_index,
+        live.power_profile_mode_index,
+    ) {
+        if expected != current {
+            fields.push("power_profile_mode_index");
+        }
+    }
+
+    if live.fan_manual_control == Some(false) {
+        fields.push("fan_control_enabled");
+    }
+
+    fields.into_iter().map(str::to_owned).collect()
+}
+
+/// Whether the hardware is known to be in its driver default state, even though the GPU already has settings.
+/// When the daemon is restarted without a reboot (e.g. on a package upgrade), the settings applied by the
+/// previous daemon are still in effect, so they must not be recorded as the baseline.
+pub fn is_driver_default(live: &LiveSettings, configured: &config::Gpu) -> bool {
+    let default_cap = match (live.power_cap_microwatts, live.power_cap_default_microwatts) {
+        (Some(current), Some(default)) => {
+            current / MICROWATTS_PER_WATT == default / MICROWATTS_PER_WATT
+        }
+        (None, _) => true,
+        (Some(_), None) => false,
+    };
+    // The clocks table has no defaults to compare with, they can only be untouched when none are configured
+    default_cap
+        && matches!(live.performance_level, None | Some(PerformanceLevel::Auto))
+        && configured.clocks_configuration == config::ClocksConfiguration::default()
+}
+
+/// Settings which reproduce the current hardware state, with every value that can be read set explicitly.
+/// The fan is left to the firmware, as the daemon doesn't control it before the settings are applied.
+pub fn baseline_settings(
+    live: &LiveSettings,
+    clocks_table: Option<&ClocksTableGen>,
+) -> config::Gpu {
+    let mut baseline = config::Gpu {
+        power_cap_microwatts: live.power_cap_microwatts,
+        performance_level: live.performance_level,
+        ..Default::default()
+    };
+    if live.performance_level == Some(PerformanceLevel::Manual) {
+        baseline.power_profile_mode_index = live.power_profile_mode_index;
+    }
+
+    if let Some(table) = clocks_table {
+        let clocks = &mut baseline.clocks_configuration;
+        let voltage_range = table.get_current_voltage_range();
+        clocks.max_core_clock = table.get_max_sclk();
+        clocks.min_core_clock = table.get_current_sclk_range().min;
+        clocks.max_memory_clock = table.get_max_mclk();
+        clocks.min_memory_clock = table.get_current_mclk_range().min;
+        clocks.max_voltage = voltage_range.and_then(|range| range.max);
+        clocks.min_voltage = voltage_range.and_then(|range| range.min);
+        if let ClocksTableGen::Vega20(table) = table {
+            clocks.voltage_offset = table.voltage_offset;
+        }
+    }
+
+    baseline
+}
+
+/// The requested settings with the values which are actually in effect on the GPU.
+/// Settings which can't be read back (such as the enabled power states) keep the requested value.
+pub fn effective_settings(
+    requested: &config::Gpu,
+    live: &LiveSettings,
+    clocks_table: Option<&ClocksTableGen>,
+) -> config::Gpu {
+    let mut effective = requested.clone();
+
+    if requested.power_cap_microwatts.is_some()
+        || live.power_cap_microwatts != live.power_cap_default_microwatts
+    {
+        effective.power_cap_microwatts =
+            live.power_cap_microwatts.or(requested.power_cap_microwatts);
+    }
+
+    // Not setting a performance level leaves it at the default of the driver
+    effective.performance_level = match live.performance_level {
+        Some(PerformanceLevel::Auto) if requested.performance_level.is_none() => None,
+        Some(level) => Some(level),
+        None => requested.performance_level,
+    };
+
+    if requested.power_profile_mode_index.is_some() {
+        effective.power_profile_mode_index = live
+            .power_profile_mode_index
+            .or(requested.power_profile_mode_index);
+    }
+
+    if let Some(manual) = live.fan_manual_control {
+        effective.fan_control_enabled = manual;
+    }
+
+    if let Some(table) = clocks_table {
+        let read_back = |requested: Option<i32>, current: Option<i32>| {
+            requested.map(|value| current.unwrap_or(value))
+        };
+        let requested_clocks = &requested.clocks_configuration;
+        let clocks = &mut effective.clocks_configuration;
+        let voltage_range = table.get_current_voltage_range();
+
+        clocks.max_core_clock = read_back(requested_clocks.max_core_clock, table.get_max_sclk());
+        clocks.min_core_clock = read_back(
+            requested_clocks.min_core_clock,
+            table.get_current_sclk_range().min,
+        );
+        clocks.max_memory_clock =
+            read_back(requested_clocks.max_memory_clock, table.get_max_mclk());
+        clocks.min_memory_clock = read_back(
+            requested_clocks.min_memory_clock,
+            table.get_current_mclk_range().min,
+        );
+        clocks.max_voltage = read_back(
+            requested_clocks.max_voltage,
+            voltage_range.and_then(|range| range.max),
+        );
+        clocks.min_voltage = read_back(
+            requested_clocks.min_voltage,
+            voltage_range.and_then(|range| range.min),
+        );
+    }
+
+    effective
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        baseline_settings, diverging_fields, effective_settings, is_driver_default, LiveSettings,
+    };
+    use crate::config;
+    use lact_schema::amdgpu_sysfs::gpu_handle::PerformanceLevel;
+
+    #[test]
+    fn unchanged_settings() {
+        let applied = config::Gpu {
+            power_cap_microwatts: Some(200_500_000),
+            performance_level: Some(PerformanceLevel::Manual),
+            power_profile_mode_index: Some(1),
+            ..Default::default()
+        };
+        let live = LiveSettings {
+            power_cap_microwatts: Some(200_000_000),
+            power_cap_default_microwatts: Some(250_000_000),
+            performance_level: Some(PerformanceLevel::Manual),
+            power_profile_mode_index: Some(1),
+            fan_manual_control: Some(true),
+        };
+        assert!(diverging_fields(&applied, &live).is_empty());
+    }
+
+    #[test]
+    fn changed_settings() {
+        let applied = config::Gpu::default();
+        let live = LiveSettings {
+            power_cap_microwatts: Some(180_000_000),
+            power_cap_default_microwatts: Some(250_000_000),
+            performance_level: Some(PerformanceLevel::High),
+            power_profile_mode_index: Some(3),
+            fan_manual_control: Some(false),
+        };
+        assert_eq!(
+            diverging_fields(&applied, &live),
+            [
+                "power_cap_microwatts",
+                "performance_level",
+                "fan_control_enabled"
+            ]
+        );
+    }
+
+    #[test]
+    fn baseline_from_live_settings() {
+        let live = LiveSettings {
+            power_cap_microwatts: Some(230_000_000),
+            power_cap_default_microwatts: Some(250_000_000),
+            performance_level: Some(PerformanceLevel::Auto),
+            power_profile_mode_index: Some(1),
+            fan_manual_control: None,
+        };
+        assert_eq!(
+            baseline_settings(&live, None),
+            config::Gpu {
+                power_cap_microwatts: Some(230_000_000),
+                performance_level: Some(PerformanceLevel::Auto),
+                ..Default::default()
+            }
+        );
+
+        let manual = LiveSettings {
+            performance_level: Some(PerformanceLevel::Manual),
+            ..live
+        };
+        assert_eq!(
+            baseline_settings(&manual, None).power_profile_mode_index,
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn baseline_driver_defaults() {
+        let live = LiveSettings {
+            power_cap_microwatts: Some(250_000_000),
+            power_cap_default_microwatts: Some(250_000_000),
+            performance_level: Some(PerformanceLevel::Auto),
+            ..Default::default()
+        };
+        let configured = config::Gpu {
+            power_cap_microwatts: Some(200_000_000),
+            ..Default::default()
+        };
+        assert!(is_driver_default(&live, &configured));
+
+        // Settings of a previous daemon which are still applied
+        let capped = LiveSettings {
+            power_cap_microwatts: Some(200_000_000),
+            ..live
+        };
+        assert!(!is_driver_default(&capped, &configured));
+        let manual = LiveSettings {
+            performance_level: Some(PerformanceLevel::Manual),
+            ..live
+        };
+        assert!(!is_driver_default(&manual, &configured));
+        let overclocked = config::Gpu {
+            clocks_configuration: config::ClocksConfiguration {
+                max_core_clock: Some(2600),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(!is_driver_default(&live, &overclocked));
+    }
+
+    #[test]
+    fn effective_settings_read_back() {
+        let requested = config::Gpu {
+            fan_control_enabled: true,
+            power_cap_microwatts: Some(200_500_000),
+            power_profile_mode_index: Some(1),
+            ..Default::default()
+        };
+        let live = LiveSettings {
+            power_cap_microwatts: Some(200_000_000),
+            power_cap_default_microwatts: Some(250_000_000),
+            performance_level: Some(PerformanceLevel::Auto),
+            power_profile_mode_index: Some(0),
+            fan_manual_control: Some(false),
+        };
+        let effective = effective_settings(&requested, &live, None);
+        assert_eq!(
+            effective,
+            config::Gpu {
+                fan_control_enabled: false,
+                power_cap_microwatts: Some(200_000_000),
+                power_profile_mode_index: Some(0),
+                ..Default::default()
+            }
+        );
+
+        let unchanged = LiveSettings {
+            power_cap_microwatts: Some(250_000_000),
+            ..live
+        };
+        assert_eq!(
+            effective_settings(&config::Gpu::default(), &unchanged, None),
+            config::Gpu::default()
+        );
+    }
+}
@@ -1,18 +1,36 @@
 use anyhow::anyhow;
-use lact_schema::{amdgpu_sysfs::hw_mon::Temperature, default_fan_curve, FanCurveMap};
+use lact_schema::{
+    amdgpu_sysfs::hw_mon::Temperature, default_fan_curve, evaluate_fan_curve, validate_fan_curve,
+    FanControlInterface, FanControlMethodKind, FanCurveMap, FanDecision, FanTemperatureSource,
+};
 use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 use tracing::warn;
 
+/// The firmware fan curve, relative to the device path
+pub const FIRMWARE_FAN_CURVE: &str = "gpu_od/fan_ctrl/fan_curve";
+
+/// Number of decisions kept in the log, which covers a minute with the default interval
+const LOG_LEN: usize = 120;
+
+pub type FanControlLog = Rc<RefCell<VecDeque<FanDecision>>>;
+
+/// Curve changes closer together than this (such as while dragging a point in a curve editor) are debounced
+pub const CURVE_UPDATE_DEBOUNCE: Duration = Duration::from_millis(500);
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FanCurve(pub FanCurveMap);
 
-#[allow(
-    clippy::cast_possible_truncation,
-    clippy::cast_precision_loss,
-    clippy::cast_sign_loss
-)]
+#[allow(clippy::cast_possible_truncation)]
 impl FanCurve {
-    pub fn pwm_at_temp(&self, temp: Temperature) -> u8 {
+    /// Evaluates the curve with all of its points shifted by `offset` degrees.
+    /// The critical temperature checks are not affected by the offset.
+    pub fn pwm_at_temp(&self, temp: Temperature, offset: i32) -> u8 {
         let current = temp.current.expect("No current temp");
 
         // This scenario is most likely unreachable as the kernel shuts down the GPU when it reaches critical temperature
@@ -23,32 +41,184 @@ impl FanCurve {
             return u8::MAX;
         }
 
-        let current = current as i32;
-        let maybe_lower = self.0.range(..current).next_back();
-        let maybe_higher = self.0.range(current..).next();
-
-        let percentage = match (maybe_lower, maybe_higher) {
-            (Some((lower_temp, lower_speed)), Some((higher_temp, higher_speed))) => {
-                let speed_ratio = (current - lower_temp) as f32 / (higher_temp - lower_temp) as f32;
-                lower_speed + (higher_speed - lower_speed) * speed_ratio
-            }
-            (Some((_, lower_speed)), None) => *lower_speed,
-            (None, Some((_, higher_speed))) => *higher_speed,
-            (None, None) => panic!("Could not find fan speed on the curve! This is a bug."),
-        };
-
-        (f32::from(u8::MAX) * percentage) as u8
+        let current = (current as i32).saturating_sub(offset);
+        evaluate_fan_curve(&self.0, current)
+            .expect("Could not find fan speed on the curve! This is a bug.")
     }
 }
 
 impl FanCurve {
     pub fn validate(&self) -> anyhow::Result<()> {
-        for percentage in self.0.values() {
-            if !(0.0..=1.0).contains(percentage) {
-                return Err(anyhow!("Fan speed percentage must be between 0 and 1"));
-            }
-        }
-        Ok(())
+        validate_fan_curve(&self.0).map_err(|err| anyhow!("Invalid fan curve: {err}"))
+    }
+}
+
+/// Combines the speeds of the main and spindown curves at the current temperature.
+/// The fan speeds up along the main curve right away, but only slows down once the temperature is below the spindown curve,
+/// which is usually above the main one. Between the two curves the previous speed is kept.
+pub fn spindown_pwm(previous: u8, rampup: u8, spindown: u8) -> u8 {
+    if rampup >= previous {
+        rampup
+    } else {
+        previous.min(spindown.max(rampup))
+    }
+}
+
+/// Picks the temperature for the curve: a simulated temperature takes priority over the sensor,
+/// and the hottest sensor is used when the configured one is not available
+pub fn curve_temperature(
+    simulated: Option<Temperature>,
+    sensor: Option<Temperature>,
+    hottest: impl FnOnce() -> Option<Temperature>,
+) -> (Option<Temperature>, FanTemperatureSource) {
+    let readable = |temp: &Temperature| temp.current.is_some();
+    if let Some(temp) = simulated {
+        (Some(temp), FanTemperatureSource::Simulated)
+    } else if let Some(temp) = sensor.filter(readable) {
+        (Some(temp), FanTemperatureSource::Sensor)
+    } else if let Some(temp) = hottest().filter(readable) {
+        (Some(temp), FanTemperatureSource::HottestSensor)
+    } else {
+        (None, FanTemperatureSource::Unavailable)
+    }
+}
+
+pub fn record_decision(log: &FanControlLog, decision: FanDecision) {
+    let mut log = log.borrow_mut();
+    if log.len() == LOG_LEN {
+        log.pop_front();
+    }
+    log.push_back(decision);
+}
+
+/// Whether a new curve should only be applied on the first tick of the fan control task instead of right away,
+/// because the previous curve was applied less than `CURVE_UPDATE_DEBOUNCE` ago.
+/// This is only done while a curve is already in control of the fan, so the fan speed doesn't depend on the firmware until then.
+pub fn debounce_curve_update(running: bool, last_update: Option<Instant>, now: Instant) -> bool {
+    running
+        && last_update
+            .and_then(|last_update| now.checked_duration_since(last_update))
+            .is_some_and(|elapsed| elapsed < CURVE_UPDATE_DEBOUNCE)
+}
+
+/// Parses the points of the firmware fan curve from `gpu_od/fan_ctrl/fan_curve`, which is available on RDNA3 GPUs.
+/// The firmware uses its built-in curve while all of the points are zero, in which case it is not known.
+pub fn parse_firmware_curve(raw: &str) -> Option<FanCurveMap> {
+    let curve: FanCurveMap = raw
+        .lines()
+        .skip_while(|line| line.trim() != "OD_FAN_CURVE:")
+        .skip(1)
+        .take_while(|line| !line.starts_with("OD_"))
+        .filter_map(|line| {
+            let (_, point) = line.split_once(':')?;
+            let mut parts = point.split_whitespace();
+            let temp = parts.next()?.strip_suffix('C')?.parse().ok()?;
+            let speed: f32 = parts.next()?.strip_suffix('%')?.parse().ok()?;
+            Some((temp, speed / 100.0))
+        })
+        .collect();
+
+    if curve.keys().all(|temp| *temp == 0) {
+        None
+    } else {
+        Some(curve)
+    }
+}
+
+/// Number of points and allowed values of the firmware fan curve, from the `OD_RANGE` section of `gpu_od/fan_ctrl/fan_curve`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmwareCurveLimits {
+    pub points: usize,
+    pub temperature: (i32, i32),
+    /// In percent
+    pub speed: (i32, i32),
+}
+
+pub fn parse_firmware_curve_limits(raw: &str) -> Option<FirmwareCurveLimits> {
+    let points = raw
+        .lines()
+        .skip_while(|line| line.trim() != "OD_FAN_CURVE:")
+        .skip(1)
+        .take_while(|line| !line.starts_with("OD_"))
+        .count();
+    let range = |name: &str| {
+        let line = raw
+            .lines()
+            .find(|line| line.trim_start().starts_with(name))?;
+        let (_, values) = line.split_once("):")?;
+        let mut values = values
+            .split_whitespace()
+            .map(|value| value.trim_end_matches(['C', '%']).parse().ok());
+        Some((values.next()??, values.next()??))
+    };
+
+    Some(FirmwareCurveLimits {
+        points: (points > 0).then_some(points)?,
+        temperature: range("FAN_CURVE(hotspot temp)")?,
+        speed: range("FAN_CURVE(fan speed)")?,
+    })
+}
+
+/// Commands for writing the curve to the firmware fan curve, which have to be followed by the `c` commit command.
+/// The firmware curve has a fixed number of points, so they are spread evenly between the first and the last point of the curve.
+/// The firmware reads the junction temperature, `offset` shifts the points like with `FanCurve::pwm_at_temp`.
+#[allow(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+pub fn firmware_curve_commands(
+    curve: &FanCurveMap,
+    offset: i32,
+    limits: FirmwareCurveLimits,
+) -> Vec<String> {
+    let (min_temp, max_temp) = limits.temperature;
+    let clamp_temp = |temp: i32| temp.saturating_add(offset).clamp(min_temp, max_temp);
+    let start = curve
+        .keys()
+        .next()
+        .map_or(min_temp, |temp| clamp_temp(*temp));
+    let end = curve
+        .keys()
+        .next_back()
+        .map_or(max_temp, |temp| clamp_temp(*temp));
+    let steps = (limits.points - 1).max(1) as i32;
+
+    (0..limits.points)
+        .map(|index| {
+            let temp = start + (end - start) * index as i32 / steps;
+            let pwm = evaluate_fan_curve(curve, temp - offset).unwrap_or(u8::MAX);
+            let speed = (i32::from(pwm) * 100 + 127) / i32::from(u8::MAX);
+            let speed = speed.clamp(limits.speed.0, limits.speed.1);
+            format!("{index} {temp} {speed}")
+        })
+        .collect()
+}
+
+/// The firmware fan curve is used on GPUs which have it (RDNA3 and newer), as manual PWM control through hwmon is not reliable there
+pub fn control_interface(
+    has_firmware_curve: bool,
+    has_pwm_enable: bool,
+) -> Option<FanControlInterface> {
+    if has_firmware_curve {
+        Some(FanControlInterface::Firmware)
+    } else if has_pwm_enable {
+        Some(FanControlInterface::Hwmon)
+    } else {
+        None
+    }
+}
+
+/// Derives how the fan can be controlled from the hwmon nodes of the GPU.
+/// Setting a target RPM needs both the tachometer (`fan1_input`) and the target node (`fan1_target`),
+/// PWM control needs `pwm1_enable` to switch the fan to manual mode.
+pub fn control_method(
+    has_pwm_enable: bool,
+    has_tach: bool,
+    has_rpm_target: bool,
+) -> Option<FanControlMethodKind> {
+    if has_tach && has_rpm_target {
+        Some(FanControlMethodKind::RpmCapable)
+    } else if has_pwm_enable {
+        Some(FanControlMethodKind::PwmOnly)
+    } else {
+        None
     }
 }
 
@@ -60,8 +230,17 @@ impl Default for FanCurve {
 
 #[cfg(test)]
 mod tests {
-    use super::FanCurve;
-    use lact_schema::amdgpu_sysfs::hw_mon::Temperature;
+    use super::{
+        control_interface, control_method, curve_temperature, debounce_curve_update,
+        firmware_curve_commands, parse_firmware_curve, parse_firmware_curve_limits,
+        record_decision, spindown_pwm, FanControlLog, FanCurve, FirmwareCurveLimits,
+        CURVE_UPDATE_DEBOUNCE, LOG_LEN,
+    };
+    use lact_schema::{
+        amdgpu_sysfs::hw_mon::Temperature, evaluate_fan_curve, FanControlInterface,
+        FanControlMethodKind, FanDecision, FanTemperatureSource,
+    };
+    use std::time::{Duration, Instant};
 
     fn simple_pwm(temp: f32) -> u8 {
         let curve = FanCurve([(0, 0.0), (100, 1.0)].into());
@@ -70,7 +249,7 @@ mod tests {
             crit: Some(150.0),
             crit_hyst: Some(-100.0),
         };
-        curve.pwm_at_temp(temp)
+        curve.pwm_at_temp(temp, 0)
     }
 
     #[test]
@@ -111,10 +290,53 @@ mod tests {
             crit: Some(90.0),
             crit_hyst: Some(0.0),
         };
-        let pwm = curve.pwm_at_temp(temp);
+        let pwm = curve.pwm_at_temp(temp, 0);
         assert_eq!(pwm, 255);
     }
 
+    #[test]
+    fn curve_offset() {
+        let curve = FanCurve([(40, 0.0), (60, 1.0)].into());
+        let pwm_at_temp = |current: f32, offset: i32| {
+            let temp = Temperature {
+                current: Some(current),
+                crit: Some(90.0),
+                crit_hyst: Some(0.0),
+            };
+            curve.pwm_at_temp(temp, offset)
+        };
+
+        assert_eq!(pwm_at_temp(50.0, 0), 127);
+        // Points shifted 5 degrees lower, so the fan spins faster at the same temperature
+        assert_eq!(pwm_at_temp(45.0, -5), 127);
+        assert_eq!(pwm_at_temp(55.0, -5), 255);
+        assert_eq!(pwm_at_temp(55.0, 5), 127);
+        assert_eq!(pwm_at_temp(40.0, 5), 0);
+        // The offset doesn't affect the critical temperature check
+        assert_eq!(pwm_at_temp(95.0, 30), 255);
+    }
+
+    #[test]
+    fn client_preview_matches() {
+        let curves = [
+            FanCurve::default(),
+            FanCurve([(30, 0.0), (40, 0.1), (55, 0.9), (61, 1.0)].into()),
+        ];
+        for curve in curves {
+            for current in 0..100u8 {
+                let temp = Temperature {
+                    current: Some(f32::from(current)),
+                    crit: Some(150.0),
+                    crit_hyst: Some(-100.0),
+                };
+                assert_eq!(
+                    Some(curve.pwm_at_temp(temp, 0)),
+                    evaluate_fan_curve(&curve.0, i32::from(current))
+                );
+            }
+        }
+    }
+
     #[test]
     fn uneven_curve() {
         let curve = FanCurve([(30, 0.0), (40, 0.1), (55, 0.9), (61, 1.0)].into());
@@ -124,7 +346,7 @@ mod tests {
                 crit: Some(90.0),
                 crit_hyst: Some(0.0),
             };
-            curve.pwm_at_temp(temp)
+            curve.pwm_at_temp(temp, 0)
         };
 
         assert_eq!(pwm_at_temp(30.0), 0);
@@ -145,7 +367,7 @@ mod tests {
                 crit: Some(90.0),
                 crit_hyst: Some(0.0),
             };
-            curve.pwm_at_temp(temp)
+            curve.pwm_at_temp(temp, 0)
         };
         assert_eq!(pwm_at_temp(20.0), 0);
         assert_eq!(pwm_at_temp(30.0), 0);
@@ -158,4 +380,166 @@ mod tests {
         assert_eq!(pwm_at_temp(100.0), 255);
         assert_eq!(pwm_at_temp(-5.0), 255);
     }
+
+    #[test]
+    fn validate_curve() {
+        FanCurve::default().validate().unwrap();
+        assert!(FanCurve([(50, 0.5)].into()).validate().is_err());
+        assert!(FanCurve([(30, 0.0), (80, 1.5)].into()).validate().is_err());
+    }
+
+    #[test]
+    fn firmware_curve() {
+        let raw = "\
+OD_FAN_CURVE:
+0: 25C 20%
+1: 45C 30%
+2: 65C 50%
+3: 80C 80%
+4: 95C 100%
+OD_RANGE:
+FAN_CURVE(hotspot temp): 25C 100C
+FAN_CURVE(fan speed): 20% 100%
+";
+        assert_eq!(
+            parse_firmware_curve(raw),
+            Some([(25, 0.2), (45, 0.3), (65, 0.5), (80, 0.8), (95, 1.0)].into())
+        );
+    }
+
+    #[test]
+    fn spindown_curve() {
+        // Heating up follows the main curve
+        assert_eq!(spindown_pwm(100, 150, 200), 150);
+        // Cooling down keeps the speed until the spindown curve is lower
+        assert_eq!(spindown_pwm(150, 100, 170), 150);
+        assert_eq!(spindown_pwm(150, 100, 120), 120);
+        // A spindown curve below the main one doesn't slow the fan down further
+        assert_eq!(spindown_pwm(150, 100, 50), 100);
+    }
+
+    #[test]
+    fn curve_update_debounce() {
+        let last_update = Instant::now();
+        let soon = last_update + Duration::from_millis(100);
+        assert!(debounce_curve_update(true, Some(last_update), soon));
+        assert!(!debounce_curve_update(false, Some(last_update), soon));
+        assert!(!debounce_curve_update(true, None, soon));
+        assert!(!debounce_curve_update(
+            true,
+            Some(last_update),
+            last_update + CURVE_UPDATE_DEBOUNCE
+        ));
+    }
+
+    #[test]
+    fn firmware_curve_unset() {
+        let raw = "\
+OD_FAN_CURVE:
+0: 0C 0%
+1: 0C 0%
+2: 0C 0%
+3: 0C 0%
+4: 0C 0%
+OD_RANGE:
+FAN_CURVE(hotspot temp): 25C 100C
+FAN_CURVE(fan speed): 20% 100%
+";
+        assert_eq!(parse_firmware_curve(raw), None);
+    }
+
+    #[test]
+    fn fan_control_methods() {
+        assert_eq!(
+            control_method(true, true, true),
+            Some(FanControlMethodKind::RpmCapable)
+        );
+        assert_eq!(
+            control_method(true, true, false),
+            Some(FanControlMethodKind::PwmOnly)
+        );
+        assert_eq!(control_method(false, false, false), None);
+    }
+
+    #[test]
+    fn firmware_curve_write() {
+        let raw = "\
+OD_FAN_CURVE:
+0: 0C 0%
+1: 0C 0%
+2: 0C 0%
+3: 0C 0%
+4: 0C 0%
+OD_RANGE:
+FAN_CURVE(hotspot temp): 25C 100C
+FAN_CURVE(fan speed): 20% 100%
+";
+        let limits = parse_firmware_curve_limits(raw).unwrap();
+        assert_eq!(
+            limits,
+            FirmwareCurveLimits {
+                points: 5,
+                temperature: (25, 100),
+                speed: (20, 100),
+            }
+        );
+
+        let curve = [(40, 0.1), (60, 0.5), (80, 1.0)].into();
+        assert_eq!(
+            firmware_curve_commands(&curve, 0, limits),
+            ["0 40 20", "1 50 30", "2 60 50", "3 70 75", "4 80 100"]
+        );
+        assert_eq!(
+            firmware_curve_commands(&curve, -20, limits),
+            ["0 25 20", "1 33 36", "2 42 55", "3 51 77", "4 60 100"]
+        );
+
+        assert_eq!(
+            control_interface(true, true),
+            Some(FanControlInterface::Firmware)
+        );
+        assert_eq!(
+            control_interface(false, true),
+            Some(FanControlInterface::Hwmon)
+        );
+    }
+
+    #[test]
+    fn fan_decisions() {
+        let temp = |current| Temperature {
+            current: Some(current),
+            crit: None,
+            crit_hyst: None,
+        };
+
+        let (picked, source) = curve_temperature(Some(temp(90.0)), Some(temp(50.0)), || None);
+        assert_eq!(picked.unwrap().current, Some(90.0));
+        assert_eq!(source, FanTemperatureSource::Simulated);
+        let (_, source) = curve_temperature(None, Some(temp(50.0)), || None);
+        assert_eq!(source, FanTemperatureSource::Sensor);
+        let (picked, source) = curve_temperature(None, None, || Some(temp(70.0)));
+        assert_eq!(picked.unwrap().current, Some(70.0));
+        assert_eq!(source, FanTemperatureSource::HottestSensor);
+        let (picked, source) = curve_temperature(None, None, || None);
+        assert!(picked.is_none());
+        assert_eq!(source, FanTemperatureSource::Unavailable);
+
+        let log = FanControlLog::default();
+        for timestamp_ms in 0..=LOG_LEN as u64 {
+            record_decision(
+                &log,
+                FanDecision {
+                    timestamp_ms,
+                    temperature: Some(50.0),
+                    temperature_source: FanTemperatureSource::Sensor,
+                    curve_pwm: 100,
+                    pwm: 100,
+                    spindown_held: false,
+                },
+            );
+        }
+        let log = log.borrow();
+        assert_eq!(log.len(), LOG_LEN);
+        assert_eq!(log.front().unwrap().timestamp_ms, 1);
+    }
 }
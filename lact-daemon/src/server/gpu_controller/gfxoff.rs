@@ -0,0 +1,78 @@
+use lact_schema::{GfxoffState, GfxoffStats};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DEBUGFS_DRI_DIR: &str = "/sys/kernel/debug/dri";
+
+/// Finds the debugfs directory of the card that the given sysfs device path belongs to.
+/// The directories are named after the DRM minor of the card, which is listed in the `drm` subdirectory of the device.
+pub fn debugfs_dir(device_path: &Path) -> Option<PathBuf> {
+    fs::read_dir(device_path.join("drm"))
+        .ok()?
+        .flatten()
+        .find_map(|entry| {
+            let name = entry.file_name();
+            let minor: u32 = name.to_str()?.strip_prefix("card")?.parse().ok()?;
+            Some(Path::new(DEBUGFS_DRI_DIR).join(minor.to_string()))
+        })
+        .filter(|dir| dir.exists())
+}
+
+/// Reads the GFXOFF status from the amdgpu debugfs files.
+/// These require debugfs to be mounted, and are only available on GPUs which support GFXOFF.
+pub fn read_stats(debugfs_dir: &Path, feature_enabled: Option<bool>) -> GfxoffStats {
+    GfxoffStats {
+        feature_enabled,
+        state: read_value(&debugfs_dir.join("amdgpu_gfxoff_status")).and_then(parse_state),
+        entry_count: read_value(&debugfs_dir.join("amdgpu_gfxoff_count")),
+        residency: read_value(&debugfs_dir.join("amdgpu_gfxoff_residency"))
+            .map(|residency| f64::from(u32::try_from(residency).unwrap_or(u32::MAX)) / 100.0),
+    }
+}
+
+/// The debugfs files contain the raw native-endian integer instead of text.
+/// Some of them are 32-bit and some are 64-bit.
+fn read_value(path: &Path) -> Option<u64> {
+    parse_value(&fs::read(path).ok()?)
+}
+
+fn parse_value(raw: &[u8]) -> Option<u64> {
+    match raw.len() {
+        4 => Some(u32::from_ne_bytes(raw.try_into().ok()?).into()),
+        8 => Some(u64::from_ne_bytes(raw.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn parse_state(value: u64) -> Option<GfxoffState> {
+    match value {
+        0 => Some(GfxoffState::Off),
+        1 => Some(GfxoffState::Exiting),
+        2 => Some(GfxoffState::On),
+        3 => Some(GfxoffState::Entering),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_state, parse_value};
+    use lact_schema::GfxoffState;
+
+    #[test]
+    fn debugfs_values() {
+        assert_eq!(parse_value(&2u32.to_ne_bytes()), Some(2));
+        assert_eq!(parse_value(&123_456u64.to_ne_bytes()), Some(123_456));
+        assert_eq!(parse_value(b"1\n"), None);
+        assert_eq!(parse_value(&[]), None);
+    }
+
+    #[test]
+    fn gfxoff_state() {
+        assert_eq!(parse_state(0), Some(GfxoffState::Off));
+        assert_eq!(parse_state(2), Some(GfxoffState::On));
+        assert_eq!(parse_state(4), None);
+    }
+}
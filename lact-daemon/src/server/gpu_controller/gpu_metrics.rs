@@ -0,0 +1,203 @@
+//! Values read from the binary `gpu_metrics` sysfs file, which is parsed by libdrm like the full table in `smu_metrics`.
+//! Without the `drm` feature none of the values are available.
+
+use lact_schema::ThrottleReason;
+#[cfg(feature = "libdrm_amdgpu_sys")]
+use libdrm_amdgpu_sys::AMDGPU::{GpuMetrics, MetricsInfo};
+
+/// Unit of the energy accumulator: 15.259 µJ (2^-16 J)
+#[cfg(feature = "libdrm_amdgpu_sys")]
+const ENERGY_UNIT_JOULES: f64 = 1.0 / 65536.0;
+
+/// Reads the energy consumed (in joules) since the driver was loaded.
+/// This is only available in the dGPU metrics tables, starting with format 1.1.
+#[cfg(feature = "libdrm_amdgpu_sys")]
+#[allow(clippy::cast_precision_loss)]
+pub fn energy_consumed(metrics: &[u8]) -> Option<f64> {
+    let accumulator = match GpuMetrics::from_raw(metrics) {
+        GpuMetrics::V1_1(table) => table.energy_accumulator,
+        GpuMetrics::V1_2(table) => table.energy_accumulator,
+        GpuMetrics::V1_3(table) => table.energy_accumulator,
+        _ => return None,
+    };
+    // Unsupported fields are filled with ones
+    if accumulator == u64::MAX {
+        return None;
+    }
+    Some(accumulator as f64 * ENERGY_UNIT_JOULES)
+}
+
+/// Reads the average and current graphics clock (in MHz).
+/// The average includes the time spent idle or in GFXOFF, which makes it lower than the current clock under partial load.
+#[cfg(feature = "libdrm_amdgpu_sys")]
+pub fn gfx_clocks(metrics: &[u8]) -> (Option<u16>, Option<u16>) {
+    let table = GpuMetrics::from_raw(metrics);
+    (
+        supported(table.get_average_gfxclk_frequency()),
+        supported(table.get_current_gfxclk()),
+    )
+}
+
+/// Reads the average and current memory clock (`uclk`, in MHz), which behave the same way as the graphics clocks
+#[cfg(feature = "libdrm_amdgpu_sys")]
+pub fn memory_clocks(metrics: &[u8]) -> (Option<u16>, Option<u16>) {
+    let table = GpuMetrics::from_raw(metrics);
+    (
+        supported(table.get_average_uclk_frequency()),
+        supported(table.get_current_uclk()),
+    )
+}
+
+#[cfg(feature = "libdrm_amdgpu_sys")]
+fn supported(value: Option<u16>) -> Option<u16> {
+    value.filter(|value| *value != u16::MAX)
+}
+
+/// Reads the reasons for which the GPU is currently throttled.
+/// This uses the ASIC-independent throttle status, which is only available in format 1.3 and starting with format 2.2.
+#[cfg(feature = "libdrm_amdgpu_sys")]
+pub fn throttle_reasons(metrics: &[u8]) -> Option<Vec<ThrottleReason>> {
+    let status = GpuMetrics::from_raw(metrics)
+        .get_indep_throttle_status()
+        .filter(|status| *status != u64::MAX)?;
+
+    // Throttler bits are grouped by category, see `SMU_THROTTLER_*` in the kernel's amdgpu_smu.h
+    let reasons = [
+        (ThrottleReason::Power, 0..16),
+        (ThrottleReason::Current, 16..32),
+        (ThrottleReason::Temperature, 32..56),
+        (ThrottleReason::Other, 56..64),
+    ]
+    .into_iter()
+    .filter(|(_, bits)| bits.clone().any(|bit| status & (1 << bit) != 0))
+    .map(|(reason, _)| reason)
+    .collect();
+    Some(reasons)
+}
+
+#[cfg(not(feature = "libdrm_amdgpu_sys"))]
+pub fn energy_consumed(_metrics: &[u8]) -> Option<f64> {
+    None
+}
+
+#[cfg(not(feature = "libdrm_amdgpu_sys"))]
+pub fn gfx_clocks(_metrics: &[u8]) -> (Option<u16>, Option<u16>) {
+    (None, None)
+}
+
+#[cfg(not(feature = "libdrm_amdgpu_sys"))]
+pub fn memory_clocks(_metrics: &[u8]) -> (Option<u16>, Option<u16>) {
+    (None, None)
+}
+
+#[cfg(not(feature = "libdrm_amdgpu_sys"))]
+pub fn throttle_reasons(_metrics: &[u8]) -> Option<Vec<ThrottleReason>> {
+    None
+}
+
+#[cfg(all(test, feature = "libdrm_amdgpu_sys"))]
+mod tests {
+    use super::{energy_consumed, gfx_clocks, memory_clocks, throttle_reasons};
+    use lact_schema::ThrottleReason;
+
+    fn metrics_table(format_revision: u8, content_revision: u8, accumulator: u64) -> Vec<u8> {
+        let mut table = vec![0; 64];
+        table[0..2].copy_from_slice(&64u16.to_le_bytes());
+        table[2] = format_revision;
+        table[3] = content_revision;
+        table[24..32].copy_from_slice(&accumulator.to_le_bytes());
+        table
+    }
+
+    #[test]
+    fn energy_v1_3() {
+        let table = metrics_table(1, 3, 65536 * 1000);
+        assert_eq!(energy_consumed(&table), Some(1000.0));
+    }
+
+    #[test]
+    fn energy_unsupported() {
+        assert_eq!(energy_consumed(&metrics_table(1, 0, 5)), None);
+        assert_eq!(energy_consumed(&metrics_table(1, 4, 5)), None);
+        assert_eq!(energy_consumed(&metrics_table(2, 1, 5)), None);
+        assert_eq!(energy_consumed(&metrics_table(1, 1, u64::MAX)), None);
+        assert_eq!(energy_consumed(&[1, 0]), None);
+    }
+
+    #[test]
+    fn gfx_clocks_average_and_current() {
+        let mut table = metrics_table(1, 3, 0);
+        table[40..42].copy_from_slice(&850u16.to_le_bytes());
+        table[54..56].copy_from_slice(&2400u16.to_le_bytes());
+        assert_eq!(gfx_clocks(&table), (Some(850), Some(2400)));
+
+        table[40..42].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(gfx_clocks(&table), (None, Some(2400)));
+
+        let mut table = vec![0; 128];
+        table[2] = 2;
+        table[3] = 0;
+        table[68..70].copy_from_slice(&400u16.to_le_bytes());
+        table[80..82].copy_from_slice(&1800u16.to_le_bytes());
+        assert_eq!(gfx_clocks(&table), (Some(400), Some(1800)));
+
+        assert_eq!(gfx_clocks(&metrics_table(3, 0, 0)), (None, None));
+    }
+
+    #[test]
+    fn memory_clocks_average_and_current() {
+        let mut table = metrics_table(1, 3, 0);
+        table[44..46].copy_from_slice(&96u16.to_le_bytes());
+        table[58..60].copy_from_slice(&1000u16.to_le_bytes());
+        assert_eq!(memory_clocks(&table), (Some(96), Some(1000)));
+
+        let mut table = vec![0; 128];
+        table[2] = 2;
+        table[3] = 0;
+        table[72..74].copy_from_slice(&800u16.to_le_bytes());
+        table[84..86].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(memory_clocks(&table), (Some(800), None));
+    }
+
+    #[test]
+    fn clocks_v2_1() {
+        // Layout of gpu_metrics_v2_1, as used by Van Gogh, Rembrandt and Phoenix
+        let mut table = vec![0; 128];
+        table[2] = 2;
+        table[3] = 1;
+        // system_clock_counter, which is in place of the gfxclk in the 2.0 layout
+        table[32..40].copy_from_slice(&123_456u64.to_le_bytes());
+        // average_gfxclk_frequency, average_uclk_frequency
+        table[64..66].copy_from_slice(&400u16.to_le_bytes());
+        table[68..70].copy_from_slice(&1600u16.to_le_bytes());
+        // current_gfxclk, current_uclk
+        table[76..78].copy_from_slice(&1800u16.to_le_bytes());
+        table[80..82].copy_from_slice(&2800u16.to_le_bytes());
+
+        assert_eq!(gfx_clocks(&table), (Some(400), Some(1800)));
+        assert_eq!(memory_clocks(&table), (Some(1600), Some(2800)));
+    }
+
+    #[test]
+    fn throttle_status() {
+        let mut table = vec![0; 128];
+        table[2] = 2;
+        table[3] = 2;
+        // PPT0 and TEMP_HOTSPOT
+        let status: u64 = (1 << 0) | (1 << 36);
+        table[120..128].copy_from_slice(&status.to_le_bytes());
+        assert_eq!(
+            throttle_reasons(&table),
+            Some(vec![ThrottleReason::Power, ThrottleReason::Temperature])
+        );
+
+        table[2] = 1;
+        table[3] = 3;
+        assert_eq!(throttle_reasons(&table), Some(vec![]));
+
+        table[3] = 1;
+        assert_eq!(throttle_reasons(&table), None);
+        table[3] = 4;
+        assert_eq!(throttle_reasons(&table), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! XGMI links between GPUs in the same hive, as found on multi-GPU compute nodes.
+//! The driver doesn't report the speed of each link, only the topology of the hive and an error counter.
+
+use anyhow::Context;
+use lact_schema::{InterconnectInfo, InterconnectKind, InterconnectLink};
+use std::{fs, path::Path};
+
+/// Reads the XGMI state of the GPU, failing when it is not part of a hive
+pub fn info(device_path: &Path) -> anyhow::Result<InterconnectInfo> {
+    let read = |file: &str| {
+        fs::read_to_string(device_path.join(file))
+            .ok()
+            .map(|value| value.trim().to_owned())
+    };
+
+    let device_id = read("xgmi_device_id").context("This GPU has no XGMI interconnect")?;
+    let hive_path = device_path.join("xgmi_hive_info");
+    let hive_id = fs::read_to_string(hive_path.join("xgmi_hive_id"))
+        .ok()
+        .map(|value| value.trim().to_owned());
+
+    // Both lists have one entry per GPU of the hive, in the order of the `node` links
+    let num_hops = read("xgmi_num_hops").map(|value| parse_hex_list(&value));
+    let num_links = read("xgmi_num_links").map(|value| parse_hex_list(&value));
+    let device_slot = fs::canonicalize(device_path)
+        .ok()
+        .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()));
+
+    let mut links = Vec::new();
+    for index in 0.. {
+        let Ok(peer_path) = fs::canonicalize(hive_path.join(format!("node{index}"))) else {
+            break;
+        };
+        let peer = peer_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        if device_slot.as_ref() == Some(&peer) {
+            continue;
+        }
+
+        links.push(InterconnectLink {
+            peer,
+            hops: num_hops
+                .as_ref()
+                .and_then(|values| values.get(index).copied()),
+            links: num_links
+                .as_ref()
+                .and_then(|values| values.get(index).copied()),
+        });
+    }
+
+    Ok(InterconnectInfo {
+        kind: InterconnectKind::Xgmi,
+        device_id,
+        hive_id,
+        error_count: read("xgmi_error").and_then(|value| value.parse().ok()),
+        links,
+    })
+}
+
+fn parse_hex_list(value: &str) -> Vec<u8> {
+    value
+        .split_whitespace()
+        .map_while(|item| u8::from_str_radix(item, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::info;
+    use crate::test_util::fake_sysfs;
+    use lact_schema::InterconnectLink;
+    use std::{fs, os::unix::fs::symlink};
+
+    #[test]
+    fn xgmi_hive() {
+        let root = fake_sysfs(
+            "xgmi",
+            &[
+                ("0000:0c:00.0/xgmi_device_id", "2733475000651049107\n"),
+                ("0000:0c:00.0/xgmi_error", "0\n"),
+                ("0000:0c:00.0/xgmi_num_hops", "01 00 01 "),
+                ("0000:0c:00.0/xgmi_num_links", "02 00 04 "),
+                (
+                    "0000:0c:00.0/xgmi_hive_info/xgmi_hive_id",
+                    "8187910841548424810\n",
+                ),
+            ],
+        );
+        for slot in ["0000:0b:00.0", "0000:0d:00.0"] {
+            fs::create_dir_all(root.join(slot)).unwrap();
+        }
+        let device_path = root.join("0000:0c:00.0");
+        let hive_path = device_path.join("xgmi_hive_info");
+        for (index, slot) in ["0000:0b:00.0", "0000:0c:00.0", "0000:0d:00.0"]
+            .into_iter()
+            .enumerate()
+        {
+            symlink(root.join(slot), hive_path.join(format!("node{index}"))).unwrap();
+        }
+
+        let interconnect = info(&device_path);
+        let missing = info(&root.join("0000:0b:00.0"));
+
+        let interconnect = interconnect.unwrap();
+        assert_eq!(interconnect.hive_id.as_deref(), Some("8187910841548424810"));
+        assert_eq!(interconnect.error_count, Some(0));
+        assert_eq!(
+            interconnect.links,
+            [
+                InterconnectLink {
+                    peer: "0000:0b:00.0".to_owned(),
+                    hops: Some(1),
+                    links: Some(2),
+                },
+                InterconnectLink {
+                    peer: "0000:0d:00.0".to_owned(),
+                    hops: Some(1),
+                    links: Some(4),
+                },
+            ]
+        );
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use super::{processes, TaskHandle};
+use crate::config::MemoryClockBoost;
+use anyhow::Context;
+use lact_schema::{
+    amdgpu_sysfs::gpu_handle::{GpuHandle, PerformanceLevel, PowerLevelKind},
+    MemoryClockBoostStatus,
+};
+use std::{cell::RefCell, fs, path::Path, rc::Rc, time::Duration};
+use tokio::{select, sync::Notify, time::sleep};
+use tracing::{debug, error, info};
+
+const INTERVAL: Duration = Duration::from_secs(2);
+
+/// Memory settings from the config, which are restored once the game exits
+pub struct RestoreSettings {
+    pub performance_level: Option<PerformanceLevel>,
+    pub memory_states: Option<Vec<u8>>,
+}
+
+/// Windows games running through Wine or Proton are detected by their executable, other games have to be listed in the settings.
+/// `comm` is truncated by the kernel, so the `.exe` suffix is checked on the first command line argument instead.
+pub fn is_game(comm: &str, argv0: &str, settings: &MemoryClockBoost) -> bool {
+    let executable = argv0.rsplit(['/', '\\']).next().unwrap_or(argv0);
+    executable.to_ascii_lowercase().ends_with(".exe")
+        || settings
+            .processes
+            .iter()
+            .any(|name| name == comm || name == executable)
+}
+
+fn find_game(slot_name: &str, settings: &MemoryClockBoost) -> Option<String> {
+    processes::get_processes(slot_name)
+        .into_iter()
+        .find(|process| {
+            let cmdline = fs::read(
+                Path::new("/proc")
+                    .join(process.pid.to_string())
+                    .join("cmdline"),
+            )
+            .unwrap_or_default();
+            let argv0 = cmdline.split(|byte| *byte == 0).next().unwrap_or_default();
+            is_game(&process.name, &String::from_utf8_lossy(argv0), settings)
+        })
+        .map(|process| process.name)
+}
+
+fn boost(handle: &GpuHandle) -> anyhow::Result<()> {
+    let levels = handle.get_memory_clock_levels()?;
+    let highest = levels
+        .levels
+        .len()
+        .checked_sub(1)
+        .context("The GPU has no memory power states")?;
+    handle.set_power_force_performance_level(PerformanceLevel::Manual)?;
+    handle.set_enabled_power_levels(PowerLevelKind::MemoryClock, &[u8::try_from(highest)?])?;
+    Ok(())
+}
+
+fn restore(handle: &GpuHandle, settings: &RestoreSettings) -> anyhow::Result<()> {
+    if settings.performance_level == Some(PerformanceLevel::Manual) {
+        let states = if let Some(states) = &settings.memory_states {
+            states.clone()
+        } else {
+            let count = handle.get_memory_clock_levels()?.levels.len();
+            (0..u8::try_from(count)?).collect()
+        };
+        handle.set_enabled_power_levels(PowerLevelKind::MemoryClock, &states)?;
+    } else {
+        let level = settings.performance_level.unwrap_or(PerformanceLevel::Auto);
+        handle.set_power_force_performance_level(level)?;
+    }
+    Ok(())
+}
+
+/// Spawns a task which periodically checks for games using the GPU,
+/// forcing the highest memory clock while one is running to avoid stutter from memory clock switching.
+pub fn spawn(
+    handle: GpuHandle,
+    slot_name: String,
+    settings: MemoryClockBoost,
+    restore_settings: RestoreSettings,
+    status: Rc<RefCell<Option<MemoryClockBoostStatus>>>,
+) -> TaskHandle {
+    let notify = Rc::new(Notify::new());
+    let task_notify = notify.clone();
+
+    let task = tokio::task::spawn_local(async move {
+        let mut boosted = false;
+
+        loop {
+            select! {
+                () = sleep(INTERVAL) => (),
+                () = task_notify.notified() => break,
+            }
+
+            let game = find_game(&slot_name, &settings);
+            if game.is_some() != boosted {
+                let result = if let Some(game) = &game {
+                    info!("game `{game}` detected, forcing the highest memory clock");
+                    boost(&handle)
+                } else {
+                    info!("no game running anymore, restoring the memory clock settings");
+                    restore(&handle, &restore_settings)
+                };
+                if let Err(err) = result {
+                    error!("could not switch memory clock: {err:#}, disabling memory clock boost");
+                    break;
+                }
+                boosted = game.is_some();
+            }
+            *status.borrow_mut() = Some(MemoryClockBoostStatus { game });
+        }
+
+        if boosted {
+            if let Err(err) = restore(&handle, &restore_settings) {
+                error!("could not restore memory clock settings: {err:#}");
+            }
+        }
+        debug!("exited memory clock boost task");
+    });
+
+    (notify, task)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_game;
+    use crate::config::MemoryClockBoost;
+
+    #[test]
+    fn game_detection() {
+        let settings = MemoryClockBoost {
+            processes: vec!["factorio".to_owned()],
+        };
+        assert!(is_game(
+            "Cyberpunk2077.e",
+            "Z:\\games\\Cyberpunk 2077\\bin\\x64\\Cyberpunk2077.exe",
+            &settings
+        ));
+        assert!(is_game(
+            "factorio",
+            "/opt/factorio/bin/x64/factorio",
+            &settings
+        ));
+        assert!(!is_game("firefox", "/usr/lib/firefox/firefox", &settings));
+        assert!(!is_game(
+            "firefox",
+            "/usr/lib/firefox/firefox",
+            &MemoryClockBoost::default()
+        ));
+    }
+}
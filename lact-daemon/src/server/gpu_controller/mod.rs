@@ -1,8 +1,41 @@
+mod adaptive_clocks;
+mod audio;
+mod board_sensors;
+pub mod clocks_ramp;
+mod controllable_nodes;
+mod engine_activity;
+mod external_changes;
 pub mod fan_control;
-
-use self::fan_control::FanCurve;
-use super::vulkan::get_vulkan_info;
-use crate::{config, fork::run_forked};
+mod gfxoff;
+mod gpu_metrics;
+mod interconnect;
+mod memory_clock_boost;
+mod pci_bars;
+mod power_profile_modes;
+mod pp_features;
+pub mod processes;
+mod pstate_residency;
+mod read_failures;
+mod retired_pages;
+#[cfg(feature = "libdrm_amdgpu_sys")]
+mod smu_metrics;
+mod temp_trend;
+mod throttle_residency;
+
+use self::{
+    engine_activity::EngineUsageSample,
+    external_changes::LiveSettings,
+    fan_control::{FanControlLog, FanCurve},
+    pstate_residency::PstateSamples,
+    read_failures::ReadFailures,
+    temp_trend::TempSamples,
+    throttle_residency::ThrottleSamples,
+};
+use super::{system, vulkan::get_vulkan_info};
+use crate::{
+    config::{self, MemoryClockBoost},
+    fork::run_forked,
+};
 use anyhow::{anyhow, Context};
 use lact_schema::{
     amdgpu_sysfs::{
@@ -11,23 +44,32 @@ use lact_schema::{
             overdrive::{ClocksTable, ClocksTableGen},
             GpuHandle, PerformanceLevel, PowerLevelKind, PowerLevels,
         },
-        hw_mon::{FanControlMethod, HwMon},
+        hw_mon::{FanControlMethod, HwMon, Temperature},
         sysfs::SysFS,
     },
-    ClocksInfo, ClockspeedStats, DeviceInfo, DeviceStats, DrmInfo, FanStats, GpuPciInfo, LinkInfo,
-    PciInfo, PowerState, PowerStates, PowerStats, VoltageStats, VramStats,
+    default_fan_curve, AdaptiveClocks, AdaptiveClocksState, AdaptiveClocksStatus, AspmInfo,
+    AudioDeviceInfo, ClockDomain, ClocksControl, ClocksInfo, ClocksRampStatus, ClockspeedStats,
+    ControllableNode, DefaultFanCurve, DeviceInfo, DeviceStats, DrmInfo, EccInfo, FanCurveDefaults,
+    FanDecision, FanStats, GfxoffStats, GpuPciInfo, GpuProcess, InterconnectInfo, LinkInfo,
+    MemoryClockBoostStatus, OverclockStatus, PciInfo, PowerProfileModesDetails, PowerState,
+    PowerStates, PowerStats, PowerTier, PowerTiers, PpFeature, QuickStats, ReadFailureCount,
+    ResetMethod, ResetMethods, RetiredPage, RuntimePmStatus, SmuMetrics, TemperatureLimits,
+    ThrottleReason, VoltageCurve, VoltageStats, VramStats,
 };
 use pciid_parser::Database;
 use std::{
     borrow::Cow,
-    cell::RefCell,
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, VecDeque},
+    fs,
     path::{Path, PathBuf},
     rc::Rc,
     str::FromStr,
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::{select, sync::Notify, task::JoinHandle, time::sleep};
-use tracing::{debug, error, trace, warn};
+use tracing::{debug, error, info, trace, warn};
 #[cfg(feature = "libdrm_amdgpu_sys")]
 use {
     lact_schema::DrmMemoryInfo,
@@ -35,14 +77,44 @@ use {
     std::{fs::File, os::fd::IntoRawFd},
 };
 
-type FanControlHandle = (Rc<Notify>, JoinHandle<()>);
+type TaskHandle = (Rc<Notify>, JoinHandle<()>);
+/// Temperature seen by the fan curve instead of the sensor reading, until it expires
+type SimulatedTemp = Rc<Cell<Option<(f32, Instant)>>>;
+/// Last stats and when they were read, for the minimum sampling interval
+type StatsCache = RefCell<Option<(Instant, DeviceStats)>>;
+
+const PASSTHROUGH_DRIVERS: [&str; 2] = ["vfio-pci", "pci-stub"];
 
 pub struct GpuController {
     pub(super) handle: GpuHandle,
     #[cfg(feature = "libdrm_amdgpu_sys")]
     pub drm_handle: Option<DrmHandle>,
     pub pci_info: Option<GpuPciInfo>,
-    pub fan_control_handle: RefCell<Option<FanControlHandle>>,
+    pub fan_control_handle: RefCell<Option<TaskHandle>>,
+    pub adaptive_clocks_handle: RefCell<Option<TaskHandle>>,
+    pub adaptive_clocks_status: Rc<Cell<Option<AdaptiveClocksStatus>>>,
+    memory_clock_boost_handle: RefCell<Option<TaskHandle>>,
+    memory_clock_boost_status: Rc<RefCell<Option<MemoryClockBoostStatus>>>,
+    pub clocks_ramp_status: Cell<Option<ClocksRampStatus>>,
+    pub reset_count: Cell<u32>,
+    pub last_reset_at: Cell<Option<u64>>,
+    simulated_temp: SimulatedTemp,
+    fan_control_log: FanControlLog,
+    /// When the fan curve was last (re)started, for debouncing rapid curve changes
+    fan_curve_updated_at: Cell<Option<Instant>>,
+    stats_cache: StatsCache,
+    read_failures: RefCell<ReadFailures>,
+    engine_usage_sample: RefCell<Option<EngineUsageSample>>,
+    engine_activity: RefCell<HashMap<String, f32>>,
+    pstate_samples: PstateSamples,
+    temp_samples: TempSamples,
+    throttle_samples: ThrottleSamples,
+    /// Monitors of the VRM and other chips on the board
+    board_hw_mons: Vec<HwMon>,
+    /// Monitors of the other GPUs (see `temp_monitors`), which can be used as the fan control temperature source
+    peer_hw_mons: RefCell<BTreeMap<String, Vec<HwMon>>>,
+    /// Settings from the last successful `apply_config`, for detecting changes made by other tools
+    applied_config: RefCell<Option<config::Gpu>>,
 }
 
 impl GpuController {
@@ -108,15 +180,65 @@ impl GpuController {
             })
         });
 
+        let board_hw_mons = board_sensors::find(handle.get_path());
+
         Ok(Self {
             handle,
             #[cfg(feature = "libdrm_amdgpu_sys")]
             drm_handle,
             pci_info,
             fan_control_handle: RefCell::new(None),
+            adaptive_clocks_handle: RefCell::new(None),
+            adaptive_clocks_status: Rc::new(Cell::new(None)),
+            memory_clock_boost_handle: RefCell::new(None),
+            memory_clock_boost_status: Rc::default(),
+            clocks_ramp_status: Cell::new(None),
+            reset_count: Cell::new(0),
+            last_reset_at: Cell::new(None),
+            simulated_temp: Rc::new(Cell::new(None)),
+            fan_control_log: Rc::default(),
+            fan_curve_updated_at: Cell::new(None),
+            stats_cache: RefCell::new(None),
+            read_failures: RefCell::default(),
+            engine_usage_sample: RefCell::new(None),
+            engine_activity: RefCell::new(HashMap::new()),
+            pstate_samples: Rc::new(RefCell::new(VecDeque::new())),
+            temp_samples: Rc::new(RefCell::new(VecDeque::new())),
+            throttle_samples: Rc::new(RefCell::new(VecDeque::new())),
+            board_hw_mons,
+            peer_hw_mons: RefCell::new(BTreeMap::new()),
+            applied_config: RefCell::new(None),
         })
     }
 
+    pub fn get_driver(&self) -> &str {
+        self.handle.get_driver()
+    }
+
+    /// GPUs bound to a passthrough driver are reserved for virtual machines and have no settings to change
+    pub fn is_controllable(&self) -> bool {
+        !PASSTHROUGH_DRIVERS.contains(&self.get_driver())
+    }
+
+    /// The file is only present on VGA class devices, others are never the boot display
+    pub fn is_boot_vga(&self) -> bool {
+        self.handle
+            .read_file("boot_vga")
+            .is_ok_and(|value| value.trim() == "1")
+    }
+
+    /// Starts recording the core clock power state, temperatures and throttle reasons in the background,
+    /// which are used for `pstate_residency`, `temp_trend` and `throttle_residency` in the stats
+    pub fn start_sampling(&self) {
+        pstate_residency::spawn(self.handle.clone(), self.pstate_samples.clone());
+        temp_trend::spawn(self.handle.clone(), self.temp_samples.clone());
+        throttle_residency::spawn(self.handle.clone(), self.throttle_samples.clone());
+    }
+
+    pub fn set_peer_hw_mons(&self, hw_mons: BTreeMap<String, Vec<HwMon>>) {
+        *self.peer_hw_mons.borrow_mut() = hw_mons;
+    }
+
     pub fn get_id(&self) -> anyhow::Result<String> {
         let handle = &self.handle;
         let pci_id = handle.get_pci_id().context("Device has no vendor id")?;
@@ -137,6 +259,48 @@ impl GpuController {
         self.handle.get_path()
     }
 
+    pub fn get_runtime_pm_status(&self) -> Option<RuntimePmStatus> {
+        self.handle
+            .read_file("power/runtime_status")
+            .ok()
+            .and_then(|status| status.parse().ok())
+    }
+
+    /// The monitor of the GPU followed by the board sensors, empty when the GPU has no monitor
+    pub fn temp_monitors(&self) -> Vec<HwMon> {
+        let Some(hw_mon) = self.handle.hw_monitors.first() else {
+            return Vec::new();
+        };
+        std::iter::once(hw_mon)
+            .chain(&self.board_hw_mons)
+            .cloned()
+            .collect()
+    }
+
+    /// Reads the sensors of the GPU along with the board sensors
+    pub fn get_temps(&self) -> HashMap<String, Temperature> {
+        board_sensors::temps(&self.temp_monitors())
+    }
+
+    /// Reads the edge temperature, falling back to the first available sensor
+    pub fn get_current_temperature(&self) -> Option<f32> {
+        let mut temps = self.hw_mon_map(get_temps)?;
+        temps
+            .remove("edge")
+            .or_else(|| temps.into_values().next())
+            .and_then(|temp| temp.current)
+    }
+
+    /// Board power draw in watts, preferring the instantaneous value over the average one
+    pub fn get_power_draw(&self) -> Option<f64> {
+        let hw_mon = self.handle.hw_monitors.first()?;
+        hw_mon
+            .get_power_input()
+            .ok()
+            .filter(|power| *power != 0.0)
+            .or_else(|| hw_mon.get_power_average().ok())
+    }
+
     fn first_hw_mon(&self) -> anyhow::Result<&HwMon> {
         self.handle
             .hw_monitors
@@ -157,11 +321,23 @@ impl GpuController {
                 }
             }
         });
+        let subsystem_pci_info = self
+            .pci_info
+            .as_ref()
+            .map(|pci_info| &pci_info.subsystem_pci_info);
+        let board_vendor = subsystem_pci_info.and_then(|info| info.vendor.clone());
+        let board_model = subsystem_pci_info.and_then(|info| info.model.clone());
         let pci_info = self.pci_info.as_ref().map(Cow::Borrowed);
         let driver = self.handle.get_driver();
         let vbios_version = self.get_full_vbios_version();
         let link_info = self.get_link_info();
         let drm_info = self.get_drm_info();
+        // Some APUs don't report the width of the shared memory
+        let memory_bus_width = drm_info
+            .as_ref()
+            .map(|info| info.vram_bit_width)
+            .filter(|width| *width != 0);
+        let overclock_status = self.get_overclock_status();
 
         DeviceInfo {
             pci_info,
@@ -170,7 +346,119 @@ impl GpuController {
             vbios_version,
             link_info,
             drm_info,
+            overclock_status,
+            ecc: self.get_ecc_info(),
+            board_vendor,
+            board_model,
+            boot_vga: self.is_boot_vga(),
+            memory_bus_width,
+            pci_bars: pci_bars::info(self.get_path(), self.handle.get_total_vram().ok()),
+        }
+    }
+
+    pub fn get_ecc_info(&self) -> Option<EccInfo> {
+        let features = self.handle.read_file("ras/features").ok()?;
+        let enabled = system::parse_ras_features(&features)? & RAS_UMC_FEATURE_MASK != 0;
+        let pending = system::configured_ecc_mode().filter(|configured| *configured != enabled);
+        Some(EccInfo { enabled, pending })
+    }
+
+    pub fn get_aspm_info(&self) -> AspmInfo {
+        // Each state has a file in the `link` directory of the device, present since Linux 5.5
+        let link_states = fs::read_dir(self.get_path().join("link"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                if !(name.ends_with("_aspm") || name == "clkpm") {
+                    return None;
+                }
+                let value = fs::read_to_string(entry.path()).ok()?;
+                Some((name, value.trim() == "1"))
+            })
+            .collect();
+
+        AspmInfo {
+            policy: system::get_aspm_policy(),
+            link_states,
+        }
+    }
+
+    fn audio_function_path(&self) -> anyhow::Result<PathBuf> {
+        audio::find_function(self.get_path()).context("This GPU has no audio device")
+    }
+
+    pub fn get_interconnect(&self) -> anyhow::Result<InterconnectInfo> {
+        interconnect::info(self.get_path())
+    }
+
+    pub fn get_audio_device(&self) -> anyhow::Result<AudioDeviceInfo> {
+        Ok(audio::info(&self.audio_function_path()?))
+    }
+
+    pub fn set_audio_pinned_active(&self, pinned: bool) -> anyhow::Result<()> {
+        audio::set_pinned_active(&self.audio_function_path()?, pinned)
+    }
+
+    pub fn get_retired_pages(&self) -> anyhow::Result<Vec<RetiredPage>> {
+        let contents = self
+            .handle
+            .read_file("ras/gpu_vram_bad_pages")
+            .context("Retired pages are not available, ECC has to be enabled on a supported GPU")?;
+        retired_pages::parse(&contents)
+    }
+
+    /// Resets the bad page table in the EEPROM of the GPU.
+    /// The pages which are already retired stay reserved until the driver is reloaded.
+    pub fn clear_retired_pages(&self) -> anyhow::Result<()> {
+        let path = gfxoff::debugfs_dir(self.get_path())
+            .context("Clearing retired pages requires debugfs to be mounted")?
+            .join("ras/ras_eeprom_reset");
+        fs::write(&path, "1").with_context(|| format!("Could not write to {}", path.display()))
+    }
+
+    pub fn get_reset_methods(&self) -> ResetMethods {
+        let pci_reset_methods: Vec<String> = self
+            .handle
+            .read_file("reset_method")
+            .map(|raw| raw.split_whitespace().map(str::to_owned).collect())
+            .unwrap_or_default();
+
+        let mut available = Vec::new();
+        if gfxoff::debugfs_dir(self.get_path())
+            .is_some_and(|dir| dir.join("amdgpu_gpu_recover").exists())
+        {
+            available.push(ResetMethod::Driver);
+        }
+        if !pci_reset_methods.is_empty() && self.get_path().join("reset").exists() {
+            available.push(ResetMethod::Pci);
+        }
+
+        ResetMethods {
+            available,
+            driver_reset_method: system::get_driver_reset_method(),
+            pci_reset_methods,
+        }
+    }
+
+    pub fn reset(&self, method: ResetMethod) -> anyhow::Result<()> {
+        match method {
+            ResetMethod::Driver => {
+                // Reading the file starts the recovery, and returns once it is done
+                let path = gfxoff::debugfs_dir(self.get_path())
+                    .context("Resetting through the driver requires debugfs to be mounted")?
+                    .join("amdgpu_gpu_recover");
+                fs::read_to_string(&path)
+                    .with_context(|| format!("Could not read {}", path.display()))?;
+            }
+            ResetMethod::Pci => {
+                let path = self.get_path().join("reset");
+                fs::write(&path, "1")
+                    .with_context(|| format!("Could not write to {}", path.display()))?;
+            }
         }
+        Ok(())
     }
 
     #[cfg(feature = "libdrm_amdgpu_sys")]
@@ -190,6 +478,68 @@ impl GpuController {
         self.handle.get_vbios_version().ok()
     }
 
+    pub fn get_overclock_status(&self) -> OverclockStatus {
+        // The clocks table is only present when overdrive is enabled
+        if self.handle.get_clocks_table().is_ok() {
+            OverclockStatus::Available
+        } else if system::is_overdrive_enabled() == Some(false) {
+            OverclockStatus::DisabledInKernel
+        } else {
+            OverclockStatus::Unsupported
+        }
+    }
+
+    /// Checks that the clocks can be changed, to fail with a clear reason instead of a sysfs write error
+    pub fn check_overclock_available(&self) -> anyhow::Result<()> {
+        match self.get_overclock_status() {
+            OverclockStatus::Available => Ok(()),
+            OverclockStatus::DisabledInKernel => Err(anyhow!(
+                "Changing clocks requires overdrive, which is not enabled. \
+                It can be enabled with the `enable_overdrive` request, and takes effect after a reboot"
+            )),
+            OverclockStatus::Unsupported => Err(anyhow!(
+                "Changing clocks is not supported on this GPU"
+            )),
+        }
+    }
+
+    pub fn get_controllable_nodes(&self) -> Vec<ControllableNode> {
+        let hw_mon = self.handle.hw_monitors.first();
+        controllable_nodes::list(self.handle.get_path(), hw_mon.map(HwMon::get_path))
+    }
+
+    #[cfg(feature = "libdrm_amdgpu_sys")]
+    pub fn get_smu_metrics(&self) -> anyhow::Result<SmuMetrics> {
+        let raw = fs::read(self.handle.get_path().join("gpu_metrics"))
+            .context("Could not read gpu_metrics, it is not supported on this GPU")?;
+        smu_metrics::parse(&raw)
+    }
+
+    #[cfg(not(feature = "libdrm_amdgpu_sys"))]
+    #[allow(clippy::unused_self)]
+    pub fn get_smu_metrics(&self) -> anyhow::Result<SmuMetrics> {
+        Err(anyhow!(
+            "Reading the gpu_metrics table requires the daemon to be built with the `drm` feature"
+        ))
+    }
+
+    /// Estimated memory bandwidth in GB/s at the given memory clockspeed (in MHz)
+    #[cfg(feature = "libdrm_amdgpu_sys")]
+    #[allow(clippy::cast_precision_loss)]
+    fn get_memory_bandwidth(&self, vram_clockspeed: u64) -> Option<f64> {
+        let drm_info = self.drm_handle.as_ref()?.device_info().ok()?;
+        let bandwidth_mbps = drm_info
+            .get_vram_type()
+            .peak_bw(vram_clockspeed * 1000, drm_info.vram_bit_width);
+        Some(bandwidth_mbps as f64 / 1000.0)
+    }
+
+    #[cfg(not(feature = "libdrm_amdgpu_sys"))]
+    #[allow(clippy::unused_self)]
+    fn get_memory_bandwidth(&self, _vram_clockspeed: u64) -> Option<f64> {
+        None
+    }
+
     #[cfg(feature = "libdrm_amdgpu_sys")]
     fn get_drm_info(&self) -> Option<DrmInfo> {
         trace!("Reading DRM info");
@@ -211,6 +561,11 @@ impl GpuController {
                 asic_name: drm_info.get_asic_name().to_string(),
                 chip_class: drm_info.get_chip_class().to_string(),
                 compute_units: drm_info.cu_active_number,
+                shader_engines: drm_info.max_se(),
+                shader_arrays_per_engine: drm_info.max_sa_per_se(),
+                rops: drm_info.calc_rop_count(),
+                // Every GCN and RDNA compute unit has 4 texture units
+                tmus: drm_info.cu_active_number * 4,
                 vram_type: drm_info.get_vram_type().to_string(),
                 vram_bit_width: drm_info.vram_bit_width,
                 vram_max_bw: drm_info.peak_memory_bw_gb().to_string(),
@@ -235,76 +590,415 @@ impl GpuController {
         }
     }
 
-    pub fn get_stats(&self, gpu_config: Option<&config::Gpu>) -> DeviceStats {
+    /// Stats which are kept in memory by the daemon, and can be reported without waking up a suspended GPU
+    fn get_cached_stats(
+        &self,
+        sampled_at: u64,
+        runtime_pm_status: Option<RuntimePmStatus>,
+    ) -> DeviceStats {
         DeviceStats {
-            fan: FanStats {
-                control_enabled: gpu_config
-                    .map(|config| config.fan_control_enabled)
-                    .unwrap_or_default(),
-                control_mode: gpu_config
-                    .and_then(|config| config.fan_control_settings.as_ref())
-                    .map(|settings| settings.mode),
-                static_speed: gpu_config
-                    .and_then(|config| config.fan_control_settings.as_ref())
-                    .map(|settings| settings.static_speed),
-                curve: gpu_config
-                    .and_then(|config| config.fan_control_settings.as_ref())
-                    .map(|settings| settings.curve.0.clone()),
-                speed_current: self.hw_mon_and_then(HwMon::get_fan_current),
-                speed_max: self.hw_mon_and_then(HwMon::get_fan_max),
-                speed_min: self.hw_mon_and_then(HwMon::get_fan_min),
-            },
+            sampled_at,
+            runtime_pm_status,
+            reset_count: self.reset_count.get(),
+            last_reset_at: self.last_reset_at.get(),
+            adaptive_clocks: self.adaptive_clocks_status.get(),
+            memory_clock_boost: self.memory_clock_boost_status.borrow().clone(),
+            clocks_ramp: self.clocks_ramp_status.get(),
+            pstate_residency: pstate_residency::residency(&self.pstate_samples.borrow()),
+            temp_trend: temp_trend::trend(&self.temp_samples.borrow()),
+            throttle_residency: throttle_residency::residency(&self.throttle_samples.borrow()),
+            ..Default::default()
+        }
+    }
+
+    pub fn get_stats(&self, gpu_config: Option<&config::Gpu>) -> DeviceStats {
+        let sampled_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|time| u64::try_from(time.as_millis()).ok())
+            .unwrap_or_default();
+        let runtime_pm_status = self.get_runtime_pm_status();
+        if matches!(
+            runtime_pm_status,
+            Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending)
+        ) {
+            return self.get_cached_stats(sampled_at, runtime_pm_status);
+        }
+
+        let errors = ReadErrors::default();
+        let temps = self.get_temps();
+        let curve_defaults = fan_curve_defaults(gpu_config, &temps);
+        let core_levels = errors.check("core_power_state", self.handle.get_core_clock_levels());
+        let memory_levels =
+            errors.check("memory_power_state", self.handle.get_memory_clock_levels());
+        let gpu_clockspeed = self.hw_mon_and_then(
+            &errors,
+            "clockspeed.gpu_clockspeed",
+            HwMon::get_gpu_clockspeed,
+        );
+        let vram_clockspeed = self.hw_mon_and_then(
+            &errors,
+            "clockspeed.vram_clockspeed",
+            HwMon::get_vram_clockspeed,
+        );
+        let power = self.get_power_stats(&errors);
+        let busy_percent = errors.check("busy_percent", self.handle.get_busy_percent());
+        let active_displays = count_active_displays(self.get_path());
+        let external_modifications = self.get_external_modifications();
+        let metrics = fs::read(self.handle.get_path().join("gpu_metrics")).ok();
+        let (gfx_clock_average, gfx_clock_current) = metrics
+            .as_deref()
+            .map_or((None, None), gpu_metrics::gfx_clocks);
+        let (vram_clock_average, vram_clock_current) = metrics
+            .as_deref()
+            .map_or((None, None), gpu_metrics::memory_clocks);
+
+        let stats = DeviceStats {
+            fan: self.get_fan_stats(&errors, gpu_config, curve_defaults),
+            clocks: self.get_clock_domains(gpu_clockspeed, vram_clockspeed),
             clockspeed: ClockspeedStats {
-                gpu_clockspeed: self.hw_mon_and_then(HwMon::get_gpu_clockspeed),
-                vram_clockspeed: self.hw_mon_and_then(HwMon::get_vram_clockspeed),
+                gpu_clockspeed,
+                vram_clockspeed,
+                memory_bandwidth_gbps: vram_clockspeed
+                    .and_then(|clockspeed| self.get_memory_bandwidth(clockspeed)),
+                gfx_clock_current,
+                gfx_clock_average,
+                gpu_clock_requested: core_levels.as_ref().and_then(active_level_clock),
+                vram_clock_requested: memory_levels.as_ref().and_then(active_level_clock),
+                vram_clock_current,
+                vram_clock_average,
             },
             voltage: VoltageStats {
-                gpu: self.hw_mon_and_then(HwMon::get_gpu_voltage),
-                northbridge: self.hw_mon_and_then(HwMon::get_northbridge_voltage),
+                gpu: self.hw_mon_and_then(&errors, "voltage.gpu", HwMon::get_gpu_voltage),
+                northbridge: self.hw_mon_and_then(
+                    &errors,
+                    "voltage.northbridge",
+                    HwMon::get_northbridge_voltage,
+                ),
             },
             vram: VramStats {
-                total: self.handle.get_total_vram().ok(),
-                used: self.handle.get_used_vram().ok(),
-            },
-            power: PowerStats {
-                average: self.hw_mon_and_then(HwMon::get_power_average),
-                current: self.hw_mon_and_then(HwMon::get_power_input),
-                cap_current: self.hw_mon_and_then(HwMon::get_power_cap),
-                cap_max: self.hw_mon_and_then(HwMon::get_power_cap_max),
-                cap_min: self.hw_mon_and_then(HwMon::get_power_cap_min),
-                cap_default: self.hw_mon_and_then(HwMon::get_power_cap_default),
+                total: errors.check("vram.total", self.handle.get_total_vram()),
+                used: errors.check("vram.used", self.handle.get_used_vram()),
             },
-            temps: self.hw_mon_map(HwMon::get_temps).unwrap_or_default(),
-            busy_percent: self.handle.get_busy_percent().ok(),
-            performance_level: self.handle.get_power_force_performance_level().ok(),
-            core_power_state: self
-                .handle
-                .get_core_clock_levels()
-                .ok()
-                .and_then(|levels| levels.active),
-            memory_power_state: self
-                .handle
-                .get_memory_clock_levels()
-                .ok()
+            energy_consumed: metrics.as_deref().and_then(gpu_metrics::energy_consumed),
+            is_power_capped: is_power_capped(&power),
+            power,
+            temps,
+            temp_limits: self.hw_mon_map(get_temp_limits).unwrap_or_default(),
+            busy_percent,
+            performance_level: errors.check(
+                "performance_level",
+                self.handle.get_power_force_performance_level(),
+            ),
+            core_power_state: core_levels.as_ref().and_then(|levels| levels.active),
+            memory_power_state: memory_levels.as_ref().and_then(|levels| levels.active),
+            memory_parked: memory_levels.as_ref().and_then(is_memory_parked),
+            memory_held_by_displays: memory_levels.as_ref().and_then(|levels| {
+                is_memory_held_by_displays(levels, busy_percent?, active_displays?)
+            }),
+            active_displays,
+            pcie_power_state: errors
+                .check("pcie_power_state", self.handle.get_pcie_clock_levels())
                 .and_then(|levels| levels.active),
-            pcie_power_state: self
+            gfxoff: self.get_gfxoff_stats(),
+            engine_activity: self.get_engine_activity(),
+            external_modification_detected: !external_modifications.is_empty(),
+            external_modifications,
+            read_errors: errors.0.into_inner(),
+            ..self.get_cached_stats(sampled_at, runtime_pm_status)
+        };
+        self.read_failures
+            .borrow_mut()
+            .record(&stats.read_errors, &stats.temps, Instant::now());
+        stats
+    }
+
+    pub fn get_read_failures(&self) -> BTreeMap<String, ReadFailureCount> {
+        self.read_failures.borrow().counts()
+    }
+
+    /// Reads only the stats of `QuickStats`, without the cost of the full stats
+    pub fn get_quick_stats(&self) -> QuickStats {
+        if matches!(
+            self.get_runtime_pm_status(),
+            Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending)
+        ) {
+            return QuickStats::default();
+        }
+
+        let hw_mon = self.handle.hw_monitors.first();
+        QuickStats {
+            temperature: self.get_current_temperature(),
+            power: self.get_power_draw(),
+            fan_percent: hw_mon.and_then(|hw_mon| {
+                let pwm = hw_mon.get_fan_pwm().ok()?;
+                Some(pwm_percent(pwm, pwm_range(hw_mon)))
+            }),
+            core_clock: hw_mon.and_then(|hw_mon| hw_mon.get_gpu_clockspeed().ok()),
+        }
+    }
+
+    /// Same as `get_stats`, but the stats are only read from the driver once per `min_interval`.
+    /// Requests in between get the cached stats, with their age in `cache_age_ms`.
+    pub fn get_stats_limited(
+        &self,
+        gpu_config: Option<&config::Gpu>,
+        min_interval: Duration,
+    ) -> DeviceStats {
+        if min_interval.is_zero() {
+            return self.get_stats(gpu_config);
+        }
+        let now = Instant::now();
+        if let Some(stats) = cached_stats(self.stats_cache.borrow().as_ref(), min_interval, now) {
+            return stats;
+        }
+
+        let stats = self.get_stats(gpu_config);
+        *self.stats_cache.borrow_mut() = Some((now, stats.clone()));
+        stats
+    }
+
+    /// The core and memory clocks are the ones measured by the sensors when available,
+    /// the other domains only report the clock of their active power level.
+    fn get_clock_domains(
+        &self,
+        gpu_clockspeed: Option<u64>,
+        vram_clockspeed: Option<u64>,
+    ) -> HashMap<String, f64> {
+        [
+            ("gfx", PowerLevelKind::CoreClock, gpu_clockspeed),
+            ("mem", PowerLevelKind::MemoryClock, vram_clockspeed),
+            ("fclk", PowerLevelKind::FabricClock, None),
+            ("socclk", PowerLevelKind::SOCClock, None),
+            ("dcefclk", PowerLevelKind::DCEFClock, None),
+        ]
+        .into_iter()
+        .filter_map(|(name, kind, measured)| {
+            let clockspeed = measured.or_else(|| {
+                // Most GPUs don't have all of the domains, so missing files are not read errors
+                let levels = self.handle.get_clock_levels::<u64>(kind).ok()?;
+                levels.active_level().copied()
+            })?;
+            #[allow(clippy::cast_precision_loss)]
+            Some((name.to_owned(), clockspeed as f64))
+        })
+        .collect()
+    }
+
+    fn get_external_modifications(&self) -> Vec<String> {
+        let applied_config = self.applied_config.borrow();
+        let Some(applied_config) = applied_config.as_ref() else {
+            return vec![];
+        };
+        external_changes::diverging_fields(applied_config, &self.get_live_settings())
+    }
+
+    /// The requested settings with the values read back from the GPU, which can differ
+    /// when they were clamped or rejected by the driver
+    pub fn get_effective_config(&self, requested: &config::Gpu) -> config::Gpu {
+        let clocks_table = self.handle.get_clocks_table().ok();
+        external_changes::effective_settings(
+            requested,
+            &self.get_live_settings(),
+            clocks_table.as_ref(),
+        )
+    }
+
+    /// The current hardware state as settings, see `external_changes::baseline_settings`
+    /// The current hardware state, `None` when the GPU has settings and its state may have been changed by the daemon
+    pub fn get_baseline_settings(&self, configured: Option<&config::Gpu>) -> Option<config::Gpu> {
+        let live = self.get_live_settings();
+        if configured
+            .is_some_and(|configured| !external_changes::is_driver_default(&live, configured))
+        {
+            return None;
+        }
+        let clocks_table = self.handle.get_clocks_table().ok();
+        Some(external_changes::baseline_settings(
+            &live,
+            clocks_table.as_ref(),
+        ))
+    }
+
+    fn get_live_settings(&self) -> LiveSettings {
+        let hw_mon = self.handle.hw_monitors.first();
+        let read_cap =
+            |name: &str| -> Option<u64> { hw_mon?.read_file(name).ok()?.trim().parse().ok() };
+        let fan_control_active = self
+            .fan_control_handle
+            .try_borrow()
+            .is_ok_and(|handle| handle.is_some());
+
+        LiveSettings {
+            power_cap_microwatts: read_cap("power1_cap"),
+            power_cap_default_microwatts: read_cap("power1_cap_default"),
+            performance_level: self.handle.get_power_force_performance_level().ok(),
+            power_profile_mode_index: self
                 .handle
-                .get_pcie_clock_levels()
+                .get_power_profile_modes()
                 .ok()
-                .and_then(|levels| levels.active),
+                .map(|modes| modes.active),
+            fan_manual_control: hw_mon
+                .filter(|_| fan_control_active)
+                .and_then(|hw_mon| hw_mon.get_fan_control_method().ok())
+                .map(|method| matches!(method, FanControlMethod::Manual)),
+        }
+    }
+
+    fn get_fan_stats(
+        &self,
+        errors: &ReadErrors,
+        gpu_config: Option<&config::Gpu>,
+        curve_defaults: FanCurveDefaults,
+    ) -> FanStats {
+        let fan_settings = gpu_config.and_then(|config| config.fan_control_settings.as_ref());
+        let pwm = self.hw_mon_and_then(errors, "fan.pwm_current", HwMon::get_fan_pwm);
+
+        FanStats {
+            control_enabled: gpu_config
+                .map(|config| config.fan_control_enabled)
+                .unwrap_or_default(),
+            control_mode: fan_settings.map(|settings| settings.mode),
+            static_speed: fan_settings.map(|settings| settings.static_speed),
+            curve: fan_settings.map(|settings| settings.curve.0.clone()),
+            spindown_curve: fan_settings
+                .and_then(|settings| settings.spindown_curve.as_ref())
+                .map(|curve| curve.0.clone()),
+            speed_current: self.hw_mon_and_then(
+                errors,
+                "fan.speed_current",
+                HwMon::get_fan_current,
+            ),
+            speed_max: self.hw_mon_and_then(errors, "fan.speed_max", HwMon::get_fan_max),
+            speed_min: self.hw_mon_and_then(errors, "fan.speed_min", HwMon::get_fan_min),
+            pwm_current: pwm,
+            speed_percent: pwm
+                .zip(self.hw_mon_map(pwm_range))
+                .map(|(pwm, range)| pwm_percent(pwm, range)),
+            curve_defaults,
+            fan_control_method: self.handle.hw_monitors.first().and_then(|hw_mon| {
+                let has_node = |name| hw_mon.read_file(name).is_ok();
+                fan_control::control_method(
+                    has_node("pwm1_enable"),
+                    has_node("fan1_input"),
+                    has_node("fan1_target"),
+                )
+            }),
+            fan_control_interface: fan_control::control_interface(
+                self.firmware_fan_curve_limits().is_some(),
+                self.hw_mon_map(|hw_mon| hw_mon.read_file("pwm1_enable").is_ok())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    fn get_power_stats(&self, errors: &ReadErrors) -> PowerStats {
+        PowerStats {
+            average: self.hw_mon_and_then(errors, "power.average", HwMon::get_power_average),
+            current: self.hw_mon_and_then(errors, "power.current", HwMon::get_power_input),
+            cap_current: self.hw_mon_and_then(errors, "power.cap_current", HwMon::get_power_cap),
+            cap_current_microwatts: self
+                .hw_mon_and_then(errors, "power.cap_current_microwatts", |hw_mon| {
+                    hw_mon.read_file("power1_cap")
+                })
+                .and_then(|raw| raw.trim().parse().ok()),
+            cap_max: self.hw_mon_and_then(errors, "power.cap_max", HwMon::get_power_cap_max),
+            cap_min: self.hw_mon_and_then(errors, "power.cap_min", HwMon::get_power_cap_min),
+            cap_default: self.hw_mon_and_then(
+                errors,
+                "power.cap_default",
+                HwMon::get_power_cap_default,
+            ),
         }
     }
 
+    fn get_gfxoff_stats(&self) -> GfxoffStats {
+        let feature_enabled = self.get_pp_features().ok().and_then(|features| {
+            features
+                .iter()
+                .find(|feature| feature.name == pp_features::GFXOFF)
+                .map(|feature| feature.enabled)
+        });
+
+        // Reading the debugfs files resumes the GPU, so they are skipped while it's suspended
+        if matches!(
+            self.get_runtime_pm_status(),
+            Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending)
+        ) {
+            return GfxoffStats {
+                feature_enabled,
+                ..Default::default()
+            };
+        }
+
+        match gfxoff::debugfs_dir(self.get_path()) {
+            Some(dir) => gfxoff::read_stats(&dir, feature_enabled),
+            None => GfxoffStats {
+                feature_enabled,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn get_performance_level(&self) -> anyhow::Result<PerformanceLevel> {
+        self.handle
+            .get_power_force_performance_level()
+            .context("Performance level not available")
+    }
+
     pub fn get_clocks_info(&self) -> anyhow::Result<ClocksInfo> {
+        if let Ok(clocks_table) = self.handle.get_clocks_table() {
+            return Ok(clocks_table.into());
+        }
+
+        let core_states = self
+            .handle
+            .get_core_clock_levels()
+            .context("Clocks table not available")?
+            .levels;
+        let memory_states = self
+            .handle
+            .get_memory_clock_levels()
+            .map(|levels| levels.levels)
+            .unwrap_or_default();
+        Ok(ClocksInfo {
+            control: ClocksControl::Discrete,
+            core_states,
+            memory_states,
+            ..Default::default()
+        })
+    }
+
+    pub fn get_voltage_curve(&self) -> anyhow::Result<VoltageCurve> {
         let clocks_table = self
             .handle
             .get_clocks_table()
             .context("Clocks table not available")?;
-        Ok(clocks_table.into())
+        Ok(VoltageCurve::from(&clocks_table))
     }
 
-    fn hw_mon_and_then<U>(&self, f: fn(&HwMon) -> Result<U, Error>) -> Option<U> {
-        self.handle.hw_monitors.first().and_then(|mon| f(mon).ok())
+    /// Reasons for which the GPU is currently throttled, `None` if they are not reported
+    pub fn get_throttle_reasons(&self) -> Option<Vec<ThrottleReason>> {
+        let metrics = fs::read(self.handle.get_path().join("gpu_metrics")).ok()?;
+        gpu_metrics::throttle_reasons(&metrics)
+    }
+
+    pub fn snap_clock(&self, domain: ClockDomain, value: i32) -> anyhow::Result<i32> {
+        let clocks_table = self
+            .handle
+            .get_clocks_table()
+            .context("Clocks table not available")?;
+        snap_clock(&clocks_table, domain, value)
+    }
+
+    fn hw_mon_and_then<U>(
+        &self,
+        errors: &ReadErrors,
+        field: &str,
+        f: fn(&HwMon) -> Result<U, Error>,
+    ) -> Option<U> {
+        let hw_mon = self.handle.hw_monitors.first()?;
+        errors.check(field, f(hw_mon))
     }
 
     fn hw_mon_map<U>(&self, f: fn(&HwMon) -> U) -> Option<U> {
@@ -322,28 +1016,31 @@ impl GpuController {
             .cloned()
             .context("This GPU has no monitor")?;
 
-        hw_mon
-            .set_fan_control_method(FanControlMethod::Manual)
-            .context("Could not set fan control method")?;
-
         #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
         let static_speed_converted = (f64::from(u8::MAX) * static_speed) as u8;
 
-        hw_mon
-            .set_fan_pwm(static_speed_converted)
-            .context("could not set fan speed")?;
+        set_manual_fan_pwm(&hw_mon, static_speed_converted)?;
 
         debug!("set fan speed to {}", static_speed);
 
         Ok(())
     }
 
+    /// Reads the temperature for the fan curve from `temp_hw_mon`, which may belong to a different GPU
     async fn start_curve_fan_control(
         &self,
         curve: FanCurve,
+        spindown_curve: Option<FanCurve>,
+        temp_hw_mon: HwMon,
         temp_key: String,
+        temp_offset: i32,
         interval: Duration,
     ) -> anyhow::Result<()> {
+        let running = self
+            .fan_control_handle
+            .try_borrow()
+            .map_err(|err| anyhow!("Lock error: {err}"))?
+            .is_some();
         // Stop existing task to re-apply new curve
         self.stop_fan_control(false).await?;
 
@@ -353,9 +1050,22 @@ impl GpuController {
             .first()
             .cloned()
             .context("This GPU has no monitor")?;
-        hw_mon
-            .set_fan_control_method(FanControlMethod::Manual)
-            .context("Could not set fan control method")?;
+
+        // The first speed is set right away, so the fan doesn't stay at an arbitrary manual speed until the first tick.
+        // When the curve keeps changing, only the last one gets written on the first tick, which comes sooner than usual.
+        let temp = get_temps(&temp_hw_mon).remove(&temp_key);
+        let initial_pwm = curve_target_pwm(&curve, temp, &hw_mon, temp_offset);
+        let now = Instant::now();
+        let debounced =
+            fan_control::debounce_curve_update(running, self.fan_curve_updated_at.get(), now);
+        self.fan_curve_updated_at.set(Some(now));
+        let mut next_tick = if debounced {
+            debug!("fan curve changed again shortly after the last change, deferring the update");
+            fan_control::CURVE_UPDATE_DEBOUNCE.min(interval)
+        } else {
+            set_manual_fan_pwm(&hw_mon, initial_pwm)?;
+            interval
+        };
 
         let mut notify_guard = self
             .fan_control_handle
@@ -364,21 +1074,60 @@ impl GpuController {
 
         let notify = Rc::new(Notify::new());
         let task_notify = notify.clone();
+        let simulated_temp = self.simulated_temp.clone();
+        let log = self.fan_control_log.clone();
 
         let handle = tokio::task::spawn_local(async move {
+            let mut source_available = true;
+            let mut previous_pwm = initial_pwm;
+
             loop {
                 select! {
-                    () = sleep(interval) => (),
+                    () = sleep(next_tick) => (),
                     () = task_notify.notified() => break,
                 }
+                next_tick = interval;
+
+                let temp = get_temps(&temp_hw_mon).remove(&temp_key);
+                if temp.is_some() != source_available {
+                    source_available = temp.is_some();
+                    if source_available {
+                        info!("temperature sensor `{temp_key}` is available again");
+                    } else {
+                        warn!("temperature sensor `{temp_key}` is not available, using the hottest sensor of this GPU");
+                    }
+                }
 
-                let mut temps = hw_mon.get_temps();
-                let temp = temps
-                    .remove(&temp_key)
-                    .expect("Could not get temperature by given key");
-                let target_pwm = curve.pwm_at_temp(temp);
+                let (temp, temperature_source) = fan_control::curve_temperature(
+                    take_simulated_temp(&simulated_temp),
+                    temp,
+                    || hottest_temp(&hw_mon),
+                );
+                let curve_pwm = curve_target_pwm(&curve, temp, &hw_mon, temp_offset);
+                let mut target_pwm = curve_pwm;
+                if let Some(spindown_curve) = &spindown_curve {
+                    let spindown = curve_target_pwm(spindown_curve, temp, &hw_mon, temp_offset);
+                    target_pwm = fan_control::spindown_pwm(previous_pwm, target_pwm, spindown);
+                }
+                previous_pwm = target_pwm;
                 trace!("fan control tick: setting pwm to {target_pwm}");
 
+                fan_control::record_decision(
+                    &log,
+                    FanDecision {
+                        timestamp_ms: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .ok()
+                            .and_then(|time| u64::try_from(time.as_millis()).ok())
+                            .unwrap_or_default(),
+                        temperature: temp.and_then(|temp| temp.current),
+                        temperature_source,
+                        curve_pwm,
+                        pwm: target_pwm,
+                        spindown_held: target_pwm > curve_pwm,
+                    },
+                );
+
                 if let Err(err) = hw_mon.set_fan_pwm(target_pwm) {
                     error!("could not set fan speed: {err}, disabling fan control");
                     break;
@@ -397,6 +1146,119 @@ impl GpuController {
         Ok(())
     }
 
+    pub fn get_fan_control_log(&self) -> Vec<FanDecision> {
+        self.fan_control_log.borrow().iter().copied().collect()
+    }
+
+    /// Makes the fan curve use the given temperature instead of the sensor reading for `duration`,
+    /// for testing a curve without heating up the GPU. Other temperature readings are not affected.
+    pub fn set_simulated_temperature(&self, temp: Option<f32>, duration: Duration) {
+        self.simulated_temp
+            .set(temp.map(|temp| (temp, Instant::now() + duration)));
+    }
+
+    pub fn get_power_cap_microwatts(&self) -> Option<u64> {
+        let hw_mon = self.handle.hw_monitors.first()?;
+        hw_mon.read_file("power1_cap").ok()?.trim().parse().ok()
+    }
+
+    pub fn get_power_tiers(&self) -> anyhow::Result<PowerTiers> {
+        let hw_mon = self.first_hw_mon()?;
+        let read_cap =
+            |name: &str| -> Option<u64> { hw_mon.read_file(name).ok()?.trim().parse().ok() };
+
+        let default = read_cap("power1_cap_default")
+            .context("The default power cap is not available on this GPU")?;
+        let tiers = power_tier_caps(
+            default,
+            read_cap("power1_cap_min").unwrap_or(0),
+            read_cap("power1_cap_max").unwrap_or(default),
+        );
+        let current = self.get_power_cap_microwatts();
+        let active = tiers
+            .iter()
+            .find(|(_, cap)| Some(*cap) == current)
+            .map(|(tier, _)| *tier);
+
+        Ok(PowerTiers { tiers, active })
+    }
+
+    /// Falls back to the generic default curve when the firmware curve can't be read
+    pub fn get_default_fan_curve(&self) -> DefaultFanCurve {
+        match self
+            .handle
+            .read_file(fan_control::FIRMWARE_FAN_CURVE)
+            .ok()
+            .and_then(|raw| fan_control::parse_firmware_curve(&raw))
+        {
+            Some(curve) => DefaultFanCurve {
+                curve,
+                synthetic: false,
+            },
+            None => DefaultFanCurve {
+                curve: default_fan_curve(),
+                synthetic: true,
+            },
+        }
+    }
+
+    fn firmware_fan_curve_limits(&self) -> Option<fan_control::FirmwareCurveLimits> {
+        self.handle
+            .read_file(fan_control::FIRMWARE_FAN_CURVE)
+            .ok()
+            .and_then(|raw| fan_control::parse_firmware_curve_limits(&raw))
+    }
+
+    /// Writes the fan settings as the firmware fan curve, see `FanControlInterface::Firmware`.
+    /// A static speed is written as a flat curve.
+    async fn apply_firmware_fan_control(
+        &self,
+        settings: &config::FanControlSettings,
+        limits: fan_control::FirmwareCurveLimits,
+    ) -> anyhow::Result<()> {
+        self.stop_fan_control(false).await?;
+
+        if settings.temperature_source.is_some() {
+            return Err(anyhow!(
+                "The firmware fan curve always follows the temperature of this GPU, a different temperature source can't be used"
+            ));
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let (curve, offset) = match settings.mode {
+            lact_schema::FanControlMode::Static => {
+                let speed = settings.static_speed as f32;
+                let (min_temp, max_temp) = limits.temperature;
+                ([(min_temp, speed), (max_temp, speed)].into(), 0)
+            }
+            lact_schema::FanControlMode::Curve => {
+                if settings.curve.0.is_empty() {
+                    return Err(anyhow!("Cannot use empty fan curve"));
+                }
+                (settings.curve.0.clone(), settings.temperature_offset)
+            }
+        };
+
+        let path = self.handle.get_path().join(fan_control::FIRMWARE_FAN_CURVE);
+        for command in fan_control::firmware_curve_commands(&curve, offset, limits) {
+            fs::write(&path, &command)
+                .with_context(|| format!("Could not write firmware fan curve point `{command}`"))?;
+        }
+        fs::write(&path, "c").context("Could not commit the firmware fan curve")?;
+        debug!("wrote firmware fan curve");
+
+        Ok(())
+    }
+
+    /// Hands fan control back to the firmware, until it is restored with `restore_fan_control`
+    pub async fn suspend_fan_control(&self) -> anyhow::Result<()> {
+        self.stop_fan_control(true).await
+    }
+
+    pub async fn restore_fan_control(&self, config: &config::Gpu) -> anyhow::Result<()> {
+        self.apply_fan_control_config(config).await
+    }
+
     async fn stop_fan_control(&self, reset_mode: bool) -> anyhow::Result<()> {
         let maybe_notify = self
             .fan_control_handle
@@ -409,6 +1271,13 @@ impl GpuController {
         }
 
         if reset_mode {
+            if self.firmware_fan_curve_limits().is_some() {
+                let path = self.handle.get_path().join(fan_control::FIRMWARE_FAN_CURVE);
+                fs::write(&path, "r")
+                    .and_then(|()| fs::write(&path, "c"))
+                    .context("Could not reset the firmware fan curve")?;
+            }
+
             if let Some(hw_mon) = self.handle.hw_monitors.first().cloned() {
                 if let Ok(current_control) = hw_mon.get_fan_control_method() {
                     if !matches!(current_control, FanControlMethod::Auto) {
@@ -423,6 +1292,151 @@ impl GpuController {
         Ok(())
     }
 
+    fn start_adaptive_clocks(&self, settings: AdaptiveClocks) -> anyhow::Result<()> {
+        // Apply the idle clock right away, so that invalid settings get reported
+        adaptive_clocks::set_max_core_clock(&self.handle, settings.idle_max)
+            .context("Could not apply adaptive clocks")?;
+        self.adaptive_clocks_status.set(Some(AdaptiveClocksStatus {
+            state: AdaptiveClocksState::Idle,
+            max_core_clock: settings.idle_max,
+        }));
+
+        let task = adaptive_clocks::spawn(
+            self.handle.clone(),
+            settings,
+            self.adaptive_clocks_status.clone(),
+        );
+        *self
+            .adaptive_clocks_handle
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("Lock error: {err}"))? = Some(task);
+
+        Ok(())
+    }
+
+    async fn stop_adaptive_clocks(&self) -> anyhow::Result<()> {
+        let maybe_notify = self
+            .adaptive_clocks_handle
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("Lock error: {err}"))?
+            .take();
+        if let Some((notify, handle)) = maybe_notify {
+            notify.notify_one();
+            handle.await?;
+        }
+        self.adaptive_clocks_status.set(None);
+
+        Ok(())
+    }
+
+    fn start_memory_clock_boost(
+        &self,
+        settings: MemoryClockBoost,
+        config: &config::Gpu,
+    ) -> anyhow::Result<()> {
+        let slot_name = self
+            .handle
+            .get_pci_slot_name()
+            .context("Device has no PCI slot name")?;
+        let restore_settings = memory_clock_boost::RestoreSettings {
+            performance_level: config.performance_level,
+            memory_states: config
+                .power_states
+                .get(&PowerLevelKind::MemoryClock)
+                .cloned(),
+        };
+        *self.memory_clock_boost_status.borrow_mut() = Some(MemoryClockBoostStatus { game: None });
+
+        let task = memory_clock_boost::spawn(
+            self.handle.clone(),
+            slot_name.to_owned(),
+            settings,
+            restore_settings,
+            self.memory_clock_boost_status.clone(),
+        );
+        *self
+            .memory_clock_boost_handle
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("Lock error: {err}"))? = Some(task);
+
+        Ok(())
+    }
+
+    /// Waits for the task to restore the memory settings if a game was running
+    async fn stop_memory_clock_boost(&self) -> anyhow::Result<()> {
+        let maybe_notify = self
+            .memory_clock_boost_handle
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("Lock error: {err}"))?
+            .take();
+        if let Some((notify, handle)) = maybe_notify {
+            notify.notify_one();
+            handle.await?;
+        }
+        *self.memory_clock_boost_status.borrow_mut() = None;
+
+        Ok(())
+    }
+
+    pub fn get_power_profile_mode_details(&self) -> anyhow::Result<PowerProfileModesDetails> {
+        let raw = self
+            .handle
+            .read_file("pp_power_profile_mode")
+            .context("Could not read pp_power_profile_mode, it is not supported on this GPU")?;
+        power_profile_modes::parse_details(&raw)
+    }
+
+    pub fn get_pp_features(&self) -> anyhow::Result<Vec<PpFeature>> {
+        let raw = self
+            .handle
+            .read_file("pp_features")
+            .context("Could not read pp_features, it is not supported on this GPU")?;
+        pp_features::parse(&raw)
+    }
+
+    pub fn set_pp_feature(&self, name: &str, enabled: bool) -> anyhow::Result<()> {
+        let features = self.get_pp_features()?;
+        let mask = pp_features::new_mask(&features, name, enabled)?;
+        self.handle
+            .write_file("pp_features", format!("0x{mask:x}"))
+            .context("Could not write pp_features")
+    }
+
+    pub fn get_processes(&self) -> anyhow::Result<Vec<GpuProcess>> {
+        let slot_name = self
+            .handle
+            .get_pci_slot_name()
+            .context("Device has no PCI slot name")?;
+        Ok(processes::get_processes(slot_name))
+    }
+
+    /// Reading the engine times of all processes is relatively slow, so reads within a second of the previous one reuse its activity
+    fn get_engine_activity(&self) -> HashMap<String, f32> {
+        let now = Instant::now();
+        let mut sample = self.engine_usage_sample.borrow_mut();
+        if sample.as_ref().is_some_and(|sample| sample.is_recent(now)) {
+            return self.engine_activity.borrow().clone();
+        }
+        let Ok(processes) = self.get_processes() else {
+            return HashMap::new();
+        };
+
+        let current = EngineUsageSample::new(&processes, now);
+        let activity = sample
+            .as_ref()
+            .map(|previous| engine_activity::activity(previous, &current))
+            .unwrap_or_default();
+        *sample = Some(current);
+        self.engine_activity.replace(activity.clone());
+        activity
+    }
+
+    pub fn is_used_by_process(&self, pid: i32) -> bool {
+        self.handle
+            .get_pci_slot_name()
+            .is_some_and(|slot_name| processes::is_using_device(pid, slot_name))
+    }
+
     pub fn get_power_states(&self, gpu_config: Option<&config::Gpu>) -> PowerStates {
         let core = self.get_power_states_kind(gpu_config, PowerLevelKind::CoreClock);
         let vram = self.get_power_states_kind(gpu_config, PowerLevelKind::MemoryClock);
@@ -459,9 +1473,13 @@ impl GpuController {
             .collect()
     }
 
-    pub async fn apply_config(&self, config: &config::Gpu) -> anyhow::Result<()> {
+    async fn apply_fan_control_config(&self, config: &config::Gpu) -> anyhow::Result<()> {
         if config.fan_control_enabled {
             if let Some(ref settings) = config.fan_control_settings {
+                if let Some(limits) = self.firmware_fan_curve_limits() {
+                    return self.apply_firmware_fan_control(settings, limits).await;
+                }
+
                 match settings.mode {
                     lact_schema::FanControlMode::Static => {
                         self.set_static_fan_control(settings.static_speed).await?;
@@ -471,10 +1489,34 @@ impl GpuController {
                             return Err(anyhow!("Cannot use empty fan curve"));
                         }
 
+                        let temp_hw_mons = match &settings.temperature_source {
+                            Some(source_id) => self
+                                .peer_hw_mons
+                                .borrow()
+                                .get(source_id)
+                                .cloned()
+                                .with_context(|| {
+                                format!("Temperature source GPU {source_id} was not found")
+                            })?,
+                            None => self.first_hw_mon().map(|_| self.temp_monitors())?,
+                        };
+
+                        let (temp_hw_mon, temp_key) =
+                            board_sensors::resolve(&temp_hw_mons, &settings.temperature_key)
+                                .with_context(|| {
+                                    format!(
+                                        "Temperature sensor `{}` is not available",
+                                        settings.temperature_key
+                                    )
+                                })?;
+
                         let interval = Duration::from_millis(settings.interval_ms);
                         self.start_curve_fan_control(
                             settings.curve.clone(),
-                            settings.temperature_key.clone(),
+                            settings.spindown_curve.clone(),
+                            temp_hw_mon,
+                            temp_key,
+                            settings.temperature_offset,
                             interval,
                         )
                         .await?;
@@ -489,12 +1531,29 @@ impl GpuController {
             self.stop_fan_control(true).await?;
         }
 
-        if let Some(cap) = config.power_cap {
+        Ok(())
+    }
+
+    pub async fn apply_config(&self, config: &config::Gpu) -> anyhow::Result<()> {
+        // The settings are unknown if applying them fails halfway
+        self.applied_config.replace(None);
+        self.apply_settings(config).await?;
+        self.applied_config.replace(Some(config.clone()));
+        Ok(())
+    }
+
+    async fn apply_settings(&self, config: &config::Gpu) -> anyhow::Result<()> {
+        self.apply_fan_control_config(config).await?;
+        // Restores the memory settings before the new ones are written
+        self.stop_memory_clock_boost().await?;
+
+        // The cap is written in microwatts as is, to not lose precision by converting it to watts
+        if let Some(cap) = config.power_cap_microwatts {
             let hw_mon = self.first_hw_mon()?;
-            hw_mon.set_power_cap(cap)?;
+            hw_mon.write_file("power1_cap", cap.to_string())?;
         } else if let Ok(hw_mon) = self.first_hw_mon() {
-            if let Ok(default_cap) = hw_mon.get_power_cap_default() {
-                hw_mon.set_power_cap(default_cap)?;
+            if let Ok(default_cap) = hw_mon.read_file("power1_cap_default") {
+                hw_mon.write_file("power1_cap", default_cap.trim())?;
             }
         }
 
@@ -515,43 +1574,14 @@ impl GpuController {
             self.handle.set_active_power_profile_mode(mode_index)?;
         }
 
+        self.stop_adaptive_clocks().await?;
+
         // Reset the clocks table in case the settings get reverted back to not having a clocks value configured
         self.handle.reset_clocks_table().ok();
 
         if config.is_core_clocks_used() {
-            let clocks = config.clocks_configuration;
             let mut table = self.handle.get_clocks_table()?;
-
-            if let ClocksTableGen::Vega20(ref mut table) = table {
-                // Avoid writing settings to the clocks table except the user-specified ones
-                // There is an issue on some GPU models where the default values are actually outside of the allowed range
-                // See https://github.com/sibradzic/amdgpu-clocks/issues/32#issuecomment-829953519 (part 2) for an example
-                table.clear();
-
-                table.voltage_offset = clocks.voltage_offset;
-            }
-
-            if let Some(min_clockspeed) = clocks.min_core_clock {
-                table.set_min_sclk(min_clockspeed)?;
-            }
-
-            if let Some(min_clockspeed) = clocks.min_memory_clock {
-                table.set_min_mclk(min_clockspeed)?;
-            }
-
-            if let Some(min_voltage) = clocks.min_voltage {
-                table.set_min_voltage(min_voltage)?;
-            }
-
-            if let Some(clockspeed) = clocks.max_core_clock {
-                table.set_max_sclk(clockspeed)?;
-            }
-            if let Some(clockspeed) = clocks.max_memory_clock {
-                table.set_max_mclk(clockspeed)?;
-            }
-            if let Some(voltage) = clocks.max_voltage {
-                table.set_max_voltage(voltage)?;
-            }
+            apply_clocks_configuration(&mut table, &config.clocks_configuration)?;
 
             debug!("writing clocks commands: {:#?}", table.get_commands()?);
 
@@ -560,6 +1590,10 @@ impl GpuController {
                 .context("Could not write clocks table")?;
         }
 
+        if let Some(settings) = config.adaptive_clocks {
+            self.start_adaptive_clocks(settings)?;
+        }
+
         for (kind, states) in &config.power_states {
             if config.performance_level != Some(PerformanceLevel::Manual) {
                 return Err(anyhow!(
@@ -572,10 +1606,370 @@ impl GpuController {
                 .with_context(|| format!("Could not set {kind:?} power states"))?;
         }
 
+        if let Some(settings) = &config.memory_clock_boost {
+            self.start_memory_clock_boost(settings.clone(), config)?;
+        }
+
         Ok(())
     }
 }
 
+fn active_level_clock(levels: &PowerLevels<u64>) -> Option<u64> {
+    levels.levels.get(levels.active?).copied()
+}
+
+fn is_memory_parked<T>(levels: &PowerLevels<T>) -> Option<bool> {
+    let active = levels.active?;
+    Some(active == 0 && levels.levels.len() > 1)
+}
+
+/// Below this load, a memory clock at the highest level is not caused by the workload
+const IDLE_BUSY_PERCENT: u8 = 10;
+
+/// The driver keeps the memory clock at its highest level when switching it could cause flickering,
+/// usually with multiple displays or high refresh rates. This checks for that state while the GPU is idle.
+fn is_memory_held_by_displays<T>(
+    levels: &PowerLevels<T>,
+    busy_percent: u8,
+    active_displays: usize,
+) -> Option<bool> {
+    let active = levels.active?;
+    Some(
+        active_displays > 0
+            && busy_percent < IDLE_BUSY_PERCENT
+            && levels.levels.len() > 1
+            && active == levels.levels.len() - 1,
+    )
+}
+
+/// Counts the enabled display connectors of the card, which are listed as `cardN-<connector>` in its DRM directory
+fn count_active_displays(device_path: &Path) -> Option<usize> {
+    let card_dir = fs::read_dir(device_path.join("drm"))
+        .ok()?
+        .flatten()
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("card"))
+                .is_some_and(|minor| minor.chars().all(|c| c.is_ascii_digit()))
+        })?;
+
+    let count = fs::read_dir(card_dir.path())
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().contains('-'))
+        .filter(|entry| {
+            fs::read_to_string(entry.path().join("enabled"))
+                .is_ok_and(|enabled| enabled.trim() == "enabled")
+        })
+        .count();
+    Some(count)
+}
+
+/// The PWM range of the fan, falling back to the full 0-255 range when it is not reported
+fn pwm_range(hw_mon: &HwMon) -> (u8, u8) {
+    let read = |name| {
+        hw_mon
+            .read_file(name)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+    };
+    (
+        read("pwm1_min").unwrap_or(0),
+        read("pwm1_max").unwrap_or(u8::MAX),
+    )
+}
+
+fn pwm_percent(pwm: u8, (min, max): (u8, u8)) -> f64 {
+    if max <= min {
+        return 0.0;
+    }
+    f64::from(pwm.clamp(min, max) - min) / f64::from(max - min) * 100.0
+}
+
+/// Editor ranges based on the critical temperature of the sensor used by the fan curve
+fn fan_curve_defaults(
+    gpu_config: Option<&config::Gpu>,
+    temps: &HashMap<String, Temperature>,
+) -> FanCurveDefaults {
+    let sensor = gpu_config
+        .and_then(|config| config.fan_control_settings.as_ref())
+        .map_or("edge", |settings| settings.temperature_key.as_str());
+    FanCurveDefaults::new(temps.get(sensor).and_then(|temp| temp.crit))
+}
+
+/// Errors of individual stats reads. A failed read leaves the field empty instead of failing the whole request.
+#[derive(Default)]
+struct ReadErrors(RefCell<BTreeMap<String, String>>);
+
+impl ReadErrors {
+    /// Missing files are not recorded, as they only mean that the value is not supported by the GPU
+    fn check<T>(&self, field: &str, result: Result<T, Error>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                if !err.is_not_found() {
+                    trace!("could not read {field}: {err}");
+                    self.0
+                        .borrow_mut()
+                        .insert(field.to_owned(), err.to_string());
+                }
+                None
+            }
+        }
+    }
+}
+
+/// The cached stats, if they were read less than `min_interval` ago
+fn cached_stats(
+    cache: Option<&(Instant, DeviceStats)>,
+    min_interval: Duration,
+    now: Instant,
+) -> Option<DeviceStats> {
+    let (read_at, stats) = cache?;
+    let age = now.checked_duration_since(*read_at)?;
+    (age < min_interval).then(|| DeviceStats {
+        cache_age_ms: u64::try_from(age.as_millis()).unwrap_or(u64::MAX),
+        ..stats.clone()
+    })
+}
+
+/// Clamps the value to the range allowed by the clocks table.
+/// The driver accepts any whole value within the range, so there is no further rounding to do.
+fn snap_clock(table: &ClocksTableGen, domain: ClockDomain, value: i32) -> anyhow::Result<i32> {
+    let range = match domain {
+        ClockDomain::MaxCoreClock => table.get_max_sclk_range(),
+        ClockDomain::MinCoreClock => table.get_min_sclk_range(),
+        ClockDomain::MaxMemoryClock => table.get_max_mclk_range(),
+        ClockDomain::MinMemoryClock => table.get_min_mclk_range(),
+        ClockDomain::MaxVoltage => table.get_max_voltage_range(),
+        ClockDomain::MinVoltage => table.get_min_voltage_range(),
+    }
+    .with_context(|| format!("{domain:?} is not adjustable on this GPU"))?;
+
+    let value = range.min.map_or(value, |min| value.max(min));
+    Ok(range.max.map_or(value, |max| value.min(max)))
+}
+
+/// Applies the configured values to a clocks table.
+/// The library writes the commands in the syntax of the table format (per-level `s`/`m` commands on Vega10, `s 0`/`s 1` on newer GPUs).
+fn apply_clocks_configuration(
+    table: &mut ClocksTableGen,
+    clocks: &config::ClocksConfiguration,
+) -> anyhow::Result<()> {
+    match table {
+        ClocksTableGen::Vega10(_) => {
+            if clocks.voltage_offset.is_some() {
+                return Err(anyhow!(
+                    "Voltage offset is not supported on this GPU, set the voltage of the power levels instead"
+                ));
+            }
+        }
+        ClocksTableGen::Vega20(table) => {
+            // Avoid writing settings to the clocks table except the user-specified ones
+            // There is an issue on some GPU models where the default values are actually outside of the allowed range
+            // See https://github.com/sibradzic/amdgpu-clocks/issues/32#issuecomment-829953519 (part 2) for an example
+            table.clear();
+
+            table.voltage_offset = clocks.voltage_offset;
+        }
+    }
+
+    if let Some(min_clockspeed) = clocks.min_core_clock {
+        table.set_min_sclk(min_clockspeed)?;
+    }
+
+    if let Some(min_clockspeed) = clocks.min_memory_clock {
+        table.set_min_mclk(min_clockspeed)?;
+    }
+
+    if let Some(min_voltage) = clocks.min_voltage {
+        table.set_min_voltage(min_voltage)?;
+    }
+
+    if let Some(clockspeed) = clocks.max_core_clock {
+        table.set_max_sclk(clockspeed)?;
+    }
+    if let Some(clockspeed) = clocks.max_memory_clock {
+        table.set_max_mclk(clockspeed)?;
+    }
+    if let Some(voltage) = clocks.max_voltage {
+        table.set_max_voltage(voltage)?;
+    }
+
+    Ok(())
+}
+
+/// The power draw is considered to be limited by the cap when it is within this factor of it,
+/// as the firmware keeps the power slightly below the cap
+const POWER_CAP_THRESHOLD: f64 = 0.97;
+
+fn is_power_capped(power: &PowerStats) -> bool {
+    let Some(cap) = power.cap_current.filter(|cap| *cap > 0.0) else {
+        return false;
+    };
+    power
+        .current
+        .or(power.average)
+        .is_some_and(|draw| draw >= cap * POWER_CAP_THRESHOLD)
+}
+
+const ECO_POWER_TIER_PERCENT: u64 = 80;
+/// RAS of the memory controller, which is the memory error correction
+const RAS_UMC_FEATURE_MASK: u64 = 1;
+
+/// Power caps (in microwatts) of the tiers, leaving out the ones which would be the same as a lower tier
+fn power_tier_caps(default: u64, min: u64, max: u64) -> Vec<(PowerTier, u64)> {
+    let eco = default * ECO_POWER_TIER_PERCENT / 100;
+    let mut tiers = Vec::with_capacity(3);
+    if eco >= min && eco < default {
+        tiers.push((PowerTier::Eco, eco));
+    }
+    tiers.push((PowerTier::Balanced, default));
+    if max > default {
+        tiers.push((PowerTier::Turbo, max));
+    }
+    tiers
+}
+
+/// Finds all temperature sensors with their hwmon index and a unique key.
+///
+/// Some GPUs (e.g. HBM cards) report multiple sensors with the same label, such as one `mem` sensor per memory stack.
+/// The first sensor keeps its label, so configs which use it as the fan curve sensor still find it,
+/// and the following ones get their occurrence appended (`mem_1`, `mem_2`) so the sensors don't overwrite each other.
+fn temp_sensors(hw_mon: &HwMon) -> Vec<(usize, String)> {
+    let mut sensors = Vec::new();
+
+    let mut i = 1;
+    while hw_mon.read_file(&format!("temp{i}_input")).is_ok() {
+        if let Ok(label) = hw_mon.read_file(&format!("temp{i}_label")) {
+            sensors.push((i, label));
+        } else {
+            sensors.push((i, i.to_string()));
+            break;
+        }
+        i += 1;
+    }
+
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    for (_, label) in &mut sensors {
+        let occurrence = occurrences.entry(label.clone()).or_default();
+        if *occurrence > 0 {
+            *label = format!("{label}_{occurrence}");
+        }
+        *occurrence += 1;
+    }
+
+    sensors
+}
+
+fn read_temp_file(hw_mon: &HwMon, index: usize, name: &str) -> Option<f32> {
+    hw_mon
+        .read_file(&format!("temp{index}_{name}"))
+        .ok()
+        .and_then(|value| value.trim().parse::<f32>().ok())
+        .map(|value| value / 1000.0)
+}
+
+/// Reads all temperature sensors, keeping sensors with repeated labels separate (see `temp_sensors`)
+fn get_temps(hw_mon: &HwMon) -> HashMap<String, Temperature> {
+    temp_sensors(hw_mon)
+        .into_iter()
+        .map(|(i, key)| {
+            let temperature = Temperature {
+                current: read_temp_file(hw_mon, i, "input"),
+                crit: read_temp_file(hw_mon, i, "crit"),
+                crit_hyst: read_temp_file(hw_mon, i, "crit_hyst"),
+            };
+            (key, temperature)
+        })
+        .collect()
+}
+
+fn hottest_temp(hw_mon: &HwMon) -> Option<Temperature> {
+    get_temps(hw_mon)
+        .into_values()
+        .filter(|temp| temp.current.is_some())
+        .max_by(|a, b| a.current.partial_cmp(&b.current).unwrap_or(Ordering::Equal))
+}
+
+/// Returns the simulated temperature if it is set, clearing it once it's expired
+fn take_simulated_temp(simulated_temp: &Cell<Option<(f32, Instant)>>) -> Option<Temperature> {
+    let (temp, expires_at) = simulated_temp.get()?;
+    if Instant::now() >= expires_at {
+        info!("simulated fan curve temperature expired");
+        simulated_temp.set(None);
+        return None;
+    }
+    Some(Temperature {
+        current: Some(temp),
+        crit: None,
+        crit_hyst: None,
+    })
+}
+
+/// Fan speed from the curve for the given temperature.
+/// If the sensor disappears (e.g. the source GPU was removed), the hottest sensor of the fan's GPU is used instead.
+fn curve_target_pwm(
+    curve: &FanCurve,
+    temp: Option<Temperature>,
+    hw_mon: &HwMon,
+    temp_offset: i32,
+) -> u8 {
+    temp.or_else(|| hottest_temp(hw_mon))
+        .filter(|temp| temp.current.is_some())
+        .map_or(u8::MAX, |temp| curve.pwm_at_temp(temp, temp_offset))
+}
+
+/// Switches the fan to manual control with the given speed.
+/// If any of the writes fails, the previous control method and speed are restored,
+/// so the fan is not left in manual mode without a valid speed.
+fn set_manual_fan_pwm(hw_mon: &HwMon, pwm: u8) -> anyhow::Result<()> {
+    let previous_method = hw_mon.get_fan_control_method().ok();
+    let previous_pwm = hw_mon.get_fan_pwm().ok();
+
+    let result = hw_mon
+        .set_fan_control_method(FanControlMethod::Manual)
+        .context("Could not set fan control method")
+        .and_then(|()| {
+            hw_mon
+                .set_fan_pwm(pwm)
+                .with_context(|| format!("Could not set fan speed to pwm {pwm}"))
+        });
+
+    if result.is_err() {
+        if let (Some(FanControlMethod::Manual), Some(previous_pwm)) =
+            (previous_method, previous_pwm)
+        {
+            if let Err(err) = hw_mon.set_fan_pwm(previous_pwm) {
+                error!("could not restore the previous fan speed: {err}");
+            }
+        }
+        if let Some(previous_method) = previous_method {
+            if let Err(err) = hw_mon.set_fan_control_method(previous_method) {
+                error!("could not restore the previous fan control method: {err}");
+            }
+        }
+    }
+    result
+}
+
+/// Reads the throttling thresholds of every temperature sensor, using the same keys as `get_temps`
+fn get_temp_limits(hw_mon: &HwMon) -> HashMap<String, TemperatureLimits> {
+    temp_sensors(hw_mon)
+        .into_iter()
+        .map(|(i, key)| {
+            let limits = TemperatureLimits {
+                crit: read_temp_file(hw_mon, i, "crit"),
+                crit_hyst: read_temp_file(hw_mon, i, "crit_hyst"),
+                emergency: read_temp_file(hw_mon, i, "emergency"),
+            };
+            (key, limits)
+        })
+        .collect()
+}
+
 #[cfg(feature = "libdrm_amdgpu_sys")]
 fn get_drm_handle(handle: &GpuHandle) -> anyhow::Result<DrmHandle> {
     let slot_name = handle
@@ -588,3 +1982,378 @@ fn get_drm_handle(handle: &GpuHandle) -> anyhow::Result<DrmHandle> {
         .map_err(|err| anyhow!("Could not open drm handle, error code {err}"))?;
     Ok(handle)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_clocks_configuration, cached_stats, count_active_displays, get_temp_limits,
+        get_temps, is_memory_held_by_displays, is_memory_parked, is_power_capped, power_tier_caps,
+        pwm_percent, set_manual_fan_pwm, snap_clock, take_simulated_temp, ReadErrors,
+    };
+    use crate::{config::ClocksConfiguration, test_util::fake_sysfs};
+    use lact_schema::{
+        amdgpu_sysfs::{
+            error::{Error, ErrorKind},
+            gpu_handle::{
+                overdrive::{ClocksTable, ClocksTableGen},
+                PowerLevels,
+            },
+            hw_mon::HwMon,
+        },
+        ClockDomain, DeviceStats, PowerStats, PowerTier, TemperatureLimits, VoltageCurve,
+    };
+    use std::{
+        cell::Cell,
+        fs,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn temp_limits_rdna3() {
+        let path = fake_sysfs(
+            "hwmon",
+            &[
+                ("name", "amdgpu"),
+                ("temp1_input", "45000"),
+                ("temp1_label", "edge"),
+                ("temp1_crit", "100000"),
+                ("temp1_crit_hyst", "-273150"),
+                ("temp1_emergency", "105000"),
+                ("temp2_input", "60000"),
+                ("temp2_label", "junction"),
+                ("temp2_crit", "110000"),
+                ("temp2_emergency", "115000"),
+                ("temp3_input", "52000"),
+                ("temp3_label", "mem"),
+                ("temp3_crit", "108000"),
+            ],
+        );
+
+        let hw_mon = HwMon::new_from_path(path.to_path_buf()).unwrap();
+        let limits = get_temp_limits(&hw_mon);
+
+        assert_eq!(limits.len(), 3);
+        assert_eq!(
+            limits["junction"],
+            TemperatureLimits {
+                crit: Some(110.0),
+                crit_hyst: None,
+                emergency: Some(115.0),
+            }
+        );
+        assert_eq!(limits["mem"].crit, Some(108.0));
+        assert_eq!(limits["mem"].emergency, None);
+    }
+
+    #[test]
+    fn simulated_temp_expiry() {
+        let simulated_temp = Cell::new(Some((75.0, Instant::now() + Duration::from_mins(1))));
+        assert_eq!(
+            take_simulated_temp(&simulated_temp).and_then(|temp| temp.current),
+            Some(75.0)
+        );
+        assert!(simulated_temp.get().is_some());
+
+        simulated_temp.set(Some((75.0, Instant::now())));
+        assert!(take_simulated_temp(&simulated_temp).is_none());
+        assert!(simulated_temp.get().is_none());
+    }
+
+    #[test]
+    fn manual_fan_pwm_rollback() {
+        let path = fake_sysfs("hwmon-fan", &[("name", "amdgpu"), ("pwm1_enable", "2")]);
+        // Writing the speed fails, as the file can't be written to
+        fs::create_dir_all(path.join("pwm1")).unwrap();
+
+        let hw_mon = HwMon::new_from_path(path.to_path_buf()).unwrap();
+        let err = set_manual_fan_pwm(&hw_mon, 100).unwrap_err();
+        let method = fs::read_to_string(path.join("pwm1_enable")).unwrap();
+
+        assert!(err.to_string().contains("fan speed"));
+        assert_eq!(method, "2");
+    }
+
+    #[test]
+    fn temps_hbm_stacks() {
+        let path = fake_sysfs(
+            "hwmon-hbm",
+            &[
+                ("name", "amdgpu"),
+                ("temp1_input", "45000"),
+                ("temp1_label", "edge"),
+                ("temp2_input", "60000"),
+                ("temp2_label", "mem"),
+                ("temp2_crit", "95000"),
+                ("temp3_input", "62000"),
+                ("temp3_label", "mem"),
+                ("temp3_crit", "95000"),
+            ],
+        );
+
+        let hw_mon = HwMon::new_from_path(path.to_path_buf()).unwrap();
+        let temps = get_temps(&hw_mon);
+        let limits = get_temp_limits(&hw_mon);
+
+        assert_eq!(temps.len(), 3);
+        assert_eq!(temps["edge"].current, Some(45.0));
+        assert_eq!(temps["mem"].current, Some(60.0));
+        assert_eq!(temps["mem_1"].current, Some(62.0));
+        assert_eq!(temps["mem_1"].crit, Some(95.0));
+        assert!(limits.contains_key("mem") && limits.contains_key("mem_1"));
+    }
+
+    #[test]
+    fn power_capped() {
+        let mut power = PowerStats {
+            average: None,
+            current: Some(198.0),
+            cap_current: Some(200.0),
+            cap_current_microwatts: Some(200_000_000),
+            cap_max: Some(250.0),
+            cap_min: Some(100.0),
+            cap_default: Some(200.0),
+        };
+        assert!(is_power_capped(&power));
+
+        power.current = Some(120.0);
+        assert!(!is_power_capped(&power));
+
+        power.current = None;
+        assert!(!is_power_capped(&power));
+    }
+
+    const VEGA10_TABLE: &str = "\
+OD_SCLK:
+0:        852Mhz        800mV
+1:        991Mhz        900mV
+2:       1138Mhz        950mV
+3:       1269Mhz       1000mV
+4:       1312Mhz       1050mV
+5:       1474Mhz       1100mV
+6:       1538Mhz       1150mV
+7:       1590Mhz       1200mV
+OD_MCLK:
+0:        167Mhz        800mV
+1:        500Mhz        800mV
+2:        700Mhz        900mV
+3:        920Mhz        950mV
+OD_RANGE:
+SCLK:     852MHz       2400MHz
+MCLK:     167MHz       1500MHz
+VDDC:     800mV        1200mV
+";
+
+    const VEGA20_TABLE: &str = "\
+OD_SCLK:
+0: 500Mhz
+1: 2499Mhz
+OD_MCLK:
+0: 97Mhz
+1: 1000MHz
+OD_VDDGFX_OFFSET:
+-10mV
+OD_RANGE:
+SCLK: 500Mhz 3000Mhz
+MCLK: 674Mhz 1075Mhz
+";
+
+    #[test]
+    fn clocks_commands_vega10() {
+        let mut table = ClocksTableGen::Vega10(VEGA10_TABLE.parse().unwrap());
+        let clocks = ClocksConfiguration {
+            max_core_clock: Some(1650),
+            max_memory_clock: Some(950),
+            max_voltage: Some(1150),
+            ..Default::default()
+        };
+        apply_clocks_configuration(&mut table, &clocks).unwrap();
+
+        let commands = table.get_commands().unwrap();
+        assert!(
+            commands.contains(&"s 7 1650 1150".to_owned()),
+            "{commands:?}"
+        );
+        assert!(commands.contains(&"m 3 950 950".to_owned()), "{commands:?}");
+    }
+
+    #[test]
+    fn clocks_voltage_offset_vega10() {
+        let mut table = ClocksTableGen::Vega10(VEGA10_TABLE.parse().unwrap());
+        let clocks = ClocksConfiguration {
+            voltage_offset: Some(-50),
+            ..Default::default()
+        };
+        assert!(apply_clocks_configuration(&mut table, &clocks).is_err());
+    }
+
+    #[test]
+    fn clocks_commands_vega20() {
+        let mut table = ClocksTableGen::Vega20(VEGA20_TABLE.parse().unwrap());
+        let clocks = ClocksConfiguration {
+            max_core_clock: Some(2600),
+            voltage_offset: Some(-50),
+            ..Default::default()
+        };
+        apply_clocks_configuration(&mut table, &clocks).unwrap();
+
+        assert_eq!(
+            table.get_commands().unwrap(),
+            ["s 1 2600", "vo -50"].map(str::to_owned)
+        );
+    }
+
+    #[test]
+    fn snap_clock_to_range() {
+        let table = ClocksTableGen::Vega20(VEGA20_TABLE.parse().unwrap());
+        assert_eq!(
+            snap_clock(&table, ClockDomain::MaxCoreClock, 2600).unwrap(),
+            2600
+        );
+        assert_eq!(
+            snap_clock(&table, ClockDomain::MaxCoreClock, 3500).unwrap(),
+            3000
+        );
+        assert_eq!(
+            snap_clock(&table, ClockDomain::MaxMemoryClock, 500).unwrap(),
+            674
+        );
+        assert!(snap_clock(&table, ClockDomain::MaxVoltage, 1100).is_err());
+    }
+
+    #[test]
+    fn stats_cache_interval() {
+        let read_at = Instant::now();
+        let stats = DeviceStats {
+            sampled_at: 1000,
+            ..Default::default()
+        };
+        let cache = Some((read_at, stats));
+        let interval = Duration::from_millis(500);
+
+        let cached = cached_stats(
+            cache.as_ref(),
+            interval,
+            read_at + Duration::from_millis(200),
+        )
+        .unwrap();
+        assert_eq!((cached.sampled_at, cached.cache_age_ms), (1000, 200));
+        assert!(cached_stats(cache.as_ref(), interval, read_at + interval).is_none());
+        assert!(cached_stats(None, interval, read_at).is_none());
+    }
+
+    #[test]
+    fn voltage_curve_points() {
+        let table = ClocksTableGen::Vega10(VEGA10_TABLE.parse().unwrap());
+        let curve = VoltageCurve::from(&table);
+        assert_eq!(curve.points.len(), 8);
+
+        let last = curve.points[7];
+        assert_eq!((last.clockspeed, last.voltage), (1590, 1200));
+        assert_eq!(last.clockspeed_range.unwrap().max, Some(2400));
+        assert_eq!(last.voltage_range.unwrap().min, Some(800));
+
+        // Only a voltage offset is available, without a curve
+        let table = ClocksTableGen::Vega20(VEGA20_TABLE.parse().unwrap());
+        assert!(VoltageCurve::from(&table).points.is_empty());
+    }
+
+    #[test]
+    fn read_errors_skip_unsupported() {
+        let errors = ReadErrors::default();
+
+        let not_found = Error::from(ErrorKind::IoError(std::io::ErrorKind::NotFound.into()));
+        assert_eq!(errors.check::<u64>("power.average", Err(not_found)), None);
+
+        let removed = Error::from(ErrorKind::IoError(std::io::Error::from_raw_os_error(19)));
+        assert_eq!(errors.check::<u64>("power.current", Err(removed)), None);
+        assert_eq!(errors.check("vram.total", Ok(1024)), Some(1024));
+
+        let errors = errors.0.into_inner();
+        assert_eq!(errors.len(), 1);
+        assert!(errors.contains_key("power.current"));
+    }
+
+    #[test]
+    fn memory_parked() {
+        let levels = |active| PowerLevels {
+            levels: vec![96, 456, 772, 875],
+            active,
+        };
+        assert_eq!(is_memory_parked(&levels(Some(0))), Some(true));
+        assert_eq!(is_memory_parked(&levels(Some(3))), Some(false));
+        assert_eq!(is_memory_parked(&levels(None)), None);
+
+        let single_level = PowerLevels {
+            levels: vec![1000],
+            active: Some(0),
+        };
+        assert_eq!(is_memory_parked(&single_level), Some(false));
+    }
+
+    #[test]
+    fn memory_held_by_displays() {
+        let levels = |active| PowerLevels {
+            levels: vec![96, 456, 772, 875],
+            active,
+        };
+        assert_eq!(
+            is_memory_held_by_displays(&levels(Some(3)), 2, 2),
+            Some(true)
+        );
+        assert_eq!(
+            is_memory_held_by_displays(&levels(Some(3)), 95, 2),
+            Some(false)
+        );
+        assert_eq!(
+            is_memory_held_by_displays(&levels(Some(0)), 2, 2),
+            Some(false)
+        );
+        assert_eq!(
+            is_memory_held_by_displays(&levels(Some(3)), 2, 0),
+            Some(false)
+        );
+        assert_eq!(is_memory_held_by_displays(&levels(None), 2, 2), None);
+    }
+
+    #[test]
+    fn active_displays() {
+        let path = fake_sysfs(
+            "displays",
+            &[
+                ("drm/card1/card1-DP-1/enabled", "enabled"),
+                ("drm/card1/card1-HDMI-A-1/enabled", "disabled"),
+                ("drm/card1/card1-DP-2/enabled", "enabled"),
+            ],
+        );
+        fs::create_dir_all(path.join("drm/renderD128")).unwrap();
+
+        let count = count_active_displays(&path);
+        assert_eq!(count, Some(2));
+    }
+
+    #[test]
+    fn power_tiers() {
+        assert_eq!(
+            power_tier_caps(250_000_000, 150_000_000, 290_000_000),
+            [
+                (PowerTier::Eco, 200_000_000),
+                (PowerTier::Balanced, 250_000_000),
+                (PowerTier::Turbo, 290_000_000),
+            ]
+        );
+        // The cap can't be lowered or raised
+        assert_eq!(
+            power_tier_caps(250_000_000, 250_000_000, 250_000_000),
+            [(PowerTier::Balanced, 250_000_000)]
+        );
+    }
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn fan_pwm_percent() {
+        assert_eq!(pwm_percent(255, (0, 255)), 100.0);
+        assert_eq!(pwm_percent(0, (0, 255)), 0.0);
+        assert_eq!(pwm_percent(125, (50, 200)), 50.0);
+        assert_eq!(pwm_percent(20, (50, 200)), 0.0);
+        assert_eq!(pwm_percent(100, (0, 0)), 0.0);
+    }
+}
@@ -0,0 +1,91 @@
+//! Memory regions (BARs) of the GPU, for checking whether Resizable BAR / Smart Access Memory is in effect
+
+use lact_schema::PciBarInfo;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// Only the first 6 lines of the `resource` file are BARs, the next ones are the expansion ROM and bridge windows
+const BAR_COUNT: usize = 6;
+
+pub fn info(device_path: &Path, total_vram: Option<u64>) -> Option<PciBarInfo> {
+    let resource = fs::read_to_string(device_path.join("resource")).ok()?;
+    let sizes = parse_resource(&resource);
+    let resizable_bar_supported =
+        (0..BAR_COUNT).any(|index| device_path.join(format!("resource{index}_resize")).exists());
+
+    Some(bar_info(sizes, total_vram, resizable_bar_supported))
+}
+
+/// The VRAM aperture is the largest BAR, which only covers all of the VRAM when it was resized
+fn bar_info(
+    sizes: BTreeMap<u8, u64>,
+    total_vram: Option<u64>,
+    resizable_bar_supported: bool,
+) -> PciBarInfo {
+    let resizable_bar_enabled = total_vram
+        .zip(sizes.values().max())
+        .map(|(total_vram, largest)| *largest >= total_vram);
+
+    PciBarInfo {
+        sizes,
+        resizable_bar_enabled,
+        resizable_bar_supported,
+    }
+}
+
+/// Parses the `start end flags` lines of the `resource` file into the sizes of the used BARs
+fn parse_resource(resource: &str) -> BTreeMap<u8, u64> {
+    resource
+        .lines()
+        .take(BAR_COUNT)
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let mut values = line
+                .split_whitespace()
+                .map(|value| u64::from_str_radix(value.trim_start_matches("0x"), 16).ok());
+            let start = values.next()??;
+            let end = values.next()??;
+            if start == 0 && end == 0 {
+                return None;
+            }
+            Some((u8::try_from(index).ok()?, end - start + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bar_info, parse_resource};
+    use std::collections::BTreeMap;
+
+    const RESOURCE: &str = "\
+0x0000007800000000 0x0000007bffffffff 0x000000000014220c
+0x0000000000000000 0x0000000000000000 0x0000000000000000
+0x0000007c00000000 0x0000007c001fffff 0x000000000014220c
+0x0000000000000000 0x0000000000000000 0x0000000000000000
+0x000000000000e000 0x000000000000e0ff 0x0000000000040101
+0x00000000fcb00000 0x00000000fcbfffff 0x0000000000040200
+0x00000000fcc00000 0x00000000fcc1ffff 0x0000000000046200
+";
+
+    #[test]
+    fn resizable_bar() {
+        let sizes = parse_resource(RESOURCE);
+        assert_eq!(
+            sizes,
+            BTreeMap::from([
+                (0, 16 * 1024 * 1024 * 1024),
+                (2, 2 * 1024 * 1024),
+                (4, 256),
+                (5, 1024 * 1024),
+            ])
+        );
+
+        let info = bar_info(sizes.clone(), Some(16 * 1024 * 1024 * 1024), true);
+        assert_eq!(info.resizable_bar_enabled, Some(true));
+
+        let mut small_bar = sizes;
+        small_bar.insert(0, 256 * 1024 * 1024);
+        let info = bar_info(small_bar, Some(16 * 1024 * 1024 * 1024), true);
+        assert_eq!(info.resizable_bar_enabled, Some(false));
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::{anyhow, Context};
+use lact_schema::{PowerProfileModeDetails, PowerProfileModeRow, PowerProfileModesDetails};
+
+/// Labels of the header which describe the mode and clock type, rather than a heuristic
+const NON_HEURISTIC_COLUMNS: [&str; 4] = [
+    "NUM",
+    "MODE_NAME",
+    "PROFILE_INDEX(NAME)",
+    "CLOCK_TYPE(NAME)",
+];
+
+/// Parses the full `pp_power_profile_mode` table, including the heuristic values of each mode.
+///
+/// Older GPUs list the values on the same line as the mode (`1 3D_FULL_SCREEN *: 0 100 30`),
+/// while RDNA GPUs list them on separate lines for every clock type (`0( GFXCLK) 0 5 1`).
+/// Some APUs only list the mode names without a header.
+pub fn parse_details(raw: &str) -> anyhow::Result<PowerProfileModesDetails> {
+    let mut columns = Vec::new();
+    let mut modes: Vec<PowerProfileModeDetails> = Vec::new();
+    let mut active = None;
+
+    for line in raw.lines() {
+        let line = line.trim_matches(char::from(0)).trim();
+        let Some(first) = line.split_whitespace().next() else {
+            continue;
+        };
+
+        if let Some((index, rest)) = first.parse::<u16>().ok().zip(line.get(first.len()..)) {
+            let (description, values) = rest.split_once(':').unwrap_or((rest, ""));
+            if description.contains('*') {
+                active = Some(index);
+            }
+            let name = description.replace('*', "").trim().to_owned();
+
+            let values = parse_values(values);
+            let rows = if values.is_empty() {
+                vec![]
+            } else {
+                vec![PowerProfileModeRow {
+                    clock_type: None,
+                    values,
+                }]
+            };
+            modes.push(PowerProfileModeDetails { index, name, rows });
+        } else if modes.is_empty() {
+            columns = line
+                .split_whitespace()
+                .filter(|column| !NON_HEURISTIC_COLUMNS.contains(column))
+                .map(str::to_owned)
+                .collect();
+        } else if let Some((clock_type, values)) =
+            line.split_once(')').filter(|_| first.contains('('))
+        {
+            let mode = modes
+                .last_mut()
+                .with_context(|| format!("Clock type row `{line}` before any mode"))?;
+            let clock_type = clock_type
+                .split_once('(')
+                .map_or(clock_type, |(_, name)| name)
+                .trim()
+                .to_owned();
+            mode.rows.push(PowerProfileModeRow {
+                clock_type: Some(clock_type),
+                values: parse_values(values),
+            });
+        } else {
+            return Err(anyhow!("Unexpected line `{line}`"));
+        }
+    }
+
+    Ok(PowerProfileModesDetails {
+        active: active.context("No active power profile mode found")?,
+        requested: None,
+        performance_level: None,
+        columns,
+        modes,
+    })
+}
+
+/// The driver shows `-` for heuristics that don't apply to a mode
+fn parse_values(raw: &str) -> Vec<Option<i32>> {
+    raw.split_whitespace()
+        .map(|value| value.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_details;
+    use lact_schema::PowerProfileModeRow;
+
+    #[test]
+    fn parse_polaris() {
+        let raw = "\
+NUM        MODE_NAME     SCLK_UP_HYST   SCLK_DOWN_HYST SCLK_ACTIVE_LEVEL     MCLK_UP_HYST   MCLK_DOWN_HYST MCLK_ACTIVE_LEVEL
+  0   BOOTUP_DEFAULT:        -                -                -                -                -                -
+  1 3D_FULL_SCREEN *:        0              100               30               10               60               25
+  2     POWER_SAVING:       10                0               30                -                -                -
+";
+        let details = parse_details(raw).unwrap();
+        assert_eq!(details.active, 1);
+        assert_eq!(
+            details.columns,
+            [
+                "SCLK_UP_HYST",
+                "SCLK_DOWN_HYST",
+                "SCLK_ACTIVE_LEVEL",
+                "MCLK_UP_HYST",
+                "MCLK_DOWN_HYST",
+                "MCLK_ACTIVE_LEVEL"
+            ]
+        );
+        assert_eq!(details.modes.len(), 3);
+        assert_eq!(details.modes[1].name, "3D_FULL_SCREEN");
+        assert_eq!(
+            details.modes[2].rows,
+            [PowerProfileModeRow {
+                clock_type: None,
+                values: vec![Some(10), Some(0), Some(30), None, None, None],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_rdna() {
+        let raw = "\
+PROFILE_INDEX(NAME) CLOCK_TYPE(NAME) FPS MinActiveFreqType MinActiveFreq BoosterFreqType BoosterFreq PD_Data_limit_c PD_Data_error_coeff PD_Data_error_rate_coeff
+ 0 BOOTUP_DEFAULT*:
+                    0(       GFXCLK)       0       1       0       4     800 4587520  -65536       0
+                    1(         FCLK)       0       3       0       1       0 3276800  -65536   -6553
+ 1 3D_FULL_SCREEN :
+                    0(       GFXCLK)       0       0    1200       4     650 3932160   -3276  -65536
+                    1(         FCLK)       0       3       0       3       0 1310720   -6553   -6553
+\0\0\0\0";
+        let details = parse_details(raw).unwrap();
+        assert_eq!(details.active, 0);
+        assert_eq!(details.columns.len(), 8);
+        assert_eq!(details.columns[0], "FPS");
+        assert_eq!(details.modes.len(), 2);
+
+        let mode = &details.modes[1];
+        assert_eq!(mode.name, "3D_FULL_SCREEN");
+        assert_eq!(mode.rows.len(), 2);
+        assert_eq!(mode.rows[0].clock_type.as_deref(), Some("GFXCLK"));
+        assert_eq!(
+            mode.rows[0].values,
+            [0, 0, 1200, 4, 650, 3_932_160, -3276, -65536].map(Some)
+        );
+    }
+
+    #[test]
+    fn parse_names_only() {
+        let raw = "\
+ 1 3D_FULL_SCREEN
+ 3          VIDEO*
+ 4             VR
+";
+        let details = parse_details(raw).unwrap();
+        assert_eq!(details.active, 3);
+        assert!(details.columns.is_empty());
+        assert_eq!(details.modes[1].name, "VIDEO");
+        assert!(details.modes.iter().all(|mode| mode.rows.is_empty()));
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{anyhow, Context};
+use lact_schema::PpFeature;
+
+pub const GFXOFF: &str = "GFXOFF";
+
+/// Parses the `pp_features` sysfs file.
+///
+/// Older powerplay implementations list every feature with its bitmask (`DPM_GFXCLK 0x0000000000000002 Y`),
+/// while SMU-based GPUs list them with their bit index (`01. DPM_GFXCLK ( 1) : enabled`).
+pub fn parse(raw: &str) -> anyhow::Result<Vec<PpFeature>> {
+    let features: Vec<PpFeature> = raw.lines().filter_map(parse_line).collect();
+    if features.is_empty() {
+        return Err(anyhow!("No features found in pp_features"));
+    }
+    Ok(features)
+}
+
+fn parse_line(line: &str) -> Option<PpFeature> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    if let [name, mask, enablement] = parts.as_slice() {
+        let mask = u64::from_str_radix(mask.strip_prefix("0x")?, 16).ok()?;
+        let enabled = match *enablement {
+            "Y" => true,
+            "N" => false,
+            _ => return None,
+        };
+        return Some(PpFeature {
+            name: (*name).to_owned(),
+            mask,
+            enabled,
+        });
+    }
+
+    let (description, enablement) = line.split_once(':')?;
+    let enabled = match enablement.trim() {
+        "enabled" => true,
+        "disabled" => false,
+        _ => return None,
+    };
+    let (name, bit) = description.split_once('(')?;
+    let name = name.split_once('.')?.1.trim();
+    let bit: u32 = bit.trim().strip_suffix(')')?.trim().parse().ok()?;
+
+    Some(PpFeature {
+        name: name.to_owned(),
+        mask: 1u64.checked_shl(bit)?,
+        enabled,
+    })
+}
+
+/// Builds the mask of enabled features after enabling or disabling the given feature
+pub fn new_mask(features: &[PpFeature], name: &str, enabled: bool) -> anyhow::Result<u64> {
+    let feature = features
+        .iter()
+        .find(|feature| feature.name.eq_ignore_ascii_case(name))
+        .with_context(|| format!("Feature `{name}` not found"))?;
+
+    let current_mask = features
+        .iter()
+        .filter(|feature| feature.enabled)
+        .fold(0, |mask, feature| mask | feature.mask);
+
+    if enabled {
+        Ok(current_mask | feature.mask)
+    } else {
+        Ok(current_mask & !feature.mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{new_mask, parse};
+    use lact_schema::PpFeature;
+
+    #[test]
+    fn parse_bitmask_format() {
+        let raw = "\
+Current ppfeatures: 0x0000000000000005
+FEATURES            BITMASK                ENABLEMENT
+DPM_PREFETCHER      0x0000000000000001     Y
+DPM_GFXCLK          0x0000000000000002     N
+DPM_UCLK            0x0000000000000004     Y
+";
+        let features = parse(raw).unwrap();
+        assert_eq!(
+            features,
+            [
+                PpFeature {
+                    name: "DPM_PREFETCHER".to_owned(),
+                    mask: 0x1,
+                    enabled: true
+                },
+                PpFeature {
+                    name: "DPM_GFXCLK".to_owned(),
+                    mask: 0x2,
+                    enabled: false
+                },
+                PpFeature {
+                    name: "DPM_UCLK".to_owned(),
+                    mask: 0x4,
+                    enabled: true
+                },
+            ]
+        );
+        assert_eq!(new_mask(&features, "dpm_gfxclk", true).unwrap(), 0x7);
+        assert_eq!(new_mask(&features, "DPM_UCLK", false).unwrap(), 0x1);
+        assert!(new_mask(&features, "GFXOFF", false).is_err());
+    }
+
+    #[test]
+    fn parse_bit_index_format() {
+        let raw = "\
+features high: 0x00000000 low: 0x00000401
+List of all features:
+00. DPM_PREFETCHER       ( 0) : enabled
+01. DPM_GFXCLK           ( 1) : disabled
+10. GFXOFF               (10) : enabled
+";
+        let features = parse(raw).unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[2].name, "GFXOFF");
+        assert_eq!(features[2].mask, 1 << 10);
+        assert!(!features[1].enabled);
+    }
+}
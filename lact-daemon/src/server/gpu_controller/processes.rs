@@ -0,0 +1,232 @@
+use anyhow::anyhow;
+use lact_schema::GpuProcess;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+use tracing::trace;
+
+/// Usage of a single DRM client, as reported in `/proc/<pid>/fdinfo/<fd>`.
+/// See <https://docs.kernel.org/gpu/drm-usage-stats.html>
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DrmClient {
+    pub pdev: String,
+    pub client_id: u64,
+    pub vram_usage: Option<u64>,
+    pub gtt_usage: Option<u64>,
+    pub engine_usage: BTreeMap<String, u64>,
+}
+
+/// Parses the DRM usage stats of an fdinfo file. Returns `None` for files that are not DRM clients.
+pub fn parse_fdinfo(raw: &str) -> Option<DrmClient> {
+    let mut client = DrmClient::default();
+    let mut pdev = None;
+    let mut client_id = None;
+
+    for line in raw.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        match key {
+            "drm-pdev" => pdev = Some(value.to_owned()),
+            "drm-client-id" => client_id = value.parse().ok(),
+            "drm-memory-vram" => client.vram_usage = parse_memory(value),
+            "drm-memory-gtt" => client.gtt_usage = parse_memory(value),
+            _ => {
+                if let Some(engine) = key.strip_prefix("drm-engine-") {
+                    if let Some(ns) = value.strip_suffix(" ns").and_then(|ns| ns.parse().ok()) {
+                        client.engine_usage.insert(engine.to_owned(), ns);
+                    }
+                }
+            }
+        }
+    }
+
+    client.pdev = pdev?;
+    client.client_id = client_id?;
+    Some(client)
+}
+
+/// Parses a memory value (`1024 KiB`) into bytes
+fn parse_memory(value: &str) -> Option<u64> {
+    let (amount, unit) = value.split_once(' ').unwrap_or((value, ""));
+    let amount: u64 = amount.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" => 1,
+        "KiB" => 1024,
+        "MiB" => 1024 * 1024,
+        "GiB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some(amount * multiplier)
+}
+
+/// Lists the DRM clients of a process that are using the device with the given PCI slot
+fn process_clients(pid_path: &Path, slot_name: &str) -> Vec<DrmClient> {
+    let Ok(entries) = fs::read_dir(pid_path.join("fdinfo")) else {
+        return vec![];
+    };
+
+    let mut seen_ids = BTreeSet::new();
+    entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|raw| parse_fdinfo(&raw))
+        // Multiple file descriptors can refer to the same client
+        .filter(|client| client.pdev == slot_name && seen_ids.insert(client.client_id))
+        .collect()
+}
+
+/// Lists the processes that have the device with the given PCI slot open
+pub fn get_processes(slot_name: &str) -> Vec<GpuProcess> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return vec![];
+    };
+
+    let mut processes: Vec<GpuProcess> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid: i32 = entry.file_name().to_str()?.parse().ok()?;
+            let path = entry.path();
+
+            let clients = process_clients(&path, slot_name);
+            if clients.is_empty() {
+                return None;
+            }
+            trace!("process {pid} has {} drm clients", clients.len());
+
+            let name = fs::read_to_string(path.join("comm"))
+                .map(|name| name.trim_end().to_owned())
+                .unwrap_or_default();
+
+            let mut process = GpuProcess {
+                pid,
+                name,
+                vram_usage: None,
+                gtt_usage: None,
+                engine_usage: BTreeMap::new(),
+            };
+            for client in clients {
+                if let Some(vram) = client.vram_usage {
+                    *process.vram_usage.get_or_insert(0) += vram;
+                }
+                if let Some(gtt) = client.gtt_usage {
+                    *process.gtt_usage.get_or_insert(0) += gtt;
+                }
+                for (engine, ns) in client.engine_usage {
+                    *process.engine_usage.entry(engine).or_default() += ns;
+                }
+            }
+            Some(process)
+        })
+        .collect();
+
+    processes.sort_by_key(|process| Reverse(process.vram_usage));
+    processes
+}
+
+/// Checks if a process has the device with the given PCI slot open
+pub fn is_using_device(pid: i32, slot_name: &str) -> bool {
+    !process_clients(&Path::new("/proc").join(pid.to_string()), slot_name).is_empty()
+}
+
+/// Real user id of a process, from the `Uid:` line of `/proc/<pid>/status`
+fn parse_owner_uid(status: &str) -> Option<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Checks that the caller with the given uid is allowed to signal the process,
+/// which is the case for root and for the user who owns the process
+pub fn check_can_signal(pid: i32, caller_uid: u32) -> anyhow::Result<()> {
+    let owner_uid = fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("status"))
+        .ok()
+        .and_then(|status| parse_owner_uid(&status));
+    check_owner(pid, caller_uid, owner_uid)
+}
+
+fn check_owner(pid: i32, caller_uid: u32, owner_uid: Option<u32>) -> anyhow::Result<()> {
+    if caller_uid == 0 || owner_uid == Some(caller_uid) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Process {pid} belongs to another user, only root can terminate it"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_owner, parse_fdinfo, parse_owner_uid, DrmClient};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn parse_amdgpu_fdinfo() {
+        let raw = "\
+pos:	0
+flags:	02100002
+mnt_id:	24
+ino:	1180
+drm-driver:	amdgpu
+drm-client-id:	36
+drm-pdev:	0000:03:00.0
+pasid:	32774
+drm-memory-vram:	285672 KiB
+drm-memory-gtt: 	10240 KiB
+drm-memory-cpu: 	0 KiB
+amd-memory-visible-vram:	285672 KiB
+drm-engine-gfx:	1447148735 ns
+drm-engine-compute:	0 ns
+drm-engine-dma:	6552836 ns
+";
+        let client = parse_fdinfo(raw).unwrap();
+        assert_eq!(
+            client,
+            DrmClient {
+                pdev: "0000:03:00.0".to_owned(),
+                client_id: 36,
+                vram_usage: Some(285_672 * 1024),
+                gtt_usage: Some(10240 * 1024),
+                engine_usage: BTreeMap::from([
+                    ("compute".to_owned(), 0),
+                    ("dma".to_owned(), 6_552_836),
+                    ("gfx".to_owned(), 1_447_148_735),
+                ]),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_non_drm_fdinfo() {
+        let raw = "\
+pos:	0
+flags:	0100002
+mnt_id:	15
+ino:	5
+";
+        assert_eq!(parse_fdinfo(raw), None);
+    }
+
+    #[test]
+    fn signal_permission() {
+        let status = "Name:\tgame\nUmask:\t0022\nState:\tS (sleeping)\nUid:\t1000\t1000\t1000\t1000\nGid:\t1000\t1000\t1000\t1000\n";
+        let owner = parse_owner_uid(status);
+        assert_eq!(owner, Some(1000));
+
+        assert!(check_owner(4242, 1000, owner).is_ok());
+        assert!(check_owner(4242, 0, owner).is_ok());
+        let err = check_owner(4242, 1001, owner).unwrap_err();
+        assert!(err.to_string().contains("another user"));
+        // The process exited or its status can't be read
+        assert!(check_owner(4242, 1000, None).is_err());
+    }
+}
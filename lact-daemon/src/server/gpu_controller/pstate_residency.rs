@@ -0,0 +1,84 @@
+use lact_schema::{
+    amdgpu_sysfs::{gpu_handle::GpuHandle, sysfs::SysFS},
+    RuntimePmStatus,
+};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc, time::Duration};
+use tokio::time::sleep;
+use tracing::debug;
+
+const INTERVAL: Duration = Duration::from_millis(500);
+/// Number of samples kept, which makes the residency cover the last minute
+const WINDOW_SAMPLES: usize = 120;
+
+pub type PstateSamples = Rc<RefCell<VecDeque<usize>>>;
+
+/// Share of samples (in percent) spent in each power state, sorted by the power state index
+#[allow(clippy::cast_precision_loss)]
+pub fn residency(samples: &VecDeque<usize>) -> Vec<(usize, f64)> {
+    let mut counts: Vec<(usize, usize)> = Vec::new();
+    for pstate in samples {
+        match counts.iter_mut().find(|(state, _)| state == pstate) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((*pstate, 1)),
+        }
+    }
+    counts.sort_unstable_by_key(|(state, _)| *state);
+
+    counts
+        .into_iter()
+        .map(|(state, count)| (state, count as f64 / samples.len() as f64 * 100.0))
+        .collect()
+}
+
+/// Spawns a task which periodically records the active core clock power state.
+/// No samples are taken while the GPU is suspended, as reading the power state would wake it up.
+pub fn spawn(handle: GpuHandle, samples: PstateSamples) {
+    tokio::task::spawn_local(async move {
+        loop {
+            sleep(INTERVAL).await;
+
+            let runtime_status = handle
+                .read_file("power/runtime_status")
+                .ok()
+                .and_then(|status| status.parse().ok());
+            if matches!(
+                runtime_status,
+                Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending)
+            ) {
+                continue;
+            }
+
+            let Some(active) = handle
+                .get_core_clock_levels()
+                .ok()
+                .and_then(|levels| levels.active)
+            else {
+                debug!("core clock power states are not available, stopping residency sampling");
+                break;
+            };
+
+            let mut samples = samples.borrow_mut();
+            if samples.len() == WINDOW_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(active);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::residency;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn residency_percentages() {
+        let samples = VecDeque::from([2, 0, 0, 1, 0, 0, 2, 0]);
+        assert_eq!(residency(&samples), [(0, 62.5), (1, 12.5), (2, 25.0)]);
+    }
+
+    #[test]
+    fn residency_empty() {
+        assert!(residency(&VecDeque::new()).is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+//! Counts of the stats which could not be read, so that sensors which only fail sometimes can be found.
+//! Failures are recorded on every full read of the stats.
+
+use lact_schema::{amdgpu_sysfs::hw_mon::Temperature, ReadFailureCount};
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// Period of the recent failure count
+const WINDOW: Duration = Duration::from_mins(1);
+
+#[derive(Default)]
+pub struct ReadFailures {
+    fields: BTreeMap<String, FieldFailures>,
+}
+
+#[derive(Default)]
+struct FieldFailures {
+    total: u64,
+    recent: VecDeque<Instant>,
+    last_error: String,
+}
+
+impl ReadFailures {
+    /// Records the errors of one read, along with the temperature sensors which returned no value
+    pub fn record(
+        &mut self,
+        errors: &BTreeMap<String, String>,
+        temps: &HashMap<String, Temperature>,
+        now: Instant,
+    ) {
+        let missing_temps = temps
+            .iter()
+            .filter(|(_, temp)| temp.current.is_none())
+            .map(|(name, _)| (format!("temps.{name}"), "No value".to_owned()));
+
+        for (field, error) in errors
+            .iter()
+            .map(|(field, error)| (field.clone(), error.clone()))
+            .chain(missing_temps)
+        {
+            let failures = self.fields.entry(field).or_default();
+            failures.total += 1;
+            failures.recent.push_back(now);
+            failures.last_error = error;
+        }
+
+        for failures in self.fields.values_mut() {
+            while failures
+                .recent
+                .front()
+                .is_some_and(|at| now.saturating_duration_since(*at) > WINDOW)
+            {
+                failures.recent.pop_front();
+            }
+        }
+    }
+
+    pub fn counts(&self) -> BTreeMap<String, ReadFailureCount> {
+        self.fields
+            .iter()
+            .map(|(field, failures)| {
+                let count = ReadFailureCount {
+                    total: failures.total,
+                    last_minute: failures.recent.len(),
+                    last_error: failures.last_error.clone(),
+                };
+                (field.clone(), count)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadFailures;
+    use lact_schema::amdgpu_sysfs::hw_mon::Temperature;
+    use std::{
+        collections::{BTreeMap, HashMap},
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn failure_counts() {
+        let start = Instant::now();
+        let errors = BTreeMap::from([("busy_percent".to_owned(), "I/O error".to_owned())]);
+        let temps = HashMap::from([(
+            "edge".to_owned(),
+            Temperature {
+                current: None,
+                crit: None,
+                crit_hyst: None,
+            },
+        )]);
+
+        let mut failures = ReadFailures::default();
+        failures.record(&errors, &temps, start);
+        failures.record(&errors, &HashMap::new(), start + Duration::from_secs(30));
+        failures.record(
+            &BTreeMap::new(),
+            &HashMap::new(),
+            start + Duration::from_secs(70),
+        );
+
+        let counts = failures.counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["busy_percent"].total, 2);
+        assert_eq!(counts["busy_percent"].last_minute, 1);
+        assert_eq!(counts["busy_percent"].last_error, "I/O error");
+        assert_eq!(counts["temps.edge"].total, 1);
+        assert_eq!(counts["temps.edge"].last_minute, 0);
+    }
+}
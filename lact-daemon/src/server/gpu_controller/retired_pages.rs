@@ -0,0 +1,63 @@
+use anyhow::{anyhow, Context};
+use lact_schema::{RetiredPage, RetiredPageStatus};
+
+/// Parses `ras/gpu_vram_bad_pages`, which has a `<page> : <size> : <status>` line for each page
+pub fn parse(contents: &str) -> anyhow::Result<Vec<RetiredPage>> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let mut parts = line.split(':').map(str::trim);
+            let (Some(page), Some(size), Some(status)) = (parts.next(), parts.next(), parts.next())
+            else {
+                return Err(anyhow!("Invalid retired page entry '{line}'"));
+            };
+
+            let status = match status {
+                "R" => RetiredPageStatus::Reserved,
+                "P" => RetiredPageStatus::Pending,
+                "F" => RetiredPageStatus::Failed,
+                other => return Err(anyhow!("Unknown retired page status '{other}'")),
+            };
+            Ok(RetiredPage {
+                page_frame: parse_hex(page)?,
+                size: parse_hex(size)?,
+                status,
+            })
+        })
+        .collect()
+}
+
+fn parse_hex(value: &str) -> anyhow::Result<u64> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    u64::from_str_radix(digits, 16).with_context(|| format!("Invalid number '{value}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use lact_schema::{RetiredPage, RetiredPageStatus};
+
+    #[test]
+    fn parse_bad_pages() {
+        let contents = "0x00000001 : 0x00001000 : R\n0x0000a2f3 : 0x00001000 : P\n";
+        assert_eq!(
+            parse(contents).unwrap(),
+            [
+                RetiredPage {
+                    page_frame: 1,
+                    size: 4096,
+                    status: RetiredPageStatus::Reserved,
+                },
+                RetiredPage {
+                    page_frame: 0xa2f3,
+                    size: 4096,
+                    status: RetiredPageStatus::Pending,
+                },
+            ]
+        );
+        assert!(parse("").unwrap().is_empty());
+        assert!(parse("0x1 : 0x1000").is_err());
+        assert!(parse("0x1 : 0x1000 : X").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Full contents of the `gpu_metrics` table, parsed by libdrm for every known table format.
+
+use lact_schema::SmuMetrics;
+use libdrm_amdgpu_sys::AMDGPU::{GpuMetrics, MetricsInfo};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Value reported by the firmware, which fills unsupported fields with ones
+trait MetricValue {
+    fn into_json(self) -> Option<Value>;
+}
+
+macro_rules! impl_metric_value {
+    ($($type:ty),*) => {
+        $(
+            impl MetricValue for $type {
+                fn into_json(self) -> Option<Value> {
+                    (self != <$type>::MAX).then(|| self.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_metric_value!(u16, u32, u64);
+
+impl<const N: usize> MetricValue for [u16; N] {
+    fn into_json(self) -> Option<Value> {
+        let values: Vec<Value> = self
+            .into_iter()
+            .map(|value| value.into_json().unwrap_or(Value::Null))
+            .collect();
+        values
+            .iter()
+            .any(|value| !value.is_null())
+            .then_some(Value::Array(values))
+    }
+}
+
+/// Name of a field, which is the getter name without the `get_` prefix unless it is given explicitly
+macro_rules! field_name {
+    ($getter:ident) => {
+        stringify!($getter).trim_start_matches("get_")
+    };
+    ($getter:ident, $name:literal) => {
+        $name
+    };
+}
+
+/// Adds every given field that the table has and that is supported
+macro_rules! collect_fields {
+    ($metrics:expr, $fields:expr, [$($getter:ident $(as $name:literal)?),* $(,)?]) => {
+        $(
+            if let Some(value) = $metrics.$getter().and_then(MetricValue::into_json) {
+                $fields.insert(field_name!($getter $(, $name)?).to_owned(), value);
+            }
+        )*
+    };
+}
+
+pub fn parse(raw: &[u8]) -> anyhow::Result<SmuMetrics> {
+    let metrics = GpuMetrics::from_raw(raw);
+    let header = metrics
+        .get_header()
+        .ok_or_else(|| anyhow::anyhow!("Unknown gpu_metrics table format"))?;
+
+    let mut fields = BTreeMap::new();
+    collect_fields!(
+        metrics,
+        fields,
+        [
+            get_temperature_edge,
+            get_temperature_hotspot,
+            get_temperature_mem,
+            get_temperature_vrgfx,
+            get_temperature_vrsoc,
+            get_temperature_vrmem,
+            get_temperature_gfx,
+            get_temperature_soc,
+            get_temperature_core,
+            get_temperature_l3,
+            get_temperature_hbm,
+            get_average_temperature_gfx,
+            get_average_temperature_soc,
+            get_average_temperature_core,
+            get_average_temperature_l3,
+            get_average_gfx_activity,
+            get_average_umc_activity,
+            get_average_mm_activity,
+            get_gfx_activity_acc,
+            get_mem_activity_acc,
+            get_system_clock_counter,
+            get_average_socket_power,
+            get_average_cpu_power,
+            get_average_soc_power,
+            get_average_gfx_power,
+            get_average_core_power,
+            get_average_gfxclk_frequency,
+            get_average_socclk_frequency,
+            get_average_uclk_frequency,
+            get_average_fclk_frequency,
+            get_average_vclk_frequency,
+            get_average_dclk_frequency,
+            get_average_vclk1_frequency,
+            get_average_dclk1_frequency,
+            get_current_gfxclk,
+            get_current_socclk,
+            get_current_uclk,
+            get_current_fclk,
+            get_current_vclk,
+            get_current_dclk,
+            get_current_vclk1,
+            get_current_dclk1,
+            get_current_coreclk,
+            get_current_l3clk,
+            get_throttle_status,
+            get_indep_throttle_status,
+            get_current_fan_speed,
+            get_fan_pwm,
+            get_pcie_link_width,
+            get_pcie_link_spped as "pcie_link_speed",
+            get_voltage_soc,
+            get_voltage_gfx,
+            get_voltage_mem,
+            get_average_cpu_voltage,
+            get_average_soc_voltage,
+            get_average_gfx_voltage,
+            get_average_cpu_current,
+            get_average_soc_current,
+            get_average_gfx_current,
+        ]
+    );
+
+    Ok(SmuMetrics {
+        format_revision: header.format_revision,
+        content_revision: header.content_revision,
+        fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use serde_json::json;
+
+    #[test]
+    fn parse_v1_3() {
+        // Unsupported fields are filled with ones
+        let mut table = vec![0xFF; 128];
+        table[0..2].copy_from_slice(&128u16.to_le_bytes());
+        table[2] = 1;
+        table[3] = 3;
+        // temperature_edge and temperature_hotspot
+        table[4..6].copy_from_slice(&45u16.to_le_bytes());
+        table[6..8].copy_from_slice(&60u16.to_le_bytes());
+
+        let metrics = parse(&table).unwrap();
+        assert_eq!((metrics.format_revision, metrics.content_revision), (1, 3));
+        assert_eq!(metrics.fields["temperature_edge"], json!(45));
+        assert_eq!(metrics.fields["temperature_hotspot"], json!(60));
+        assert!(!metrics.fields.contains_key("temperature_mem"));
+    }
+
+    #[test]
+    fn unknown_format() {
+        assert!(parse(&[4, 0, 9, 9]).is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use super::get_temps;
+use lact_schema::{
+    amdgpu_sysfs::{gpu_handle::GpuHandle, sysfs::SysFS},
+    RuntimePmStatus,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+const INTERVAL: Duration = Duration::from_secs(1);
+/// Number of samples kept, which makes the trend cover the last 10 seconds
+const WINDOW_SAMPLES: usize = 11;
+
+pub type TempSamples = Rc<RefCell<VecDeque<(Instant, HashMap<String, f32>)>>>;
+
+/// Rate of change of each sensor in °C per second, between the oldest and the newest sample in the window
+pub fn trend(samples: &VecDeque<(Instant, HashMap<String, f32>)>) -> HashMap<String, f32> {
+    let (Some((first_at, first)), Some((last_at, last))) = (samples.front(), samples.back()) else {
+        return HashMap::new();
+    };
+    let elapsed = last_at.duration_since(*first_at);
+    if elapsed.is_zero() {
+        return HashMap::new();
+    }
+    let elapsed = elapsed.as_secs_f32();
+
+    last.iter()
+        .filter_map(|(sensor, current)| {
+            let previous = first.get(sensor)?;
+            Some((sensor.clone(), (current - previous) / elapsed))
+        })
+        .collect()
+}
+
+/// Spawns a task which periodically records the temperatures for computing their trend.
+/// No samples are taken while the GPU is suspended, and the window is reset when it wakes up.
+pub fn spawn(handle: GpuHandle, samples: TempSamples) {
+    tokio::task::spawn_local(async move {
+        loop {
+            sleep(INTERVAL).await;
+
+            let Some(hw_mon) = handle.hw_monitors.first() else {
+                break;
+            };
+
+            let runtime_status = handle
+                .read_file("power/runtime_status")
+                .ok()
+                .and_then(|status| status.parse().ok());
+            if matches!(
+                runtime_status,
+                Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending)
+            ) {
+                samples.borrow_mut().clear();
+                continue;
+            }
+
+            let temps = get_temps(hw_mon)
+                .into_iter()
+                .filter_map(|(sensor, temp)| Some((sensor, temp.current?)))
+                .collect();
+
+            let mut samples = samples.borrow_mut();
+            if samples.len() == WINDOW_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back((Instant::now(), temps));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::trend;
+    use std::{
+        collections::{HashMap, VecDeque},
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn rising_temperature() {
+        let start = Instant::now();
+        let samples = VecDeque::from([
+            (
+                start,
+                HashMap::from([("edge".to_owned(), 50.0), ("junction".to_owned(), 60.0)]),
+            ),
+            (
+                start + Duration::from_secs(5),
+                HashMap::from([("edge".to_owned(), 55.0), ("junction".to_owned(), 70.0)]),
+            ),
+            (
+                start + Duration::from_secs(10),
+                HashMap::from([("edge".to_owned(), 60.0), ("junction".to_owned(), 65.0)]),
+            ),
+        ]);
+
+        assert_eq!(
+            trend(&samples),
+            HashMap::from([("edge".to_owned(), 1.0), ("junction".to_owned(), 0.5)])
+        );
+    }
+
+    #[test]
+    fn single_sample() {
+        let samples =
+            VecDeque::from([(Instant::now(), HashMap::from([("edge".to_owned(), 50.0)]))]);
+        assert!(trend(&samples).is_empty());
+    }
+}
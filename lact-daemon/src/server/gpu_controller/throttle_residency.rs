@@ -0,0 +1,100 @@
+use super::gpu_metrics;
+use lact_schema::{
+    amdgpu_sysfs::{gpu_handle::GpuHandle, sysfs::SysFS},
+    RuntimePmStatus, ThrottleReason,
+};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fs,
+    rc::Rc,
+    time::Duration,
+};
+use tokio::time::sleep;
+use tracing::debug;
+
+const INTERVAL: Duration = Duration::from_millis(500);
+/// Number of samples kept, which makes the residency cover the last minute
+const WINDOW_SAMPLES: usize = 120;
+
+pub type ThrottleSamples = Rc<RefCell<VecDeque<Vec<ThrottleReason>>>>;
+
+/// Fraction of samples in which each throttle reason was active
+#[allow(clippy::cast_precision_loss)]
+pub fn residency(samples: &VecDeque<Vec<ThrottleReason>>) -> HashMap<ThrottleReason, f32> {
+    let mut counts: HashMap<ThrottleReason, usize> = HashMap::new();
+    for reason in samples.iter().flatten() {
+        *counts.entry(*reason).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|(reason, count)| (reason, count as f32 / samples.len() as f32))
+        .collect()
+}
+
+/// Spawns a task which periodically records the active throttle reasons from `gpu_metrics`.
+/// No samples are taken while the GPU is suspended, as reading the metrics would wake it up.
+pub fn spawn(handle: GpuHandle, samples: ThrottleSamples) {
+    tokio::task::spawn_local(async move {
+        let metrics_path = handle.get_path().join("gpu_metrics");
+
+        loop {
+            sleep(INTERVAL).await;
+
+            let runtime_status = handle
+                .read_file("power/runtime_status")
+                .ok()
+                .and_then(|status| status.parse().ok());
+            if matches!(
+                runtime_status,
+                Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending)
+            ) {
+                continue;
+            }
+
+            let Some(reasons) = fs::read(&metrics_path)
+                .ok()
+                .and_then(|metrics| gpu_metrics::throttle_reasons(&metrics))
+            else {
+                debug!("throttle status is not available, stopping throttle residency sampling");
+                break;
+            };
+
+            let mut samples = samples.borrow_mut();
+            if samples.len() == WINDOW_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back(reasons);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::residency;
+    use lact_schema::ThrottleReason;
+    use std::collections::{HashMap, VecDeque};
+
+    #[test]
+    fn residency_fractions() {
+        let samples = VecDeque::from([
+            vec![ThrottleReason::Power],
+            vec![],
+            vec![ThrottleReason::Power, ThrottleReason::Temperature],
+            vec![],
+        ]);
+        assert_eq!(
+            residency(&samples),
+            HashMap::from([
+                (ThrottleReason::Power, 0.5),
+                (ThrottleReason::Temperature, 0.25)
+            ])
+        );
+    }
+
+    #[test]
+    fn no_samples() {
+        assert!(residency(&VecDeque::new()).is_empty());
+    }
+}
@@ -1,26 +1,95 @@
-use super::gpu_controller::{fan_control::FanCurve, GpuController};
-use crate::config::{self, default_fan_static_speed, Config, FanControlSettings};
+use super::{
+    baseline,
+    fleet::{self, GpuSample},
+    gpu_controller::{clocks_ramp, fan_control::FanCurve, processes, GpuController},
+    presets::{self, Preset},
+    self_test,
+    settings_history::SettingsHistory,
+    system, system_power,
+    tuning::{self, ClockLimits},
+    warnings,
+};
+use crate::{
+    config::{self, default_fan_static_speed, Config, FanControlSettings},
+    schedule,
+};
 use anyhow::{anyhow, Context};
 use lact_schema::{
-    amdgpu_sysfs::gpu_handle::{
-        power_profile_mode::PowerProfileModesTable, PerformanceLevel, PowerLevelKind,
+    amdgpu_sysfs::{
+        gpu_handle::{
+            power_profile_mode::PowerProfileModesTable, PerformanceLevel, PowerLevelKind,
+        },
+        hw_mon::HwMon,
     },
     default_fan_curve,
+    diff::diff_json,
     request::{ConfirmCommand, SetClocksCommand},
-    ClocksInfo, DeviceInfo, DeviceListEntry, DeviceStats, FanControlMode, FanCurveMap, PowerStates,
+    AdaptiveClocks, AppliedChange, ApplyStats, AspmInfo, AspmPolicy, AudioDeviceInfo, ClockDomain,
+    ClocksInfo, ClocksRampStatus, ConfigReloadSummary, Control, ControlStatus, ControllableNode,
+    DaemonEvent, DayTime, DefaultFanCurve, DeviceInfo, DeviceListEntry, DeviceMatch,
+    DeviceSnapshot, DeviceStats, EffectiveConfig, FanControlMode, FanCurveMap, FanDecision,
+    FleetSummary, GpuProcess, InterconnectInfo, PendingConfigStatus, PowerProfileModesDetails,
+    PowerSource, PowerSourceProfiles, PowerStates, PowerTier, PowerTiers, PpFeature, PresetInfo,
+    ProfileLoadResult, QuickStats, ReadFailureCount, ResetMethod, ResetMethods, RetiredPage,
+    RunMode, RuntimePmStatus, Schedule, ScheduleStatus, SelfTestReport, SettingChange,
+    SettingSource, SettingsDiff, SmuMetrics, SystemInfo, SystemPower, TemperatureUnit, TimeRange,
+    TotalPower, TuningSuggestions, VoltageCurve,
+};
+use nix::{
+    sys::signal::{kill, Signal},
+    unistd::{getpid, Pid},
+};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{broadcast, oneshot},
+    time::sleep,
 };
-use std::{cell::RefCell, collections::BTreeMap, env, path::PathBuf, rc::Rc, time::Duration};
-use tokio::{sync::oneshot, time::sleep};
 use tracing::{debug, error, info, trace, warn};
 
 const CONTROLLERS_LOAD_RETRY_ATTEMPTS: u8 = 5;
 const CONTROLLERS_LOAD_RETRY_INTERVAL: u64 = 1;
+const PASSTHROUGH_DRIVERS_PATHS: [&str; 2] = [
+    "/sys/bus/pci/drivers/vfio-pci",
+    "/sys/bus/pci/drivers/pci-stub",
+];
+const FAN_CURVE_OFFSET_RANGE: RangeInclusive<i32> = -30..=30;
+/// Fan control should not be left to the firmware indefinitely by accident
+const MAX_FAN_CONTROL_SUSPEND_SECS: u64 = 60 * 60;
+/// The simulated temperature is reset after this time, so the fan can't be left on a fake temperature by accident
+const SIMULATED_TEMPERATURE_TIMEOUT: Duration = Duration::from_mins(5);
+const MAX_CLOCKS_RAMP_INTERVAL_SECS: u64 = 60;
+/// Events are dropped for subscribers which fall further behind than this
+const EVENTS_CAPACITY: usize = 64;
+
+struct PendingConfig {
+    id: String,
+    revert_at: Instant,
+    changed_fields: Vec<String>,
+}
 
 #[derive(Clone)]
 pub struct Handler {
     pub config: Rc<RefCell<Config>>,
     pub gpu_controllers: Rc<BTreeMap<String, GpuController>>,
     confirm_config_tx: Rc<RefCell<Option<oneshot::Sender<ConfirmCommand>>>>,
+    /// Details of the last change which had to be confirmed, only pending while `confirm_config_tx` is set
+    pending_config: Rc<RefCell<Option<PendingConfig>>>,
+    /// Profile which is currently applied by a schedule or for the power source
+    active_profile: Rc<RefCell<Option<String>>>,
+    power_source: Rc<Cell<Option<PowerSource>>>,
+    started_at: Instant,
+    apply_stats: Rc<RefCell<ApplyStats>>,
+    settings_history: Rc<RefCell<SettingsHistory>>,
+    config_generation: Rc<Cell<u64>>,
+    events: broadcast::Sender<DaemonEvent>,
 }
 
 impl<'a> Handler {
@@ -40,24 +109,106 @@ impl<'a> Handler {
             }
         }
         info!("initialized {} GPUs", controllers.len());
+        for controller in controllers.values().filter(|c| c.is_controllable()) {
+            controller.start_sampling();
+        }
+
+        let hw_mons: BTreeMap<String, Vec<HwMon>> = controllers
+            .iter()
+            .map(|(id, controller)| (id.clone(), controller.temp_monitors()))
+            .filter(|(_, hw_mons)| !hw_mons.is_empty())
+            .collect();
+        for (id, controller) in &controllers {
+            let mut peer_hw_mons = hw_mons.clone();
+            peer_hw_mons.remove(id);
+            controller.set_peer_hw_mons(peer_hw_mons);
+        }
+
+        let handler = Self::with_controllers(controllers, config);
+        handler.record_baselines();
+        handler.load_config(&SettingSource::Startup).await;
+
+        Ok(handler)
+    }
+
+    /// A handler without any GPUs, for testing the requests which don't depend on the hardware
+    #[cfg(test)]
+    pub fn without_gpus(config: Config) -> Self {
+        Self::with_controllers(BTreeMap::new(), config)
+    }
 
-        let handler = Self {
+    fn with_controllers(controllers: BTreeMap<String, GpuController>, config: Config) -> Self {
+        Self {
             gpu_controllers: Rc::new(controllers),
             config: Rc::new(RefCell::new(config)),
             confirm_config_tx: Rc::new(RefCell::new(None)),
-        };
-        handler.load_config().await;
+            pending_config: Rc::new(RefCell::new(None)),
+            active_profile: Rc::new(RefCell::new(None)),
+            power_source: Rc::new(Cell::new(None)),
+            started_at: Instant::now(),
+            apply_stats: Rc::new(RefCell::new(ApplyStats::default())),
+            settings_history: Rc::new(RefCell::new(SettingsHistory::default())),
+            config_generation: Rc::new(Cell::new(0)),
+            events: broadcast::channel(EVENTS_CAPACITY).0,
+        }
+    }
 
-        Ok(handler)
+    /// Records the hardware state of GPUs which didn't have their baseline recorded yet, before any settings are applied
+    fn record_baselines(&self) {
+        if system::run_mode() == RunMode::User {
+            return;
+        }
+
+        let current = self
+            .gpu_controllers
+            .iter()
+            .filter(|(_, controller)| controller.is_controllable())
+            .filter_map(|(id, controller)| {
+                let configured = self.current_gpu_config(id).ok().flatten();
+                let baseline = controller.get_baseline_settings(configured.as_ref());
+                if baseline.is_none() {
+                    debug!("not recording the hardware baseline of gpu {id}, as its settings may already be applied");
+                }
+                Some((id.clone(), baseline?))
+            });
+        if let Err(err) = baseline::record_missing(&config::baselines_path(), current) {
+            error!("could not record the hardware baselines: {err:#}");
+        }
     }
 
-    pub async fn load_config(&self) {
-        let config = self.config.borrow().clone(); // Clone to avoid locking the RwLock on an await point
+    pub async fn load_config(&self, source: &SettingSource) {
+        if system::run_mode() == RunMode::User {
+            info!("running in user mode, not applying the GPU settings");
+            return;
+        }
+
+        // Clone to avoid locking the RwLock on an await point
+        let (gpus, unmanaged_gpus) = {
+            let config = self.config.borrow();
+            let gpus = config.gpus_with_profile(self.active_profile.borrow().as_deref());
+            (gpus, config.unmanaged_gpus.clone())
+        };
+
+        for (id, gpu_config) in &gpus {
+            if unmanaged_gpus.contains(id) {
+                debug!("skipping unmanaged gpu {id}");
+            } else if let Some(controller) = self.gpu_controllers.get(id) {
+                if !controller.is_controllable() {
+                    info!(
+                        "not applying config for gpu {id}, it is bound to {}",
+                        controller.get_driver()
+                    );
+                    continue;
+                }
 
-        for (id, gpu_config) in &config.gpus {
-            if let Some(controller) = self.gpu_controllers.get(id) {
-                if let Err(err) = controller.apply_config(gpu_config).await {
-                    error!("could not apply existing config for gpu {id}: {err}");
+                let result = controller.apply_config(gpu_config).await;
+                self.record_apply_result(&result);
+                match result {
+                    Ok(()) => self
+                        .settings_history
+                        .borrow_mut()
+                        .record(id, gpu_config, source),
+                    Err(err) => error!("could not apply existing config for gpu {id}: {err}"),
                 }
             } else {
                 info!("could not find GPU with id {id} defined in configuration");
@@ -70,22 +221,31 @@ impl<'a> Handler {
         id: String,
         f: F,
     ) -> anyhow::Result<u64> {
-        if self
-            .confirm_config_tx
-            .try_borrow_mut()
-            .map_err(|err| anyhow!("{err}"))?
-            .is_some()
-        {
-            return Err(anyhow!(
-                "There is an unconfirmed configuration change pending"
-            ));
-        }
+        self.check_no_pending_change()?;
+
+        self.check_managed(&id)?;
 
-        let (gpu_config, apply_timer) = {
+        let (gpu_config, apply_timer, profile) = {
             let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
             let apply_timer = config.apply_settings_timer;
-            let gpu_config = config.gpus.get(&id).cloned().unwrap_or_default();
-            (gpu_config, apply_timer)
+
+            // When the active profile has settings for this GPU, they get edited instead of the base settings
+            let profile = self
+                .active_profile
+                .try_borrow()
+                .map_err(|err| anyhow!("{err}"))?
+                .clone()
+                .filter(|name| {
+                    config
+                        .profiles
+                        .get(name)
+                        .is_some_and(|profile| profile.gpus.contains_key(&id))
+                });
+            let gpu_config = match &profile {
+                Some(name) => config.profiles[name].gpus[&id].clone(),
+                None => config.gpus.get(&id).cloned().unwrap_or_default(),
+            };
+            (gpu_config, apply_timer, profile)
         };
 
         let mut new_config = gpu_config.clone();
@@ -93,9 +253,11 @@ impl<'a> Handler {
 
         let controller = self.controller_by_id(&id)?;
 
-        match controller.apply_config(&new_config).await {
+        let result = controller.apply_config(&new_config).await;
+        self.record_apply_result(&result);
+        match result {
             Ok(()) => {
-                self.wait_config_confirm(id, gpu_config, new_config, apply_timer)?;
+                self.wait_config_confirm(id, profile, gpu_config, new_config, apply_timer)?;
                 Ok(apply_timer)
             }
             Err(apply_err) => {
@@ -112,10 +274,25 @@ impl<'a> Handler {
     fn wait_config_confirm(
         &self,
         id: String,
+        profile: Option<String>,
         previous_config: config::Gpu,
         new_config: config::Gpu,
         apply_timer: u64,
     ) -> anyhow::Result<()> {
+        let changed_fields = config::diff_gpu_settings(&previous_config, &new_config)?
+            .into_iter()
+            .map(|diff| diff.field)
+            .collect();
+        let revert_at = Instant::now() + Duration::from_secs(apply_timer);
+        *self
+            .pending_config
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("{err}"))? = Some(PendingConfig {
+            id: id.clone(),
+            revert_at,
+            changed_fields,
+        });
+
         let (tx, rx) = oneshot::channel();
         *self
             .confirm_config_tx
@@ -133,17 +310,41 @@ impl<'a> Handler {
                 () = tokio::time::sleep(Duration::from_secs(apply_timer)) => {
                     info!("no confirmation received, reverting settings");
 
+                    // The sender is only left in place when the change was not confirmed or reverted in time.
+                    // Otherwise it was already taken, and a newer change may be pending by now.
+                    match handler.confirm_config_tx.try_borrow_mut() {
+                        Ok(mut guard) => *guard = None,
+                        Err(err) => error!("{err}"),
+                    }
+
                     if let Err(err) = controller.apply_config(&previous_config).await {
                         error!("could not revert settings: {err:#}");
                     }
+                    handler.bump_config_generation();
                 }
                 result = rx => {
                     match result {
                         Ok(ConfirmCommand::Confirm) => {
                             info!("saving updated config");
+                            handler
+                                .settings_history
+                                .borrow_mut()
+                                .record(&id, &new_config, &SettingSource::Manual);
 
                             let mut config_guard = handler.config.borrow_mut();
-                            config_guard.gpus.insert(id, new_config);
+                            match profile {
+                                Some(profile) => {
+                                    config_guard
+                                        .profiles
+                                        .entry(profile)
+                                        .or_default()
+                                        .gpus
+                                        .insert(id, new_config);
+                                }
+                                None => {
+                                    config_guard.gpus.insert(id, new_config);
+                                }
+                            }
 
                             if let Err(err) = config_guard.save() {
                                 error!("{err}");
@@ -153,20 +354,94 @@ impl<'a> Handler {
                             if let Err(err) = controller.apply_config(&previous_config).await {
                                 error!("could not revert settings: {err:#}");
                             }
+                            handler.bump_config_generation();
                         }
                     }
                 }
             }
-
-            match handler.confirm_config_tx.try_borrow_mut() {
-                Ok(mut guard) => *guard = None,
-                Err(err) => error!("{err}"),
-            }
         });
 
         Ok(())
     }
 
+    /// A clocks ramp counts as a pending change for its whole length, as its last step is applied as one
+    fn check_no_pending_change(&self) -> anyhow::Result<()> {
+        if let Some(id) = self
+            .gpu_controllers
+            .iter()
+            .find(|(_, controller)| controller.clocks_ramp_status.get().is_some())
+            .map(|(id, _)| id)
+        {
+            return Err(anyhow!(
+                "The clocks of GPU {id} are being ramped, settings can be changed after the ramp finished"
+            ));
+        }
+        if self
+            .confirm_config_tx
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .is_some()
+        {
+            return Err(anyhow!(
+                "There is an unconfirmed configuration change pending"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Currently applied settings of a GPU, taking the active profile into account
+    fn current_gpu_config(&self, id: &str) -> anyhow::Result<Option<config::Gpu>> {
+        let config = self
+            .config
+            .try_borrow()
+            .map_err(|err| anyhow!("Could not read config: {err:?}"))?;
+        let active_profile = self
+            .active_profile
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?;
+
+        let profile_config = active_profile
+            .as_ref()
+            .and_then(|name| config.profiles.get(name))
+            .and_then(|profile| profile.gpus.get(id));
+        Ok(profile_config.or_else(|| config.gpus.get(id)).cloned())
+    }
+
+    fn bump_config_generation(&self) -> u64 {
+        let generation = self.config_generation.get() + 1;
+        self.config_generation.set(generation);
+        self.send_event(DaemonEvent::ConfigChanged { generation });
+        generation
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<DaemonEvent> {
+        self.events.subscribe()
+    }
+
+    fn send_event(&self, event: DaemonEvent) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.events.send(event);
+    }
+
+    /// Marks a change of the settings made by a request
+    pub fn applied_change(
+        &self,
+        confirm_timeout: Option<u64>,
+        effective: Option<serde_json::Value>,
+    ) -> AppliedChange {
+        AppliedChange {
+            generation: self.bump_config_generation(),
+            confirm_timeout,
+            effective,
+        }
+    }
+
+    /// The power cap used by the driver, which is rounded to whole watts
+    pub fn effective_power_cap(&self, id: &str) -> Option<serde_json::Value> {
+        let cap = self.controller_by_id(id).ok()?.get_power_cap_microwatts()?;
+        Some(cap.into())
+    }
+
     fn controller_by_id(&self, id: &str) -> anyhow::Result<&GpuController> {
         Ok(self
             .gpu_controllers
@@ -175,6 +450,99 @@ impl<'a> Handler {
             .context("No controller with such id")?)
     }
 
+    pub fn get_total_power(&self) -> TotalPower {
+        let gpus: BTreeMap<String, f64> = self
+            .gpu_controllers
+            .iter()
+            .filter_map(|(id, controller)| {
+                // Reading sensors on a suspended GPU would wake it up
+                let power = match controller.get_runtime_pm_status() {
+                    Some(RuntimePmStatus::Suspended | RuntimePmStatus::Suspending) => 0.0,
+                    _ => controller.get_power_draw()?,
+                };
+                Some((id.clone(), power))
+            })
+            .collect();
+
+        TotalPower {
+            total: gpus.values().sum(),
+            gpus,
+        }
+    }
+
+    pub async fn get_system_power(&self) -> SystemPower {
+        SystemPower {
+            cpu_package: system_power::cpu_package_power().await,
+            system: system_power::platform_power(),
+            gpus: self.get_total_power().total,
+        }
+    }
+
+    pub fn get_fleet_summary(&'a self) -> FleetSummary {
+        let gpus = self
+            .gpu_controllers
+            .iter()
+            .map(|(id, controller)| {
+                let sample = match self.get_gpu_stats(id) {
+                    Ok(stats) => Some(GpuSample {
+                        stats,
+                        retired_pages: controller
+                            .get_retired_pages()
+                            .map_or(0, |pages| pages.len()),
+                    }),
+                    Err(err) => {
+                        warn!("could not get stats of gpu {id}: {err:#}");
+                        None
+                    }
+                };
+                (id.clone(), sample)
+            })
+            .collect();
+        fleet::summarize(gpus)
+    }
+
+    pub fn self_test(&self) -> SelfTestReport {
+        let gpus = self
+            .gpu_controllers
+            .iter()
+            .map(|(id, controller)| (id.clone(), self_test::check_gpu(controller)))
+            .collect();
+        self_test::report(gpus)
+    }
+
+    pub fn explain_control(&self, id: &str, control: Control) -> anyhow::Result<ControlStatus> {
+        let controller = self.controller_by_id(id)?;
+        let managed = self
+            .config
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .is_managed(id);
+        Ok(self_test::explain_control(controller, control, managed))
+    }
+
+    /// Changing the settings of a GPU that can't be controlled would only fail with confusing sysfs errors
+    pub fn check_controllable(&self, id: &str) -> anyhow::Result<()> {
+        check_privileged()?;
+        match self.gpu_controllers.get(id) {
+            Some(controller) if !controller.is_controllable() => Err(anyhow!(
+                "GPU {id} is bound to {} and can't be controlled",
+                controller.get_driver()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// GPUs which were excluded from management can't have their settings changed
+    fn check_managed(&self, id: &str) -> anyhow::Result<()> {
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        if !config.is_managed(id) {
+            return Err(anyhow!(
+                "GPU {id} is not managed by LACT, management has to be enabled before changing its settings"
+            ));
+        }
+        Ok(())
+    }
+
     pub fn list_devices(&'a self) -> Vec<DeviceListEntry<'a>> {
         self.gpu_controllers
             .iter()
@@ -183,7 +551,36 @@ impl<'a> Handler {
                     .pci_info
                     .as_ref()
                     .and_then(|pci_info| pci_info.device_pci_info.model.as_deref());
-                DeviceListEntry { id, name }
+                let runtime_pm_status = controller.get_runtime_pm_status();
+                // Reading sensors on a suspended GPU would wake it up
+                let temperature = match runtime_pm_status {
+                    Some(RuntimePmStatus::Active | RuntimePmStatus::Unsupported) | None => {
+                        controller.get_current_temperature()
+                    }
+                    Some(_) => None,
+                };
+                let (managed, label) = {
+                    let config = self.config.borrow();
+                    (config.is_managed(id), config.labels.get(id).cloned())
+                };
+                let configured = self
+                    .current_gpu_config(id)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|gpu_config| gpu_config != config::Gpu::default());
+
+                DeviceListEntry {
+                    id,
+                    name,
+                    label,
+                    runtime_pm_status,
+                    temperature,
+                    managed,
+                    driver: controller.get_driver(),
+                    controllable: controller.is_controllable(),
+                    configured,
+                    boot_vga: controller.is_boot_vga(),
+                }
             })
             .collect()
     }
@@ -192,19 +589,233 @@ impl<'a> Handler {
         Ok(self.controller_by_id(id)?.get_info())
     }
 
+    pub fn get_system_info(&self) -> anyhow::Result<SystemInfo<'static>> {
+        let apply_stats = self
+            .apply_stats
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .clone();
+        let (groups, temperature_unit) = {
+            let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+            (config.groups.clone(), config.daemon.temperature_unit)
+        };
+        system::info(
+            self.schedule_status()?,
+            self.started_at.elapsed().as_secs(),
+            apply_stats,
+            groups,
+            self.config_generation.get(),
+            self.power_source.get(),
+            temperature_unit,
+        )
+    }
+
+    fn record_apply_result(&self, result: &anyhow::Result<()>) {
+        let mut stats = self.apply_stats.borrow_mut();
+        match result {
+            Ok(()) => stats.successful += 1,
+            Err(err) => {
+                stats.failed += 1;
+                stats.last_error = Some(format!("{err:#}"));
+            }
+        }
+    }
+
     pub fn get_gpu_stats(&'a self, id: &str) -> anyhow::Result<DeviceStats> {
-        let config = self
+        let gpu_config = self.current_gpu_config(id)?;
+        Ok(self
+            .controller_by_id(id)?
+            .get_stats_limited(gpu_config.as_ref(), self.min_stats_interval()))
+    }
+
+    fn min_stats_interval(&self) -> Duration {
+        let interval_ms = self
             .config
             .try_borrow()
-            .map_err(|err| anyhow!("Could not read config: {err:?}"))?;
-        let gpu_config = config.gpus.get(id);
-        Ok(self.controller_by_id(id)?.get_stats(gpu_config))
+            .map_or(0, |config| config.daemon.min_stats_interval_ms);
+        Duration::from_millis(interval_ms)
+    }
+
+    fn auto_manual_performance_level(&self) -> bool {
+        self.config
+            .try_borrow()
+            .is_ok_and(|config| config.daemon.auto_manual_performance_level)
+    }
+
+    /// The mode is a module option of amdgpu, so it applies to every GPU using the driver
+    pub fn set_ecc_mode(&'a self, id: &str, enabled: bool) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.check_managed(id)?;
+        let ecc = self
+            .controller_by_id(id)?
+            .get_ecc_info()
+            .context("ECC is not supported on this GPU")?;
+        if ecc.enabled == enabled && ecc.pending.is_none() {
+            let state = if enabled { "enabled" } else { "disabled" };
+            return Err(anyhow!("ECC is already {state}"));
+        }
+        system::set_ecc_mode(enabled)?;
+        warnings::add("The ECC mode will be applied after a reboot");
+        let amdgpu_count = self
+            .gpu_controllers
+            .values()
+            .filter(|controller| controller.get_driver() == "amdgpu")
+            .count();
+        if amdgpu_count > 1 {
+            warnings::add(format!(
+                "The ECC mode is set for the amdgpu driver, so it applies to all {amdgpu_count} AMD GPUs"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn get_aspm_info(&self, id: &str) -> anyhow::Result<AspmInfo> {
+        Ok(self.controller_by_id(id)?.get_aspm_info())
+    }
+
+    /// The policy is system-wide, the GPU is only checked for being allowed to change it
+    pub fn set_aspm_policy(&self, id: &str, policy: AspmPolicy) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.check_managed(id)?;
+        system::set_aspm_policy(policy)?;
+        info!("set ASPM policy to {policy:?}");
+        Ok(())
+    }
+
+    pub fn get_effective_config(&self, id: &str) -> anyhow::Result<EffectiveConfig> {
+        let controller = self.controller_by_id(id)?;
+        let requested = self.current_gpu_config(id)?.unwrap_or_default();
+        let effective = controller.get_effective_config(&requested);
+
+        let requested = serde_json::to_value(requested)?;
+        let effective = serde_json::to_value(effective)?;
+        Ok(EffectiveConfig {
+            diff: diff_json(&requested, &effective),
+            requested,
+            effective,
+        })
+    }
+
+    pub fn get_tuning_suggestions(&'a self, id: &str) -> anyhow::Result<TuningSuggestions> {
+        let controller = self.controller_by_id(id)?;
+        let current = self.current_gpu_config(id)?.unwrap_or_default();
+        let stats = self.get_gpu_stats(id)?;
+        let clocks = controller
+            .handle
+            .get_clocks_table()
+            .map(|table| ClockLimits::from_table(&table))
+            .unwrap_or_default();
+
+        let (settings, notes) = tuning::suggest(&current, clocks, &stats);
+        Ok(TuningSuggestions {
+            settings: serde_json::to_value(settings)?,
+            notes,
+        })
+    }
+
+    pub fn get_interconnect(&self, id: &str) -> anyhow::Result<InterconnectInfo> {
+        self.controller_by_id(id)?.get_interconnect()
+    }
+
+    pub fn get_audio_device(&self, id: &str) -> anyhow::Result<AudioDeviceInfo> {
+        self.controller_by_id(id)?.get_audio_device()
+    }
+
+    pub fn set_audio_pinned_active(&self, id: &str, pinned: bool) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.check_managed(id)?;
+        self.controller_by_id(id)?.set_audio_pinned_active(pinned)?;
+        info!("set audio device of {id} to pinned active: {pinned}");
+        Ok(())
+    }
+
+    pub fn get_retired_pages(&self, id: &str) -> anyhow::Result<Vec<RetiredPage>> {
+        self.controller_by_id(id)?.get_retired_pages()
+    }
+
+    pub fn clear_retired_pages(&self, id: &str, count: usize) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.check_managed(id)?;
+        let controller = self.controller_by_id(id)?;
+        let current_count = controller.get_retired_pages()?.len();
+        if current_count != count {
+            return Err(anyhow!(
+                "The GPU has {current_count} retired pages instead of the expected {count}, not clearing them"
+            ));
+        }
+        controller.clear_retired_pages()?;
+        info!("cleared {count} retired pages of GPU {id}");
+        Ok(())
+    }
+
+    pub fn get_reset_methods(&self, id: &str) -> anyhow::Result<ResetMethods> {
+        Ok(self.controller_by_id(id)?.get_reset_methods())
+    }
+
+    pub fn reset_gpu(&self, id: &str, method: ResetMethod, confirm: bool) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.check_managed(id)?;
+        let controller = self.controller_by_id(id)?;
+        if controller.is_boot_vga() {
+            return Err(anyhow!(
+                "GPU {id} was used for the display on boot, and can't be reset while the system is running"
+            ));
+        }
+        if !controller.get_reset_methods().available.contains(&method) {
+            return Err(anyhow!(
+                "Resetting GPU {id} with {method:?} is not supported"
+            ));
+        }
+        if !confirm {
+            return Err(anyhow!(
+                "Resetting the GPU stops all applications using it, set `confirm` to reset it anyway"
+            ));
+        }
+
+        warn!("resetting GPU {id} with {method:?}");
+        controller.reset(method)
+    }
+
+    pub fn get_default_fan_curve(&'a self, id: &str) -> anyhow::Result<DefaultFanCurve> {
+        Ok(self.controller_by_id(id)?.get_default_fan_curve())
+    }
+
+    pub fn get_fan_control_log(&'a self, id: &str) -> anyhow::Result<Vec<FanDecision>> {
+        Ok(self.controller_by_id(id)?.get_fan_control_log())
+    }
+
+    pub fn get_read_failures(
+        &'a self,
+        id: &str,
+    ) -> anyhow::Result<BTreeMap<String, ReadFailureCount>> {
+        Ok(self.controller_by_id(id)?.get_read_failures())
+    }
+
+    pub fn get_quick_stats(&'a self, id: &str) -> anyhow::Result<QuickStats> {
+        Ok(self.controller_by_id(id)?.get_quick_stats())
+    }
+
+    pub fn get_device_snapshot(&'a self, id: &str) -> anyhow::Result<DeviceSnapshot<'a>> {
+        let controller = self.controller_by_id(id)?;
+        let gpu_config = self.current_gpu_config(id)?;
+
+        Ok(DeviceSnapshot {
+            info: controller.get_info(),
+            stats: controller.get_stats_limited(gpu_config.as_ref(), self.min_stats_interval()),
+            clocks_info: controller.get_clocks_info().ok(),
+            power_profile_modes: controller.handle.get_power_profile_modes().ok(),
+            power_states: controller.get_power_states(gpu_config.as_ref()),
+        })
     }
 
     pub fn get_clocks_info(&'a self, id: &str) -> anyhow::Result<ClocksInfo> {
         self.controller_by_id(id)?.get_clocks_info()
     }
 
+    pub fn get_voltage_curve(&'a self, id: &str) -> anyhow::Result<VoltageCurve> {
+        self.controller_by_id(id)?.get_voltage_curve()
+    }
+
     pub async fn set_fan_control(
         &'a self,
         id: &str,
@@ -214,11 +825,7 @@ impl<'a> Handler {
         curve: Option<FanCurveMap>,
     ) -> anyhow::Result<u64> {
         let settings = {
-            let mut config_guard = self
-                .config
-                .try_borrow_mut()
-                .map_err(|err| anyhow!("{err}"))?;
-            let gpu_config = config_guard.gpus.entry(id.to_owned()).or_default();
+            let gpu_config = self.current_gpu_config(id)?.unwrap_or_default();
 
             match mode {
                 Some(mode) => match mode {
@@ -276,46 +883,259 @@ impl<'a> Handler {
         .await
     }
 
-    pub async fn set_power_cap(&'a self, id: &str, maybe_cap: Option<f64>) -> anyhow::Result<u64> {
-        self.edit_gpu_config(id.to_owned(), |gpu_config| {
-            gpu_config.power_cap = maybe_cap;
+    /// Sets the temperature sensor used by the fan curve, optionally from a different GPU
+    pub async fn set_fan_temperature_source(
+        &'a self,
+        id: &str,
+        gpu_id: Option<&str>,
+        sensor: &str,
+    ) -> anyhow::Result<u64> {
+        let source_id = gpu_id.filter(|gpu_id| *gpu_id != id);
+        let source_controller = self.controller_by_id(source_id.unwrap_or(id))?;
+        let source_temps = source_controller.get_temps();
+        if !source_temps.contains_key(sensor) {
+            let available: Vec<&str> = source_temps.keys().map(String::as_str).collect();
+            return Err(anyhow!(
+                "Temperature sensor `{sensor}` not found, available sensors: {}",
+                available.join(", ")
+            ));
+        }
+
+        self.edit_gpu_config(id.to_owned(), |config| {
+            let settings = config
+                .fan_control_settings
+                .get_or_insert_with(Default::default);
+            sensor.clone_into(&mut settings.temperature_key);
+            settings.temperature_source = source_id.map(str::to_owned);
         })
         .await
     }
 
-    pub fn get_power_states(&self, id: &str) -> anyhow::Result<PowerStates> {
-        let config = self
-            .config
-            .try_borrow()
-            .map_err(|err| anyhow!("Could not read config: {err:?}"))?;
-        let gpu_config = config.gpus.get(id);
+    pub async fn set_fan_curve_offset(&'a self, id: &str, temp_offset: i32) -> anyhow::Result<u64> {
+        if !FAN_CURVE_OFFSET_RANGE.contains(&temp_offset) {
+            return Err(anyhow!(
+                "Fan curve offset must be between {} and {} degrees",
+                FAN_CURVE_OFFSET_RANGE.start(),
+                FAN_CURVE_OFFSET_RANGE.end()
+            ));
+        }
 
-        let states = self.controller_by_id(id)?.get_power_states(gpu_config);
-        Ok(states)
+        self.edit_gpu_config(id.to_owned(), |config| {
+            config
+                .fan_control_settings
+                .get_or_insert_with(Default::default)
+                .temperature_offset = temp_offset;
+        })
+        .await
     }
 
-    pub async fn set_performance_level(
-        &self,
+    pub async fn set_fan_spindown_curve(
+        &'a self,
         id: &str,
-        level: PerformanceLevel,
+        curve: Option<FanCurveMap>,
     ) -> anyhow::Result<u64> {
-        self.edit_gpu_config(id.to_owned(), |gpu_config| {
-            gpu_config.performance_level = Some(level);
+        let curve = curve.map(FanCurve);
+        if let Some(curve) = &curve {
+            curve.validate()?;
+        }
 
-            if level != PerformanceLevel::Manual {
-                gpu_config.power_states.clear();
-            }
+        self.edit_gpu_config(id.to_owned(), |config| {
+            config
+                .fan_control_settings
+                .get_or_insert_with(Default::default)
+                .spindown_curve = curve;
         })
         .await
     }
 
-    pub async fn set_clocks_value(
-        &self,
+    pub async fn set_power_tier(&'a self, id: &str, tier: PowerTier) -> anyhow::Result<u64> {
+        let tiers = self.get_power_tiers(id)?;
+        let (_, cap) = tiers
+            .tiers
+            .into_iter()
+            .find(|(available_tier, _)| *available_tier == tier)
+            .with_context(|| format!("Power tier {tier:?} is not available on this GPU"))?;
+        self.set_power_cap_microwatts(id, Some(cap)).await
+    }
+
+    pub fn get_power_tiers(&'a self, id: &str) -> anyhow::Result<PowerTiers> {
+        self.controller_by_id(id)?.get_power_tiers()
+    }
+
+    /// Makes the fan curve use the given temperature instead of the sensor reading.
+    /// Each request extends the simulation by `SIMULATED_TEMPERATURE_TIMEOUT`, `None` ends it right away
+    pub fn set_simulated_temperature(&self, id: &str, temp: Option<f32>) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.check_managed(id)?;
+        let allowed = self
+            .config
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .daemon
+            .allow_simulated_temperature;
+        if !allowed {
+            return Err(anyhow!(
+                "Simulating temperatures has to be enabled with `allow_simulated_temperature` in the daemon config"
+            ));
+        }
+        if temp.is_some_and(|temp| !(0.0..=150.0).contains(&temp)) {
+            return Err(anyhow!(
+                "Simulated temperature has to be between 0 and 150°C"
+            ));
+        }
+
+        self.controller_by_id(id)?
+            .set_simulated_temperature(temp, SIMULATED_TEMPERATURE_TIMEOUT);
+        if let Some(temp) = temp {
+            info!("simulating fan curve temperature {temp}°C on gpu {id}");
+        } else {
+            info!("stopped simulating fan curve temperature on gpu {id}");
+        }
+        Ok(())
+    }
+
+    /// Lets the firmware control the fan for the given amount of seconds, then restores the configured fan control.
+    /// Returns once the fan control was restored.
+    pub async fn suspend_fan_control(&'a self, id: &str, duration: u64) -> anyhow::Result<u64> {
+        if !(1..=MAX_FAN_CONTROL_SUSPEND_SECS).contains(&duration) {
+            return Err(anyhow!(
+                "Fan control can be suspended for 1 to {MAX_FAN_CONTROL_SUSPEND_SECS} seconds"
+            ));
+        }
+
+        // Not handled as a group request, as the members would be suspended one after another
+        self.check_controllable(id)?;
+        let controller = self.controller_by_id(id)?;
+        if !self
+            .current_gpu_config(id)?
+            .is_some_and(|config| config.fan_control_enabled)
+        {
+            return Err(anyhow!("Fan control is not enabled on this GPU"));
+        }
+
+        controller.suspend_fan_control().await?;
+        info!("suspended fan control of gpu {id} for {duration}s");
+        sleep(Duration::from_secs(duration)).await;
+
+        // Use the current settings, as they may have been changed in the meantime
+        let config = self.current_gpu_config(id)?.unwrap_or_default();
+        controller
+            .restore_fan_control(&config)
+            .await
+            .context("Could not restore fan control")?;
+        info!("restored fan control of gpu {id}");
+
+        let restored_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|time| u64::try_from(time.as_millis()).ok())
+            .unwrap_or_default();
+        Ok(restored_at)
+    }
+
+    pub async fn set_power_cap_microwatts(
+        &'a self,
+        id: &str,
+        maybe_cap: Option<u64>,
+    ) -> anyhow::Result<u64> {
+        self.edit_gpu_config(id.to_owned(), |gpu_config| {
+            gpu_config.power_cap_microwatts = maybe_cap;
+        })
+        .await
+    }
+
+    pub fn get_power_states(&self, id: &str) -> anyhow::Result<PowerStates> {
+        let gpu_config = self.current_gpu_config(id)?;
+
+        let states = self
+            .controller_by_id(id)?
+            .get_power_states(gpu_config.as_ref());
+        Ok(states)
+    }
+
+    pub fn get_pp_features(&self, id: &str) -> anyhow::Result<Vec<PpFeature>> {
+        self.controller_by_id(id)?.get_pp_features()
+    }
+
+    pub fn set_pp_feature(&self, id: &str, feature: &str, enabled: bool) -> anyhow::Result<()> {
+        let controller = self.controller_by_id(id)?;
+        if !self
+            .config
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .is_managed(id)
+        {
+            return Err(anyhow!("GPU {id} is not managed by LACT"));
+        }
+
+        info!(
+            "{} powerplay feature {feature} on gpu {id}",
+            if enabled { "enabling" } else { "disabling" }
+        );
+        controller.set_pp_feature(feature, enabled)
+    }
+
+    pub fn get_gpu_processes(&self, id: &str) -> anyhow::Result<Vec<GpuProcess>> {
+        self.controller_by_id(id)?.get_processes()
+    }
+
+    /// Sends SIGTERM to a process. Only processes that have one of the GPUs open can be terminated,
+    /// and only by root or the user who owns them (`caller_uid` is the uid of the connected client).
+    pub fn kill_gpu_process(&self, pid: i32, caller_uid: u32) -> anyhow::Result<()> {
+        if pid <= 1 || pid == getpid().as_raw() {
+            return Err(anyhow!("Refusing to terminate process {pid}"));
+        }
+
+        if !self
+            .gpu_controllers
+            .values()
+            .any(|controller| controller.is_used_by_process(pid))
+        {
+            return Err(anyhow!("Process {pid} is not using any GPU"));
+        }
+        processes::check_can_signal(pid, caller_uid)?;
+
+        info!("terminating process {pid}");
+        kill(Pid::from_raw(pid), Signal::SIGTERM)
+            .with_context(|| format!("Could not terminate process {pid}"))
+    }
+
+    pub fn get_performance_level(&self, id: &str) -> anyhow::Result<PerformanceLevel> {
+        self.controller_by_id(id)?.get_performance_level()
+    }
+
+    pub fn snap_clock(&self, id: &str, domain: ClockDomain, value: i32) -> anyhow::Result<i32> {
+        self.controller_by_id(id)?.snap_clock(domain, value)
+    }
+
+    pub async fn set_performance_level(
+        &self,
+        id: &str,
+        level: PerformanceLevel,
+    ) -> anyhow::Result<u64> {
+        self.edit_gpu_config(id.to_owned(), |gpu_config| {
+            gpu_config.performance_level = Some(level);
+
+            if level != PerformanceLevel::Manual {
+                gpu_config.power_states.clear();
+            }
+        })
+        .await
+    }
+
+    pub async fn set_clocks_value(
+        &self,
         id: &str,
         command: SetClocksCommand,
     ) -> anyhow::Result<u64> {
+        let controller = self.controller_by_id(id)?;
+        if let Some((kind, state)) = command.power_state() {
+            check_power_state(controller, kind, state)?;
+        } else {
+            controller.check_overclock_available()?;
+        }
         if let SetClocksCommand::Reset = command {
-            self.controller_by_id(id)?.handle.reset_clocks_table()?;
+            controller.handle.reset_clocks_table()?;
         }
 
         self.edit_gpu_config(id.to_owned(), |gpu_config| {
@@ -329,6 +1149,14 @@ impl<'a> Handler {
         id: &str,
         commands: Vec<SetClocksCommand>,
     ) -> anyhow::Result<u64> {
+        let controller = self.controller_by_id(id)?;
+        for command in &commands {
+            if let Some((kind, state)) = command.power_state() {
+                check_power_state(controller, kind, state)?;
+            } else {
+                controller.check_overclock_available()?;
+            }
+        }
         self.edit_gpu_config(id.to_owned(), |gpu_config| {
             for command in commands {
                 gpu_config.apply_clocks_command(&command);
@@ -337,6 +1165,208 @@ impl<'a> Handler {
         .await
     }
 
+    /// Steps the clock up or down to the target, and then sets the target like `set_clocks_value`.
+    /// The ramp is not handled as a group request, as the members would be ramped one after another.
+    pub async fn set_clocks_ramped(
+        &self,
+        id: &str,
+        target: SetClocksCommand,
+        step: i32,
+        interval: u64,
+        abort_on_throttling: bool,
+    ) -> anyhow::Result<u64> {
+        if step <= 0 {
+            return Err(anyhow!("Ramp step has to be positive"));
+        }
+        if !(1..=MAX_CLOCKS_RAMP_INTERVAL_SECS).contains(&interval) {
+            return Err(anyhow!(
+                "Ramp interval has to be between 1 and {MAX_CLOCKS_RAMP_INTERVAL_SECS} seconds"
+            ));
+        }
+
+        self.check_controllable(id)?;
+        let controller = self.controller_by_id(id)?;
+        controller.check_overclock_available()?;
+        self.check_no_pending_change()?;
+
+        let gpu_config = self.current_gpu_config(id)?.unwrap_or_default();
+        let clocks_info = controller.get_clocks_info()?;
+        let (start, target_value, domain) = match target {
+            SetClocksCommand::MaxCoreClock(value) => (
+                gpu_config
+                    .clocks_configuration
+                    .max_core_clock
+                    .or(clocks_info.max_sclk),
+                value,
+                ClockDomain::MaxCoreClock,
+            ),
+            SetClocksCommand::MaxMemoryClock(value) => (
+                gpu_config
+                    .clocks_configuration
+                    .max_memory_clock
+                    .or(clocks_info.max_mclk),
+                value,
+                ClockDomain::MaxMemoryClock,
+            ),
+            _ => {
+                return Err(anyhow!(
+                    "Only the maximum core and memory clocks can be ramped"
+                ))
+            }
+        };
+        let start = start.context("Current clockspeed is not available")?;
+        let allowed = controller.snap_clock(domain, target_value)?;
+        if allowed != target_value {
+            return Err(anyhow!(
+                "Target clockspeed is out of range, the closest allowed value is {allowed}MHz"
+            ));
+        }
+        let reset_count = controller.reset_count.get();
+        let mut steps = clocks_ramp::steps(start, target_value, step);
+        // The target itself is applied with a regular pending change
+        steps.pop();
+        info!("ramping clocks of gpu {id} from {start} to {target_value} in {step}MHz steps");
+
+        for value in steps {
+            let mut step_config = gpu_config.clone();
+            step_config.apply_clocks_command(&match domain {
+                ClockDomain::MaxCoreClock => SetClocksCommand::MaxCoreClock(value),
+                _ => SetClocksCommand::MaxMemoryClock(value),
+            });
+            controller.clocks_ramp_status.set(Some(ClocksRampStatus {
+                current: value,
+                target: target_value,
+            }));
+
+            let mut abort_reason = controller
+                .apply_config(&step_config)
+                .await
+                .err()
+                .map(|err| format!("could not be applied: {err:#}"));
+            if abort_reason.is_none() {
+                sleep(Duration::from_secs(interval)).await;
+                abort_reason =
+                    clocks_ramp::check_stability(controller, reset_count, abort_on_throttling);
+            }
+
+            if let Some(reason) = abort_reason {
+                controller.clocks_ramp_status.set(None);
+                warn!("clocks ramp of gpu {id} aborted at {value}MHz, restoring the previous settings");
+                controller
+                    .apply_config(&gpu_config)
+                    .await
+                    .context("Could not restore the previous settings after aborting the ramp")?;
+                self.bump_config_generation();
+                return Err(anyhow!(
+                    "Aborted the ramp, as the {value}MHz step {reason}. The previous settings were restored"
+                ));
+            }
+        }
+
+        controller.clocks_ramp_status.set(None);
+        match self.set_clocks_value(id, target).await {
+            Ok(confirm_timeout) => Ok(confirm_timeout),
+            Err(err) => {
+                warn!("could not set the ramp target of gpu {id}, restoring the previous settings");
+                controller
+                    .apply_config(&gpu_config)
+                    .await
+                    .context("Could not restore the previous settings after the ramp")?;
+                self.bump_config_generation();
+                Err(err)
+            }
+        }
+    }
+
+    pub async fn set_adaptive_clocks(
+        &self,
+        id: &str,
+        adaptive_clocks: Option<AdaptiveClocks>,
+    ) -> anyhow::Result<u64> {
+        if let Some(settings) = &adaptive_clocks {
+            validate_adaptive_clocks(settings)?;
+        }
+
+        self.edit_gpu_config(id.to_owned(), |gpu_config| {
+            gpu_config.adaptive_clocks = adaptive_clocks;
+        })
+        .await
+    }
+
+    /// The list of extra game processes in the config is kept when toggling the boost
+    pub async fn set_memory_clock_boost(&self, id: &str, enabled: bool) -> anyhow::Result<u64> {
+        self.edit_gpu_config(id.to_owned(), |gpu_config| {
+            gpu_config.memory_clock_boost = if enabled {
+                Some(gpu_config.memory_clock_boost.take().unwrap_or_default())
+            } else {
+                None
+            };
+        })
+        .await
+    }
+
+    pub async fn apply_device_config(
+        &self,
+        id: &str,
+        config: serde_json::Value,
+        expected_device: Option<&DeviceMatch>,
+    ) -> anyhow::Result<u64> {
+        if let Some(expected) = expected_device {
+            check_device_match(self.controller_by_id(id)?, expected).with_context(|| {
+                format!("Device mismatch, the settings are not meant for GPU {id}")
+            })?;
+        }
+
+        let new_config: config::Gpu =
+            serde_json::from_value(config).context("Invalid GPU settings")?;
+        if let Some(settings) = &new_config.fan_control_settings {
+            settings.curve.validate().context("Invalid fan curve")?;
+            if let Some(curve) = &settings.spindown_curve {
+                curve.validate().context("Invalid spindown fan curve")?;
+            }
+        }
+        if let Some(settings) = &new_config.adaptive_clocks {
+            validate_adaptive_clocks(settings)?;
+        }
+
+        self.edit_gpu_config(id.to_owned(), |gpu_config| *gpu_config = new_config)
+            .await
+    }
+
+    /// Applies the hardware state from before LACT first managed the GPU, which is confirmed like with `apply_device_config`
+    pub async fn restore_factory_baseline(&self, id: &str) -> anyhow::Result<u64> {
+        self.controller_by_id(id)?;
+        let baseline = baseline::load(&config::baselines_path())?
+            .remove(id)
+            .with_context(|| format!("No hardware baseline was recorded for GPU {id}"))?;
+
+        self.edit_gpu_config(id.to_owned(), |gpu_config| *gpu_config = baseline)
+            .await
+    }
+
+    fn find_presets(&self, id: &str) -> anyhow::Result<Vec<Preset>> {
+        match &self.controller_by_id(id)?.pci_info {
+            Some(pci_info) => presets::list(&config::presets_dir(), pci_info),
+            None => Ok(vec![]),
+        }
+    }
+
+    pub fn list_presets(&self, id: &str) -> anyhow::Result<Vec<PresetInfo>> {
+        let presets = self.find_presets(id)?;
+        Ok(presets.into_iter().map(|preset| preset.info).collect())
+    }
+
+    pub async fn apply_preset(&self, id: &str, name: &str) -> anyhow::Result<u64> {
+        let preset = self
+            .find_presets(id)?
+            .into_iter()
+            .find(|preset| preset.info.name == name)
+            .with_context(|| format!("No preset named '{name}' for GPU {id}"))?;
+        self.apply_device_config(id, preset.settings, None)
+            .await
+            .with_context(|| format!("Could not apply preset {}", preset.info.path))
+    }
+
     pub fn get_power_profile_modes(&self, id: &str) -> anyhow::Result<PowerProfileModesTable> {
         let modes_table = self
             .controller_by_id(id)?
@@ -345,25 +1375,75 @@ impl<'a> Handler {
         Ok(modes_table)
     }
 
+    pub fn get_smu_metrics(&self, id: &str) -> anyhow::Result<SmuMetrics> {
+        self.controller_by_id(id)?.get_smu_metrics()
+    }
+
+    pub fn get_controllable_nodes(&self, id: &str) -> anyhow::Result<Vec<ControllableNode>> {
+        Ok(self.controller_by_id(id)?.get_controllable_nodes())
+    }
+
+    pub fn get_power_profile_mode_details(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<PowerProfileModesDetails> {
+        let controller = self.controller_by_id(id)?;
+        let mut details = controller.get_power_profile_mode_details()?;
+        details.requested = self
+            .current_gpu_config(id)?
+            .and_then(|config| config.power_profile_mode_index);
+        details.performance_level = controller.handle.get_power_force_performance_level().ok();
+        Ok(details)
+    }
+
     pub async fn set_power_profile_mode(
         &self,
         id: &str,
         index: Option<u16>,
     ) -> anyhow::Result<u64> {
+        let auto_manual = self.auto_manual_performance_level();
         self.edit_gpu_config(id.to_owned(), |gpu_config| {
             gpu_config.power_profile_mode_index = index;
+            if auto_manual && gpu_config.ensure_manual_performance_level() {
+                warnings::add("Switched the performance level to manual");
+            }
         })
         .await
     }
 
+    pub async fn set_power_profile_mode_by_name(
+        &self,
+        id: &str,
+        name: &str,
+    ) -> anyhow::Result<u64> {
+        let modes_table = self.get_power_profile_modes(id)?;
+        let index = modes_table
+            .modes
+            .iter()
+            .find(|(_, mode_name)| mode_name.eq_ignore_ascii_case(name))
+            .map(|(index, _)| *index)
+            .with_context(|| {
+                let available: Vec<&str> = modes_table.modes.values().map(String::as_str).collect();
+                format!(
+                    "Power profile mode `{name}` not found, available modes: {}",
+                    available.join(", ")
+                )
+            })?;
+        self.set_power_profile_mode(id, Some(index)).await
+    }
+
     pub async fn set_enabled_power_states(
         &self,
         id: &str,
         kind: PowerLevelKind,
         enabled_states: Vec<u8>,
     ) -> anyhow::Result<u64> {
+        let auto_manual = self.auto_manual_performance_level();
         self.edit_gpu_config(id.to_owned(), |gpu| {
             gpu.power_states.insert(kind, enabled_states);
+            if auto_manual && gpu.ensure_manual_performance_level() {
+                warnings::add("Switched the performance level to manual");
+            }
         })
         .await
     }
@@ -382,6 +1462,615 @@ impl<'a> Handler {
         }
     }
 
+    /// Only one change can be pending at a time, so this reverts it when it's for the given GPU
+    pub fn discard_pending(&self, id: &str) -> anyhow::Result<()> {
+        self.controller_by_id(id)?;
+        let Some(pending) = self.get_pending_config()? else {
+            return Ok(());
+        };
+        if pending.id != id {
+            return Err(anyhow!(
+                "The pending configuration change is for GPU {}",
+                pending.id
+            ));
+        }
+
+        info!("discarding pending changes of {id}");
+        self.confirm_pending_config(ConfirmCommand::Revert)
+    }
+
+    pub fn get_pending_config(&self) -> anyhow::Result<Option<PendingConfigStatus>> {
+        let is_pending = self
+            .confirm_config_tx
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .is_some();
+        if !is_pending {
+            return Ok(None);
+        }
+
+        let pending_config = self
+            .pending_config
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?;
+        Ok(pending_config.as_ref().map(|pending| PendingConfigStatus {
+            id: pending.id.clone(),
+            remaining_ms: u64::try_from(
+                pending
+                    .revert_at
+                    .saturating_duration_since(Instant::now())
+                    .as_millis(),
+            )
+            .unwrap_or(u64::MAX),
+            changed_fields: pending.changed_fields.clone(),
+        }))
+    }
+
+    pub async fn set_managed(&self, id: &str, managed: bool) -> anyhow::Result<()> {
+        let controller = self.controller_by_id(id)?;
+        if self
+            .confirm_config_tx
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .is_some()
+        {
+            return Err(anyhow!(
+                "There is an unconfirmed configuration change pending"
+            ));
+        }
+
+        {
+            let mut config = self
+                .config
+                .try_borrow_mut()
+                .map_err(|err| anyhow!("{err}"))?;
+            if config.is_managed(id) == managed {
+                return Ok(());
+            }
+
+            if managed {
+                config.unmanaged_gpus.remove(id);
+            } else {
+                config.unmanaged_gpus.insert(id.to_owned());
+            }
+            config.save()?;
+        }
+
+        if let Some(gpu_config) = self.current_gpu_config(id)? {
+            if managed {
+                info!("enabled management of gpu {id}, applying settings");
+                let result = controller.apply_config(&gpu_config).await;
+                self.record_apply_result(&result);
+                result?;
+                self.settings_history
+                    .borrow_mut()
+                    .record(id, &gpu_config, &SettingSource::Manual);
+            } else {
+                info!("disabled management of gpu {id}, resetting settings");
+                controller
+                    .apply_config(&config::Gpu::default())
+                    .await
+                    .context("Could not reset settings")?;
+                self.settings_history.borrow_mut().record(
+                    id,
+                    &config::Gpu::default(),
+                    &SettingSource::Manual,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_config(&self) -> anyhow::Result<serde_json::Value> {
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        serde_json::to_value(&*config).context("Could not serialize config")
+    }
+
+    pub async fn set_config(&self, mut config: serde_json::Value) -> anyhow::Result<()> {
+        // Allows importing backups made with an older version
+        config::migrate(&mut config)?;
+        let new_config: Config = serde_json::from_value(config).context("Invalid config")?;
+        new_config.validate()?;
+        self.check_no_pending_change()?;
+        new_config.save()?;
+
+        self.replace_config(new_config, &SettingSource::Import)
+            .await
+    }
+
+    /// Re-reads the config file, applying the settings from it like `set_config`
+    pub async fn reload_config(&self) -> anyhow::Result<ConfigReloadSummary> {
+        self.check_no_pending_change()?;
+        let new_config = Config::load()?.context("The config file does not exist")?;
+        new_config.validate()?;
+
+        let (previous_config, previous_profile) = {
+            let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+            let active_profile = self
+                .active_profile
+                .try_borrow()
+                .map_err(|err| anyhow!("{err}"))?;
+            (config.clone(), active_profile.clone())
+        };
+
+        self.replace_config(new_config, &SettingSource::Reload)
+            .await?;
+        self.bump_config_generation();
+
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        let active_profile = self
+            .active_profile
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?;
+        let summary = config::summarize_changes(
+            &previous_config,
+            previous_profile.as_deref(),
+            &config,
+            active_profile.as_deref(),
+        )?;
+        info!("reloaded the config file: {summary:?}");
+        Ok(summary)
+    }
+
+    /// Replaces the config in memory and applies it, without saving it
+    async fn replace_config(
+        &self,
+        new_config: Config,
+        source: &SettingSource,
+    ) -> anyhow::Result<()> {
+        let previous_gpus = {
+            let mut config = self
+                .config
+                .try_borrow_mut()
+                .map_err(|err| anyhow!("{err}"))?;
+            let mut active_profile = self
+                .active_profile
+                .try_borrow_mut()
+                .map_err(|err| anyhow!("{err}"))?;
+
+            let previous_gpus = config.gpus_with_profile(active_profile.as_deref());
+            *config = new_config;
+            // The matching scheduled profile gets picked again below
+            *active_profile = None;
+            previous_gpus
+        };
+        info!("replaced the configuration");
+
+        // GPUs which are no longer configured go back to the default settings
+        let new_gpus = self.config.borrow().gpus.clone();
+        for id in previous_gpus.keys() {
+            if !new_gpus.contains_key(id) {
+                self.reset_gpu_settings(id, source).await;
+            }
+        }
+
+        self.load_config(source).await;
+        self.apply_schedule(schedule::current_time()).await;
+        Ok(())
+    }
+
+    /// Member ids of the group with the given name. GPU ids take priority over group names.
+    pub fn group_members(&self, name: &str) -> anyhow::Result<Option<Vec<String>>> {
+        if self.gpu_controllers.contains_key(name) {
+            return Ok(None);
+        }
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        Ok(config.groups.get(name).cloned())
+    }
+
+    pub fn define_group(&self, name: &str, ids: Vec<String>) -> anyhow::Result<()> {
+        if name.is_empty() {
+            return Err(anyhow!("Group name cannot be empty"));
+        }
+        if self.gpu_controllers.contains_key(name) {
+            return Err(anyhow!("Group name {name} is already used as a GPU id"));
+        }
+        if let Some(id) = ids
+            .iter()
+            .find(|id| !self.gpu_controllers.contains_key(id.as_str()))
+        {
+            return Err(anyhow!("No GPU with id {id}"));
+        }
+
+        let mut config = self
+            .config
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("{err}"))?;
+        if ids.is_empty() {
+            info!("deleting gpu group {name}");
+            config.groups.remove(name);
+        } else {
+            info!("defining gpu group {name} with {ids:?}");
+            config.groups.insert(name.to_owned(), ids);
+        }
+        config.save()
+    }
+
+    /// Labels are stored by GPU id, so they are matched to the GPU again when it is reconnected
+    pub fn set_device_label(&self, id: &str, label: &str) -> anyhow::Result<()> {
+        self.controller_by_id(id)?;
+        let label = label.trim();
+
+        let mut config = self
+            .config
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("{err}"))?;
+        if label.is_empty() {
+            info!("removing label of gpu {id}");
+            config.labels.remove(id);
+        } else {
+            info!("setting label of gpu {id} to '{label}'");
+            config.labels.insert(id.to_owned(), label.to_owned());
+        }
+        config.save()
+    }
+
+    pub async fn set_schedule(&self, profile: &str, time_range: TimeRange) -> anyhow::Result<()> {
+        if time_range.start == time_range.end {
+            return Err(anyhow!("Schedule start and end times must be different"));
+        }
+
+        {
+            let mut config = self
+                .config
+                .try_borrow_mut()
+                .map_err(|err| anyhow!("{err}"))?;
+            if !config.profiles.contains_key(profile) {
+                return Err(anyhow!("Profile `{profile}` does not exist"));
+            }
+
+            config
+                .schedules
+                .retain(|schedule| schedule.profile != profile);
+            config.schedules.push(Schedule {
+                profile: profile.to_owned(),
+                time_range,
+            });
+            config.save()?;
+        }
+
+        self.apply_schedule(schedule::current_time()).await;
+        Ok(())
+    }
+
+    pub fn diff_profile(&self, id: &str, name: &str) -> anyhow::Result<Vec<SettingsDiff>> {
+        self.controller_by_id(id)?;
+        let current = self.current_gpu_config(id)?.unwrap_or_default();
+
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        if !config.profiles.contains_key(name) {
+            return Err(anyhow!("Profile `{name}` does not exist"));
+        }
+        let profile = config
+            .gpus_with_profile(Some(name))
+            .remove(id)
+            .unwrap_or_default();
+
+        config::diff_gpu_settings(&current, &profile)
+    }
+
+    /// Applies the settings between the ones the GPU has with each profile, see `config::blend_gpu_settings`
+    pub async fn blend_profiles(
+        &self,
+        id: &str,
+        from: &str,
+        to: &str,
+        factor: f64,
+    ) -> anyhow::Result<u64> {
+        self.controller_by_id(id)?;
+        let settings = {
+            let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+            let profile_settings = |name: &str| {
+                if !config.profiles.contains_key(name) {
+                    return Err(anyhow!("Profile `{name}` does not exist"));
+                }
+                Ok(config
+                    .gpus_with_profile(Some(name))
+                    .remove(id)
+                    .unwrap_or_default())
+            };
+            config::blend_gpu_settings(&profile_settings(from)?, &profile_settings(to)?, factor)?
+        };
+
+        self.apply_device_config(id, settings, None).await
+    }
+
+    /// Applies the model section of the profile (see `Profile::model_settings`) to every managed GPU.
+    /// Unlike the other settings changes, each GPU's settings are confirmed right away.
+    pub async fn load_profile_all(
+        &self,
+        name: &str,
+    ) -> anyhow::Result<BTreeMap<String, ProfileLoadResult>> {
+        let sections: Vec<(String, Option<(String, config::Gpu)>)> = {
+            let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+            let profile = config
+                .profiles
+                .get(name)
+                .with_context(|| format!("Profile `{name}` does not exist"))?;
+            self.gpu_controllers
+                .keys()
+                .filter(|id| config.is_managed(id))
+                .map(|id| {
+                    let section = profile
+                        .model_settings(id)
+                        .map(|(model, settings)| (model.to_owned(), settings.clone()));
+                    (id.clone(), section)
+                })
+                .collect()
+        };
+
+        let mut results = BTreeMap::new();
+        for (id, section) in sections {
+            let result = match section {
+                Some((model, settings)) => match self.load_profile_section(&id, settings).await {
+                    Ok(()) => ProfileLoadResult::Applied { section: model },
+                    Err(err) => ProfileLoadResult::Failed {
+                        error: format!("{err:#}"),
+                    },
+                },
+                None => ProfileLoadResult::Skipped,
+            };
+            results.insert(id, result);
+        }
+        Ok(results)
+    }
+
+    async fn load_profile_section(&self, id: &str, settings: config::Gpu) -> anyhow::Result<()> {
+        self.check_controllable(id)?;
+        self.edit_gpu_config(id.to_owned(), |gpu_config| *gpu_config = settings)
+            .await?;
+        self.confirm_pending_config(ConfirmCommand::Confirm)
+    }
+
+    /// Profiles are saved with the currently applied settings, so saving the active profile again doesn't change anything
+    pub fn save_profile(&self, name: &str) -> anyhow::Result<()> {
+        if name.is_empty() {
+            return Err(anyhow!("Profile name cannot be empty"));
+        }
+
+        let mut gpus = HashMap::new();
+        for id in self.gpu_controllers.keys() {
+            if !self.config.borrow().is_managed(id) {
+                continue;
+            }
+            if let Some(gpu_config) = self.current_gpu_config(id)? {
+                gpus.insert(id.clone(), gpu_config);
+            }
+        }
+
+        let mut config = self
+            .config
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("{err}"))?;
+        info!(
+            "saving the settings of {} GPUs as profile {name}",
+            gpus.len()
+        );
+        config.profiles.entry(name.to_owned()).or_default().gpus = gpus;
+        config.save()
+    }
+
+    pub fn list_profiles(&self) -> anyhow::Result<Vec<String>> {
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        Ok(config.profiles.keys().cloned().collect())
+    }
+
+    pub fn list_schedules(&self) -> anyhow::Result<Vec<Schedule>> {
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        Ok(config.schedules.clone())
+    }
+
+    pub async fn delete_schedule(&self, profile: &str) -> anyhow::Result<()> {
+        {
+            let mut config = self
+                .config
+                .try_borrow_mut()
+                .map_err(|err| anyhow!("{err}"))?;
+            let schedules_len = config.schedules.len();
+            config
+                .schedules
+                .retain(|schedule| schedule.profile != profile);
+
+            if config.schedules.len() == schedules_len {
+                return Err(anyhow!("Profile `{profile}` has no schedule"));
+            }
+            config.save()?;
+        }
+
+        self.apply_schedule(schedule::current_time()).await;
+        Ok(())
+    }
+
+    pub fn set_temperature_unit(&self, unit: TemperatureUnit) -> anyhow::Result<()> {
+        let mut config = self
+            .config
+            .try_borrow_mut()
+            .map_err(|err| anyhow!("{err}"))?;
+        config.daemon.temperature_unit = unit;
+        config.save()
+    }
+
+    pub async fn set_power_source_profiles(
+        &self,
+        ac_profile: Option<&str>,
+        battery_profile: Option<&str>,
+    ) -> anyhow::Result<()> {
+        {
+            let mut config = self
+                .config
+                .try_borrow_mut()
+                .map_err(|err| anyhow!("{err}"))?;
+            if let Some(profile) = ac_profile
+                .iter()
+                .chain(&battery_profile)
+                .find(|profile| !config.profiles.contains_key(**profile))
+            {
+                return Err(anyhow!("Profile `{profile}` does not exist"));
+            }
+
+            config.power_source_profiles = (ac_profile.is_some() || battery_profile.is_some())
+                .then(|| PowerSourceProfiles {
+                    ac_profile: ac_profile.map(str::to_owned),
+                    battery_profile: battery_profile.map(str::to_owned),
+                });
+            config.save()?;
+        }
+
+        self.apply_schedule(schedule::current_time()).await;
+        Ok(())
+    }
+
+    /// Should be called periodically with the current power source, switching to its profile when it changes
+    pub async fn set_power_source(&self, power_source: Option<PowerSource>) {
+        if self.power_source.replace(power_source) != power_source {
+            info!("power source changed to {power_source:?}");
+            self.apply_schedule(schedule::current_time()).await;
+        }
+    }
+
+    /// Switches to the profile of the schedule matching the given time if it is not already active.
+    /// When no schedule matches, the profile for the current power source is used.
+    pub async fn apply_schedule(&self, time: DayTime) {
+        if self
+            .confirm_config_tx
+            .try_borrow()
+            .map_or(true, |tx| tx.is_some())
+        {
+            debug!("not switching scheduled profile while a config change is pending");
+            return;
+        }
+
+        let (previous_gpus, new_gpus, profile) = {
+            let config = self.config.borrow();
+            let new_profile = config
+                .schedules
+                .iter()
+                .find(|schedule| schedule.time_range.contains(time))
+                .map(|schedule| schedule.profile.clone())
+                .or_else(|| {
+                    let power_source = self.power_source.get()?;
+                    config
+                        .power_source_profiles
+                        .as_ref()?
+                        .profile(power_source)
+                        .map(str::to_owned)
+                });
+
+            let mut active_profile = self.active_profile.borrow_mut();
+            if *active_profile == new_profile {
+                return;
+            }
+            info!(
+                "switching automatic profile from {:?} to {:?}",
+                *active_profile, new_profile
+            );
+
+            let previous_gpus = config.gpus_with_profile(active_profile.as_deref());
+            let new_gpus = config.gpus_with_profile(new_profile.as_deref());
+            active_profile.clone_from(&new_profile);
+            (previous_gpus, new_gpus, new_profile)
+        };
+        let source = SettingSource::Schedule { profile };
+        self.bump_config_generation();
+
+        // GPUs which were only configured by the previous profile go back to the default settings
+        for id in previous_gpus.keys() {
+            if !new_gpus.contains_key(id) {
+                self.reset_gpu_settings(id, &source).await;
+            }
+        }
+
+        self.load_config(&source).await;
+    }
+
+    /// Resets a managed GPU which is no longer configured to the default settings
+    async fn reset_gpu_settings(&self, id: &str, source: &SettingSource) {
+        if !self.config.borrow().is_managed(id) {
+            return;
+        }
+        if let Some(controller) = self
+            .gpu_controllers
+            .get(id)
+            .filter(|controller| controller.is_controllable())
+        {
+            let default_config = config::Gpu::default();
+            match controller.apply_config(&default_config).await {
+                Ok(()) => self
+                    .settings_history
+                    .borrow_mut()
+                    .record(id, &default_config, source),
+                Err(err) => error!("could not reset settings for gpu {id}: {err:#}"),
+            }
+        }
+    }
+
+    pub fn get_settings_history(
+        &self,
+        id: &str,
+    ) -> anyhow::Result<BTreeMap<String, SettingChange>> {
+        self.controller_by_id(id)?;
+        Ok(self
+            .settings_history
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .changes(id))
+    }
+
+    fn schedule_status(&self) -> anyhow::Result<ScheduleStatus> {
+        let config = self.config.try_borrow().map_err(|err| anyhow!("{err}"))?;
+        let active_profile = self
+            .active_profile
+            .try_borrow()
+            .map_err(|err| anyhow!("{err}"))?
+            .clone();
+
+        Ok(ScheduleStatus {
+            active_profile,
+            next_transition: schedule::next_transition(&config.schedules),
+        })
+    }
+
+    /// Should be called when the driver reports that the GPU in the given PCI slot was reset
+    pub async fn handle_gpu_reset(&self, slot_name: &str, reset_at: Option<u64>, reapply: bool) {
+        let disable_reset_recovery = self
+            .config
+            .try_borrow()
+            .is_ok_and(|config| config.daemon.disable_reset_recovery);
+
+        for (id, controller) in &*self.gpu_controllers {
+            if controller.handle.get_pci_slot_name() != Some(slot_name) {
+                continue;
+            }
+
+            controller.reset_count.set(controller.reset_count.get() + 1);
+            controller.last_reset_at.set(reset_at);
+            self.send_event(DaemonEvent::GpuReset {
+                id: id.clone(),
+                reset_at,
+            });
+
+            if reapply
+                && !disable_reset_recovery
+                && controller.is_controllable()
+                && self.config.borrow().is_managed(id)
+            {
+                warn!("GPU {id} was reset, re-applying settings");
+                match self.current_gpu_config(id) {
+                    Ok(Some(gpu_config)) => {
+                        let result = controller.apply_config(&gpu_config).await;
+                        self.record_apply_result(&result);
+                        if let Err(err) = result {
+                            error!("could not re-apply settings for gpu {id}: {err:#}");
+                        }
+                    }
+                    Ok(None) => (),
+                    Err(err) => error!("{err:#}"),
+                }
+            }
+        }
+    }
+
     pub async fn cleanup(self) {
         let disable_clocks_cleanup = self
             .config
@@ -389,7 +2078,17 @@ impl<'a> Handler {
             .map(|config| config.daemon.disable_clocks_cleanup)
             .unwrap_or(false);
 
+        let unmanaged_gpus = self
+            .config
+            .try_borrow()
+            .map(|config| config.unmanaged_gpus.clone())
+            .unwrap_or_default();
+
         for (id, controller) in &*self.gpu_controllers {
+            if unmanaged_gpus.contains(id) || !controller.is_controllable() {
+                continue;
+            }
+
             if !disable_clocks_cleanup && controller.handle.get_clocks_table().is_ok() {
                 debug!("resetting clocks table");
                 if let Err(err) = controller.handle.reset_clocks_table() {
@@ -444,5 +2143,106 @@ fn load_controllers() -> anyhow::Result<BTreeMap<String, GpuController>> {
         }
     }
 
+    // GPUs bound to a passthrough driver have no DRM device, so they are found through the driver instead
+    if env::var("_LACT_DRM_SYSFS_PATH").is_err() {
+        for driver in PASSTHROUGH_DRIVERS_PATHS {
+            load_passthrough_controllers(Path::new(driver), &mut controllers);
+        }
+    }
+
     Ok(controllers)
 }
+
+fn load_passthrough_controllers(
+    driver_path: &Path,
+    controllers: &mut BTreeMap<String, GpuController>,
+) {
+    let Ok(entries) = driver_path.read_dir() else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Besides the bound devices, the driver directory also contains files such as `bind` and `new_id`
+        let is_display_device = entry.file_name().to_string_lossy().contains(':')
+            && fs::read_to_string(path.join("class"))
+                .is_ok_and(|class| class.trim().starts_with("0x03"));
+        if !is_display_device {
+            continue;
+        }
+
+        match GpuController::new_from_path(path) {
+            Ok(controller) => match controller.get_id() {
+                Ok(id) => {
+                    info!("found GPU {id} bound to {}", controller.get_driver());
+                    controllers.entry(id).or_insert(controller);
+                }
+                Err(err) => warn!("could not initialize controller: {err:#}"),
+            },
+            Err(err) => warn!(
+                "failed to initialize passthrough controller at {:?}: {err}",
+                entry.path()
+            ),
+        }
+    }
+}
+
+fn check_power_state(
+    controller: &GpuController,
+    kind: PowerLevelKind,
+    state: u8,
+) -> anyhow::Result<()> {
+    let states = controller.get_power_states(None);
+    let count = match kind {
+        PowerLevelKind::CoreClock => states.core.len(),
+        _ => states.vram.len(),
+    };
+    if usize::from(state) >= count {
+        return Err(anyhow!(
+            "Power state {state} does not exist, the GPU has {count} {kind:?} states"
+        ));
+    }
+    Ok(())
+}
+
+/// Device configs made for another model are refused, as their clocks and voltages could be unsafe on this GPU
+fn check_device_match(controller: &GpuController, expected: &DeviceMatch) -> anyhow::Result<()> {
+    let pci_info = controller
+        .pci_info
+        .as_ref()
+        .context("The PCI ids of the GPU are not known")?;
+    if expected.match_kind(pci_info).is_none() {
+        return Err(anyhow!(
+            "Expected {}:{}, but the GPU is {}:{} ({}:{})",
+            expected.vendor_id,
+            expected.device_id,
+            pci_info.device_pci_info.vendor_id,
+            pci_info.device_pci_info.model_id,
+            pci_info.subsystem_pci_info.vendor_id,
+            pci_info.subsystem_pci_info.model_id,
+        ));
+    }
+    Ok(())
+}
+
+/// Settings changes would only fail with permission errors from sysfs without root
+pub fn check_privileged() -> anyhow::Result<()> {
+    match system::run_mode() {
+        RunMode::System => Ok(()),
+        RunMode::User => Err(anyhow!(
+            "The daemon is running in user mode, changing GPU settings requires running it as root"
+        )),
+    }
+}
+
+fn validate_adaptive_clocks(settings: &AdaptiveClocks) -> anyhow::Result<()> {
+    if settings.idle_max > settings.load_max {
+        return Err(anyhow!(
+            "Idle clock has to be lower than or equal to the load clock"
+        ));
+    }
+    if !(1..=100).contains(&settings.load_threshold_pct) {
+        return Err(anyhow!("Load threshold has to be between 1 and 100"));
+    }
+    Ok(())
+}
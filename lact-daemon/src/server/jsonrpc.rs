@@ -0,0 +1,133 @@
+//! JSON-RPC 2.0 framing, as an alternative to the native protocol.
+//!
+//! The codec is picked by the first message on a connection: if it is a JSON-RPC request, the whole connection uses it.
+//! Requests are translated to the native format, so `method` is the snake case request name and `params` are its arguments.
+use lact_schema::Response;
+use serde_json::{json, Map, Value};
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const SERVER_ERROR: i32 = -32000;
+
+pub fn is_jsonrpc(line: &str) -> bool {
+    serde_json::from_str::<Map<String, Value>>(line)
+        .is_ok_and(|object| object.get("jsonrpc").and_then(Value::as_str) == Some("2.0"))
+}
+
+/// A JSON-RPC request translated to the native request format
+pub struct TranslatedRequest {
+    /// `None` for notifications, which don't get a response
+    pub id: Option<Value>,
+    pub request: String,
+}
+
+/// Translates a JSON-RPC request to the native request format.
+/// On failure, the error response that should be sent instead is returned.
+pub fn translate_request(line: &str) -> Result<TranslatedRequest, Vec<u8>> {
+    let mut object = match serde_json::from_str::<Map<String, Value>>(line) {
+        Ok(object) => object,
+        Err(err) => return Err(error_response(&Value::Null, PARSE_ERROR, &err.to_string())),
+    };
+    let id = object.remove("id");
+    let response_id = id.clone().unwrap_or(Value::Null);
+
+    if object.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(error_response(
+            &response_id,
+            INVALID_REQUEST,
+            "Only JSON-RPC 2.0 is supported",
+        ));
+    }
+    let Some(Value::String(method)) = object.remove("method") else {
+        return Err(error_response(
+            &response_id,
+            INVALID_REQUEST,
+            "Missing method name",
+        ));
+    };
+
+    let request = match object.remove("params") {
+        Some(params) => json!({ "command": method, "args": params }),
+        None => json!({ "command": method }),
+    };
+
+    Ok(TranslatedRequest {
+        id,
+        request: request.to_string(),
+    })
+}
+
+/// Translates a native response to a JSON-RPC response
+pub fn translate_response(id: &Value, response: &[u8]) -> Vec<u8> {
+    match serde_json::from_slice::<Response<Value>>(response) {
+        Ok(Response::Ok(result)) => json!({ "jsonrpc": "2.0", "result": result, "id": id })
+            .to_string()
+            .into_bytes(),
+        Ok(Response::Error(message)) => error_response(id, SERVER_ERROR, &message),
+        Err(err) => error_response(id, SERVER_ERROR, &format!("Invalid response: {err}")),
+    }
+}
+
+fn error_response(id: &Value, code: i32, message: &str) -> Vec<u8> {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+    .to_string()
+    .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_jsonrpc, translate_request, translate_response};
+    use lact_schema::Request;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn detect_jsonrpc() {
+        assert!(is_jsonrpc(
+            r#"{"jsonrpc": "2.0", "method": "ping", "id": 1}"#
+        ));
+        assert!(!is_jsonrpc(r#"{"command": "ping"}"#));
+        assert!(!is_jsonrpc("not json"));
+    }
+
+    #[test]
+    fn translate_with_params() {
+        let line =
+            r#"{"jsonrpc": "2.0", "method": "device_stats", "params": {"id": "my-gpu"}, "id": 5}"#;
+        let translated = translate_request(line).unwrap();
+        assert_eq!(translated.id, Some(json!(5)));
+
+        let request: Request = serde_json::from_str(&translated.request).unwrap();
+        assert_eq!(request, Request::DeviceStats { id: "my-gpu" });
+    }
+
+    #[test]
+    fn translate_notification() {
+        let translated = translate_request(r#"{"jsonrpc": "2.0", "method": "ping"}"#).unwrap();
+        assert_eq!(translated.id, None);
+
+        let request: Request = serde_json::from_str(&translated.request).unwrap();
+        assert_eq!(request, Request::Ping);
+    }
+
+    #[test]
+    fn translate_responses() {
+        let ok: Value = serde_json::from_slice(&translate_response(
+            &json!(1),
+            br#"{"status":"ok","data":5}"#,
+        ))
+        .unwrap();
+        assert_eq!(ok, json!({ "jsonrpc": "2.0", "result": 5, "id": 1 }));
+
+        let error: Value = serde_json::from_slice(&translate_response(
+            &json!(2),
+            br#"{"status":"error","data":"oops"}"#,
+        ))
+        .unwrap();
+        assert_eq!(error["error"]["message"], "oops");
+        assert_eq!(error["id"], 2);
+    }
+}
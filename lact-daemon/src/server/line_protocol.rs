@@ -0,0 +1,135 @@
+use lact_schema::DeviceStats;
+use std::fmt::{self, Write};
+
+const MEASUREMENT: &str = "gpu";
+
+pub enum FieldValue {
+    Float(f64),
+    Integer(u64),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Float(value) => write!(f, "{value}"),
+            FieldValue::Integer(value) => write!(f, "{value}i"),
+        }
+    }
+}
+
+/// Formats the stats of a GPU as a single line of the `InfluxDB` line protocol.
+/// See <https://docs.influxdata.com/influxdb/v2/reference/syntax/line-protocol/>
+pub fn format_stats(id: &str, stats: &DeviceStats) -> String {
+    let mut fields = Vec::new();
+
+    let mut temps: Vec<_> = stats.temps.iter().collect();
+    temps.sort_by_key(|(sensor, _)| *sensor);
+    for (sensor, temp) in temps {
+        if let Some(current) = temp.current {
+            fields.push((format!("temp_{sensor}"), FieldValue::Float(current.into())));
+        }
+    }
+
+    let optional_fields = [
+        (
+            "power",
+            stats
+                .power
+                .current
+                .or(stats.power.average)
+                .map(FieldValue::Float),
+        ),
+        ("power_cap", stats.power.cap_current.map(FieldValue::Float)),
+        (
+            "fan_speed",
+            stats
+                .fan
+                .speed_current
+                .map(|speed| FieldValue::Integer(speed.into())),
+        ),
+        (
+            "gpu_clock",
+            stats.clockspeed.gpu_clockspeed.map(FieldValue::Integer),
+        ),
+        (
+            "vram_clock",
+            stats.clockspeed.vram_clockspeed.map(FieldValue::Integer),
+        ),
+        ("voltage", stats.voltage.gpu.map(FieldValue::Integer)),
+        ("vram_used", stats.vram.used.map(FieldValue::Integer)),
+        ("vram_total", stats.vram.total.map(FieldValue::Integer)),
+        (
+            "busy_percent",
+            stats
+                .busy_percent
+                .map(|busy| FieldValue::Integer(busy.into())),
+        ),
+    ];
+    fields.extend(
+        optional_fields
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_owned(), value?))),
+    );
+
+    // The line protocol uses nanosecond timestamps by default
+    format_line(&[("id", id)], &fields, stats.sampled_at * 1_000_000)
+}
+
+fn format_line(tags: &[(&str, &str)], fields: &[(String, FieldValue)], timestamp: u64) -> String {
+    let mut line = MEASUREMENT.to_owned();
+    for (key, value) in tags {
+        write!(line, ",{}={}", escape(key), escape(value)).unwrap();
+    }
+
+    for (i, (key, value)) in fields.iter().enumerate() {
+        let separator = if i == 0 { ' ' } else { ',' };
+        write!(line, "{separator}{}={value}", escape(key)).unwrap();
+    }
+
+    write!(line, " {timestamp}").unwrap();
+    line
+}
+
+/// Escapes the characters which have a special meaning in tag keys, tag values and field keys
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | '=' | ' ' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_line, FieldValue};
+
+    #[test]
+    fn format() {
+        let line = format_line(
+            &[("id", "1002:73BF-1DA2:E445-0000:03:00.0")],
+            &[
+                ("temp_edge".to_owned(), FieldValue::Float(65.5)),
+                ("power".to_owned(), FieldValue::Float(220.0)),
+                ("fan_speed".to_owned(), FieldValue::Integer(1500)),
+            ],
+            1_700_000_000_000_000_000,
+        );
+        assert_eq!(
+            line,
+            "gpu,id=1002:73BF-1DA2:E445-0000:03:00.0 temp_edge=65.5,power=220,fan_speed=1500i 1700000000000000000"
+        );
+    }
+
+    #[test]
+    fn escape_tags() {
+        let line = format_line(
+            &[("id", "my gpu,1=2")],
+            &[("temp_my sensor".to_owned(), FieldValue::Float(1.0))],
+            0,
+        );
+        assert_eq!(line, r"gpu,id=my\ gpu\,1\=2 temp_my\ sensor=1 0");
+    }
+}
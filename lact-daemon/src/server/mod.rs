@@ -1,13 +1,29 @@
+mod baseline;
+mod events;
+mod fleet;
 pub mod gpu_controller;
 pub mod handler;
+mod jsonrpc;
+mod line_protocol;
+mod presets;
+mod self_test;
+mod settings_history;
 pub(crate) mod system;
+mod system_power;
+mod tuning;
 mod vulkan;
+pub mod warnings;
 
 use self::handler::Handler;
-use crate::{config::Config, socket};
-use lact_schema::{Pong, Request, Response};
+use crate::{
+    config::{watts_to_microwatts, Config},
+    log_stream, socket,
+};
+use anyhow::{anyhow, Context};
+use lact_schema::{request::ConfirmCommand, EventMask, LogLevel, Pong, Request, Response};
+use miniz_oxide::deflate::compress_to_vec_zlib;
 use serde::Serialize;
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Instant};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     net::{UnixListener, UnixStream},
@@ -16,6 +32,9 @@ use tracing::{debug, error, instrument};
 
 pub use system::MODULE_CONF_PATH;
 
+/// Default zlib level, which compresses JSON well without slowing down the responses much
+const COMPRESSION_LEVEL: u8 = 6;
+
 pub struct Server {
     pub handler: Handler,
     listener: UnixListener,
@@ -50,25 +69,83 @@ impl Server {
 
 #[instrument(level = "debug", skip(stream, handler))]
 pub async fn handle_stream(stream: UnixStream, handler: Handler) -> anyhow::Result<()> {
+    let caller_uid = stream
+        .peer_cred()
+        .context("Could not read the credentials of the client")?
+        .uid();
     let mut stream = BufReader::new(stream);
 
     let mut buf = String::new();
+    let mut use_jsonrpc = None;
+    let mut response_timing = false;
+    let mut response_compression = false;
     while stream.read_line(&mut buf).await? != 0 {
         debug!("handling request: {}", buf.trim_end());
 
-        let maybe_request = serde_json::from_str(&buf);
-        let response = match maybe_request {
-            Ok(request) => match handle_request(request, &handler).await {
-                Ok(response) => response,
-                Err(error) => serde_json::to_vec(&Response::<()>::Error(format!("{error:#}")))?,
-            },
-            Err(error) => serde_json::to_vec(&Response::<()>::Error(format!(
-                "Failed to deserialize request: {error}"
-            )))?,
+        let use_jsonrpc = *use_jsonrpc.get_or_insert_with(|| jsonrpc::is_jsonrpc(&buf));
+        if !use_jsonrpc {
+            if let Some(level) = log_subscription_level(&buf) {
+                let response = serde_json::to_vec(&Response::Ok(()))?;
+                stream.write_all(&response).await?;
+                stream.write_all(b"\n").await?;
+                return log_stream::forward(&mut stream, level).await;
+            }
+
+            if let Some(mask) = event_subscription(&buf) {
+                let response = serde_json::to_vec(&Response::Ok(()))?;
+                stream.write_all(&response).await?;
+                stream.write_all(b"\n").await?;
+                return events::forward(&mut stream, &handler, mask).await;
+            }
+
+            if let Some(enabled) = response_timing_request(&buf) {
+                response_timing = enabled;
+                let response = serde_json::to_vec(&Response::Ok(()))?;
+                stream.write_all(&response).await?;
+                stream.write_all(b"\n").await?;
+                buf.clear();
+                continue;
+            }
+
+            if let Some(enabled) = response_compression_request(&buf) {
+                response_compression = enabled;
+                let response = serde_json::to_vec(&Response::Ok(()))?;
+                stream.write_all(&response).await?;
+                stream.write_all(b"\n").await?;
+                buf.clear();
+                continue;
+            }
+        }
+
+        let response = if use_jsonrpc {
+            match jsonrpc::translate_request(&buf) {
+                Ok(translated) => {
+                    let response =
+                        process_request(&translated.request, &handler, caller_uid).await?;
+                    translated
+                        .id
+                        .map(|id| jsonrpc::translate_response(&id, &response))
+                }
+                Err(error_response) => Some(error_response),
+            }
+        } else {
+            let start = Instant::now();
+            let mut response = process_request(&buf, &handler, caller_uid).await?;
+            if response_timing {
+                let micros = u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX);
+                append_field(&mut response, "processing_time_us", &micros)?;
+            }
+            Some(response)
         };
 
-        stream.write_all(&response).await?;
-        stream.write_all(b"\n").await?;
+        if let Some(response) = response {
+            if response_compression {
+                stream.write_all(&compress_response(&response)).await?;
+            } else {
+                stream.write_all(&response).await?;
+                stream.write_all(b"\n").await?;
+            }
+        }
 
         buf.clear();
     }
@@ -76,52 +153,446 @@ pub async fn handle_stream(stream: UnixStream, handler: Handler) -> anyhow::Resu
     Ok(())
 }
 
+/// A log subscription takes over the connection, so it is handled before the other requests
+fn log_subscription_level(raw_request: &str) -> Option<LogLevel> {
+    // Avoids parsing every request twice
+    if !raw_request.contains("subscribe_logs") {
+        return None;
+    }
+    match serde_json::from_str(raw_request).ok()? {
+        Request::SubscribeLogs { level } => Some(level),
+        _ => None,
+    }
+}
+
+/// An event subscription takes over the connection as well
+fn event_subscription(raw_request: &str) -> Option<EventMask> {
+    if !raw_request.contains("\"subscribe\"") {
+        return None;
+    }
+    match serde_json::from_str(raw_request).ok()? {
+        Request::Subscribe { events } => Some(events),
+        _ => None,
+    }
+}
+
+/// Response timing is a setting of the connection, so it is handled outside of the other requests
+fn response_timing_request(raw_request: &str) -> Option<bool> {
+    if !raw_request.contains("set_response_timing") {
+        return None;
+    }
+    match serde_json::from_str(raw_request).ok()? {
+        Request::SetResponseTiming { enabled } => Some(enabled),
+        _ => None,
+    }
+}
+
+/// Compression is a setting of the connection as well, like response timing
+fn response_compression_request(raw_request: &str) -> Option<bool> {
+    if !raw_request.contains("set_response_compression") {
+        return None;
+    }
+    match serde_json::from_str(raw_request).ok()? {
+        Request::SetResponseCompression { enabled } => Some(enabled),
+        _ => None,
+    }
+}
+
+/// The compressed response with its length on a separate line, see `Request::SetResponseCompression`
+fn compress_response(response: &[u8]) -> Vec<u8> {
+    let compressed = compress_to_vec_zlib(response, COMPRESSION_LEVEL);
+    let mut frame = format!("{}\n", compressed.len()).into_bytes();
+    frame.extend(compressed);
+    frame
+}
+
+/// Adds a field next to the status and data of a serialized response object, such as the `ResponseTiming`
+fn append_field<T: Serialize>(response: &mut Vec<u8>, key: &str, value: &T) -> anyhow::Result<()> {
+    if response.pop() == Some(b'}') {
+        response.push(b',');
+        serde_json::to_writer(&mut *response, key)?;
+        response.push(b':');
+        serde_json::to_writer(&mut *response, value)?;
+        response.push(b'}');
+    }
+    Ok(())
+}
+
+/// The uid of the connected user is used for requests which act on behalf of the user, such as terminating a process
+async fn process_request(
+    raw_request: &str,
+    handler: &Handler,
+    caller_uid: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let maybe_request = serde_json::from_str(raw_request);
+    let response = match maybe_request {
+        Ok(request) => {
+            match warnings::collect(Box::pin(handle_request(request, handler, caller_uid))).await {
+                (Ok(mut response), warnings) => {
+                    if !warnings.is_empty() {
+                        append_field(&mut response, "warnings", &warnings)?;
+                    }
+                    response
+                }
+                (Err(error), _) => {
+                    serde_json::to_vec(&Response::<()>::Error(format!("{error:#}")))?
+                }
+            }
+        }
+        Err(error) => serde_json::to_vec(&Response::<()>::Error(format!(
+            "Failed to deserialize request: {error}"
+        )))?,
+    };
+    Ok(response)
+}
+
 #[instrument(level = "debug", skip(handler))]
-async fn handle_request<'a>(request: Request<'a>, handler: &'a Handler) -> anyhow::Result<Vec<u8>> {
+async fn handle_request<'a>(
+    mut request: Request<'a>,
+    handler: &'a Handler,
+    caller_uid: u32,
+) -> anyhow::Result<Vec<u8>> {
+    if let Some(target) = request.target_id_mut() {
+        if let Some(members) = handler.group_members(target)? {
+            return handle_group_request(request, &members, handler, caller_uid).await;
+        }
+        handler.check_controllable(target)?;
+    }
+
     match request {
         Request::Ping => ok_response(ping()),
-        Request::SystemInfo => ok_response(system::info()?),
+        Request::SystemInfo => ok_response(handler.get_system_info()?),
         Request::ListDevices => ok_response(handler.list_devices()),
+        Request::GetTotalPower => ok_response(handler.get_total_power()),
+        Request::GetSystemPower => ok_response(handler.get_system_power().await),
+        Request::GetFleetSummary => ok_response(handler.get_fleet_summary()),
+        Request::SelfTest => ok_response(handler.self_test()),
+        Request::ExplainControl { id, control } => {
+            ok_response(handler.explain_control(id, control)?)
+        }
         Request::DeviceInfo { id } => ok_response(handler.get_device_info(id)?),
         Request::DeviceStats { id } => ok_response(handler.get_gpu_stats(id)?),
+        Request::GetDefaultFanCurve { id } => ok_response(handler.get_default_fan_curve(id)?),
+        Request::GetFanControlLog { id } => ok_response(handler.get_fan_control_log(id)?),
+        Request::GetReadFailures { id } => ok_response(handler.get_read_failures(id)?),
+        Request::GetQuickStats { id } => ok_response(handler.get_quick_stats(id)?),
+        Request::GetStatsLineProtocol { id } => {
+            let stats = handler.get_gpu_stats(id)?;
+            ok_response(line_protocol::format_stats(id, &stats))
+        }
+        Request::GetDeviceSnapshot { id } => ok_response(handler.get_device_snapshot(id)?),
+        Request::GetSmuMetrics { id } => ok_response(handler.get_smu_metrics(id)?),
+        Request::GetControllableNodes { id } => ok_response(handler.get_controllable_nodes(id)?),
         Request::DeviceClocksInfo { id } => ok_response(handler.get_clocks_info(id)?),
+        Request::GetVoltageCurve { id } => ok_response(handler.get_voltage_curve(id)?),
         Request::DevicePowerProfileModes { id } => {
             ok_response(handler.get_power_profile_modes(id)?)
         }
+        Request::GetPowerProfileModeDetails { id } => {
+            ok_response(handler.get_power_profile_mode_details(id)?)
+        }
         Request::SetFanControl {
             id,
             enabled,
             mode,
             static_speed,
             curve,
-        } => ok_response(
+        } => pending_change(
+            handler,
             handler
                 .set_fan_control(id, enabled, mode, static_speed, curve)
                 .await?,
         ),
-        Request::SetPowerCap { id, cap } => ok_response(handler.set_power_cap(id, cap).await?),
+        Request::SetFanTemperatureSource { id, gpu_id, sensor } => pending_change(
+            handler,
+            handler
+                .set_fan_temperature_source(id, gpu_id, sensor)
+                .await?,
+        ),
+        Request::SetFanCurveOffset { id, temp_offset } => pending_change(
+            handler,
+            handler.set_fan_curve_offset(id, temp_offset).await?,
+        ),
+        Request::SetFanSpindownCurve { id, curve } => {
+            pending_change(handler, handler.set_fan_spindown_curve(id, curve).await?)
+        }
+        Request::SetSimulatedTemperature { id, temp } => {
+            handler.set_simulated_temperature(id, temp)?;
+            applied_change(handler)
+        }
+        Request::SuspendFanControl { id, duration } => {
+            ok_response(handler.suspend_fan_control(id, duration).await?)
+        }
+        Request::SetPowerCap { id, cap } => {
+            let confirm_timeout = handler
+                .set_power_cap_microwatts(id, cap.map(watts_to_microwatts))
+                .await?;
+            power_cap_change(handler, id, confirm_timeout)
+        }
+        Request::SetPowerCapMicrowatts { id, cap } => {
+            let confirm_timeout = handler.set_power_cap_microwatts(id, cap).await?;
+            power_cap_change(handler, id, confirm_timeout)
+        }
+        Request::SetPowerTier { id, tier } => {
+            let confirm_timeout = handler.set_power_tier(id, tier).await?;
+            power_cap_change(handler, id, confirm_timeout)
+        }
+        Request::GetPowerTiers { id } => ok_response(handler.get_power_tiers(id)?),
         Request::SetPerformanceLevel {
             id,
             performance_level,
-        } => ok_response(handler.set_performance_level(id, performance_level).await?),
+        } => pending_change(
+            handler,
+            handler.set_performance_level(id, performance_level).await?,
+        ),
         Request::SetClocksValue { id, command } => {
-            ok_response(handler.set_clocks_value(id, command).await?)
+            pending_change(handler, handler.set_clocks_value(id, command).await?)
         }
         Request::BatchSetClocksValue { id, commands } => {
-            ok_response(handler.batch_set_clocks_value(id, commands).await?)
+            pending_change(handler, handler.batch_set_clocks_value(id, commands).await?)
         }
+        Request::SetClocksRamped {
+            id,
+            target,
+            step,
+            interval,
+            abort_on_throttling,
+        } => pending_change(
+            handler,
+            handler
+                .set_clocks_ramped(id, target, step, interval, abort_on_throttling)
+                .await?,
+        ),
         Request::SetPowerProfileMode { id, index } => {
-            ok_response(handler.set_power_profile_mode(id, index).await?)
+            pending_change(handler, handler.set_power_profile_mode(id, index).await?)
         }
+        Request::SetManaged { id, managed } => {
+            handler.set_managed(id, managed).await?;
+            applied_change(handler)
+        }
+        Request::SetPowerProfileModeByName { id, name } => pending_change(
+            handler,
+            handler.set_power_profile_mode_by_name(id, name).await?,
+        ),
         Request::GetPowerStates { id } => ok_response(handler.get_power_states(id)?),
-        Request::SetEnabledPowerStates { id, kind, states } => {
-            ok_response(handler.set_enabled_power_states(id, kind, states).await?)
+        Request::GetPerformanceLevel { id } => ok_response(handler.get_performance_level(id)?),
+        Request::SnapClock { id, domain, value } => {
+            ok_response(handler.snap_clock(id, domain, value)?)
+        }
+        Request::GetPpFeatures { id } => ok_response(handler.get_pp_features(id)?),
+        Request::SetPpFeature {
+            id,
+            feature,
+            enabled,
+        } => {
+            handler.set_pp_feature(id, feature, enabled)?;
+            applied_change(handler)
+        }
+        Request::SetEnabledPowerStates { id, kind, states } => pending_change(
+            handler,
+            handler.set_enabled_power_states(id, kind, states).await?,
+        ),
+        Request::EnableOverdrive => {
+            handler::check_privileged()?;
+            system::enable_overdrive()?;
+            warnings::add("Overclocking will be available after a reboot");
+            applied_change(handler)
+        }
+        Request::SetEccMode { id, enabled } => {
+            handler.set_ecc_mode(id, enabled)?;
+            applied_change(handler)
+        }
+        Request::GetAspmPolicy { id } => ok_response(handler.get_aspm_info(id)?),
+        Request::SetAspmPolicy { id, policy } => {
+            handler.set_aspm_policy(id, policy)?;
+            applied_change(handler)
+        }
+        Request::GetInterconnect { id } => ok_response(handler.get_interconnect(id)?),
+        Request::GetEffectiveConfig { id } => ok_response(handler.get_effective_config(id)?),
+        Request::GetTuningSuggestions { id } => ok_response(handler.get_tuning_suggestions(id)?),
+        Request::GetAudioDevice { id } => ok_response(handler.get_audio_device(id)?),
+        Request::SetAudioPinnedActive { id, pinned } => {
+            handler.set_audio_pinned_active(id, pinned)?;
+            applied_change(handler)
+        }
+        Request::GetRetiredPages { id } => ok_response(handler.get_retired_pages(id)?),
+        Request::ClearRetiredPages { id, count } => {
+            handler.clear_retired_pages(id, count)?;
+            applied_change(handler)
+        }
+        Request::GetResetMethods { id } => ok_response(handler.get_reset_methods(id)?),
+        Request::ResetGpu {
+            id,
+            method,
+            confirm,
+        } => {
+            handler.reset_gpu(id, method, confirm)?;
+            applied_change(handler)
+        }
+        Request::GetPendingConfig => ok_response(handler.get_pending_config()?),
+        Request::DiscardAllPending { id } => {
+            handler.discard_pending(id)?;
+            applied_change(handler)
         }
-        Request::EnableOverdrive => ok_response(system::enable_overdrive()?),
         Request::ConfirmPendingConfig(command) => {
-            ok_response(handler.confirm_pending_config(command)?)
+            handler.confirm_pending_config(command)?;
+            applied_change(handler)
+        }
+        Request::GetConfig => ok_response(handler.get_config()?),
+        Request::ReloadConfig => ok_response(handler.reload_config().await?),
+        Request::SetConfig { config } => {
+            handler.set_config(config).await?;
+            applied_change(handler)
+        }
+        Request::ApplyConfirmed(mut request) => {
+            if !request.needs_confirmation() {
+                return Err(anyhow!("Request {request:?} does not change settings"));
+            }
+            // Changes to a group are already confirmed for each member as they get applied
+            let targets_group = match request.target_id_mut() {
+                Some(id) => handler.group_members(id)?.is_some(),
+                None => false,
+            };
+            // There is no await point between applying and confirming, so the pending change can't be taken by another connection
+            Box::pin(handle_request(*request, handler, caller_uid)).await?;
+            if !targets_group {
+                handler.confirm_pending_config(ConfirmCommand::Confirm)?;
+            }
+            applied_change(handler)
+        }
+        Request::SetSchedule {
+            profile,
+            time_range,
+        } => {
+            handler.set_schedule(profile, time_range).await?;
+            applied_change(handler)
+        }
+        Request::ListSchedules => ok_response(handler.list_schedules()?),
+        Request::SaveProfile { name } => {
+            handler.save_profile(name)?;
+            applied_change(handler)
+        }
+        Request::ListProfiles => ok_response(handler.list_profiles()?),
+        Request::DiffProfile { id, name } => ok_response(handler.diff_profile(id, name)?),
+        Request::BlendProfiles {
+            id,
+            from,
+            to,
+            factor,
+        } => pending_change(handler, handler.blend_profiles(id, from, to, factor).await?),
+        Request::LoadProfileAll { name } => ok_response(handler.load_profile_all(name).await?),
+        Request::GetGpuProcesses { id } => ok_response(handler.get_gpu_processes(id)?),
+        Request::GetSettingsHistory { id } => ok_response(handler.get_settings_history(id)?),
+        Request::KillGpuProcess { pid } => ok_response(handler.kill_gpu_process(pid, caller_uid)?),
+        Request::DeleteSchedule { profile } => {
+            handler.delete_schedule(profile).await?;
+            applied_change(handler)
+        }
+        Request::SetTemperatureUnit { unit } => {
+            handler.set_temperature_unit(unit)?;
+            applied_change(handler)
+        }
+        Request::SetPowerSourceProfiles {
+            ac_profile,
+            battery_profile,
+        } => {
+            handler
+                .set_power_source_profiles(ac_profile, battery_profile)
+                .await?;
+            applied_change(handler)
+        }
+        Request::SetAdaptiveClocks {
+            id,
+            adaptive_clocks,
+        } => pending_change(
+            handler,
+            handler.set_adaptive_clocks(id, adaptive_clocks).await?,
+        ),
+        Request::SetMemoryClockBoost { id, enabled } => {
+            pending_change(handler, handler.set_memory_clock_boost(id, enabled).await?)
+        }
+        Request::ApplyDeviceConfig {
+            id,
+            config,
+            expected_device,
+        } => pending_change(
+            handler,
+            handler
+                .apply_device_config(id, config, expected_device.as_ref())
+                .await?,
+        ),
+        Request::RestoreFactoryBaseline { id } => {
+            pending_change(handler, handler.restore_factory_baseline(id).await?)
+        }
+        Request::ListPresets { id } => ok_response(handler.list_presets(id)?),
+        Request::ApplyPreset { id, name } => {
+            pending_change(handler, handler.apply_preset(id, name).await?)
+        }
+        Request::SubscribeLogs { .. } => Err(anyhow!(
+            "Logs can only be subscribed to as a standalone request over the socket protocol"
+        )),
+        Request::Subscribe { .. } => Err(anyhow!(
+            "Events can only be subscribed to as a standalone request over the socket protocol"
+        )),
+        Request::SetResponseTiming { .. } => Err(anyhow!(
+            "Response timing can only be enabled as a standalone request over the socket protocol"
+        )),
+        Request::SetResponseCompression { .. } => Err(anyhow!(
+            "Response compression can only be enabled as a standalone request over the socket protocol"
+        )),
+        Request::DefineGroup { name, ids } => {
+            handler.define_group(name, ids)?;
+            applied_change(handler)
+        }
+        Request::SetDeviceLabel { id, label } => {
+            handler.set_device_label(id, label)?;
+            applied_change(handler)
+        }
+    }
+}
+
+/// Applies a request to every member of a group.
+/// Settings changes are confirmed right away, as there is only a single pending change at a time.
+/// The response is the one of the last member.
+async fn handle_group_request(
+    request: Request<'_>,
+    members: &[String],
+    handler: &Handler,
+    caller_uid: u32,
+) -> anyhow::Result<Vec<u8>> {
+    let needs_confirmation = request.needs_confirmation();
+    let mut response = ok_response(())?;
+
+    for member in members {
+        let mut member_request = request.clone();
+        if let Some(id) = member_request.target_id_mut() {
+            *id = member;
+        }
+
+        response = Box::pin(handle_request(member_request, handler, caller_uid))
+            .await
+            .with_context(|| format!("Could not apply to GPU {member}"))?;
+        if needs_confirmation {
+            handler.confirm_pending_config(ConfirmCommand::Confirm)?;
         }
     }
+
+    Ok(response)
+}
+
+/// Response to a settings change which is reverted unless it gets confirmed within `confirm_timeout` seconds
+fn pending_change(handler: &Handler, confirm_timeout: u64) -> anyhow::Result<Vec<u8>> {
+    ok_response(handler.applied_change(Some(confirm_timeout), None))
+}
+
+fn power_cap_change(handler: &Handler, id: &str, confirm_timeout: u64) -> anyhow::Result<Vec<u8>> {
+    let effective = handler.effective_power_cap(id);
+    ok_response(handler.applied_change(Some(confirm_timeout), effective))
+}
+
+/// Response to a change which is applied right away
+fn applied_change(handler: &Handler) -> anyhow::Result<Vec<u8>> {
+    ok_response(handler.applied_change(None, None))
 }
 
 fn ok_response<T: Serialize + Debug>(data: T) -> anyhow::Result<Vec<u8>> {
@@ -132,3 +603,70 @@ fn ok_response<T: Serialize + Debug>(data: T) -> anyhow::Result<Vec<u8>> {
 fn ping() -> Pong {
     Pong
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{append_field, compress_response, handle_request, handler::Handler};
+    use crate::config::Config;
+    use lact_schema::{DeviceStats, Request, Response, ResponseTiming, ResponseWarnings};
+    use miniz_oxide::inflate::decompress_to_vec_zlib;
+
+    #[test]
+    fn compressed_response() {
+        let mut stats = DeviceStats::default();
+        for i in 0..500 {
+            stats.clocks.insert(format!("domain_{i}"), f64::from(i));
+        }
+        let response = serde_json::to_vec(&Response::Ok(stats)).unwrap();
+
+        let frame = compress_response(&response);
+        let (header, compressed) = frame.split_at(frame.iter().position(|b| *b == b'\n').unwrap());
+        let compressed = &compressed[1..];
+        assert_eq!(
+            std::str::from_utf8(header)
+                .unwrap()
+                .parse::<usize>()
+                .unwrap(),
+            compressed.len()
+        );
+        assert!(compressed.len() < response.len() / 2);
+        assert_eq!(decompress_to_vec_zlib(compressed).unwrap(), response);
+    }
+
+    #[test]
+    fn response_extra_fields() {
+        let mut response = serde_json::to_vec(&Response::Ok(5)).unwrap();
+        append_field(&mut response, "processing_time_us", &1500).unwrap();
+        append_field(&mut response, "warnings", &["reboot required"]).unwrap();
+
+        let timing: ResponseTiming = serde_json::from_slice(&response).unwrap();
+        assert_eq!(timing.processing_time_us, Some(1500));
+        let warnings: ResponseWarnings = serde_json::from_slice(&response).unwrap();
+        assert_eq!(warnings.warnings, ["reboot required"]);
+        let response: Response<u32> = serde_json::from_slice(&response).unwrap();
+        assert!(matches!(response, Response::Ok(5)));
+    }
+
+    #[tokio::test]
+    async fn apply_confirmed_targets() {
+        let mut config = Config::default();
+        config.groups.insert("empty".to_owned(), Vec::new());
+        let handler = Handler::without_gpus(config);
+        let apply_confirmed = |id| {
+            Request::ApplyConfirmed(Box::new(Request::SetPowerCap {
+                id,
+                cap: Some(200.0),
+            }))
+        };
+
+        // The members of a group confirm their own changes, so there is nothing left to confirm afterwards
+        let response = handle_request(apply_confirmed("empty"), &handler, 0).await;
+        assert!(response.is_ok());
+
+        let err = handle_request(apply_confirmed("missing-gpu"), &handler, 0)
+            .await
+            .unwrap_err();
+        assert!(!err.to_string().contains("No pending config changes"));
+        assert!(handler.get_pending_config().unwrap().is_none());
+    }
+}
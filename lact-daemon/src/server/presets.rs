@@ -0,0 +1,179 @@
+//! Settings shared for specific card models, stored as files in the `presets` directory next to the config.
+//!
+//! A preset file can be in JSON, TOML or YAML, and contains the PCI ids of the cards it is meant for
+//! and the settings in the same format as a `gpus` entry of the config:
+//! ```toml
+//! name = "Quiet"
+//! description = "Lower power limit and a relaxed fan curve"
+//!
+//! [match]
+//! vendor_id = "1002"
+//! device_id = "73BF"
+//! subsystem_vendor_id = "1DA2"
+//! subsystem_device_id = "E438"
+//!
+//! [settings]
+//! fan_control_enabled = false
+//! power_cap_microwatts = 230000000
+//! ```
+
+use crate::config;
+use anyhow::{anyhow, Context};
+use lact_schema::{DeviceMatch, GpuPciInfo, PresetInfo};
+use serde::Deserialize;
+use std::{fs, io, path::Path};
+use tracing::warn;
+
+#[derive(Deserialize, Debug)]
+struct PresetFile {
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(rename = "match")]
+    device: DeviceMatch,
+    settings: serde_json::Value,
+}
+
+pub struct Preset {
+    pub info: PresetInfo,
+    pub settings: serde_json::Value,
+}
+
+/// Lists the presets which match the given card, the ones made for this exact card first.
+/// Files which can't be parsed are skipped with a warning.
+pub fn list(dir: &Path, pci_info: &GpuPciInfo) -> anyhow::Result<Vec<Preset>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err).context("Could not read presets directory"),
+    };
+
+    let mut presets = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+
+        match read_preset(&path) {
+            Ok(file) => {
+                if let Some(exact_match) = file.device.match_kind(pci_info) {
+                    let name = file.name.unwrap_or_else(|| {
+                        path.file_stem()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                            .into_owned()
+                    });
+                    presets.push(Preset {
+                        info: PresetInfo {
+                            name,
+                            description: file.description,
+                            path: path.to_string_lossy().into_owned(),
+                            exact_match,
+                        },
+                        settings: file.settings,
+                    });
+                }
+            }
+            Err(err) => warn!("skipping preset {}: {err:#}", path.display()),
+        }
+    }
+
+    presets.sort_by(|a, b| {
+        b.info
+            .exact_match
+            .cmp(&a.info.exact_match)
+            .then_with(|| a.info.name.cmp(&b.info.name))
+    });
+    Ok(presets)
+}
+
+fn read_preset(path: &Path) -> anyhow::Result<PresetFile> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    let contents = fs::read_to_string(path)?;
+    parse_preset(&contents, extension)
+}
+
+fn parse_preset(contents: &str, extension: &str) -> anyhow::Result<PresetFile> {
+    let mut preset: PresetFile = match extension {
+        "json" => serde_json::from_str(contents)?,
+        "toml" => toml::from_str(contents)?,
+        "yaml" | "yml" => serde_yaml::from_str(contents)?,
+        _ => return Err(anyhow!("Unknown preset format '{extension}'")),
+    };
+    if let Some(settings) = preset.settings.as_object_mut() {
+        config::migrate_gpu_settings(settings);
+    }
+    Ok(preset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_preset;
+    use lact_schema::{GpuPciInfo, PciInfo};
+
+    fn pci_info(subsystem_model_id: &str) -> GpuPciInfo {
+        GpuPciInfo {
+            device_pci_info: PciInfo {
+                vendor_id: "1002".to_owned(),
+                vendor: None,
+                model_id: "73BF".to_owned(),
+                model: None,
+            },
+            subsystem_pci_info: PciInfo {
+                vendor_id: "1DA2".to_owned(),
+                vendor: None,
+                model_id: subsystem_model_id.to_owned(),
+                model: None,
+            },
+        }
+    }
+
+    #[test]
+    fn preset_matching() {
+        let toml = r#"
+name = "Quiet"
+
+[match]
+vendor_id = "1002"
+device_id = "73bf"
+subsystem_vendor_id = "1DA2"
+subsystem_device_id = "E438"
+
+[settings]
+fan_control_enabled = false
+        "#;
+        let preset = parse_preset(toml, "toml").unwrap();
+        assert_eq!(preset.name.as_deref(), Some("Quiet"));
+        assert_eq!(preset.settings["fan_control_enabled"], false);
+        assert_eq!(preset.device.match_kind(&pci_info("E438")), Some(true));
+        assert_eq!(preset.device.match_kind(&pci_info("E439")), None);
+
+        let json = r#"{"match": {"vendor_id": "1002", "device_id": "73BF"}, "settings": {}}"#;
+        let preset = parse_preset(json, "json").unwrap();
+        assert_eq!(preset.device.match_kind(&pci_info("E439")), Some(false));
+
+        assert!(parse_preset(json, "txt").is_err());
+    }
+    #[test]
+    fn old_preset_migrated() {
+        let yaml = r#"
+match:
+  vendor_id: "1002"
+  device_id: "73BF"
+settings:
+  max_core_clock: 2500
+  power_cap: 230.0
+        "#;
+        let preset = parse_preset(yaml, "yaml").unwrap();
+        assert_eq!(
+            preset.settings["clocks_configuration"]["max_core_clock"],
+            2500
+        );
+        assert_eq!(preset.settings["power_cap_microwatts"], 230_000_000);
+        assert!(preset.settings.get("power_cap").is_none());
+    }
+}
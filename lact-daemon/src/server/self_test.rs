@@ -0,0 +1,331 @@
+use super::{gpu_controller::GpuController, system};
+use lact_schema::{
+    Control, ControlStatus, ControlUnavailable, ControllableNode, GpuSelfTest, NodeStatus,
+    OverclockStatus, RunMode, SelfTestReport,
+};
+use nix::unistd::{access, AccessFlags};
+use std::{collections::BTreeMap, path::Path};
+
+/// Written by most of the settings, so it shows whether the daemon can change the GPU at all
+const PERFORMANCE_LEVEL_FILE: &str = "power_dpm_force_performance_level";
+
+pub fn check_gpu(controller: &GpuController) -> GpuSelfTest {
+    GpuSelfTest {
+        driver: controller.get_driver().to_owned(),
+        controllable: controller.is_controllable(),
+        overclock_status: controller.get_overclock_status(),
+        sysfs_writable: access(
+            &controller.get_path().join(PERFORMANCE_LEVEL_FILE),
+            AccessFlags::W_OK,
+        )
+        .is_ok(),
+    }
+}
+
+/// Sysfs files of a control, where being able to write to any of them is enough
+fn control_files(control: Control) -> &'static [&'static str] {
+    match control {
+        Control::Clocks => &["pp_od_clk_voltage"],
+        Control::PowerCap => &["power1_cap"],
+        Control::FanControl => &["pwm1_enable", "fan_curve"],
+        Control::PerformanceLevel => &["power_dpm_force_performance_level"],
+        Control::PowerProfileMode => &["pp_power_profile_mode"],
+        Control::PowerStates => &["pp_dpm_sclk", "pp_dpm_mclk"],
+    }
+}
+
+pub fn explain_control(
+    controller: &GpuController,
+    control: Control,
+    managed: bool,
+) -> ControlStatus {
+    let overdrive_configured = Path::new(system::MODULE_CONF_PATH).exists();
+    explain(
+        control,
+        system::run_mode(),
+        &check_gpu(controller),
+        managed,
+        overdrive_configured,
+        &controller.get_controllable_nodes(),
+    )
+}
+
+fn explain(
+    control: Control,
+    run_mode: RunMode,
+    gpu: &GpuSelfTest,
+    managed: bool,
+    overdrive_configured: bool,
+    nodes: &[ControllableNode],
+) -> ControlStatus {
+    let unavailable = if run_mode == RunMode::User {
+        Some(ControlUnavailable::NotPrivileged)
+    } else if !gpu.controllable {
+        Some(ControlUnavailable::PassthroughDriver {
+            driver: gpu.driver.clone(),
+        })
+    } else if !managed {
+        Some(ControlUnavailable::Unmanaged)
+    } else if control == Control::Clocks && gpu.overclock_status != OverclockStatus::Available {
+        Some(match gpu.overclock_status {
+            OverclockStatus::DisabledInKernel => ControlUnavailable::OverdriveDisabled {
+                pending_reboot: overdrive_configured,
+            },
+            _ => ControlUnavailable::Unsupported {
+                file: control_files(control)[0].to_owned(),
+            },
+        })
+    } else {
+        check_files(control_files(control), nodes)
+    };
+
+    let message = match &unavailable {
+        None => "Available".to_owned(),
+        Some(ControlUnavailable::NotPrivileged) => {
+            "The daemon is running as a regular user, settings can only be changed when it runs as root".to_owned()
+        }
+        Some(ControlUnavailable::PassthroughDriver { driver }) => {
+            format!("The GPU is bound to {driver}, so it can't be controlled")
+        }
+        Some(ControlUnavailable::Unmanaged) => {
+            "Management of this GPU is disabled, enable it to change its settings".to_owned()
+        }
+        Some(ControlUnavailable::OverdriveDisabled {
+            pending_reboot: true,
+        }) => "Overdrive was enabled, but is only active after a reboot".to_owned(),
+        Some(ControlUnavailable::OverdriveDisabled {
+            pending_reboot: false,
+        }) => "Overdrive is disabled, enable it and reboot to change the clocks".to_owned(),
+        Some(ControlUnavailable::Unsupported { file }) => {
+            format!("The GPU or its driver version doesn't support this ({file} is missing)")
+        }
+        Some(ControlUnavailable::ReadOnly { path }) => {
+            format!("The driver doesn't allow changing {path}")
+        }
+    };
+
+    ControlStatus {
+        unavailable,
+        message,
+    }
+}
+
+fn check_files(files: &[&str], nodes: &[ControllableNode]) -> Option<ControlUnavailable> {
+    let matching: Vec<&ControllableNode> = nodes
+        .iter()
+        .filter(|node| {
+            Path::new(&node.path)
+                .file_name()
+                .is_some_and(|name| files.iter().any(|file| name == *file))
+        })
+        .collect();
+
+    if matching
+        .iter()
+        .any(|node| node.status == NodeStatus::Writable)
+    {
+        None
+    } else if let Some(node) = matching
+        .iter()
+        .find(|node| node.status == NodeStatus::ReadOnly)
+    {
+        Some(ControlUnavailable::ReadOnly {
+            path: node.path.clone(),
+        })
+    } else {
+        Some(ControlUnavailable::Unsupported {
+            file: files[0].to_owned(),
+        })
+    }
+}
+
+pub fn report(gpus: BTreeMap<String, GpuSelfTest>) -> SelfTestReport {
+    let overdrive_configured = Path::new(system::MODULE_CONF_PATH).exists();
+    build_report(
+        system::run_mode(),
+        system::is_overdrive_enabled(),
+        overdrive_configured,
+        gpus,
+    )
+}
+
+fn build_report(
+    run_mode: RunMode,
+    overdrive_enabled: Option<bool>,
+    overdrive_configured: bool,
+    gpus: BTreeMap<String, GpuSelfTest>,
+) -> SelfTestReport {
+    let mut issues = Vec::new();
+
+    if run_mode == RunMode::User {
+        issues.push(
+            "The daemon is running as a regular user, so GPU settings can't be changed. \
+            Run it as root with `systemctl enable --now lactd`"
+                .to_owned(),
+        );
+    }
+
+    let overdrive_pending_reboot = overdrive_enabled == Some(false) && overdrive_configured;
+    if overdrive_pending_reboot {
+        issues.push(format!(
+            "Overdrive is enabled in {}, but not active yet. \
+            Reboot, and regenerate the initramfs first if your distribution includes the module options in it",
+            system::MODULE_CONF_PATH
+        ));
+    } else if overdrive_enabled == Some(false) {
+        issues.push(
+            "Overdrive is disabled, so clocks and voltages can't be changed. \
+            Enable it with `enable_overdrive` or the `amdgpu.ppfeaturemask=0xffffffff` kernel parameter and reboot"
+                .to_owned(),
+        );
+    }
+
+    if gpus.is_empty() {
+        issues.push("No GPUs were found".to_owned());
+    }
+    for (id, gpu) in &gpus {
+        if !gpu.controllable {
+            issues.push(format!(
+                "GPU {id} is bound to {} and can't be controlled",
+                gpu.driver
+            ));
+        } else if run_mode == RunMode::System && !gpu.sysfs_writable {
+            issues.push(format!(
+                "The sysfs settings of GPU {id} are not writable, check that nothing restricts the daemon's access to /sys"
+            ));
+        } else if overdrive_enabled == Some(true)
+            && gpu.overclock_status == OverclockStatus::Unsupported
+        {
+            issues.push(format!(
+                "Overclocking is not supported by GPU {id} or its driver version"
+            ));
+        }
+    }
+
+    SelfTestReport {
+        run_mode,
+        overdrive_enabled,
+        overdrive_pending_reboot,
+        gpus,
+        issues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_report, explain};
+    use lact_schema::{
+        Control, ControlUnavailable, ControllableNode, GpuSelfTest, NodeStatus, OverclockStatus,
+        RunMode,
+    };
+    use std::collections::BTreeMap;
+
+    fn gpu(driver: &str, overclock_status: OverclockStatus) -> GpuSelfTest {
+        GpuSelfTest {
+            driver: driver.to_owned(),
+            controllable: driver == "amdgpu",
+            overclock_status,
+            sysfs_writable: true,
+        }
+    }
+
+    #[test]
+    fn control_explanations() {
+        let node = |path: &str, status| ControllableNode {
+            path: path.to_owned(),
+            status,
+            value: None,
+            range: None,
+            requires_manual_performance_level: false,
+        };
+        let nodes = [
+            node(
+                "/sys/class/drm/card1/device/pp_od_clk_voltage",
+                NodeStatus::Missing,
+            ),
+            node(
+                "/sys/class/drm/card1/device/hwmon/hwmon3/power1_cap",
+                NodeStatus::ReadOnly,
+            ),
+            node(
+                "/sys/class/drm/card1/device/hwmon/hwmon3/pwm1_enable",
+                NodeStatus::Missing,
+            ),
+            node(
+                "/sys/class/drm/card1/device/gpu_od/fan_ctrl/fan_curve",
+                NodeStatus::Writable,
+            ),
+        ];
+        let amdgpu = gpu("amdgpu", OverclockStatus::DisabledInKernel);
+        let explain_control = |control, run_mode, gpu: &GpuSelfTest, managed| {
+            explain(control, run_mode, gpu, managed, false, &nodes).unavailable
+        };
+
+        assert_eq!(
+            explain_control(Control::FanControl, RunMode::System, &amdgpu, true),
+            None
+        );
+        assert_eq!(
+            explain_control(Control::PowerCap, RunMode::System, &amdgpu, true),
+            Some(ControlUnavailable::ReadOnly {
+                path: "/sys/class/drm/card1/device/hwmon/hwmon3/power1_cap".to_owned()
+            })
+        );
+        assert_eq!(
+            explain_control(Control::Clocks, RunMode::System, &amdgpu, true),
+            Some(ControlUnavailable::OverdriveDisabled {
+                pending_reboot: false
+            })
+        );
+        assert_eq!(
+            explain_control(Control::PowerProfileMode, RunMode::System, &amdgpu, true),
+            Some(ControlUnavailable::Unsupported {
+                file: "pp_power_profile_mode".to_owned()
+            })
+        );
+        assert_eq!(
+            explain_control(Control::FanControl, RunMode::System, &amdgpu, false),
+            Some(ControlUnavailable::Unmanaged)
+        );
+        assert_eq!(
+            explain_control(
+                Control::FanControl,
+                RunMode::System,
+                &gpu("vfio-pci", OverclockStatus::Unsupported),
+                true
+            ),
+            Some(ControlUnavailable::PassthroughDriver {
+                driver: "vfio-pci".to_owned()
+            })
+        );
+        assert_eq!(
+            explain_control(Control::FanControl, RunMode::User, &amdgpu, true),
+            Some(ControlUnavailable::NotPrivileged)
+        );
+    }
+
+    #[test]
+    fn report_issues() {
+        let gpus = BTreeMap::from([
+            (
+                "gpu-1".to_owned(),
+                gpu("amdgpu", OverclockStatus::Available),
+            ),
+            (
+                "gpu-2".to_owned(),
+                gpu("vfio-pci", OverclockStatus::Unsupported),
+            ),
+        ]);
+        let report = build_report(RunMode::System, Some(true), true, gpus.clone());
+        assert!(!report.overdrive_pending_reboot);
+        assert_eq!(
+            report.issues,
+            ["GPU gpu-2 is bound to vfio-pci and can't be controlled"]
+        );
+
+        let report = build_report(RunMode::User, Some(false), true, gpus);
+        assert!(report.overdrive_pending_reboot);
+        assert_eq!(report.issues.len(), 3);
+        assert!(report.issues[1].contains("not active yet"));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::config::{self, diff_gpu_settings};
+use lact_schema::{SettingChange, SettingSource};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::error;
+
+/// Tracks when each setting of a GPU was last changed, and what changed it.
+/// This is only kept in memory, so after a restart all settings show up as restored on startup.
+#[derive(Default)]
+pub struct SettingsHistory {
+    applied: HashMap<String, config::Gpu>,
+    changes: HashMap<String, BTreeMap<String, SettingChange>>,
+}
+
+impl SettingsHistory {
+    /// Records the settings which differ from the previously applied ones
+    pub fn record(&mut self, id: &str, new_config: &config::Gpu, source: &SettingSource) {
+        let previous = self.applied.get(id).cloned().unwrap_or_default();
+        let diffs = match diff_gpu_settings(&previous, new_config) {
+            Ok(diffs) => diffs,
+            Err(err) => {
+                error!("could not compare settings of gpu {id}: {err:#}");
+                return;
+            }
+        };
+
+        let changed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|time| u64::try_from(time.as_millis()).ok())
+            .unwrap_or_default();
+        let changes = self.changes.entry(id.to_owned()).or_default();
+        for diff in diffs {
+            changes.insert(
+                diff.field,
+                SettingChange {
+                    changed_at,
+                    source: source.clone(),
+                },
+            );
+        }
+
+        self.applied.insert(id.to_owned(), new_config.clone());
+    }
+
+    pub fn changes(&self, id: &str) -> BTreeMap<String, SettingChange> {
+        self.changes.get(id).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SettingsHistory;
+    use crate::config::Gpu;
+    use lact_schema::SettingSource;
+
+    #[test]
+    fn records_changed_fields() {
+        let mut history = SettingsHistory::default();
+        let mut gpu = Gpu {
+            power_cap_microwatts: Some(200_000_000),
+            ..Default::default()
+        };
+        history.record("my-gpu", &gpu, &SettingSource::Startup);
+
+        gpu.clocks_configuration.max_core_clock = Some(2000);
+        history.record("my-gpu", &gpu, &SettingSource::Manual);
+
+        let changes = history.changes("my-gpu");
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            changes["power_cap_microwatts"].source,
+            SettingSource::Startup
+        );
+        assert_eq!(
+            changes["clocks_configuration.max_core_clock"].source,
+            SettingSource::Manual
+        );
+
+        // Applying the same settings again doesn't change their provenance
+        history.record(
+            "my-gpu",
+            &gpu,
+            &SettingSource::Schedule {
+                profile: Some("quiet".to_owned()),
+            },
+        );
+        assert_eq!(
+            history.changes("my-gpu")["power_cap_microwatts"].source,
+            SettingSource::Startup
+        );
+        assert!(history.changes("other-gpu").is_empty());
+    }
+}
@@ -1,17 +1,40 @@
+use crate::{config, socket};
 use anyhow::{anyhow, Context};
-use lact_schema::SystemInfo;
+use lact_schema::{
+    ApplyStats, AspmPolicy, Codec, DriverStack, PowerSource, RunMode, ScheduleStatus, SystemInfo,
+    TemperatureUnit,
+};
+use nix::unistd::getuid;
 use std::{
+    collections::BTreeMap,
     fs::{self, File, Permissions},
     io::Write,
     os::unix::prelude::PermissionsExt,
+    path::Path,
     process::Command,
 };
 
 const PP_OVERDRIVE_MASK: u64 = 0x4000;
 const PP_FEATURE_MASK_PATH: &str = "/sys/module/amdgpu/parameters/ppfeaturemask";
 pub const MODULE_CONF_PATH: &str = "/etc/modprobe.d/99-amdgpu-overdrive.conf";
+pub const ECC_MODULE_CONF_PATH: &str = "/etc/modprobe.d/99-amdgpu-ecc.conf";
+const ASPM_POLICY_PATH: &str = "/sys/module/pcie_aspm/parameters/policy";
+const RESET_METHOD_PATH: &str = "/sys/module/amdgpu/parameters/reset_method";
+const AMDGPU_MODULE_PATH: &str = "/sys/module/amdgpu";
+const RADEON_MODULE_PATH: &str = "/sys/module/radeon";
+/// Only set for out-of-tree builds of the module
+const AMDGPU_MODULE_VERSION_PATH: &str = "/sys/module/amdgpu/version";
+const AMDGPU_PRO_PATH: &str = "/opt/amdgpu-pro";
 
-pub fn info() -> anyhow::Result<SystemInfo<'static>> {
+pub fn info(
+    schedule: ScheduleStatus,
+    daemon_uptime_secs: u64,
+    apply_stats: ApplyStats,
+    groups: BTreeMap<String, Vec<String>>,
+    config_generation: u64,
+    power_source: Option<PowerSource>,
+    temperature_unit: TemperatureUnit,
+) -> anyhow::Result<SystemInfo<'static>> {
     let version = env!("CARGO_PKG_VERSION");
     let profile = if cfg!(debug_assertions) {
         "debug"
@@ -27,20 +50,89 @@ pub fn info() -> anyhow::Result<SystemInfo<'static>> {
         .trim()
         .to_owned();
 
-    let amdgpu_overdrive_enabled = if let Ok(mask) = read_current_mask() {
-        Some((mask & PP_OVERDRIVE_MASK) > 0)
-    } else {
-        None
-    };
+    let amdgpu_overdrive_enabled = is_overdrive_enabled();
 
     Ok(SystemInfo {
         version,
         profile,
         kernel_version,
         amdgpu_overdrive_enabled,
+        driver_stack: driver_stack(),
+        schedule,
+        daemon_uptime_secs,
+        apply_stats,
+        groups,
+        config_generation,
+        power_source,
+        features: enabled_features(),
+        temperature_unit,
+        run_mode: run_mode(),
+        config_dir: config::config_dir().to_string_lossy().into_owned(),
+        socket_path: socket::get_socket_path().to_string_lossy().into_owned(),
+        codecs: vec![Codec::Json, Codec::JsonRpc],
     })
 }
 
+/// The socket and config paths depend on the user as well, see `socket::get_socket_path`
+pub fn run_mode() -> RunMode {
+    if getuid().is_root() {
+        RunMode::System
+    } else {
+        RunMode::User
+    }
+}
+
+pub fn driver_stack() -> DriverStack {
+    let module_version = fs::read_to_string(AMDGPU_MODULE_VERSION_PATH).ok();
+    detect_driver_stack(
+        Path::new(AMDGPU_MODULE_PATH).exists(),
+        module_version.as_deref(),
+        Path::new(AMDGPU_PRO_PATH).exists(),
+        Path::new(RADEON_MODULE_PATH).exists(),
+    )
+}
+
+fn detect_driver_stack(
+    amdgpu_loaded: bool,
+    module_version: Option<&str>,
+    pro_installed: bool,
+    radeon_loaded: bool,
+) -> DriverStack {
+    let module_version = module_version
+        .map(str::trim)
+        .filter(|version| !version.is_empty())
+        .map(str::to_owned);
+
+    if !amdgpu_loaded {
+        return if radeon_loaded {
+            DriverStack::Radeon
+        } else {
+            DriverStack::Unknown
+        };
+    }
+
+    match (pro_installed, module_version) {
+        (true, module_version) => DriverStack::Pro { module_version },
+        (false, Some(module_version)) => DriverStack::Dkms { module_version },
+        (false, None) => DriverStack::Mainline,
+    }
+}
+
+fn enabled_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if cfg!(feature = "drm") {
+        features.push("drm".to_owned());
+    }
+    features
+}
+
+/// Whether overdrive is enabled in the amdgpu `ppfeaturemask` module parameter
+pub fn is_overdrive_enabled() -> Option<bool> {
+    read_current_mask()
+        .ok()
+        .map(|mask| (mask & PP_OVERDRIVE_MASK) > 0)
+}
+
 pub fn enable_overdrive() -> anyhow::Result<()> {
     let current_mask = read_current_mask()?;
 
@@ -61,6 +153,96 @@ pub fn enable_overdrive() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Sets the `ras_enable` module parameter, which is used after the next reboot
+pub fn set_ecc_mode(enabled: bool) -> anyhow::Result<()> {
+    let conf = format!("options amdgpu ras_enable={}", u8::from(enabled));
+
+    let mut file = File::create(ECC_MODULE_CONF_PATH).context("Could not open module conf file")?;
+    file.set_permissions(Permissions::from_mode(0o644))
+        .context("Could not conf file permissions")?;
+
+    file.write_all(conf.as_bytes())
+        .context("Could not write config")?;
+
+    Ok(())
+}
+
+/// The ECC mode written by `set_ecc_mode`, if any
+pub fn configured_ecc_mode() -> Option<bool> {
+    let conf = fs::read_to_string(ECC_MODULE_CONF_PATH).ok()?;
+    parse_ras_enable_conf(&conf)
+}
+
+fn parse_ras_enable_conf(conf: &str) -> Option<bool> {
+    conf.split_whitespace()
+        .find_map(|option| option.strip_prefix("ras_enable="))
+        .and_then(|value| match value {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        })
+}
+
+/// Parses the mask of the enabled RAS features from `ras/features` (`feature mask: 0x3fbb`)
+pub fn parse_ras_features(raw: &str) -> Option<u64> {
+    let (_, mask) = raw.split_once(':')?;
+    u64::from_str_radix(mask.trim().strip_prefix("0x")?, 16).ok()
+}
+
+pub fn get_aspm_policy() -> Option<AspmPolicy> {
+    let raw = fs::read_to_string(ASPM_POLICY_PATH).ok()?;
+    parse_aspm_policy(&raw)
+}
+
+pub fn set_aspm_policy(policy: AspmPolicy) -> anyhow::Result<()> {
+    fs::write(ASPM_POLICY_PATH, aspm_policy_name(policy))
+        .context("Could not set the ASPM policy, the firmware may not allow the OS to control ASPM")
+}
+
+/// Parses the active policy from the list of all policies, (`default [performance] powersave powersupersave`)
+fn parse_aspm_policy(raw: &str) -> Option<AspmPolicy> {
+    let active = raw
+        .split_whitespace()
+        .find_map(|name| name.strip_prefix('[')?.strip_suffix(']'))?;
+    [
+        AspmPolicy::Default,
+        AspmPolicy::Performance,
+        AspmPolicy::Powersave,
+        AspmPolicy::Powersupersave,
+    ]
+    .into_iter()
+    .find(|policy| aspm_policy_name(*policy) == active)
+}
+
+fn aspm_policy_name(policy: AspmPolicy) -> &'static str {
+    match policy {
+        AspmPolicy::Default => "default",
+        AspmPolicy::Performance => "performance",
+        AspmPolicy::Powersave => "powersave",
+        AspmPolicy::Powersupersave => "powersupersave",
+    }
+}
+
+pub fn get_driver_reset_method() -> Option<String> {
+    let raw = fs::read_to_string(RESET_METHOD_PATH).ok()?;
+    parse_reset_method(&raw).map(str::to_owned)
+}
+
+/// Names of the values of the `reset_method` amdgpu module parameter
+fn parse_reset_method(raw: &str) -> Option<&'static str> {
+    let name = match raw.trim().parse::<i32>().ok()? {
+        -1 => "auto",
+        0 => "legacy",
+        1 => "mode0",
+        2 => "mode1",
+        3 => "mode2",
+        4 => "baco",
+        5 => "pci",
+        _ => return None,
+    };
+    Some(name)
+}
+
 fn read_current_mask() -> anyhow::Result<u64> {
     let ppfeaturemask = fs::read_to_string(PP_FEATURE_MASK_PATH)?;
     let ppfeaturemask = ppfeaturemask
@@ -70,3 +252,79 @@ fn read_current_mask() -> anyhow::Result<u64> {
 
     u64::from_str_radix(ppfeaturemask, 16).context("Invalid ppfeaturemask")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        detect_driver_stack, parse_aspm_policy, parse_ras_enable_conf, parse_ras_features,
+        parse_reset_method,
+    };
+    use lact_schema::{AspmPolicy, DriverStack};
+
+    #[test]
+    fn driver_stack() {
+        assert_eq!(
+            detect_driver_stack(true, None, false, true),
+            DriverStack::Mainline
+        );
+        assert_eq!(
+            detect_driver_stack(true, Some("6.8.5\n"), false, false),
+            DriverStack::Dkms {
+                module_version: "6.8.5".to_owned()
+            }
+        );
+        assert_eq!(
+            detect_driver_stack(true, Some("6.8.5"), true, false),
+            DriverStack::Pro {
+                module_version: Some("6.8.5".to_owned())
+            }
+        );
+        assert_eq!(
+            detect_driver_stack(false, None, false, true),
+            DriverStack::Radeon
+        );
+        assert_eq!(
+            detect_driver_stack(false, None, true, false),
+            DriverStack::Unknown
+        );
+    }
+
+    #[test]
+    fn reset_method() {
+        assert_eq!(parse_reset_method("-1\n"), Some("auto"));
+        assert_eq!(parse_reset_method("4"), Some("baco"));
+        assert_eq!(parse_reset_method("9"), None);
+    }
+
+    #[test]
+    fn aspm_policy() {
+        assert_eq!(
+            parse_aspm_policy("default performance [powersave] powersupersave\n"),
+            Some(AspmPolicy::Powersave)
+        );
+        assert_eq!(
+            parse_aspm_policy("[default] performance powersave powersupersave"),
+            Some(AspmPolicy::Default)
+        );
+        assert_eq!(parse_aspm_policy("default performance"), None);
+    }
+
+    #[test]
+    fn ras_features() {
+        assert_eq!(parse_ras_features("feature mask: 0x3fbb\n"), Some(0x3fbb));
+        assert_eq!(parse_ras_features("0x3fbb"), None);
+    }
+
+    #[test]
+    fn ras_enable_conf() {
+        assert_eq!(
+            parse_ras_enable_conf("options amdgpu ras_enable=0"),
+            Some(false)
+        );
+        assert_eq!(parse_ras_enable_conf("options amdgpu ras_enable=-1"), None);
+        assert_eq!(
+            parse_ras_enable_conf("options amdgpu ppfeaturemask=0xffffffff"),
+            None
+        );
+    }
+}
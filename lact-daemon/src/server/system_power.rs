@@ -0,0 +1,151 @@
+//! Power sensors outside of the GPUs, for combining the CPU and GPU draw in efficiency dashboards.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tokio::time::sleep;
+
+const POWERCAP_PATH: &str = "/sys/class/powercap";
+const HW_MON_PATH: &str = "/sys/class/hwmon";
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+/// RAPL only reports consumed energy, so the power is averaged over this period
+const RAPL_SAMPLE_PERIOD: Duration = Duration::from_millis(200);
+
+struct RaplZone {
+    energy_path: PathBuf,
+    /// The energy counter wraps around after this value
+    max_energy_uj: u64,
+}
+
+/// Power of all CPU packages in watts, from the RAPL energy counters (available on Intel and recent AMD CPUs)
+pub async fn cpu_package_power() -> Option<f64> {
+    let zones = package_zones();
+    if zones.is_empty() {
+        return None;
+    }
+
+    let start_values = zones
+        .iter()
+        .map(|zone| read_u64(&zone.energy_path))
+        .collect::<Option<Vec<_>>>()?;
+    let start = Instant::now();
+    sleep(RAPL_SAMPLE_PERIOD).await;
+    let elapsed = start.elapsed();
+
+    zones
+        .iter()
+        .zip(start_values)
+        .map(|(zone, start_value)| {
+            let end_value = read_u64(&zone.energy_path)?;
+            energy_to_watts(start_value, end_value, zone.max_energy_uj, elapsed)
+        })
+        .sum()
+}
+
+/// Power of the whole system in watts, from an ACPI power meter or the battery while running on it
+pub fn platform_power() -> Option<f64> {
+    power_meter_power().or_else(battery_discharge_power)
+}
+
+/// Top-level zones such as `intel-rapl:0`, the subzones (`intel-rapl:0:0`) are parts of the package
+fn package_zones() -> Vec<RaplZone> {
+    let Ok(entries) = fs::read_dir(POWERCAP_PATH) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let file_name = path.file_name()?.to_str()?;
+            if !file_name.starts_with("intel-rapl:") || file_name.matches(':').count() != 1 {
+                return None;
+            }
+            let name = fs::read_to_string(path.join("name")).ok()?;
+            if !name.starts_with("package") {
+                return None;
+            }
+            Some(RaplZone {
+                energy_path: path.join("energy_uj"),
+                max_energy_uj: read_u64(&path.join("max_energy_range_uj"))?,
+            })
+        })
+        .collect()
+}
+
+fn power_meter_power() -> Option<f64> {
+    fs::read_dir(HW_MON_PATH)
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| {
+            fs::read_to_string(entry.path().join("name"))
+                .is_ok_and(|name| name.trim() == "power_meter")
+        })
+        .and_then(|entry| read_u64(&entry.path().join("power1_average")))
+        .map(microwatts_to_watts)
+}
+
+/// While charging, the battery power doesn't show what the system uses
+fn battery_discharge_power() -> Option<f64> {
+    let microwatts = fs::read_dir(POWER_SUPPLY_PATH)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            fs::read_to_string(path.join("type")).is_ok_and(|kind| kind.trim() == "Battery")
+                && fs::read_to_string(path.join("status"))
+                    .is_ok_and(|status| status.trim() == "Discharging")
+        })
+        .filter_map(|path| read_u64(&path.join("power_now")))
+        .reduce(|total, power| total + power)?;
+    Some(microwatts_to_watts(microwatts))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn energy_to_watts(
+    start_uj: u64,
+    end_uj: u64,
+    max_energy_uj: u64,
+    elapsed: Duration,
+) -> Option<f64> {
+    if elapsed.is_zero() {
+        return None;
+    }
+    let consumed_uj = if end_uj >= start_uj {
+        end_uj - start_uj
+    } else {
+        max_energy_uj.checked_sub(start_uj)? + end_uj
+    };
+    Some(consumed_uj as f64 / 1_000_000.0 / elapsed.as_secs_f64())
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn microwatts_to_watts(microwatts: u64) -> f64 {
+    microwatts as f64 / 1_000_000.0
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::energy_to_watts;
+    use std::time::Duration;
+
+    #[test]
+    fn rapl_energy_to_watts() {
+        let elapsed = Duration::from_millis(200);
+        assert_eq!(
+            energy_to_watts(1_000_000, 6_000_000, u64::MAX, elapsed),
+            Some(25.0)
+        );
+        // The counter wrapped around during the sample
+        assert_eq!(
+            energy_to_watts(9_000_000, 1_000_000, 10_000_000, elapsed),
+            Some(10.0)
+        );
+        assert_eq!(energy_to_watts(0, 100, 1000, Duration::ZERO), None);
+    }
+}
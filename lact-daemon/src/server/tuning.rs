@@ -0,0 +1,183 @@
+//! Conservative starting points for tuning a GPU, derived from its limits and current behaviour.
+//! The suggestions are small steps within what the driver allows, their stability still has to be tested.
+
+use crate::config;
+use lact_schema::{
+    amdgpu_sysfs::gpu_handle::overdrive::{ClocksTable, ClocksTableGen},
+    DeviceStats,
+};
+
+/// Increases are only suggested when all sensors are at least this far (in °C) from their critical temperature
+const THERMAL_HEADROOM: f32 = 15.0;
+const POWER_CAP_STEP_PERCENT: u64 = 5;
+const CLOCK_STEP_PERCENT: i32 = 2;
+/// In millivolts
+const UNDERVOLT_OFFSET: i32 = -25;
+
+/// The parts of the clocks table used for the suggestions
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClockLimits {
+    pub max_core_clock: Option<i32>,
+    pub max_core_clock_limit: Option<i32>,
+    pub voltage_offset_supported: bool,
+}
+
+impl ClockLimits {
+    pub fn from_table(table: &ClocksTableGen) -> Self {
+        Self {
+            max_core_clock: table.get_max_sclk(),
+            max_core_clock_limit: table.get_max_sclk_range().and_then(|range| range.max),
+            voltage_offset_supported: matches!(
+                table,
+                ClocksTableGen::Vega20(table) if table.voltage_offset.is_some()
+            ),
+        }
+    }
+}
+
+/// Returns the current settings with the suggestions applied, along with an explanation of each suggestion
+pub fn suggest(
+    current: &config::Gpu,
+    clocks: ClockLimits,
+    stats: &DeviceStats,
+) -> (config::Gpu, Vec<String>) {
+    let mut settings = current.clone();
+    let mut notes = Vec::new();
+
+    let headroom = thermal_headroom(stats);
+    let can_increase = match headroom {
+        Some(headroom) if headroom >= THERMAL_HEADROOM => true,
+        Some(headroom) => {
+            notes.push(format!("The GPU is only {headroom:.0}°C away from its critical temperature, so no increases are suggested"));
+            false
+        }
+        None => {
+            notes.push(
+                "The critical temperatures are not known, so no increases are suggested".to_owned(),
+            );
+            false
+        }
+    };
+
+    if clocks.voltage_offset_supported && current.clocks_configuration.voltage_offset.is_none() {
+        settings.clocks_configuration.voltage_offset = Some(UNDERVOLT_OFFSET);
+        notes.push(format!(
+            "Voltage offset of {UNDERVOLT_OFFSET} mV, which lowers the power draw and temperature. Reduce it if the GPU becomes unstable"
+        ));
+    } else if can_increase {
+        let suggested = clocks
+            .max_core_clock
+            .zip(clocks.max_core_clock_limit)
+            .and_then(|(max_core_clock, limit)| {
+                let suggested =
+                    (max_core_clock + max_core_clock * CLOCK_STEP_PERCENT / 100).min(limit);
+                (suggested > max_core_clock).then_some((max_core_clock, suggested))
+            });
+        if let Some((max_core_clock, suggested)) = suggested {
+            settings.clocks_configuration.max_core_clock = Some(suggested);
+            notes.push(format!(
+                "Maximum core clock of {suggested} MHz, {CLOCK_STEP_PERCENT}% above the current {max_core_clock} MHz"
+            ));
+        }
+    }
+
+    if can_increase && stats.is_power_capped {
+        let suggested = stats
+            .power
+            .cap_current_microwatts
+            .zip(stats.power.cap_max)
+            .and_then(|(cap, cap_max)| {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let cap_max = (cap_max * 1_000_000.0) as u64;
+                let suggested = (cap + cap * POWER_CAP_STEP_PERCENT / 100).min(cap_max);
+                (suggested > cap).then_some(suggested)
+            });
+        if let Some(suggested) = suggested {
+            settings.power_cap_microwatts = Some(suggested);
+            notes.push(format!(
+                "Power cap of {} W, as the GPU is being limited by its current power cap",
+                suggested / 1_000_000
+            ));
+        }
+    }
+
+    if notes.is_empty() {
+        notes.push("The GPU is already at its limits, there is nothing to suggest".to_owned());
+    }
+
+    (settings, notes)
+}
+
+/// Smallest distance of any sensor to its critical temperature
+fn thermal_headroom(stats: &DeviceStats) -> Option<f32> {
+    stats
+        .temps
+        .iter()
+        .filter_map(|(sensor, temp)| {
+            let crit = stats
+                .temp_limits
+                .get(sensor)
+                .and_then(|limits| limits.crit)
+                .or(temp.crit)?;
+            Some(crit - temp.current?)
+        })
+        .reduce(f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{suggest, ClockLimits};
+    use crate::config;
+    use lact_schema::{amdgpu_sysfs::hw_mon::Temperature, DeviceStats, TemperatureLimits};
+
+    fn stats(junction: f32) -> DeviceStats {
+        let mut stats = DeviceStats {
+            is_power_capped: true,
+            ..Default::default()
+        };
+        stats.temps.insert(
+            "junction".to_owned(),
+            Temperature {
+                current: Some(junction),
+                crit: None,
+                crit_hyst: None,
+            },
+        );
+        stats.temp_limits.insert(
+            "junction".to_owned(),
+            TemperatureLimits {
+                crit: Some(110.0),
+                crit_hyst: None,
+                emergency: None,
+            },
+        );
+        stats.power.cap_current_microwatts = Some(200_000_000);
+        stats.power.cap_max = Some(205.0);
+        stats
+    }
+
+    #[test]
+    fn tuning_suggestions() {
+        let clocks = ClockLimits {
+            max_core_clock: Some(2500),
+            max_core_clock_limit: Some(3000),
+            voltage_offset_supported: false,
+        };
+
+        let (settings, notes) = suggest(&config::Gpu::default(), clocks, &stats(70.0));
+        assert_eq!(settings.clocks_configuration.max_core_clock, Some(2550));
+        assert_eq!(settings.power_cap_microwatts, Some(205_000_000));
+        assert_eq!(notes.len(), 2);
+
+        let (settings, _) = suggest(&config::Gpu::default(), clocks, &stats(100.0));
+        assert_eq!(settings, config::Gpu::default());
+
+        let undervolt = ClockLimits {
+            voltage_offset_supported: true,
+            ..clocks
+        };
+        let (settings, _) = suggest(&config::Gpu::default(), undervolt, &stats(100.0));
+        assert_eq!(settings.clocks_configuration.voltage_offset, Some(-25));
+        assert_eq!(settings.clocks_configuration.max_core_clock, None);
+    }
+}
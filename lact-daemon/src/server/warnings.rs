@@ -0,0 +1,44 @@
+//! Advisories for requests which succeeded with caveats, sent next to the response data.
+
+use std::{cell::RefCell, future::Future};
+
+tokio::task_local! {
+    static WARNINGS: RefCell<Vec<String>>;
+}
+
+/// Runs the handling of a request, collecting the warnings added while it runs
+pub async fn collect<T>(future: impl Future<Output = T>) -> (T, Vec<String>) {
+    WARNINGS
+        .scope(RefCell::new(Vec::new()), async {
+            let output = future.await;
+            let warnings = WARNINGS.with(RefCell::take);
+            (output, warnings)
+        })
+        .await
+}
+
+/// Adds a warning to the response of the current request. Outside of a request it is only logged.
+pub fn add(warning: impl Into<String>) {
+    let warning = warning.into();
+    tracing::warn!("{warning}");
+    let _ = WARNINGS.try_with(|warnings| warnings.borrow_mut().push(warning));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add, collect};
+
+    #[tokio::test]
+    async fn collect_warnings() {
+        let ((), warnings) = collect(async {
+            add("reboot required");
+            tokio::task::yield_now().await;
+            add("value clamped");
+        })
+        .await;
+        assert_eq!(warnings, ["reboot required", "value clamped"]);
+
+        // Not collected when outside of a request
+        add("ignored");
+    }
+}
@@ -3,7 +3,7 @@ use nix::{
     sys::stat::{umask, Mode},
     unistd::{chown, getuid, Gid, Group},
 };
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{env, fs, path::PathBuf, str::FromStr};
 use tokio::net::UnixListener;
 use tracing::{debug, info};
 
@@ -11,6 +11,8 @@ pub fn get_socket_path() -> PathBuf {
     let uid = getuid();
     if uid.is_root() {
         PathBuf::from_str("/var/run/lactd.sock").unwrap()
+    } else if let Some(runtime_dir) = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        PathBuf::from(runtime_dir).join("lactd.sock")
     } else {
         PathBuf::from_str(&format!("/var/run/user/{uid}/lactd.sock")).unwrap()
     }
@@ -1,5 +1,6 @@
 use crate::server::handler::Handler;
 use futures::StreamExt;
+use lact_schema::SettingSource;
 use tracing::{error, info};
 use zbus::{Connection, Proxy};
 
@@ -10,7 +11,7 @@ pub async fn listen_events(handler: Handler) {
             Ok(mut stream) => {
                 while stream.next().await.is_some() {
                     info!("suspend/resume event detected, reloading config");
-                    handler.load_config().await;
+                    handler.load_config(&SettingSource::Resume).await;
                 }
             }
             Err(err) => error!("could not subscribe to suspend events: {err:#}"),
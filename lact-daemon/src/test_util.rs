@@ -0,0 +1,46 @@
+//! Helpers shared by the tests.
+
+use std::{
+    env, fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// Directory in the system temp dir, removed when dropped (including when the test panics)
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// The name has to be unique across the tests, as they run in parallel
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("lact-test-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        // The temp dir may be behind a symlink, while sysfs paths get resolved by the code under test
+        Self(fs::canonicalize(path).unwrap())
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A directory with the given files, whose paths are relative to it and may include subdirectories
+pub fn fake_sysfs(name: &str, files: &[(&str, &str)]) -> TempDir {
+    let dir = TempDir::new(name);
+    for (file, contents) in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
@@ -37,7 +37,10 @@ impl Header {
 
     pub fn set_devices(&self, gpus: &[DeviceListEntry<'_>]) {
         for (i, entry) in gpus.iter().enumerate() {
-            let name = format!("{i}: {}", entry.name.unwrap_or_default());
+            let name = format!(
+                "{i}: {}",
+                entry.label.as_deref().or(entry.name).unwrap_or_default()
+            );
             self.gpu_selector.append(Some(entry.id), &name);
         }
 
@@ -11,8 +11,11 @@ use glib::clone;
 use gtk::glib::{timeout_future, ControlFlow};
 use gtk::{gio::ApplicationFlags, prelude::*, *};
 use header::Header;
+use lact_client::schema::amdgpu_sysfs::gpu_handle::{
+    overdrive::ClocksTableGen, power_profile_mode::PowerProfileModesTable,
+};
 use lact_client::schema::request::{ConfirmCommand, SetClocksCommand};
-use lact_client::schema::DeviceStats;
+use lact_client::schema::{DeviceStats, PowerStates};
 use lact_client::DaemonClient;
 use lact_daemon::MODULE_CONF_PATH;
 use root_stack::RootStack;
@@ -110,9 +113,10 @@ impl App {
                     let gpu_id = current_gpu_id.borrow().clone();
 
                     match app.daemon_client.set_clocks_value(&gpu_id, SetClocksCommand::Reset)
-                        .and_then(|_| app.daemon_client.confirm_pending_config(ConfirmCommand::Confirm))
+                        .and_then(|_| app.daemon_client.confirm())
                     {
-                        Ok(()) => {
+                        Ok(_) => {
+                            app.show_warnings();
                             app.set_initial(&gpu_id);
                         }
                         Err(err) => {
@@ -124,7 +128,9 @@ impl App {
                 app.apply_revealer.connect_apply_button_clicked(
                     clone!(@strong app, @strong current_gpu_id => move || {
                         glib::idle_add_local_once(clone!(@strong app, @strong current_gpu_id => move || {
-                            if let Err(err) = app.apply_settings(current_gpu_id.clone()) {
+                            let result = app.apply_settings(current_gpu_id.clone());
+                            app.show_warnings();
+                            if let Err(err) = result {
                                 show_error(&app.window, err.context("Could not apply settings"));
 
                                 glib::idle_add_local_once(clone!(@strong app, @strong current_gpu_id => move || {
@@ -198,17 +204,22 @@ impl App {
     }
 
     fn set_info(&self, gpu_id: &str) {
-        let info_buf = self
+        let snapshot_buf = self
             .daemon_client
-            .get_device_info(gpu_id)
-            .expect("Could not fetch info");
-        let info = info_buf.inner().unwrap();
+            .get_device_snapshot(gpu_id)
+            .expect("Could not fetch device snapshot");
+        let snapshot = snapshot_buf.inner().unwrap();
 
-        trace!("setting info {info:?}");
+        trace!("setting info {:?}", snapshot.info);
 
-        self.root_stack.info_page.set_info(&info);
+        self.root_stack.info_page.set_info(&snapshot.info);
 
-        self.set_initial(gpu_id);
+        self.set_initial_values(
+            &snapshot.stats,
+            snapshot.clocks_info.and_then(|info| info.table),
+            snapshot.power_profile_modes,
+            Some(snapshot.power_states),
+        );
     }
 
     fn set_initial(&self, gpu_id: &str) {
@@ -219,10 +230,6 @@ impl App {
             .expect("Could not fetch stats");
         let stats = stats_buf.inner().unwrap();
 
-        self.root_stack.oc_page.set_stats(&stats, true);
-        self.root_stack.thermals_page.set_stats(&stats, true);
-        self.root_stack.info_page.set_stats(&stats);
-
         let maybe_clocks_table = match self.daemon_client.get_device_clocks_info(gpu_id) {
             Ok(clocks_buf) => match clocks_buf.inner() {
                 Ok(info) => info.table,
@@ -236,7 +243,6 @@ impl App {
                 None
             }
         };
-        self.root_stack.oc_page.set_clocks_table(maybe_clocks_table);
 
         let maybe_modes_table = match self.daemon_client.get_device_power_profile_modes(gpu_id) {
             Ok(buf) => match buf.inner() {
@@ -251,23 +257,50 @@ impl App {
                 None
             }
         };
-        self.root_stack
-            .oc_page
-            .performance_frame
-            .set_power_profile_modes(maybe_modes_table);
 
-        match self
+        let maybe_power_states = match self
             .daemon_client
             .get_power_states(gpu_id)
             .and_then(|states| states.inner())
         {
-            Ok(power_states) => {
-                self.root_stack
-                    .oc_page
-                    .power_states_frame
-                    .set_power_states(power_states);
+            Ok(power_states) => Some(power_states),
+            Err(err) => {
+                warn!("could not get power states: {err:?}");
+                None
             }
-            Err(err) => warn!("could not get power states: {err:?}"),
+        };
+
+        self.set_initial_values(
+            &stats,
+            maybe_clocks_table,
+            maybe_modes_table,
+            maybe_power_states,
+        );
+    }
+
+    fn set_initial_values(
+        &self,
+        stats: &DeviceStats,
+        maybe_clocks_table: Option<ClocksTableGen>,
+        maybe_modes_table: Option<PowerProfileModesTable>,
+        maybe_power_states: Option<PowerStates>,
+    ) {
+        self.root_stack.oc_page.set_stats(stats, true);
+        self.root_stack.thermals_page.set_stats(stats, true);
+        self.root_stack.info_page.set_stats(stats);
+
+        self.root_stack.oc_page.set_clocks_table(maybe_clocks_table);
+
+        self.root_stack
+            .oc_page
+            .performance_frame
+            .set_power_profile_modes(maybe_modes_table);
+
+        if let Some(power_states) = maybe_power_states {
+            self.root_stack
+                .oc_page
+                .power_states_frame
+                .set_power_states(power_states);
         }
 
         // Show apply button on setting changes
@@ -350,7 +383,7 @@ impl App {
                 .context("Failed to set power cap")?;
 
             self.daemon_client
-                .confirm_pending_config(ConfirmCommand::Confirm)
+                .confirm()
                 .context("Could not commit config")?;
         }
 
@@ -359,7 +392,7 @@ impl App {
             .set_power_profile_mode(&gpu_id, None)
             .context("Could not set default power profile mode")?;
         self.daemon_client
-            .confirm_pending_config(ConfirmCommand::Confirm)
+            .confirm()
             .context("Could not commit config")?;
 
         if let Some(level) = self.root_stack.oc_page.get_performance_level() {
@@ -367,7 +400,7 @@ impl App {
                 .set_performance_level(&gpu_id, level)
                 .context("Failed to set power profile")?;
             self.daemon_client
-                .confirm_pending_config(ConfirmCommand::Confirm)
+                .confirm()
                 .context("Could not commit config")?;
 
             let mode_index = self
@@ -379,7 +412,7 @@ impl App {
                 .set_power_profile_mode(&gpu_id, mode_index)
                 .context("Could not set active power profile mode")?;
             self.daemon_client
-                .confirm_pending_config(ConfirmCommand::Confirm)
+                .confirm()
                 .context("Could not commit config")?;
         }
 
@@ -396,7 +429,7 @@ impl App {
                 )
                 .context("Could not set fan control")?;
             self.daemon_client
-                .confirm_pending_config(ConfirmCommand::Confirm)
+                .confirm()
                 .context("Could not commit config")?;
         }
 
@@ -442,17 +475,17 @@ impl App {
                     .context("Could not set power states")?;
 
                 self.daemon_client
-                    .confirm_pending_config(ConfirmCommand::Confirm)
+                    .confirm()
                     .context("Could not commit config")?;
             }
         }
 
         if !clocks_commands.is_empty() {
-            let delay = self
+            let change = self
                 .daemon_client
                 .batch_set_clocks_value(&gpu_id, clocks_commands)
                 .context("Could not commit clocks settins")?;
-            self.ask_confirmation(gpu_id.clone(), delay);
+            self.ask_confirmation(gpu_id.clone(), change.confirm_timeout.unwrap_or_default());
         }
 
         self.set_initial(&gpu_id);
@@ -473,8 +506,10 @@ impl App {
 
         dialog.run_async(clone!(@strong self as app => move |diag, response| {
             if response == ResponseType::Ok {
-                match app.daemon_client.enable_overdrive().and_then(|buffer| buffer.inner()) {
+                match app.daemon_client.enable_overdrive() {
                     Ok(_) => {
+                        // The reboot warning is already part of the success message
+                        app.daemon_client.take_warnings();
                         let success_dialog = MessageDialog::builder()
                             .title("Success")
                             .text("Overclocking successfully enabled. A system reboot is required to apply the changes")
@@ -541,9 +576,28 @@ impl App {
             if let Err(err) = app.daemon_client.confirm_pending_config(command) {
                 show_error(&app.window, err);
             }
+            app.show_warnings();
             app.set_initial(&gpu_id);
         }));
     }
+
+    /// Shows the warnings of the requests made since the last call, which also keeps the client from buffering them
+    fn show_warnings(&self) {
+        let warnings = self.daemon_client.take_warnings();
+        if warnings.is_empty() {
+            return;
+        }
+        let diag = MessageDialog::builder()
+            .title("Warning")
+            .message_type(MessageType::Warning)
+            .text(&warnings.join("\n"))
+            .buttons(ButtonsType::Close)
+            .transient_for(&self.window)
+            .build();
+        diag.run_async(|diag, _| {
+            diag.hide();
+        });
+    }
 }
 
 enum GuiUpdateMsg {
@@ -20,6 +20,9 @@ pub struct InformationPage {
     vram_type_label: Label,
     vram_peak_bw_label: Label,
     compute_units_label: Label,
+    shader_engines_label: Label,
+    rops_label: Label,
+    tmus_label: Label,
     l1_cache_label: Label,
     l2_cache_label: Label,
     l3_cache_label: Label,
@@ -54,6 +57,9 @@ impl InformationPage {
         let family_name = sequential_label_row("GPU Family:", &values_grid, &mut row);
         let asic_name = sequential_label_row("ASIC Name:", &values_grid, &mut row);
         let compute_units_label = sequential_label_row("Compute Units:", &values_grid, &mut row);
+        let shader_engines_label = sequential_label_row("Shader Engines:", &values_grid, &mut row);
+        let rops_label = sequential_label_row("ROPs:", &values_grid, &mut row);
+        let tmus_label = sequential_label_row("TMUs:", &values_grid, &mut row);
         let vbios_version_label = sequential_label_row("VBIOS Version:", &values_grid, &mut row);
         let driver_label = sequential_label_row("Driver Used:", &values_grid, &mut row);
 
@@ -110,6 +116,9 @@ impl InformationPage {
             resizable_bar_enabled,
             cpu_accessible_vram_label,
             compute_units_label,
+            shader_engines_label,
+            rops_label,
+            tmus_label,
             vram_peak_bw_label,
             l1_cache_label,
             l2_cache_label,
@@ -150,6 +159,9 @@ impl InformationPage {
         let mut family_name = "Unknown";
         let mut asic_name = "Unknown";
         let mut compute_units = "Unknown".to_owned();
+        let mut shader_engines = "Unknown".to_owned();
+        let mut rops = "Unknown".to_owned();
+        let mut tmus = "Unknown".to_owned();
         let mut vram_type = "Unknown";
         let mut vram_max_bw = "Unknown";
         let mut cpu_accessible_vram = "Unknown".to_owned();
@@ -162,6 +174,12 @@ impl InformationPage {
             family_name = &drm_info.family_name;
             asic_name = &drm_info.asic_name;
             compute_units = drm_info.compute_units.to_string();
+            shader_engines = format!(
+                "{} ({} shader arrays each)",
+                drm_info.shader_engines, drm_info.shader_arrays_per_engine
+            );
+            rops = drm_info.rops.to_string();
+            tmus = drm_info.tmus.to_string();
             vram_type = &drm_info.vram_type;
             vram_max_bw = &drm_info.vram_max_bw;
             l1_cache = format!("{} KiB", drm_info.l1_cache_per_cu / 1024);
@@ -184,6 +202,10 @@ impl InformationPage {
         self.asic_name.set_markup(&format!("<b>{asic_name}</b>"));
         self.compute_units_label
             .set_markup(&format!("<b>{compute_units}</b>"));
+        self.shader_engines_label
+            .set_markup(&format!("<b>{shader_engines}</b>"));
+        self.rops_label.set_markup(&format!("<b>{rops}</b>"));
+        self.tmus_label.set_markup(&format!("<b>{tmus}</b>"));
         self.vram_type_label
             .set_markup(&format!("<b>{vram_type}</b>"));
         self.vram_peak_bw_label
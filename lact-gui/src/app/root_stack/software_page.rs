@@ -26,6 +26,9 @@ impl SoftwarePage {
             .property("daemon-version", daemon_version)
             .property("gui-version", gui_version)
             .property("kernel-version", system_info.kernel_version)
+            .property("driver-stack", system_info.driver_stack.to_string())
+            .property("config-dir", system_info.config_dir)
+            .property("socket-path", system_info.socket_path)
             .build()
     }
 }
@@ -55,6 +58,12 @@ mod imp {
         gui_version: RefCell<String>,
         #[property(get, set)]
         kernel_version: RefCell<String>,
+        #[property(get, set)]
+        driver_stack: RefCell<String>,
+        #[property(get, set)]
+        config_dir: RefCell<String>,
+        #[property(get, set)]
+        socket_path: RefCell<String>,
     }
 
     #[glib::object_subclass]
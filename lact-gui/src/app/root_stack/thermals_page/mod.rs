@@ -3,7 +3,9 @@ mod fan_curve_frame;
 use glib::clone;
 use gtk::prelude::*;
 use gtk::*;
-use lact_client::schema::{default_fan_curve, DeviceStats, FanControlMode, FanCurveMap};
+use lact_client::schema::{
+    default_fan_curve, DeviceStats, FanControlInterface, FanControlMode, FanCurveMap,
+};
 
 use crate::app::page_section::PageSection;
 
@@ -24,6 +26,7 @@ pub struct ThermalsPage {
     pub container: Box,
     temperatures_label: Label,
     fan_speed_label: Label,
+    firmware_fan_control_label: Label,
     fan_static_speed_adjustment: Adjustment,
     fan_curve_frame: FanCurveFrame,
     fan_control_mode_stack: Stack,
@@ -77,6 +80,14 @@ impl ThermalsPage {
 
         fan_control_mode_stack.add_titled(&fan_static_speed_frame, Some("static"), "Static");
 
+        let firmware_fan_control_label = Label::builder()
+            .label("Fan control is handled by the GPU firmware: the curve is followed on the junction temperature with a fixed number of points, and the fan may still stop at low temperatures.")
+            .wrap(true)
+            .halign(Align::Start)
+            .visible(false)
+            .build();
+        fan_control_section.append(&firmware_fan_control_label);
+
         fan_control_section.append(&fan_control_mode_stack_switcher);
         fan_control_section.append(&fan_control_mode_stack);
 
@@ -92,6 +103,7 @@ impl ThermalsPage {
             container,
             temperatures_label,
             fan_speed_label,
+            firmware_fan_control_label,
             fan_static_speed_adjustment,
             fan_curve_frame,
             fan_control_mode_stack,
@@ -115,16 +127,21 @@ impl ThermalsPage {
         self.temperatures_label
             .set_markup(&format!("<b>{temperatures_text}</b>",));
 
-        match stats.fan.speed_current {
-            Some(fan_speed_current) => self.fan_speed_label.set_markup(&format!(
-                "<b>{} RPM ({}%)</b>",
-                fan_speed_current,
-                (fan_speed_current as f64
-                    / stats.fan.speed_max.unwrap_or(fan_speed_current) as f64
-                    * 100.0)
-                    .round()
-            )),
-            None => self.fan_speed_label.set_text("No fan detected"),
+        self.firmware_fan_control_label
+            .set_visible(stats.fan.fan_control_interface == Some(FanControlInterface::Firmware));
+
+        match (stats.fan.speed_current, stats.fan.speed_percent) {
+            (Some(fan_speed_current), Some(speed_percent)) => {
+                self.fan_speed_label.set_markup(&format!(
+                    "<b>{} RPM ({}%)</b>",
+                    fan_speed_current,
+                    speed_percent.round()
+                ))
+            }
+            (Some(fan_speed_current), None) => self
+                .fan_speed_label
+                .set_markup(&format!("<b>{fan_speed_current} RPM</b>")),
+            (None, _) => self.fan_speed_label.set_text("No fan detected"),
         }
 
         if initial {
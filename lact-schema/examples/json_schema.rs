@@ -0,0 +1,9 @@
+//! Prints the JSON Schema of the protocol
+
+fn main() {
+    let schema = lact_schema::json_schema::protocol_schema();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).expect("Could not serialize schema")
+    );
+}
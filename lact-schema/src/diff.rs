@@ -0,0 +1,55 @@
+//! Field by field comparison of values through their JSON representation
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// A field which has a different value on each side
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ValueDiff {
+    /// Path of the field, with nested fields separated by dots (e.g. `stats.clockspeed.gpu_clockspeed`)
+    pub field: String,
+    /// `None` when the field is not set
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Lists the fields which differ between two values, in alphabetical order.
+/// Objects are compared recursively, any other values (including arrays) as a whole.
+pub fn diff_json(before: &Value, after: &Value) -> Vec<ValueDiff> {
+    let mut diffs = Vec::new();
+    diff_values("", Some(before), Some(after), &mut diffs);
+    diffs
+}
+
+fn diff_values(
+    path: &str,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    diffs: &mut Vec<ValueDiff>,
+) {
+    // Unset values are often skipped when serializing, so missing and null values are treated the same
+    let before = before.filter(|value| !value.is_null());
+    let after = after.filter(|value| !value.is_null());
+
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_values(&path, before.get(key), after.get(key), diffs);
+            }
+        }
+        (before, after) if before != after => diffs.push(ValueDiff {
+            field: path.to_owned(),
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => (),
+    }
+}
@@ -0,0 +1,99 @@
+//! JSON Schema of the protocol, for generating clients in other languages.
+//! Print it with `cargo run -p lact-schema --example json_schema --features schema`.
+//!
+//! The types from amdgpu-sysfs don't implement `JsonSchema`, so they are described by the definitions below,
+//! which have to be kept in sync with their serde representation.
+
+use crate::{
+    ClocksInfo, DaemonEvent, DayTime, DeviceInfo, DeviceListEntry, DeviceStats, PowerStates,
+    Request, Response, SystemInfo,
+};
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{InstanceType, RootSchema, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PerformanceLevel {
+    Auto,
+    Low,
+    High,
+    Manual,
+}
+
+#[derive(JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerLevelKind {
+    CoreClock,
+    MemoryClock,
+    SOCClock,
+    FabricClock,
+    DCEFClock,
+    PcieSpeed,
+}
+
+#[derive(JsonSchema)]
+pub struct PowerProfileModesTable {
+    /// Names of the modes by their index
+    pub modes: BTreeMap<u16, String>,
+    pub active: u16,
+}
+
+/// Raw clocks table, the contents of which depend on the table format
+#[derive(JsonSchema)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum ClocksTableGen {
+    Vega10(Value),
+    Vega20(Value),
+}
+
+#[derive(JsonSchema)]
+pub struct Range {
+    pub min: Option<i32>,
+    pub max: Option<i32>,
+}
+
+/// In °C
+#[derive(JsonSchema)]
+pub struct Temperature {
+    pub current: Option<f32>,
+    pub crit: Option<f32>,
+    pub crit_hyst: Option<f32>,
+}
+
+/// Serialized as a `HH:MM` string instead of its fields
+impl JsonSchema for DayTime {
+    fn schema_name() -> String {
+        "DayTime".to_owned()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some("^[0-9]{1,2}:[0-9]{2}$".to_owned()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// Schema of the requests, with the definitions of the responses and the core types
+pub fn protocol_schema() -> RootSchema {
+    let mut generator = SchemaGenerator::new(SchemaSettings::draft07());
+    generator.subschema_for::<Response<Value>>();
+    generator.subschema_for::<DeviceListEntry>();
+    generator.subschema_for::<DeviceInfo>();
+    generator.subschema_for::<DeviceStats>();
+    generator.subschema_for::<ClocksInfo>();
+    generator.subschema_for::<PowerStates>();
+    generator.subschema_for::<SystemInfo>();
+    generator.subschema_for::<DaemonEvent>();
+    generator.into_root_schema_for::<Request>()
+}
@@ -1,18 +1,23 @@
 #[cfg(feature = "args")]
 pub mod args;
+pub mod diff;
+#[cfg(feature = "schema")]
+pub mod json_schema;
 pub mod request;
 mod response;
+pub mod units;
 
 #[cfg(test)]
 mod tests;
 
 pub use amdgpu_sysfs;
 pub use request::Request;
-pub use response::Response;
+pub use response::{Response, ResponseTiming, ResponseWarnings};
 
 use amdgpu_sysfs::{
     gpu_handle::{
-        overdrive::{ClocksTable, ClocksTableGen},
+        overdrive::{ClocksTable, ClocksTableGen, Range},
+        power_profile_mode::PowerProfileModesTable,
         PerformanceLevel,
     },
     hw_mon::Temperature,
@@ -22,10 +27,12 @@ use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     collections::{BTreeMap, HashMap},
+    fmt,
     str::FromStr,
 };
 
 #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum FanControlMode {
     Static,
@@ -59,30 +66,660 @@ pub fn default_fan_curve() -> FanCurveMap {
     .into()
 }
 
+/// When a setting was last changed, and what changed it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SettingChange {
+    /// Unix timestamp in milliseconds
+    pub changed_at: u64,
+    pub source: SettingSource,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingSource {
+    /// Restored from the config when the daemon started
+    Startup,
+    /// Re-applied from the config after resuming from suspend
+    Resume,
+    /// Changed through a settings request
+    Manual,
+    /// Switch to a profile from a schedule or the power source profiles, or back to the base settings when `profile` is empty
+    Schedule { profile: Option<String> },
+    /// Replaced the whole config
+    Import,
+    /// Re-read the config file after it was edited
+    Reload,
+}
+
+/// Axis ranges and the recommended curve for editing a fan curve
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FanCurveDefaults {
+    pub temperature_min: i32,
+    pub temperature_max: i32,
+    pub speed_min: f32,
+    pub speed_max: f32,
+    pub default_curve: FanCurveMap,
+}
+
+impl Default for FanCurveDefaults {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl FanCurveDefaults {
+    /// The temperature axis goes up to the critical temperature of the sensor (or 100°C when it is unknown)
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn new(crit_temperature: Option<f32>) -> Self {
+        let temperature_min = 20;
+        let temperature_max = crit_temperature
+            .map(|crit| crit.ceil() as i32)
+            .filter(|crit| *crit > temperature_min)
+            .unwrap_or(100);
+
+        Self {
+            temperature_min,
+            temperature_max,
+            speed_min: 0.0,
+            speed_max: 1.0,
+            default_curve: default_fan_curve(),
+        }
+    }
+}
+
+/// Preset power cap, relative to the power cap limits of the GPU
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PowerTier {
+    /// 80% of the default power cap
+    Eco,
+    /// The default power cap
+    Balanced,
+    /// The maximum power cap
+    Turbo,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PowerTiers {
+    /// Power cap (in microwatts) of each tier which is available on the GPU
+    pub tiers: Vec<(PowerTier, u64)>,
+    /// Tier which matches the current power cap, if any
+    pub active: Option<PowerTier>,
+}
+
+/// Fan curve which is used by the firmware when fan control is not enabled
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DefaultFanCurve {
+    pub curve: FanCurveMap,
+    /// Whether the firmware curve could not be read, and a generic default curve is returned instead
+    pub synthetic: bool,
+}
+
+/// One tick of the fan curve, as recorded for `GetFanControlLog`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FanDecision {
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// The temperature the curve was evaluated at, before the curve offset
+    pub temperature: Option<f32>,
+    pub temperature_source: FanTemperatureSource,
+    /// Speed from the main curve
+    pub curve_pwm: u8,
+    /// Speed which was written to the fan
+    pub pwm: u8,
+    /// The spindown curve kept the fan faster than the main curve
+    pub spindown_held: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FanTemperatureSource {
+    /// The configured sensor
+    Sensor,
+    /// The temperature set with `SetSimulatedTemperature`
+    Simulated,
+    /// The configured sensor was not available, so the hottest sensor of the GPU was used
+    HottestSensor,
+    /// No temperature could be read, so the fan was set to full speed
+    Unavailable,
+}
+
+/// A setting which has a different value in a profile than in the current settings
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SettingsDiff {
+    /// Path of the setting, with nested settings separated by dots (e.g. `fan_control_settings.mode`)
+    pub field: String,
+    /// `None` when the setting is not set
+    pub current: Option<serde_json::Value>,
+    pub profile: Option<serde_json::Value>,
+}
+
+/// The settings of a GPU as requested in the config, next to the values which are actually in effect
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EffectiveConfig {
+    /// The configured settings, in the format of a `gpus` entry of the config. These are the defaults when nothing is configured.
+    pub requested: serde_json::Value,
+    /// The settings read back from the GPU. Settings which can't be read back keep the requested value.
+    pub effective: serde_json::Value,
+    /// Settings whose effective value differs from the requested one, `before` being the requested value
+    pub diff: Vec<diff::ValueDiff>,
+}
+
+/// Suggested settings for getting started with tuning the GPU, which are not tested for stability
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TuningSuggestions {
+    /// The current settings with the suggestions applied, in the format of a `gpus` entry of the config.
+    /// These can be applied with `ApplyDeviceConfig`.
+    pub settings: serde_json::Value,
+    /// Explanation of each suggestion, or why nothing is suggested
+    pub notes: Vec<String>,
+}
+
+/// A settings change which is reverted unless it gets confirmed with `ConfirmPendingConfig`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PendingConfigStatus {
+    pub id: String,
+    /// Milliseconds left until the change is reverted
+    pub remaining_ms: u64,
+    /// Paths of the changed settings, as in `SettingsDiff`
+    pub changed_fields: Vec<String>,
+}
+
+/// What changed after reloading the config file
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ConfigReloadSummary {
+    /// Paths of the changed settings (as in `SettingsDiff`) for each GPU, taking the active profile into account
+    pub gpus: BTreeMap<String, Vec<String>>,
+    pub profiles_changed: bool,
+    /// The schedules or the power source profiles changed
+    pub schedules_changed: bool,
+    /// The apply settings timer, unmanaged GPUs, groups or daemon settings changed.
+    /// Daemon settings such as the log level or the stats endpoint only take effect after a restart.
+    pub other_changed: bool,
+}
+
+/// A powerplay feature from the `pp_features` sysfs file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PpFeature {
+    pub name: String,
+    /// Bit of the feature in the features mask
+    pub mask: u64,
+    pub enabled: bool,
+}
+
+/// A process that has the GPU open, as reported by the DRM usage stats
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GpuProcess {
+    pub pid: i32,
+    pub name: String,
+    /// VRAM usage in bytes
+    pub vram_usage: Option<u64>,
+    /// GTT usage in bytes
+    pub gtt_usage: Option<u64>,
+    /// Total time the process has spent on each engine, in nanoseconds
+    pub engine_usage: BTreeMap<String, u64>,
+}
+
+/// Checks that a fan curve has at least two points and that all speeds are between 0 and 1.
+/// Temperatures are always strictly increasing, as the map is sorted and can't contain duplicate keys.
+pub fn validate_fan_curve(curve: &FanCurveMap) -> Result<(), String> {
+    if curve.len() < 2 {
+        return Err("Fan curve must have at least two points".to_owned());
+    }
+
+    for (temp, speed) in curve {
+        if !(0.0..=1.0).contains(speed) {
+            return Err(format!(
+                "Fan speed {speed} at {temp}°C is out of range, it must be between 0 and 1"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Calculates the PWM value (0-255) of a fan curve at the given temperature, the same way the daemon does.
+/// Between the points the speed is interpolated linearly, outside of them the speed of the nearest point is used.
+/// Returns `None` for an empty curve.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn evaluate_fan_curve(curve: &FanCurveMap, temp: i32) -> Option<u8> {
+    let maybe_lower = curve.range(..temp).next_back();
+    let maybe_higher = curve.range(temp..).next();
+
+    let percentage = match (maybe_lower, maybe_higher) {
+        (Some((lower_temp, lower_speed)), Some((higher_temp, higher_speed))) => {
+            let speed_ratio = (temp - lower_temp) as f32 / (higher_temp - lower_temp) as f32;
+            lower_speed + (higher_speed - lower_speed) * speed_ratio
+        }
+        (Some((_, lower_speed)), None) => *lower_speed,
+        (None, Some((_, higher_speed))) => *higher_speed,
+        (None, None) => return None,
+    };
+
+    Some((f32::from(u8::MAX) * percentage) as u8)
+}
+
+/// How the daemon was started. In user mode it can only read the GPU information and stats,
+/// as changing the GPU settings requires root.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    /// Running as root, usually as the system service
+    #[default]
+    System,
+    /// Running as a regular user, with a per-user socket and config
+    User,
+}
+
+/// Kernel driver and userspace stack used for AMD GPUs. The available controls differ between them,
+/// for example the out-of-tree modules can lag behind or ahead of the sysfs interface of the running kernel.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DriverStack {
+    /// The amdgpu driver included in the kernel
+    Mainline,
+    /// The amdgpu-pro userspace, with the kernel module it was installed with
+    Pro { module_version: Option<String> },
+    /// An out-of-tree amdgpu module (`amdgpu-dkms`, such as the one from ROCm) with the regular userspace
+    Dkms { module_version: String },
+    /// Only the legacy radeon driver is loaded, which LACT can't control
+    Radeon,
+    /// No AMD kernel driver is loaded
+    #[default]
+    Unknown,
+}
+
+impl fmt::Display for DriverStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriverStack::Mainline => write!(f, "amdgpu"),
+            DriverStack::Pro {
+                module_version: Some(version),
+            } => write!(f, "amdgpu-pro (module {version})"),
+            DriverStack::Pro {
+                module_version: None,
+            } => write!(f, "amdgpu-pro"),
+            DriverStack::Dkms { module_version } => write!(f, "amdgpu-dkms {module_version}"),
+            DriverStack::Radeon => write!(f, "radeon"),
+            DriverStack::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Unit for showing temperatures. The daemon always uses °C, other units are only for presentation.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub fn from_celsius(self, temp: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => temp,
+            TemperatureUnit::Fahrenheit => temp * 9.0 / 5.0 + 32.0,
+        }
+    }
+
+    pub fn to_celsius(self, temp: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => temp,
+            TemperatureUnit::Fahrenheit => (temp - 32.0) * 5.0 / 9.0,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+
+    /// Converts the temperatures of a curve from the daemon to this unit, rounded to whole degrees
+    pub fn curve_from_celsius(self, curve: &FanCurveMap) -> FanCurveMap {
+        convert_curve(curve, |temp| self.from_celsius(temp))
+    }
+
+    /// Converts the temperatures of a curve in this unit to °C for sending it to the daemon.
+    /// As the daemon uses whole degrees, points less than a degree apart may get merged.
+    pub fn curve_to_celsius(self, curve: &FanCurveMap) -> FanCurveMap {
+        convert_curve(curve, |temp| self.to_celsius(temp))
+    }
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn convert_curve(curve: &FanCurveMap, convert: impl Fn(f32) -> f32) -> FanCurveMap {
+    curve
+        .iter()
+        .map(|(temp, speed)| (convert(*temp as f32).round() as i32, *speed))
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Pong;
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SystemInfo<'a> {
     pub version: &'a str,
     pub profile: &'a str,
     pub kernel_version: String,
     pub amdgpu_overdrive_enabled: Option<bool>,
+    #[serde(default)]
+    pub driver_stack: DriverStack,
+    pub schedule: ScheduleStatus,
+    pub daemon_uptime_secs: u64,
+    pub apply_stats: ApplyStats,
+    /// Defined GPU groups and their member ids
+    pub groups: BTreeMap<String, Vec<String>>,
+    /// Current value of `AppliedChange::generation`
+    pub config_generation: u64,
+    /// Whether the system currently runs on AC or battery power. `None` on systems without a battery.
+    #[serde(default)]
+    pub power_source: Option<PowerSource>,
+    /// Optional features the daemon was built with, such as `drm` for the device info and metrics read through libdrm
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Unit in which clients should show temperatures
+    #[serde(default)]
+    pub temperature_unit: TemperatureUnit,
+    #[serde(default)]
+    pub run_mode: RunMode,
+    /// Directory with the config file and the presets
+    #[serde(default)]
+    pub config_dir: String,
+    /// Socket the daemon is listening on
+    #[serde(default)]
+    pub socket_path: String,
+    /// Message formats the daemon accepts. The format of a connection is chosen by its first request.
+    /// Empty for daemons from before this was reported, which only accept `json` and `json_rpc`.
+    #[serde(default)]
+    pub codecs: Vec<Codec>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Codec {
+    /// Line-delimited `{"command": ..., "args": ...}` requests
+    Json,
+    /// JSON-RPC 2.0 requests, with the command name as the method
+    JsonRpc,
+}
+
+/// Response to a request which changes settings
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AppliedChange {
+    /// Incremented by the daemon on every change of the settings, including reverts and scheduled profile switches.
+    /// Comparing it with `SystemInfo::config_generation` shows whether the settings were changed by someone else since.
+    pub generation: u64,
+    /// Seconds until the change is reverted, unless it is confirmed with `ConfirmPendingConfig`.
+    /// `None` when the change doesn't have to be confirmed.
+    pub confirm_timeout: Option<u64>,
+    /// Value which is used after the change, for settings where it can differ from the requested one
+    pub effective: Option<serde_json::Value>,
+}
+
+/// Counters of configuration applies since the daemon was started
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApplyStats {
+    pub successful: u32,
+    pub failed: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ScheduleStatus {
+    /// Profile which is currently applied by a schedule or for the current power source
+    pub active_profile: Option<String>,
+    /// Unix timestamp (in seconds) of the next time a schedule starts or ends
+    pub next_transition: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Schedule {
+    pub profile: String,
+    pub time_range: TimeRange,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+/// Profiles which get applied automatically depending on the power source.
+/// A matching schedule takes priority over them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PowerSourceProfiles {
+    pub ac_profile: Option<String>,
+    pub battery_profile: Option<String>,
+}
+
+impl PowerSourceProfiles {
+    pub fn profile(&self, source: PowerSource) -> Option<&str> {
+        match source {
+            PowerSource::Ac => self.ac_profile.as_deref(),
+            PowerSource::Battery => self.battery_profile.as_deref(),
+        }
+    }
+}
+
+/// A daily time range. The end may be before the start, in which case the range wraps around midnight.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TimeRange {
+    pub start: DayTime,
+    pub end: DayTime,
+}
+
+impl TimeRange {
+    /// Checks if the given time falls into the range. The start is inclusive, the end is exclusive.
+    pub fn contains(&self, time: DayTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Time of day, represented as `HH:MM`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct DayTime {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl DayTime {
+    pub fn minute_of_day(self) -> u16 {
+        u16::from(self.hour) * 60 + u16::from(self.minute)
+    }
+}
+
+impl FromStr for DayTime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hour, minute) = s
+            .split_once(':')
+            .ok_or_else(|| format!("invalid time `{s}`, expected HH:MM"))?;
+        let hour: u8 = hour
+            .parse()
+            .map_err(|_| format!("invalid hour in time `{s}`"))?;
+        let minute: u8 = minute
+            .parse()
+            .map_err(|_| format!("invalid minute in time `{s}`"))?;
+
+        if hour > 23 || minute > 59 {
+            return Err(format!("time `{s}` is out of range"));
+        }
+        Ok(Self { hour, minute })
+    }
+}
+
+impl TryFrom<String> for DayTime {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<DayTime> for String {
+    fn from(time: DayTime) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for DayTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeviceListEntry<'a> {
     pub id: &'a str,
     pub name: Option<&'a str>,
+    /// Nickname given to the GPU with `SetDeviceLabel`
+    #[serde(default)]
+    pub label: Option<String>,
+    pub runtime_pm_status: Option<RuntimePmStatus>,
+    /// Current temperature (in degrees celsius). Only read while the GPU is active, to avoid waking it up.
+    pub temperature: Option<f32>,
+    /// Whether LACT manages the GPU, which can be disabled with `SetManaged`
+    pub managed: bool,
+    /// Whether LACT has any settings configured for the GPU
+    pub configured: bool,
+    /// Kernel driver the device is bound to
+    pub driver: &'a str,
+    /// Whether the settings of the GPU can be changed.
+    /// This is not the case for GPUs bound to a passthrough driver such as `vfio-pci`, which are only listed.
+    pub controllable: bool,
+    /// Whether the GPU was used for the display when the system booted, see `DeviceInfo::boot_vga`
+    #[serde(default)]
+    pub boot_vga: bool,
+}
+
+/// Runtime power management status of a device, as reported in `power/runtime_status`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RuntimePmStatus {
+    Active,
+    Suspended,
+    Suspending,
+    Resuming,
+    Error,
+    Unsupported,
+}
+
+impl FromStr for RuntimePmStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(Self::Active),
+            "suspended" => Ok(Self::Suspended),
+            "suspending" => Ok(Self::Suspending),
+            "resuming" => Ok(Self::Resuming),
+            "error" => Ok(Self::Error),
+            "unsupported" => Ok(Self::Unsupported),
+            _ => Err(format!("unknown runtime pm status `{s}`")),
+        }
+    }
+}
+
+/// PCI ids of the cards which settings are meant for.
+/// Without the subsystem ids, the settings apply to all cards with the given GPU.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[allow(clippy::struct_field_names)]
+pub struct DeviceMatch {
+    pub vendor_id: String,
+    pub device_id: String,
+    #[serde(default)]
+    pub subsystem_vendor_id: Option<String>,
+    #[serde(default)]
+    pub subsystem_device_id: Option<String>,
+}
+
+impl DeviceMatch {
+    /// `None` if the card doesn't match, otherwise whether it also matches the subsystem ids
+    pub fn match_kind(&self, pci_info: &GpuPciInfo) -> Option<bool> {
+        let matches = |expected: &str, actual: &str| expected.eq_ignore_ascii_case(actual);
+
+        if !matches(&self.vendor_id, &pci_info.device_pci_info.vendor_id)
+            || !matches(&self.device_id, &pci_info.device_pci_info.model_id)
+        {
+            return None;
+        }
+
+        let subsystem_ids = [
+            (
+                &self.subsystem_vendor_id,
+                &pci_info.subsystem_pci_info.vendor_id,
+            ),
+            (
+                &self.subsystem_device_id,
+                &pci_info.subsystem_pci_info.model_id,
+            ),
+        ];
+        let mut exact_match = true;
+        for (expected, actual) in subsystem_ids {
+            match expected {
+                Some(expected) if !matches(expected, actual) => return None,
+                Some(_) => (),
+                None => exact_match = false,
+            }
+        }
+        Some(exact_match)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct GpuPciInfo {
     pub device_pci_info: PciInfo,
     pub subsystem_pci_info: PciInfo,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeviceInfo<'a> {
     #[serde(borrow)]
     pub pci_info: Option<Cow<'a, GpuPciInfo>>,
@@ -91,14 +728,440 @@ pub struct DeviceInfo<'a> {
     pub vbios_version: Option<String>,
     pub link_info: LinkInfo,
     pub drm_info: Option<DrmInfo>,
+    pub overclock_status: OverclockStatus,
+    /// `None` when the GPU doesn't support error correction
+    pub ecc: Option<EccInfo>,
+    /// Manufacturer of the card (such as Sapphire or ASUS), from the subsystem vendor in the PCI ID database
+    #[serde(default)]
+    pub board_vendor: Option<String>,
+    /// Name of the card model, from the subsystem device in the PCI ID database
+    #[serde(default)]
+    pub board_model: Option<String>,
+    /// Whether the firmware used this GPU for the display when the system booted, from the `boot_vga` sysfs file.
+    /// This is usually the GPU driving the primary display, which can't be fully reset while the system is running.
+    #[serde(default)]
+    pub boot_vga: bool,
+    /// Width (in bits) of the memory bus, as reported by the driver in the DRM device info.
+    /// Cut-down cards can have a narrower bus than other cards with the same chip.
+    #[serde(default)]
+    pub memory_bus_width: Option<u32>,
+    /// Memory regions of the PCI device, `None` when they can't be read
+    #[serde(default)]
+    pub pci_bars: Option<PciBarInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PciBarInfo {
+    /// Sizes (in bytes) of the used BARs, by their index
+    pub sizes: BTreeMap<u8, u64>,
+    /// Whether the CPU can access all of the VRAM at once (Resizable BAR, also called Smart Access Memory).
+    /// `None` when the VRAM size is not known.
+    pub resizable_bar_enabled: Option<bool>,
+    /// Whether the device supports resizing its BARs, which is also needed for enabling it in the firmware settings
+    pub resizable_bar_supported: bool,
+}
+
+/// Memory error correction mode. It is set with the `ras_enable` parameter of the amdgpu module,
+/// so changes only take effect after a reboot and apply to all GPUs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EccInfo {
+    pub enabled: bool,
+    /// Mode which will be used after a reboot, when it is different from the current one
+    pub pending: Option<bool>,
+}
+
+/// Log event of the daemon, sent to clients subscribed with `SubscribeLogs`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LogRecord {
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub level: LogLevel,
+    /// Module which logged the event
+    pub target: String,
+    pub message: String,
 }
 
+/// Ordered from the most to the least severe
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+/// Kinds of events to receive with `Subscribe`, the ones which are not set are not sent
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EventMask {
+    /// Send the stats of every GPU at this interval
+    #[serde(default)]
+    pub stats_interval_ms: Option<u64>,
+    /// Send the log records up to this level
+    #[serde(default)]
+    pub logs: Option<LogLevel>,
+    /// Send GPU resets and configuration changes
+    #[serde(default)]
+    pub device_events: bool,
+}
+
+/// Event sent to clients subscribed with `Subscribe`
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DaemonEvent {
+    Stats {
+        id: String,
+        stats: Box<DeviceStats>,
+    },
+    Log(LogRecord),
+    /// The GPU recovered from a reset, after which the daemon re-applies its settings
+    GpuReset {
+        id: String,
+        /// Approximate unix timestamp in milliseconds
+        reset_at: Option<u64>,
+    },
+    /// The configuration was changed, with the new value of `SystemInfo::config_generation`
+    ConfigChanged {
+        generation: u64,
+    },
+}
+
+/// The HDMI/DisplayPort audio function of a GPU, which has its own runtime power management
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AudioDeviceInfo {
+    /// PCI slot of the audio function, such as `0000:0b:00.1`
+    pub pci_slot: String,
+    pub driver: Option<String>,
+    pub runtime_pm_status: Option<RuntimePmStatus>,
+    /// Whether the device is kept active instead of being suspended while unused (`power/control` is `on`)
+    pub pinned_active: bool,
+}
+
+/// Links of a GPU to the other GPUs of the same hive
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InterconnectInfo {
+    pub kind: InterconnectKind,
+    /// Id of the GPU within the interconnect
+    pub device_id: String,
+    /// Id of the group of connected GPUs
+    pub hive_id: Option<String>,
+    /// Number of errors detected on the links, `None` when the driver doesn't report it
+    pub error_count: Option<u64>,
+    pub links: Vec<InterconnectLink>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum InterconnectKind {
+    /// AMD Infinity Fabric links
+    Xgmi,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InterconnectLink {
+    /// PCI slot of the peer GPU
+    pub peer: String,
+    /// Number of hops to reach the peer, 1 when it is connected directly
+    pub hops: Option<u8>,
+    /// Number of direct links to the peer
+    pub links: Option<u8>,
+}
+
+/// PCIe Active State Power Management settings of a GPU
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AspmInfo {
+    /// System-wide policy of the `pcie_aspm` module, `None` when the kernel was built without ASPM support
+    pub policy: Option<AspmPolicy>,
+    /// Whether each power state of the GPU's link (such as `l1_aspm`) is enabled, when the kernel exposes them
+    pub link_states: BTreeMap<String, bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AspmPolicy {
+    /// Use the settings configured by the firmware
+    Default,
+    /// Disable ASPM for the lowest latency
+    Performance,
+    Powersave,
+    /// Like `Powersave`, with the L1 substates also enabled
+    Powersupersave,
+}
+
+/// Ways in which a GPU can be reset while the system is running
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ResetMethod {
+    /// Recovery through the driver, the same as after a GPU hang. It requires debugfs.
+    Driver,
+    /// Reset of the PCI function through the `reset` sysfs file
+    Pci,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ResetMethods {
+    pub available: Vec<ResetMethod>,
+    /// Kind of reset the driver does on recovery (such as `mode1`, `mode2` or `baco`), from the `reset_method` parameter of the amdgpu module.
+    /// It can only be changed with the module parameter, `auto` lets the driver pick one that the GPU supports.
+    pub driver_reset_method: Option<String>,
+    /// PCI reset mechanisms the kernel can use for the `pci` method, from the `reset_method` sysfs file
+    pub pci_reset_methods: Vec<String>,
+}
+
+/// VRAM page which was retired by the driver after memory errors were detected in it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RetiredPage {
+    /// Page frame number, the address of the page divided by its size
+    pub page_frame: u64,
+    /// Size of the page in bytes
+    pub size: u64,
+    pub status: RetiredPageStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RetiredPageStatus {
+    /// The page is not used anymore
+    Reserved,
+    /// The page will be retired once it's not in use
+    Pending,
+    /// The page could not be reserved
+    Failed,
+}
+
+/// A settings file from the presets directory which applies to a GPU, see `ListPresets`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PresetInfo {
+    pub name: String,
+    pub description: Option<String>,
+    /// Path of the preset file
+    pub path: String,
+    /// Whether the preset was made for this exact card model (the subsystem ids match),
+    /// instead of all cards with the same GPU
+    pub exact_match: bool,
+}
+
+/// Result of a `SelfTest`, for finding out why settings can't be changed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SelfTestReport {
+    pub run_mode: RunMode,
+    /// `None` when the amdgpu module parameters can't be read
+    pub overdrive_enabled: Option<bool>,
+    /// Overdrive was enabled in the module options, but the driver was not reloaded since
+    pub overdrive_pending_reboot: bool,
+    pub gpus: BTreeMap<String, GpuSelfTest>,
+    /// Detected problems, each with a suggestion on how to fix it
+    pub issues: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GpuSelfTest {
+    pub driver: String,
+    /// `false` for GPUs bound to a passthrough driver
+    pub controllable: bool,
+    pub overclock_status: OverclockStatus,
+    /// Whether the daemon has write access to the settings in sysfs
+    pub sysfs_writable: bool,
+}
+
+/// A group of GPU settings, for `ExplainControl`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Control {
+    Clocks,
+    PowerCap,
+    FanControl,
+    PerformanceLevel,
+    PowerProfileMode,
+    PowerStates,
+}
+
+/// Whether a control can be used, with an explanation for showing to the user (such as in a tooltip)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ControlStatus {
+    /// `None` when the control can be used
+    pub unavailable: Option<ControlUnavailable>,
+    pub message: String,
+}
+
+/// Why a control can't be used. When there are several reasons, the one which has to be fixed first is reported.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum ControlUnavailable {
+    /// The daemon is running in user mode
+    NotPrivileged,
+    /// The GPU is bound to a passthrough driver such as `vfio-pci`
+    PassthroughDriver { driver: String },
+    /// Management of the GPU was disabled with `SetManaged`
+    Unmanaged,
+    /// Overdrive is disabled in the `ppfeaturemask` kernel parameter
+    OverdriveDisabled {
+        /// Overdrive was enabled in the module options, but the driver was not reloaded since
+        pending_reboot: bool,
+    },
+    /// The GPU or driver doesn't provide the sysfs file of the control
+    Unsupported { file: String },
+    /// The sysfs file exists, but the driver doesn't allow writing to it
+    ReadOnly { path: String },
+}
+
+/// Why the clocks of a GPU can or can't be adjusted
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OverclockStatus {
+    /// The clocks table is available and can be changed
+    Available,
+    /// Overdrive is disabled in the `ppfeaturemask` kernel parameter, it can be enabled with `EnableOverdrive`
+    DisabledInKernel,
+    /// Overdrive is enabled, but the GPU or its driver doesn't support it
+    Unsupported,
+}
+
+/// Every value of the `gpu_metrics` table reported by the firmware, for debugging.
+/// The available fields depend on the table format, and unsupported fields are left out.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SmuMetrics {
+    pub format_revision: u8,
+    pub content_revision: u8,
+    /// Values by field name, as defined in the kernel's `kgd_pp_interface.h`.
+    /// Temperatures are in °C (°mC for the APU core temperatures), powers in W and clocks in MHz.
+    pub fields: BTreeMap<String, serde_json::Value>,
+}
+
+/// Sysfs file used for controlling the GPU
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ControllableNode {
+    pub path: String,
+    pub status: NodeStatus,
+    /// Contents of the file, `None` if it can't be read
+    pub value: Option<String>,
+    /// Range of accepted values, when the driver reports it in separate `_min`/`_max` files
+    pub range: Option<NodeRange>,
+    /// Writes only have an effect while `power_dpm_force_performance_level` is set to `manual`
+    #[serde(default)]
+    pub requires_manual_performance_level: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStatus {
+    Writable,
+    /// The file exists, but the driver doesn't allow writing to it
+    ReadOnly,
+    /// The file doesn't exist, usually because the GPU or driver version doesn't support it
+    Missing,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NodeRange {
+    pub min: i64,
+    pub max: i64,
+}
+
+/// Full contents of `pp_power_profile_mode`, including the heuristics used by each mode
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PowerProfileModesDetails {
+    /// Mode which is currently used by the driver
+    pub active: u16,
+    /// Mode from the settings, `None` when no mode is configured.
+    /// It only takes effect with the `manual` performance level, otherwise the driver chooses the active mode itself.
+    #[serde(default)]
+    pub requested: Option<u16>,
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "Option<json_schema::PerformanceLevel>")
+    )]
+    pub performance_level: Option<PerformanceLevel>,
+    /// Names of the heuristics, in the same order as the values of each row
+    pub columns: Vec<String>,
+    pub modes: Vec<PowerProfileModeDetails>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PowerProfileModeDetails {
+    pub index: u16,
+    pub name: String,
+    /// Empty on GPUs which don't report the heuristics
+    pub rows: Vec<PowerProfileModeRow>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PowerProfileModeRow {
+    /// Clock the values apply to (e.g. `GFXCLK`), on GPUs which have separate values per clock
+    pub clock_type: Option<String>,
+    /// `None` for heuristics which are not used by the mode
+    pub values: Vec<Option<i32>>,
+}
+
+/// Everything needed to show the page of a GPU, fetched in a single request.
+/// Values which are not supported by the GPU are empty.
+#[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeviceSnapshot<'a> {
+    #[serde(borrow)]
+    pub info: DeviceInfo<'a>,
+    pub stats: DeviceStats,
+    pub clocks_info: Option<ClocksInfo>,
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "Option<json_schema::PowerProfileModesTable>")
+    )]
+    pub power_profile_modes: Option<PowerProfileModesTable>,
+    pub power_states: PowerStates,
+}
+
+impl DeviceSnapshot<'_> {
+    /// Compares the snapshot with a later one, listing every field of the info, stats and clocks that changed
+    pub fn diff(&self, other: &DeviceSnapshot) -> serde_json::Result<Vec<diff::ValueDiff>> {
+        let before = serde_json::to_value(self)?;
+        let after = serde_json::to_value(other)?;
+        Ok(diff::diff_json(&before, &after))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DrmInfo {
     pub family_name: String,
     pub asic_name: String,
     pub chip_class: String,
     pub compute_units: u32,
+    pub shader_engines: u32,
+    pub shader_arrays_per_engine: u32,
+    pub rops: u32,
+    pub tmus: u32,
     pub vram_type: String,
     pub vram_bit_width: u32,
     pub vram_max_bw: String,
@@ -109,6 +1172,7 @@ pub struct DrmInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DrmMemoryInfo {
     pub cpu_accessible_used: u64,
     pub cpu_accessible_total: u64,
@@ -116,11 +1180,64 @@ pub struct DrmMemoryInfo {
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClocksInfo {
     pub max_sclk: Option<i32>,
     pub max_mclk: Option<i32>,
     pub max_voltage: Option<i32>,
+    pub table_format: Option<ClocksTableFormat>,
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "Option<json_schema::ClocksTableGen>")
+    )]
     pub table: Option<ClocksTableGen>,
+    #[serde(default)]
+    pub control: ClocksControl,
+    /// Clockspeeds (in MHz) of the core clock power states, which can be selected with `SetClocksCommand::CoreState`.
+    /// Only filled in for GPUs with discrete clocks.
+    #[serde(default)]
+    pub core_states: Vec<u64>,
+    /// Clockspeeds (in MHz) of the memory clock power states, which can be selected with `SetClocksCommand::MemoryState`.
+    /// Only filled in for GPUs with discrete clocks.
+    #[serde(default)]
+    pub memory_states: Vec<u64>,
+}
+
+/// How the clockspeeds of a GPU can be changed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ClocksControl {
+    /// Any value in the ranges of the clocks table can be set
+    #[default]
+    Continuous,
+    /// The GPU has no clocks table (for example without overdrive support),
+    /// and only one of its fixed power states can be selected
+    Discrete,
+}
+
+/// A clocks table setting, used for looking up its allowed values
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ClockDomain {
+    MaxCoreClock,
+    MinCoreClock,
+    MaxMemoryClock,
+    MinMemoryClock,
+    MaxVoltage,
+    MinVoltage,
+}
+
+/// Format of the `pp_od_clk_voltage` table, which determines the available settings
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ClocksTableFormat {
+    /// Vega10 and older: voltages are set per power level, without a voltage offset
+    Vega10,
+    /// Vega20 and newer: only the min/max levels are set, with a global voltage offset on RDNA
+    Vega20,
 }
 
 impl From<ClocksTableGen> for ClocksInfo {
@@ -128,16 +1245,78 @@ impl From<ClocksTableGen> for ClocksInfo {
         let max_sclk = table.get_max_sclk();
         let max_mclk = table.get_max_mclk();
         let max_voltage = table.get_max_sclk_voltage();
+        let table_format = match table {
+            ClocksTableGen::Vega10(_) => ClocksTableFormat::Vega10,
+            ClocksTableGen::Vega20(_) => ClocksTableFormat::Vega20,
+        };
         Self {
             max_sclk,
             max_mclk,
             max_voltage,
+            table_format: Some(table_format),
             table: Some(table),
+            control: ClocksControl::Continuous,
+            core_states: Vec::new(),
+            memory_states: Vec::new(),
         }
     }
 }
 
+/// Points of the voltage-frequency curve of a GPU, ordered by clockspeed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct VoltageCurve {
+    pub points: Vec<VoltageCurvePoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct VoltageCurvePoint {
+    /// Clockspeed in MHz
+    pub clockspeed: i32,
+    /// Voltage in mV
+    pub voltage: i32,
+    /// Allowed clockspeeds for this point, when the driver reports them
+    #[cfg_attr(feature = "schema", schemars(with = "Option<json_schema::Range>"))]
+    pub clockspeed_range: Option<Range>,
+    /// Allowed voltages for this point, when the driver reports them
+    #[cfg_attr(feature = "schema", schemars(with = "Option<json_schema::Range>"))]
+    pub voltage_range: Option<Range>,
+}
+
+/// Vega10 and older GPUs use the core clock power levels as the curve, with the same ranges for every level.
+/// Newer GPUs have a separate curve, which is empty on GPUs that only support a voltage offset.
+impl From<&ClocksTableGen> for VoltageCurve {
+    fn from(table: &ClocksTableGen) -> Self {
+        let points = match table {
+            ClocksTableGen::Vega10(table) => table
+                .sclk_levels
+                .iter()
+                .map(|level| VoltageCurvePoint {
+                    clockspeed: level.clockspeed,
+                    voltage: level.voltage,
+                    clockspeed_range: Some(table.od_range.sclk),
+                    voltage_range: table.od_range.vddc,
+                })
+                .collect(),
+            ClocksTableGen::Vega20(table) => table
+                .vddc_curve
+                .iter()
+                .enumerate()
+                .map(|(i, level)| VoltageCurvePoint {
+                    clockspeed: level.clockspeed,
+                    voltage: level.voltage,
+                    clockspeed_range: table.od_range.curve_sclk_points.get(i).copied(),
+                    voltage_range: table.od_range.curve_voltage_points.get(i).copied(),
+                })
+                .collect(),
+        };
+        Self { points }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LinkInfo {
     pub current_width: Option<String>,
     pub current_speed: Option<String>,
@@ -146,6 +1325,7 @@ pub struct LinkInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct VulkanInfo {
     pub device_name: String,
     pub api_version: String,
@@ -156,6 +1336,7 @@ pub struct VulkanInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct VulkanDriverInfo {
     pub version: u32,
     pub name: Option<String>,
@@ -164,6 +1345,7 @@ pub struct VulkanDriverInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PciInfo {
     pub vendor_id: String,
     pub vendor: Option<String>,
@@ -171,61 +1353,394 @@ pub struct PciInfo {
     pub model: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DeviceStats {
+    /// Unix timestamp (in milliseconds) of when the stats were read
+    pub sampled_at: u64,
+    /// How long ago (in milliseconds) the stats were read, when they were served from the cache
+    /// because of the daemon's `min_stats_interval_ms`
+    #[serde(default)]
+    pub cache_age_ms: u64,
+    /// While the GPU is runtime suspended, only the stats which the daemon keeps in memory (such as the residencies) are filled in,
+    /// as reading the others would wake it up
+    #[serde(default)]
+    pub runtime_pm_status: Option<RuntimePmStatus>,
     pub fan: FanStats,
     pub clockspeed: ClockspeedStats,
     pub voltage: VoltageStats,
     pub vram: VramStats,
     pub power: PowerStats,
+    /// Temperatures in °C, keyed by sensor
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "HashMap<String, json_schema::Temperature>")
+    )]
     pub temps: HashMap<String, Temperature>,
+    #[serde(default)]
+    pub temp_limits: HashMap<String, TemperatureLimits>,
+    /// In percent (0-100)
     pub busy_percent: Option<u8>,
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "Option<json_schema::PerformanceLevel>")
+    )]
     pub performance_level: Option<PerformanceLevel>,
     pub core_power_state: Option<usize>,
     pub memory_power_state: Option<usize>,
+    /// Whether the memory clock is at its lowest power state while higher ones are available.
+    /// Memory stuck in this state can cause stutter, which can be avoided by disabling the lowest memory power state.
+    pub memory_parked: Option<bool>,
+    /// Whether the memory clock is kept at its highest level while the GPU is idle, because of the connected displays.
+    /// The driver does this when changing the clock could cause flickering, such as with multiple or high refresh rate displays,
+    /// which increases the idle power usage.
+    #[serde(default)]
+    pub memory_held_by_displays: Option<bool>,
+    /// Number of enabled display outputs on this GPU
+    #[serde(default)]
+    pub active_displays: Option<usize>,
     pub pcie_power_state: Option<usize>,
+    /// Number of GPU resets reported by the driver since boot
+    pub reset_count: u32,
+    /// Unix timestamp (in milliseconds) of the last GPU reset
+    pub last_reset_at: Option<u64>,
+    pub adaptive_clocks: Option<AdaptiveClocksStatus>,
+    /// State of the memory clock boost, when it is enabled with `SetMemoryClockBoost`
+    #[serde(default)]
+    pub memory_clock_boost: Option<MemoryClockBoostStatus>,
+    /// Progress of a `SetClocksRamped` request, while it's running
+    #[serde(default)]
+    pub clocks_ramp: Option<ClocksRampStatus>,
+    /// Energy consumed (in joules) since the driver was loaded, on GPUs which report it in `gpu_metrics`
+    pub energy_consumed: Option<f64>,
+    /// Whether the power draw is currently being limited by the power cap
+    pub is_power_capped: bool,
+    /// Share of time (in percent) each engine (such as `gfx`, `compute`, `dma`, `dec` and `enc`) was busy since the previous read of the stats,
+    /// from the engine time reported by the processes using the GPU. Empty on the first read.
+    #[serde(default)]
+    pub engine_activity: HashMap<String, f32>,
+    /// Share of time (in percent) spent in each core clock power state over the last minute
+    pub pstate_residency: Vec<(usize, f64)>,
+    /// Rate of change of each temperature sensor in °C per second, over the last 10 seconds
+    pub temp_trend: HashMap<String, f32>,
+    /// Fraction of time (from 0 to 1) spent throttled for each reason over the last minute, on GPUs which report it in `gpu_metrics`.
+    /// Multiple reasons can be active at the same time.
+    #[serde(default)]
+    pub throttle_residency: HashMap<ThrottleReason, f32>,
+    #[serde(default)]
+    pub gfxoff: GfxoffStats,
+    /// Whether settings applied by the daemon were changed by something else, such as another tool writing to sysfs
+    #[serde(default)]
+    pub external_modification_detected: bool,
+    /// Settings which no longer have the value applied by the daemon
+    #[serde(default)]
+    pub external_modifications: Vec<String>,
+    /// Current clockspeed (in MHz) of every clock domain the GPU reports, such as `gfx`, `mem`, `fclk`, `socclk` and `dcefclk`
+    #[serde(default)]
+    pub clocks: HashMap<String, f64>,
+    /// Errors of the values that could not be read, for debugging. Unsupported values are not included.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub read_errors: BTreeMap<String, String>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// How often a value of the stats could not be read, keyed like `DeviceStats::read_errors`.
+/// Temperature sensors which returned no value are keyed as `temps.<sensor>`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReadFailureCount {
+    /// Failures since the daemon started
+    pub total: u64,
+    pub last_minute: usize,
+    pub last_error: String,
+}
+
+/// Category of the limit which is making the firmware lower the clockspeeds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleReason {
+    Power,
+    Current,
+    Temperature,
+    Other,
+}
+
+/// Combined power draw of all GPUs
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TotalPower {
+    /// Sum of the board power (in watts) of all GPUs
+    pub total: f64,
+    /// Board power of each GPU that reports it. Suspended GPUs are counted as 0W.
+    pub gpus: BTreeMap<String, f64>,
+}
+
+/// Aggregated stats of all GPUs, for monitoring many GPUs with one compact response
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FleetSummary {
+    pub gpu_count: usize,
+    /// GPUs whose stats could not be read
+    pub unavailable: Vec<String>,
+    /// GPUs which were throttled over the last minute or are being limited by the power cap
+    pub throttling: Vec<String>,
+    /// Sum of the board power (in watts) of all GPUs
+    pub total_power: f64,
+    /// The GPU with the highest temperature on any of its sensors
+    pub hottest: Option<HottestGpu>,
+    /// Number of resets since boot of the GPUs which were reset
+    pub resets: BTreeMap<String, u32>,
+    /// Number of VRAM pages retired because of memory errors, on the GPUs which have any
+    pub retired_pages: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HottestGpu {
+    pub id: String,
+    pub sensor: String,
+    /// In degrees celsius
+    pub temperature: f32,
+}
+
+/// The few stats needed by a status bar, read without the rest of `DeviceStats`.
+/// All values are `None` while the GPU is runtime suspended, as reading them would wake it up.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct QuickStats {
+    /// Edge temperature in °C, or the first sensor when there is no edge sensor
+    pub temperature: Option<f32>,
+    /// Board power draw in W
+    pub power: Option<f64>,
+    /// Fan speed in percent (0-100)
+    pub fan_percent: Option<f64>,
+    /// In MHz
+    pub core_clock: Option<u64>,
+}
+
+/// Outcome of loading a profile on one GPU with `LoadProfileAll`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum ProfileLoadResult {
+    /// The settings of the given model section were applied
+    Applied {
+        section: String,
+    },
+    /// The profile has no section for the model of the GPU
+    Skipped,
+    Failed {
+        error: String,
+    },
+}
+
+/// Power draw of the whole system, for combining the CPU and GPU draw. Sensors the platform doesn't expose are `None`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SystemPower {
+    /// Power of all CPU packages (in watts), from RAPL
+    pub cpu_package: Option<f64>,
+    /// Power of the whole system (in watts), from an ACPI power meter or the battery while discharging
+    pub system: Option<f64>,
+    /// Combined board power of all GPUs, the same as `GetTotalPower`
+    pub gpus: f64,
+}
+
+/// GFXOFF powers down the graphics engine while it is idle
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GfxoffStats {
+    /// Whether the `GFXOFF` powerplay feature is enabled
+    pub feature_enabled: Option<bool>,
+    pub state: Option<GfxoffState>,
+    /// Number of times the graphics engine was powered down since boot
+    pub entry_count: Option<u64>,
+    /// Share of time (in percent) spent powered down over the last residency logging period
+    pub residency: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum GfxoffState {
+    /// The graphics engine is powered down
+    Off,
+    Exiting,
+    /// The graphics engine is powered up
+    On,
+    Entering,
+}
+
+/// Settings for switching the maximum core clock based on GPU utilization
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AdaptiveClocks {
+    /// Maximum core clock (in MHz) used while the GPU is idle
+    pub idle_max: i32,
+    /// Maximum core clock (in MHz) used while the GPU is under load
+    pub load_max: i32,
+    /// GPU utilization (in percent) at which the load clock is used
+    pub load_threshold_pct: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AdaptiveClocksState {
+    Idle,
+    Load,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AdaptiveClocksStatus {
+    pub state: AdaptiveClocksState,
+    /// Currently applied maximum core clock (in MHz)
+    pub max_core_clock: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MemoryClockBoostStatus {
+    /// Name of the game process for which the highest memory clock is forced, `None` while no game is running
+    pub game: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClocksRampStatus {
+    /// Clockspeed (in MHz) of the step which is currently applied
+    pub current: i32,
+    pub target: i32,
+}
+
+/// Throttling thresholds of a temperature sensor, in degrees celsius
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TemperatureLimits {
+    pub crit: Option<f32>,
+    pub crit_hyst: Option<f32>,
+    pub emergency: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct FanStats {
     pub control_enabled: bool,
     pub control_mode: Option<FanControlMode>,
+    /// Fan speed as a ratio (0.0-1.0)
     pub static_speed: Option<f64>,
+    /// Temperatures in °C mapped to fan speeds as a ratio (0.0-1.0)
     pub curve: Option<FanCurveMap>,
+    /// Curve used while the fan slows down, when one is set
+    #[serde(default)]
+    pub spindown_curve: Option<FanCurveMap>,
+    /// Fan speed in RPM
     pub speed_current: Option<u32>,
     pub speed_max: Option<u32>,
     pub speed_min: Option<u32>,
+    /// Raw PWM value of the fan, usually between 0 and 255
+    #[serde(default)]
+    pub pwm_current: Option<u8>,
+    /// Fan speed in percent (0-100), based on the PWM range of the card
+    #[serde(default)]
+    pub speed_percent: Option<f64>,
+    /// Ranges for the fan curve editor, based on the limits of the curve temperature sensor
+    pub curve_defaults: FanCurveDefaults,
+    /// How the fan speed can be controlled, `None` when the fan can't be controlled at all
+    #[serde(default)]
+    pub fan_control_method: Option<FanControlMethodKind>,
+    /// Where custom fan control is written to, `None` when the fan can't be controlled at all
+    #[serde(default)]
+    pub fan_control_interface: Option<FanControlInterface>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FanControlInterface {
+    /// The daemon sets the PWM duty cycle through hwmon, following the curve itself
+    Hwmon,
+    /// The power management firmware follows a curve with a fixed number of points (`gpu_od/fan_ctrl`, RDNA3 and newer).
+    /// The firmware always uses the junction temperature and keeps stopping the fan at low temperatures (zero RPM) on its own,
+    /// the spindown curve and the update interval have no effect.
+    Firmware,
+}
+
+/// Whether the fan can only be controlled by its PWM duty cycle, or also with a target speed in RPM
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum FanControlMethodKind {
+    PwmOnly,
+    /// The fan has a tachometer and accepts a target speed in RPM
+    RpmCapable,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClockspeedStats {
+    /// In MHz
     pub gpu_clockspeed: Option<u64>,
+    /// In MHz
     pub vram_clockspeed: Option<u64>,
+    /// Theoretical memory bandwidth (in GB/s) at the current memory clock, calculated as clockspeed × data rate × bus width.
+    /// This is an estimate and not a measurement of the actual throughput.
+    pub memory_bandwidth_gbps: Option<f64>,
+    /// Graphics clock (in MHz) reported by the firmware in `gpu_metrics` at the time of reading
+    #[serde(default)]
+    pub gfx_clock_current: Option<u16>,
+    /// Graphics clock (in MHz) from `gpu_metrics` averaged over a short period, including the time the GPU was idle.
+    /// This reads lower than the current clock when the GPU is not fully loaded.
+    #[serde(default)]
+    pub gfx_clock_average: Option<u16>,
+    /// Core clock (in MHz) of the DPM level the driver requested. The GPU can still spend part of the time gated off (GFXOFF)
+    /// or idle, which is why the average clock can be much lower.
+    #[serde(default)]
+    pub gpu_clock_requested: Option<u64>,
+    /// Memory clock (in MHz) of the DPM level the driver requested
+    #[serde(default)]
+    pub vram_clock_requested: Option<u64>,
+    /// Memory clock (in MHz) reported by the firmware in `gpu_metrics` at the time of reading
+    #[serde(default)]
+    pub vram_clock_current: Option<u16>,
+    /// Memory clock (in MHz) from `gpu_metrics` averaged over a short period
+    #[serde(default)]
+    pub vram_clock_average: Option<u16>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// Voltages in mV
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct VoltageStats {
     pub gpu: Option<u64>,
     pub northbridge: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// Memory sizes in bytes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct VramStats {
     pub total: Option<u64>,
     pub used: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// Power values in W, unless noted otherwise
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PowerStats {
     pub average: Option<f64>,
     pub current: Option<f64>,
     pub cap_current: Option<f64>,
+    /// Current power cap in microwatts, exactly as reported by the driver
+    pub cap_current_microwatts: Option<u64>,
     pub cap_max: Option<f64>,
     pub cap_min: Option<f64>,
     pub cap_default: Option<f64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PowerStates {
     pub core: Vec<PowerState<u64>>,
     pub vram: Vec<PowerState<u64>>,
@@ -238,6 +1753,7 @@ impl PowerStates {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PowerState<T> {
     pub enabled: bool,
     pub value: T,
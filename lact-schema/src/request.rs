@@ -1,25 +1,82 @@
-use crate::{FanControlMode, FanCurveMap};
+use crate::{
+    AdaptiveClocks, AspmPolicy, ClockDomain, Control, DeviceMatch, EventMask, FanControlMode,
+    FanCurveMap, LogLevel, PowerTier, ResetMethod, TemperatureUnit, TimeRange,
+};
 use amdgpu_sysfs::gpu_handle::{PerformanceLevel, PowerLevelKind};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "command", content = "args", rename_all = "snake_case")]
 pub enum Request<'a> {
     Ping,
     ListDevices,
     SystemInfo,
+    GetTotalPower,
+    /// Get the CPU and whole system power next to the GPU power, where the platform exposes them
+    GetSystemPower,
+    /// Aggregates of the stats of all GPUs, such as how many are throttling and the total power
+    GetFleetSummary,
+    /// Checks the common reasons for settings not being applied, such as missing privileges or overdrive being disabled
+    SelfTest,
+    /// Why a control of the GPU can't be used, such as overdrive being disabled or the GPU being bound to a passthrough driver
+    ExplainControl {
+        id: &'a str,
+        control: Control,
+    },
     DeviceInfo {
         id: &'a str,
     },
     DeviceStats {
         id: &'a str,
     },
+    /// The fan curve of the firmware, for starting a custom curve from it
+    GetDefaultFanCurve {
+        id: &'a str,
+    },
+    /// The most recent decisions of the fan curve, oldest first.
+    /// Empty when the daemon doesn't control the fan with a curve, including when the firmware follows the curve.
+    GetFanControlLog {
+        id: &'a str,
+    },
+    /// How often each value of the stats failed to read, for finding sensors which fail intermittently
+    GetReadFailures {
+        id: &'a str,
+    },
+    /// Only the temperature, power, fan speed and core clock of a GPU, for widgets which update often
+    GetQuickStats {
+        id: &'a str,
+    },
+    /// The stats of a GPU formatted as a line of the InfluxDB line protocol
+    GetStatsLineProtocol {
+        id: &'a str,
+    },
+    /// Info, stats, clocks info, power profile modes and power states of a GPU in a single response
+    GetDeviceSnapshot {
+        id: &'a str,
+    },
+    /// All values of the firmware metrics table, unlike the summarized `DeviceStats`
+    GetSmuMetrics {
+        id: &'a str,
+    },
+    /// Sysfs files the daemon writes to with their permissions and current values, for diagnosing missing controls
+    GetControllableNodes {
+        id: &'a str,
+    },
     DeviceClocksInfo {
         id: &'a str,
     },
+    /// Voltage-frequency curve from the clocks table, with the allowed range of each point
+    GetVoltageCurve {
+        id: &'a str,
+    },
     DevicePowerProfileModes {
         id: &'a str,
     },
+    /// The power profile modes with the heuristic values of each mode
+    GetPowerProfileModeDetails {
+        id: &'a str,
+    },
     SetFanControl {
         id: &'a str,
         enabled: bool,
@@ -27,12 +84,67 @@ pub enum Request<'a> {
         static_speed: Option<f64>,
         curve: Option<FanCurveMap>,
     },
+    /// Set the temperature sensor used by the fan curve. `gpu_id` allows using a sensor of a different GPU.
+    SetFanTemperatureSource {
+        id: &'a str,
+        gpu_id: Option<&'a str>,
+        sensor: &'a str,
+    },
+    /// Shift the trigger temperatures of the fan curve by `temp_offset` degrees, without changing the curve itself
+    SetFanCurveOffset {
+        id: &'a str,
+        temp_offset: i32,
+    },
+    /// Set a separate curve for slowing the fan down. The fan speeds up along the main curve,
+    /// and only slows down when the temperature drops below the spindown curve. `None` removes it.
+    SetFanSpindownCurve {
+        id: &'a str,
+        curve: Option<FanCurveMap>,
+    },
+    /// Make the fan curve use the given temperature (in °C) instead of the sensor reading, for testing curves.
+    /// It requires `allow_simulated_temperature` in the daemon config, and expires after 5 minutes. `None` stops the simulation.
+    SetSimulatedTemperature {
+        id: &'a str,
+        temp: Option<f32>,
+    },
+    /// Hand fan control back to the firmware for `duration` seconds, then re-apply the saved settings.
+    /// Responds with the time (in milliseconds since the epoch) when the settings were restored.
+    SuspendFanControl {
+        id: &'a str,
+        duration: u64,
+    },
     SetPowerCap {
         id: &'a str,
         cap: Option<f64>,
     },
+    /// Same as `SetPowerCap`, with the cap in microwatts to avoid rounding
+    SetPowerCapMicrowatts {
+        id: &'a str,
+        cap: Option<u64>,
+    },
+    /// Set the power cap to one of the presets, computed from the power cap limits of the GPU
+    SetPowerTier {
+        id: &'a str,
+        tier: PowerTier,
+    },
+    GetPowerTiers {
+        id: &'a str,
+    },
+    GetPerformanceLevel {
+        id: &'a str,
+    },
+    /// Get the closest value to `value` that the GPU accepts for the given clocks setting
+    SnapClock {
+        id: &'a str,
+        domain: ClockDomain,
+        value: i32,
+    },
     SetPerformanceLevel {
         id: &'a str,
+        #[cfg_attr(
+            feature = "schema",
+            schemars(with = "crate::json_schema::PerformanceLevel")
+        )]
         performance_level: PerformanceLevel,
     },
     SetClocksValue {
@@ -43,30 +155,379 @@ pub enum Request<'a> {
         id: &'a str,
         commands: Vec<SetClocksCommand>,
     },
+    /// Move a maximum clock to the `target` value (which has to be `MaxCoreClock` or `MaxMemoryClock`)
+    /// in steps of `step` MHz, waiting `interval` seconds on each step.
+    /// The intermediate steps are not saved, and the target value has to be confirmed like with `SetClocksValue`.
+    ///
+    /// The ramp is aborted and the previous clocks are restored when the GPU gets reset,
+    /// or when it hits a temperature or current limit with `abort_on_throttling`.
+    /// Its progress is reported in `DeviceStats::clocks_ramp`.
+    /// Other settings changes are refused until the ramp finished.
+    SetClocksRamped {
+        id: &'a str,
+        target: SetClocksCommand,
+        step: i32,
+        interval: u64,
+        #[serde(default)]
+        abort_on_throttling: bool,
+    },
     SetPowerProfileMode {
         id: &'a str,
         index: Option<u16>,
     },
+    /// Enable or disable LACT management of a GPU. Unmanaged GPUs have no settings applied to them.
+    SetManaged {
+        id: &'a str,
+        managed: bool,
+    },
+    /// Set the power profile mode matching the given name (case-insensitive)
+    SetPowerProfileModeByName {
+        id: &'a str,
+        name: &'a str,
+    },
     GetPowerStates {
         id: &'a str,
     },
+    GetPpFeatures {
+        id: &'a str,
+    },
+    /// Enable or disable a powerplay feature by its name. This is written to the driver directly and is not persisted.
+    SetPpFeature {
+        id: &'a str,
+        feature: &'a str,
+        enabled: bool,
+    },
     SetEnabledPowerStates {
         id: &'a str,
+        #[cfg_attr(
+            feature = "schema",
+            schemars(with = "crate::json_schema::PowerLevelKind")
+        )]
         kind: PowerLevelKind,
         states: Vec<u8>,
     },
     EnableOverdrive,
+    /// Enable or disable memory error correction on the next boot, see `EccInfo`.
+    /// This is an option of the amdgpu driver, so it changes the mode of every AMD GPU and not only of `id`.
+    SetEccMode {
+        id: &'a str,
+        enabled: bool,
+    },
+    GetAspmPolicy {
+        id: &'a str,
+    },
+    /// Set the PCIe ASPM policy. The policy is system-wide, so it applies to all PCIe devices and not only to `id`.
+    /// It is not kept after a reboot.
+    /// Changing it is not possible when the firmware doesn't give the OS control over ASPM.
+    SetAspmPolicy {
+        id: &'a str,
+        policy: AspmPolicy,
+    },
+    /// Get the runtime power management state of the GPU's HDMI/DisplayPort audio function
+    GetAudioDevice {
+        id: &'a str,
+    },
+    /// Keep the audio function active instead of letting it suspend while unused, which avoids audio pops on some receivers.
+    /// This is not persisted, and is reset when the system reboots.
+    SetAudioPinnedActive {
+        id: &'a str,
+        pinned: bool,
+    },
+    /// Get the links to the other GPUs of the same hive, on GPUs with an XGMI interconnect
+    GetInterconnect {
+        id: &'a str,
+    },
+    /// Compare the configured settings with the values which are actually in effect on the GPU
+    GetEffectiveConfig {
+        id: &'a str,
+    },
+    /// Conservative clock, voltage and power cap adjustments based on the limits and current behaviour of the GPU
+    GetTuningSuggestions {
+        id: &'a str,
+    },
+    /// List the VRAM pages retired because of memory errors, which requires ECC to be enabled
+    GetRetiredPages {
+        id: &'a str,
+    },
+    /// Clear the table of retired pages stored on the GPU, which requires debugfs.
+    /// `count` has to match the current number of retired pages, so that pages which were not seen yet are not cleared.
+    ClearRetiredPages {
+        id: &'a str,
+        count: usize,
+    },
+    /// List the ways in which the GPU can be reset, see `ResetGpu`
+    GetResetMethods {
+        id: &'a str,
+    },
+    /// Reset a hung GPU without rebooting. This stops all applications using it, so `confirm` has to be set.
+    /// The GPU which was used for the display on boot can't be reset.
+    ResetGpu {
+        id: &'a str,
+        method: ResetMethod,
+        confirm: bool,
+    },
     ConfirmPendingConfig(ConfirmCommand),
+    /// Get the settings change which is waiting for a confirmation, if there is one
+    GetPendingConfig,
+    /// Revert the settings of the GPU to the last confirmed state, if it has a change waiting for a confirmation.
+    /// Unlike reverting with `ConfirmPendingConfig`, this succeeds when nothing is pending.
+    DiscardAllPending {
+        id: &'a str,
+    },
+    SetSchedule {
+        profile: &'a str,
+        time_range: TimeRange,
+    },
+    ListSchedules,
+    /// Save the current settings of every managed GPU as a profile, which can then be used in schedules.
+    /// An existing profile with the same name gets its GPU settings replaced, its model sections are kept.
+    SaveProfile {
+        name: &'a str,
+    },
+    /// Names of the profiles in the config
+    ListProfiles,
+    /// Compare the current settings of a GPU with the ones it would have with the given profile
+    DiffProfile {
+        id: &'a str,
+        name: &'a str,
+    },
+    /// Apply settings between the ones the GPU has with the `from` and `to` profiles, confirmed like with `ApplyDeviceConfig`.
+    /// Numeric settings are interpolated by `factor` (0.0 to 1.0), the others switch from `from` to `to` at 0.5.
+    BlendProfiles {
+        id: &'a str,
+        from: &'a str,
+        to: &'a str,
+        factor: f64,
+    },
+    /// Apply the model sections of a profile to every managed GPU of a matching model, confirming the changes right away.
+    /// GPUs without a matching section are skipped.
+    LoadProfileAll {
+        name: &'a str,
+    },
+    /// When each setting of the GPU was last changed, and what changed it
+    GetSettingsHistory {
+        id: &'a str,
+    },
+    /// List the processes that are using the GPU
+    GetGpuProcesses {
+        id: &'a str,
+    },
+    /// Terminate a process that is using one of the GPUs. Only root can terminate the processes of other users.
+    KillGpuProcess {
+        pid: i32,
+    },
+    DeleteSchedule {
+        profile: &'a str,
+    },
+    /// Set the unit in which clients should show temperatures, reported in `SystemInfo`
+    SetTemperatureUnit {
+        unit: TemperatureUnit,
+    },
+    /// Switch between profiles automatically depending on whether the system runs on AC or battery power.
+    /// Setting both profiles to `None` disables the switching.
+    SetPowerSourceProfiles {
+        ac_profile: Option<&'a str>,
+        battery_profile: Option<&'a str>,
+    },
+    SetAdaptiveClocks {
+        id: &'a str,
+        adaptive_clocks: Option<AdaptiveClocks>,
+    },
+    /// Force the highest memory clock only while a game is using the GPU, for avoiding stutter caused by memory clock switching
+    /// without the power cost of keeping the clock high all the time.
+    /// Windows games running through Wine or Proton are detected automatically, others can be listed in `memory_clock_boost.processes` in the config.
+    SetMemoryClockBoost {
+        id: &'a str,
+        enabled: bool,
+    },
+    /// Replace all settings of a GPU at once, in the format of a `gpus` entry from `GetConfig`.
+    /// The settings are confirmed or reverted together, unlike when changing them with separate requests.
+    ApplyDeviceConfig {
+        id: &'a str,
+        config: serde_json::Value,
+        /// The settings are refused when the GPU doesn't match these ids, for settings made for a specific card
+        #[serde(default)]
+        expected_device: Option<DeviceMatch>,
+    },
+    /// Apply the hardware state recorded when the daemon first managed the GPU, confirmed like with `ApplyDeviceConfig`.
+    /// Unlike resetting the settings, this sets the values that were in use before LACT instead of the driver defaults.
+    RestoreFactoryBaseline {
+        id: &'a str,
+    },
+    /// List the shared presets for the card model of the GPU, from the `presets` directory next to the config
+    ListPresets {
+        id: &'a str,
+    },
+    /// Apply the settings of a preset from `ListPresets`, which are confirmed like with `ApplyDeviceConfig`
+    ApplyPreset {
+        id: &'a str,
+        name: &'a str,
+    },
+    /// Receive the log events of the daemon up to the given level on this connection.
+    /// After the `Ok` response, every line is a `LogRecord`, and no other requests can be made on the connection.
+    SubscribeLogs {
+        level: LogLevel,
+    },
+    /// Receive the given kinds of events on this connection, which replaces subscribing to each of them separately.
+    /// After the `Ok` response, every line is a `DaemonEvent`, and no other requests can be made on the connection.
+    Subscribe {
+        events: EventMask,
+    },
+    /// Include how long the daemon took to handle each following request on this connection in the responses,
+    /// see `ResponseTiming`. Only available over the socket protocol.
+    SetResponseTiming {
+        enabled: bool,
+    },
+    /// Compress the following responses on this connection with zlib (deflate), for large responses over slow links.
+    /// After the uncompressed `Ok` response, every response is sent as a line with the compressed length in bytes,
+    /// followed by that many bytes of the compressed JSON. Only available over the socket protocol.
+    /// The responses to this request and to `SetResponseTiming` are never compressed, including when disabling it.
+    SetResponseCompression {
+        enabled: bool,
+    },
+    /// Get the persisted daemon configuration
+    GetConfig,
+    /// Re-read the config file and apply the settings from it, for picking up changes made by editing the file
+    ReloadConfig,
+    /// Replace the whole daemon configuration, in the same format as returned by `GetConfig`
+    SetConfig {
+        config: serde_json::Value,
+    },
+    /// Apply a settings request and confirm it right away, without waiting for `ConfirmPendingConfig`
+    ApplyConfirmed(#[serde(borrow)] Box<Request<'a>>),
+    /// Define a named group of GPUs, which can be used in place of a GPU id in requests that change settings.
+    /// An empty list of ids deletes the group.
+    DefineGroup {
+        name: &'a str,
+        ids: Vec<String>,
+    },
+    /// Give the GPU a nickname, which is shown in the device list. An empty label removes it.
+    SetDeviceLabel {
+        id: &'a str,
+        label: &'a str,
+    },
+}
+
+impl<'a> Request<'a> {
+    /// Whether the request only reads state, meaning it can be safely sent again after a connection failure.
+    ///
+    /// Requests that modify state are never considered idempotent, as a failure may happen after the change was partially applied.
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            Request::Ping
+                | Request::ListDevices
+                | Request::SystemInfo
+                | Request::GetTotalPower
+                | Request::GetSystemPower
+                | Request::GetFleetSummary
+                | Request::SelfTest
+                | Request::ExplainControl { .. }
+                | Request::SetResponseTiming { .. }
+                | Request::SetResponseCompression { .. }
+                | Request::DeviceInfo { .. }
+                | Request::DeviceStats { .. }
+                | Request::GetStatsLineProtocol { .. }
+                | Request::GetPowerTiers { .. }
+                | Request::GetDefaultFanCurve { .. }
+                | Request::GetFanControlLog { .. }
+                | Request::GetQuickStats { .. }
+                | Request::GetReadFailures { .. }
+                | Request::GetDeviceSnapshot { .. }
+                | Request::GetSmuMetrics { .. }
+                | Request::GetControllableNodes { .. }
+                | Request::GetRetiredPages { .. }
+                | Request::GetResetMethods { .. }
+                | Request::GetAudioDevice { .. }
+                | Request::GetEffectiveConfig { .. }
+                | Request::GetTuningSuggestions { .. }
+                | Request::GetInterconnect { .. }
+                | Request::ListPresets { .. }
+                | Request::GetAspmPolicy { .. }
+                | Request::DeviceClocksInfo { .. }
+                | Request::GetVoltageCurve { .. }
+                | Request::DevicePowerProfileModes { .. }
+                | Request::GetPowerProfileModeDetails { .. }
+                | Request::GetPowerStates { .. }
+                | Request::GetPerformanceLevel { .. }
+                | Request::SnapClock { .. }
+                | Request::GetPpFeatures { .. }
+                | Request::ListSchedules
+                | Request::ListProfiles
+                | Request::DiffProfile { .. }
+                | Request::GetGpuProcesses { .. }
+                | Request::GetSettingsHistory { .. }
+                | Request::GetConfig
+                | Request::GetPendingConfig
+        )
+    }
+
+    /// Whether the request changes GPU settings, which then have to be confirmed with `ConfirmPendingConfig`
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(
+            self,
+            Request::SetFanControl { .. }
+                | Request::SetFanTemperatureSource { .. }
+                | Request::SetFanCurveOffset { .. }
+                | Request::SetFanSpindownCurve { .. }
+                | Request::SetPowerCap { .. }
+                | Request::SetPowerCapMicrowatts { .. }
+                | Request::SetPowerTier { .. }
+                | Request::SetPerformanceLevel { .. }
+                | Request::SetClocksValue { .. }
+                | Request::BatchSetClocksValue { .. }
+                | Request::SetClocksRamped { .. }
+                | Request::SetPowerProfileMode { .. }
+                | Request::SetPowerProfileModeByName { .. }
+                | Request::SetEnabledPowerStates { .. }
+                | Request::SetAdaptiveClocks { .. }
+                | Request::SetMemoryClockBoost { .. }
+                | Request::ApplyDeviceConfig { .. }
+                | Request::RestoreFactoryBaseline { .. }
+                | Request::BlendProfiles { .. }
+                | Request::ApplyPreset { .. }
+        )
+    }
+
+    /// The id of the GPU whose settings are changed by the request, which may also be a group name
+    pub fn target_id_mut(&mut self) -> Option<&mut &'a str> {
+        match self {
+            Request::SetFanControl { id, .. }
+            | Request::SetFanTemperatureSource { id, .. }
+            | Request::SetFanCurveOffset { id, .. }
+            | Request::SetFanSpindownCurve { id, .. }
+            | Request::SetPowerCap { id, .. }
+            | Request::SetPowerCapMicrowatts { id, .. }
+            | Request::SetPowerTier { id, .. }
+            | Request::SetPerformanceLevel { id, .. }
+            | Request::SetClocksValue { id, .. }
+            | Request::BatchSetClocksValue { id, .. }
+            | Request::SetPowerProfileMode { id, .. }
+            | Request::SetPowerProfileModeByName { id, .. }
+            | Request::SetManaged { id, .. }
+            | Request::SetPpFeature { id, .. }
+            | Request::SetEnabledPowerStates { id, .. }
+            | Request::SetAdaptiveClocks { id, .. }
+            | Request::SetMemoryClockBoost { id, .. }
+            | Request::ApplyDeviceConfig { id, .. }
+            | Request::RestoreFactoryBaseline { id }
+            | Request::BlendProfiles { id, .. }
+            | Request::ApplyPreset { id, .. } => Some(id),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "command", rename_all = "snake_case")]
 pub enum ConfirmCommand {
     Confirm,
     Revert,
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", content = "value", rename_all = "snake_case")]
 pub enum SetClocksCommand {
     MaxCoreClock(i32),
@@ -76,5 +537,112 @@ pub enum SetClocksCommand {
     MinMemoryClock(i32),
     MinVoltage(i32),
     VoltageOffset(i32),
+    /// Select a single core clock power state by its index, for GPUs with discrete clocks
+    CoreState(u8),
+    /// Select a single memory clock power state by its index, for GPUs with discrete clocks
+    MemoryState(u8),
     Reset,
 }
+
+impl SetClocksCommand {
+    /// Power state selections go through the enabled power states instead of the overdrive clocks table
+    pub fn power_state(&self) -> Option<(PowerLevelKind, u8)> {
+        match self {
+            Self::CoreState(state) => Some((PowerLevelKind::CoreClock, *state)),
+            Self::MemoryState(state) => Some((PowerLevelKind::MemoryClock, *state)),
+            _ => None,
+        }
+    }
+}
+
+/// Builder for a set of clocks commands, which validates that the values are consistent with each other
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SetClocksCommandBuilder {
+    core_min: Option<i32>,
+    core_max: Option<i32>,
+    memory_min: Option<i32>,
+    memory_max: Option<i32>,
+    voltage_min: Option<i32>,
+    voltage_max: Option<i32>,
+    voltage_offset: Option<i32>,
+}
+
+impl SetClocksCommandBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn core_min(mut self, clockspeed: i32) -> Self {
+        self.core_min = Some(clockspeed);
+        self
+    }
+
+    pub fn core_max(mut self, clockspeed: i32) -> Self {
+        self.core_max = Some(clockspeed);
+        self
+    }
+
+    pub fn memory_min(mut self, clockspeed: i32) -> Self {
+        self.memory_min = Some(clockspeed);
+        self
+    }
+
+    pub fn memory_max(mut self, clockspeed: i32) -> Self {
+        self.memory_max = Some(clockspeed);
+        self
+    }
+
+    pub fn voltage_min(mut self, voltage: i32) -> Self {
+        self.voltage_min = Some(voltage);
+        self
+    }
+
+    pub fn voltage_max(mut self, voltage: i32) -> Self {
+        self.voltage_max = Some(voltage);
+        self
+    }
+
+    pub fn voltage_offset(mut self, offset: i32) -> Self {
+        self.voltage_offset = Some(offset);
+        self
+    }
+
+    /// Validates the values and produces the commands, to be used with `BatchSetClocksValue`
+    pub fn build(self) -> Result<Vec<SetClocksCommand>, String> {
+        let pairs = [
+            ("core clock", self.core_min, self.core_max),
+            ("memory clock", self.memory_min, self.memory_max),
+            ("voltage", self.voltage_min, self.voltage_max),
+        ];
+        for (name, min, max) in pairs {
+            if min.into_iter().chain(max).any(|value| value <= 0) {
+                return Err(format!("{name} values have to be positive"));
+            }
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!(
+                        "minimum {name} {min} is higher than the maximum {max}"
+                    ));
+                }
+            }
+        }
+
+        let commands: Vec<SetClocksCommand> = [
+            self.core_min.map(SetClocksCommand::MinCoreClock),
+            self.core_max.map(SetClocksCommand::MaxCoreClock),
+            self.memory_min.map(SetClocksCommand::MinMemoryClock),
+            self.memory_max.map(SetClocksCommand::MaxMemoryClock),
+            self.voltage_min.map(SetClocksCommand::MinVoltage),
+            self.voltage_max.map(SetClocksCommand::MaxVoltage),
+            self.voltage_offset.map(SetClocksCommand::VoltageOffset),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if commands.is_empty() {
+            return Err("no clocks values were specified".to_owned());
+        }
+        Ok(commands)
+    }
+}
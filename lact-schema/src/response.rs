@@ -1,8 +1,25 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "status", content = "data", rename_all = "snake_case")]
 pub enum Response<T> {
     Ok(T),
     Error(String),
 }
+
+/// Sent next to the status of every response after `SetResponseTiming` was enabled on the connection
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ResponseTiming {
+    /// Time the daemon spent handling the request (in microseconds), without the time on the socket
+    pub processing_time_us: Option<u64>,
+}
+
+/// Sent next to the status of successful responses, when the request succeeded with caveats
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ResponseWarnings {
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
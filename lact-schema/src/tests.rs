@@ -1,4 +1,14 @@
-use crate::{Pong, Request, Response};
+use crate::{
+    default_fan_curve,
+    diff::{diff_json, ValueDiff},
+    evaluate_fan_curve,
+    request::{SetClocksCommand, SetClocksCommandBuilder},
+    units::{
+        bytes_to_mib, microwatts_to_watts, watts_to_microwatts, ClockUnit, PowerUnit, BYTES_PER_MIB,
+    },
+    DaemonEvent, DayTime, DeviceStats, EventMask, FanCurveDefaults, FanCurveMap, LogLevel,
+    LogRecord, Pong, QuickStats, Request, Response, TemperatureUnit, TimeRange,
+};
 use serde_json::json;
 
 #[test]
@@ -43,3 +53,317 @@ fn error_response() {
 
     assert_eq!(serde_json::to_value(response).unwrap(), expected_response);
 }
+
+#[test]
+fn request_idempotency() {
+    assert!(Request::Ping.is_idempotent());
+    assert!(Request::DeviceStats { id: "my-gpu" }.is_idempotent());
+    assert!(!Request::SetClocksValue {
+        id: "my-gpu",
+        command: SetClocksCommand::MaxCoreClock(2000)
+    }
+    .is_idempotent());
+    assert!(!Request::EnableOverdrive.is_idempotent());
+}
+
+#[test]
+fn time_range_contains() {
+    let time = |s: &str| s.parse::<DayTime>().unwrap();
+
+    let day = TimeRange {
+        start: time("08:00"),
+        end: time("22:30"),
+    };
+    assert!(day.contains(time("08:00")));
+    assert!(day.contains(time("12:15")));
+    assert!(!day.contains(time("22:30")));
+    assert!(!day.contains(time("03:00")));
+
+    let night = TimeRange {
+        start: time("22:30"),
+        end: time("08:00"),
+    };
+    assert!(night.contains(time("23:59")));
+    assert!(night.contains(time("00:00")));
+    assert!(!night.contains(time("08:00")));
+    assert!(!night.contains(time("12:15")));
+}
+
+#[test]
+fn day_time_serde() {
+    let time: DayTime = serde_json::from_value(json!("07:05")).unwrap();
+    assert_eq!(time, DayTime { hour: 7, minute: 5 });
+    assert_eq!(serde_json::to_value(time).unwrap(), json!("07:05"));
+
+    assert!(serde_json::from_value::<DayTime>(json!("24:00")).is_err());
+    assert!(serde_json::from_value::<DayTime>(json!("1200")).is_err());
+}
+
+#[test]
+fn clocks_command_builder() {
+    let commands = SetClocksCommandBuilder::new()
+        .core_max(2500)
+        .memory_max(1000)
+        .voltage_offset(-50)
+        .build()
+        .unwrap();
+    assert_eq!(
+        commands,
+        [
+            SetClocksCommand::MaxCoreClock(2500),
+            SetClocksCommand::MaxMemoryClock(1000),
+            SetClocksCommand::VoltageOffset(-50),
+        ]
+    );
+}
+
+#[test]
+fn clocks_command_builder_invalid() {
+    assert!(SetClocksCommandBuilder::new().build().is_err());
+    assert!(SetClocksCommandBuilder::new()
+        .core_min(2000)
+        .core_max(1000)
+        .build()
+        .is_err());
+    assert!(SetClocksCommandBuilder::new()
+        .memory_max(-100)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn apply_confirmed_request() {
+    let request = Request::ApplyConfirmed(Box::new(Request::SetPowerCap {
+        id: "my-gpu",
+        cap: Some(200.0),
+    }));
+    let data = serde_json::to_string(&request).unwrap();
+    let deserialized: Request = serde_json::from_str(&data).unwrap();
+    assert_eq!(request, deserialized);
+    assert!(!request.needs_confirmation());
+}
+
+#[test]
+fn request_target_id() {
+    let mut request = Request::SetPowerCap {
+        id: "compute",
+        cap: None,
+    };
+    *request.target_id_mut().unwrap() = "my-gpu";
+    assert_eq!(
+        request,
+        Request::SetPowerCap {
+            id: "my-gpu",
+            cap: None
+        }
+    );
+
+    assert!(Request::DeviceStats { id: "my-gpu" }
+        .target_id_mut()
+        .is_none());
+}
+
+#[test]
+fn fan_curve_defaults_range() {
+    let defaults = FanCurveDefaults::new(Some(109.5));
+    assert_eq!(defaults.temperature_min, 20);
+    assert_eq!(defaults.temperature_max, 110);
+
+    assert_eq!(FanCurveDefaults::new(None).temperature_max, 100);
+    assert_eq!(FanCurveDefaults::new(Some(0.0)).temperature_max, 100);
+}
+
+#[test]
+fn fan_curve_evaluation() {
+    let curve = default_fan_curve();
+    assert_eq!(evaluate_fan_curve(&curve, 20), Some(0));
+    assert_eq!(evaluate_fan_curve(&curve, 45), Some(70));
+    assert_eq!(evaluate_fan_curve(&curve, 70), Some(191));
+    assert_eq!(evaluate_fan_curve(&curve, 90), Some(255));
+    assert_eq!(evaluate_fan_curve(&FanCurveMap::new(), 50), None);
+}
+
+#[test]
+fn json_diff() {
+    let before = json!({
+        "info": {"vbios_version": "113-D4300100-100"},
+        "stats": {"clockspeed": {"gpu_clockspeed": 500}, "temps": ["edge"], "busy_percent": null},
+    });
+    let after = json!({
+        "info": {"vbios_version": "113-D4300100-100"},
+        "stats": {"clockspeed": {"gpu_clockspeed": 2400}, "temps": ["edge", "junction"]},
+        "power_states": {"core": []},
+    });
+
+    let diffs = diff_json(&before, &after);
+    assert_eq!(
+        diffs,
+        [
+            ValueDiff {
+                field: "power_states".to_owned(),
+                before: None,
+                after: Some(json!({"core": []})),
+            },
+            ValueDiff {
+                field: "stats.clockspeed.gpu_clockspeed".to_owned(),
+                before: Some(json!(500)),
+                after: Some(json!(2400)),
+            },
+            ValueDiff {
+                field: "stats.temps".to_owned(),
+                before: Some(json!(["edge"])),
+                after: Some(json!(["edge", "junction"])),
+            },
+        ]
+    );
+    assert!(diff_json(&before, &before).is_empty());
+}
+
+#[test]
+fn fahrenheit_round_trip() {
+    let unit = TemperatureUnit::Fahrenheit;
+    assert_eq!(unit.from_celsius(100.0), 212.0);
+    assert_eq!(unit.to_celsius(-40.0), -40.0);
+
+    let curve = default_fan_curve();
+    let converted = unit.curve_from_celsius(&curve);
+    assert_eq!(converted.first_key_value(), Some((&86, &0.0)));
+    assert_eq!(unit.curve_to_celsius(&converted), curve);
+
+    let celsius = TemperatureUnit::Celsius;
+    assert_eq!(celsius.curve_from_celsius(&curve), curve);
+}
+
+#[test]
+fn apply_device_config_request() {
+    let value = r#"{
+        "command": "apply_device_config",
+        "args": {
+            "id": "my-gpu",
+            "config": {"fan_control_enabled": false, "power_cap_microwatts": 200000000}
+        }
+    }"#;
+    let mut request: Request = serde_json::from_str(value).unwrap();
+    assert!(request.needs_confirmation());
+    assert_eq!(request.target_id_mut().map(|id| *id), Some("my-gpu"));
+}
+
+#[test]
+fn canonical_units() {
+    let mut stats = DeviceStats::default();
+    stats.clockspeed.gpu_clockspeed = Some(2500);
+    stats.voltage.gpu = Some(1100);
+    stats.vram.total = Some(16 * BYTES_PER_MIB * 1024);
+    stats.power.average = Some(250.5);
+    stats.power.cap_current_microwatts = Some(watts_to_microwatts(300.0));
+
+    let value = serde_json::to_value(&stats).unwrap();
+    assert_eq!(value["clockspeed"]["gpu_clockspeed"], 2500);
+    assert_eq!(value["voltage"]["gpu"], 1100);
+    assert_eq!(value["vram"]["total"], 17_179_869_184_u64);
+    assert_eq!(value["power"]["average"], 250.5);
+    assert_eq!(value["power"]["cap_current_microwatts"], 300_000_000);
+
+    let parsed: DeviceStats = serde_json::from_value(value).unwrap();
+    assert_eq!(
+        microwatts_to_watts(parsed.power.cap_current_microwatts.unwrap()),
+        300.0
+    );
+    assert_eq!(bytes_to_mib(parsed.vram.total.unwrap()), 16384.0);
+    assert_eq!(watts_to_microwatts(-1.0), 0);
+
+    let mhz = parsed.clockspeed.gpu_clockspeed.unwrap() as f64;
+    assert_eq!(ClockUnit::Gigahertz.from_mhz(mhz), 2.5);
+    assert_eq!(ClockUnit::Gigahertz.to_mhz(2.5), mhz);
+    assert_eq!(PowerUnit::Milliwatts.from_watts(250.5), 250_500.0);
+    assert_eq!(PowerUnit::Milliwatts.to_watts(250_500.0), 250.5);
+    assert_eq!(PowerUnit::Watts.symbol(), "W");
+}
+
+#[test]
+fn stats_without_temp_limits() {
+    // Sent by daemons from before the limits were reported
+    let mut value = serde_json::to_value(DeviceStats::default()).unwrap();
+    value.as_object_mut().unwrap().remove("temp_limits");
+    let stats: DeviceStats = serde_json::from_value(value).unwrap();
+    assert!(stats.temp_limits.is_empty());
+}
+
+#[test]
+fn quick_stats_response() {
+    let stats = QuickStats {
+        temperature: Some(45.0),
+        power: Some(30.5),
+        fan_percent: None,
+        core_clock: Some(500),
+    };
+    assert_eq!(
+        serde_json::to_value(Response::Ok(stats)).unwrap(),
+        json!({
+            "status": "ok",
+            "data": {
+                "temperature": 45.0,
+                "power": 30.5,
+                "fan_percent": null,
+                "core_clock": 500
+            }
+        })
+    );
+}
+
+#[test]
+fn subscribe_request() {
+    let request: Request = serde_json::from_str(
+        r#"{"command": "subscribe", "args": {"events": {"logs": "warn", "device_events": true}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        request,
+        Request::Subscribe {
+            events: EventMask {
+                stats_interval_ms: None,
+                logs: Some(LogLevel::Warn),
+                device_events: true,
+            }
+        }
+    );
+
+    let log = DaemonEvent::Log(LogRecord {
+        timestamp: 1000,
+        level: LogLevel::Warn,
+        target: "lact_daemon".to_owned(),
+        message: "could not set fan speed".to_owned(),
+    });
+    assert_eq!(
+        serde_json::to_value(log).unwrap(),
+        json!({
+            "kind": "log",
+            "timestamp": 1000,
+            "level": "warn",
+            "target": "lact_daemon",
+            "message": "could not set fan speed"
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(DaemonEvent::ConfigChanged { generation: 3 }).unwrap(),
+        json!({ "kind": "config_changed", "generation": 3 })
+    );
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn protocol_json_schema() {
+    let schema = serde_json::to_value(crate::json_schema::protocol_schema()).unwrap();
+    let definitions = &schema["definitions"];
+    assert!(definitions["DeviceStats"]["properties"]["temps"].is_object());
+    assert_eq!(definitions["DayTime"]["type"], "string");
+    assert_eq!(
+        definitions["PerformanceLevel"]["enum"],
+        json!(["auto", "low", "high", "manual"])
+    );
+    assert!(schema["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|variant| variant["properties"]["command"]["enum"] == json!(["list_devices"])));
+}
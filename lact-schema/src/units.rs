@@ -0,0 +1,92 @@
+//! Conversions between the canonical units used by the daemon and the units used for presentation.
+//! The daemon always responds in the canonical units, which are documented on each field:
+//! power in W (or µW where noted), clocks in MHz, voltages in mV, memory in bytes, temperatures in °C and fan speeds as a ratio or RPM.
+
+use serde::{Deserialize, Serialize};
+
+pub const MICROWATTS_PER_WATT: f64 = 1_000_000.0;
+pub const MILLIWATTS_PER_WATT: f64 = 1_000.0;
+pub const MHZ_PER_GHZ: f64 = 1_000.0;
+pub const BYTES_PER_MIB: u64 = 1024 * 1024;
+
+#[allow(clippy::cast_precision_loss)]
+pub fn microwatts_to_watts(microwatts: u64) -> f64 {
+    microwatts as f64 / MICROWATTS_PER_WATT
+}
+
+/// Rounds to the nearest microwatt, negative values become 0
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub fn watts_to_microwatts(watts: f64) -> u64 {
+    (watts * MICROWATTS_PER_WATT).round().max(0.0) as u64
+}
+
+#[allow(clippy::cast_precision_loss)]
+pub fn bytes_to_mib(bytes: u64) -> f64 {
+    bytes as f64 / BYTES_PER_MIB as f64
+}
+
+/// Unit for showing power values
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUnit {
+    #[default]
+    Watts,
+    Milliwatts,
+}
+
+impl PowerUnit {
+    pub fn from_watts(self, watts: f64) -> f64 {
+        match self {
+            PowerUnit::Watts => watts,
+            PowerUnit::Milliwatts => watts * MILLIWATTS_PER_WATT,
+        }
+    }
+
+    pub fn to_watts(self, value: f64) -> f64 {
+        match self {
+            PowerUnit::Watts => value,
+            PowerUnit::Milliwatts => value / MILLIWATTS_PER_WATT,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            PowerUnit::Watts => "W",
+            PowerUnit::Milliwatts => "mW",
+        }
+    }
+}
+
+/// Unit for showing clockspeeds
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ClockUnit {
+    #[default]
+    Megahertz,
+    Gigahertz,
+}
+
+impl ClockUnit {
+    pub fn from_mhz(self, mhz: f64) -> f64 {
+        match self {
+            ClockUnit::Megahertz => mhz,
+            ClockUnit::Gigahertz => mhz / MHZ_PER_GHZ,
+        }
+    }
+
+    pub fn to_mhz(self, value: f64) -> f64 {
+        match self {
+            ClockUnit::Megahertz => value,
+            ClockUnit::Gigahertz => value * MHZ_PER_GHZ,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ClockUnit::Megahertz => "MHz",
+            ClockUnit::Gigahertz => "GHz",
+        }
+    }
+}